@@ -0,0 +1,224 @@
+use crate::{list_all_objects, maybe_read_json, prettyprint_usize, Error, Timer};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Translates UI text from English into another language. Messages are looked up by their English
+// text, so the code using them stays readable, and anything a catalog is missing just falls back
+// to English. Messages can contain named arguments like "{name}", filled in by tr_args.
+//
+// Catalogs are loaded from data/system/i18n/<language>.json. English is built-in.
+#[derive(Serialize, Deserialize)]
+pub struct Catalog {
+    // Filled out from the filename, like "es"
+    #[serde(skip)]
+    pub language: String,
+    // In the language itself, for the language picker
+    pub name: String,
+    plural_rule: PluralRule,
+    thousands_separator: String,
+    pub decimal_separator: String,
+    // If false, use AM/PM
+    pub clock_24h: bool,
+    messages: BTreeMap<String, Message>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Message {
+    Simple(String),
+    // Keyed by the plural category: "zero", "one", or "other"
+    Plural(BTreeMap<String, String>),
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum PluralRule {
+    // Only exactly 1 is singular, like English, Spanish, and German
+    OneIsSingular,
+    // 0 and 1 are both singular, like French
+    ZeroAndOneAreSingular,
+    // Nouns don't change, like Chinese and Japanese
+    NoPlurals,
+}
+
+impl Catalog {
+    pub fn english() -> Catalog {
+        Catalog {
+            language: "en".to_string(),
+            name: "English".to_string(),
+            plural_rule: PluralRule::OneIsSingular,
+            thousands_separator: ",".to_string(),
+            decimal_separator: ".".to_string(),
+            clock_24h: false,
+            messages: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(language: &str) -> Result<Catalog, Error> {
+        if language == "en" {
+            return Ok(Catalog::english());
+        }
+        let mut catalog: Catalog =
+            maybe_read_json(crate::path_i18n(language), &mut Timer::throwaway())?;
+        catalog.language = language.to_string();
+        Ok(catalog)
+    }
+
+    // Like load, but falls back to English when the catalog is missing or broken
+    pub fn load_or_english(language: &str) -> Catalog {
+        match Catalog::load(language) {
+            Ok(c) => c,
+            Err(err) => {
+                println!(
+                    "Couldn't load {} messages, using English: {}",
+                    language, err
+                );
+                Catalog::english()
+            }
+        }
+    }
+
+    // (language, name) for every catalog, starting with English
+    pub fn all_languages() -> Vec<(String, String)> {
+        let mut results = vec![("en".to_string(), "English".to_string())];
+        for language in list_all_objects(crate::path_all_i18n()) {
+            if let Ok(c) = Catalog::load(&language) {
+                results.push((language, c.name));
+            }
+        }
+        results
+    }
+
+    pub fn tr(&self, msg: &str) -> String {
+        match self.messages.get(msg) {
+            Some(Message::Simple(x)) => x.clone(),
+            // Somebody translated a plural form for a message that's never counted
+            Some(Message::Plural(forms)) => forms
+                .get("other")
+                .cloned()
+                .unwrap_or_else(|| msg.to_string()),
+            None => msg.to_string(),
+        }
+    }
+
+    // Every "{key}" in the message is replaced by the value.
+    pub fn tr_args(&self, msg: &str, args: Vec<(&str, String)>) -> String {
+        fill(self.tr(msg), args)
+    }
+
+    // Picks a form of the message based on n, which is passed in as "{n}". The English singular
+    // form is what catalogs use to look up the message.
+    pub fn plural(
+        &self,
+        one: &str,
+        other: &str,
+        n: usize,
+        mut args: Vec<(&str, String)>,
+    ) -> String {
+        let template = match self.messages.get(one) {
+            Some(Message::Plural(forms)) => {
+                let category = match self.plural_rule {
+                    PluralRule::OneIsSingular if n == 1 => "one",
+                    PluralRule::ZeroAndOneAreSingular if n <= 1 => "one",
+                    _ => "other",
+                };
+                // An explicit zero form takes priority
+                forms
+                    .get(if n == 0 { "zero" } else { category })
+                    .or_else(|| forms.get(category))
+                    .or_else(|| forms.get("other"))
+                    .cloned()
+                    .unwrap_or_else(|| other.to_string())
+            }
+            Some(Message::Simple(x)) => x.clone(),
+            // Fall back to English, and English rules
+            None => {
+                if n == 1 {
+                    one.to_string()
+                } else {
+                    other.to_string()
+                }
+            }
+        };
+        args.push(("n", self.number(n)));
+        fill(template, args)
+    }
+
+    pub fn number(&self, n: usize) -> String {
+        prettyprint_usize(n).replace(',', &self.thousands_separator)
+    }
+
+    pub fn decimal(&self, x: f64, places: usize) -> String {
+        format!("{:.*}", places, x).replace('.', &self.decimal_separator)
+    }
+}
+
+fn fill(mut template: String, args: Vec<(&str, String)>) -> String {
+    for (key, value) in args {
+        template = template.replace(&format!("{{{}}}", key), &value);
+    }
+    template
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spanish() -> Catalog {
+        let mut c: Catalog = serde_json::from_str(
+            r#"{
+                "name": "Español",
+                "plural_rule": "OneIsSingular",
+                "thousands_separator": ".",
+                "decimal_separator": ",",
+                "clock_24h": true,
+                "messages": {
+                    "walk": "caminar",
+                    "riding bus {route}": "en el autobús {route}",
+                    "{n} trip": { "zero": "ningún viaje", "one": "{n} viaje", "other": "{n} viajes" }
+                }
+            }"#,
+        )
+        .unwrap();
+        c.language = "es".to_string();
+        c
+    }
+
+    #[test]
+    fn lookup() {
+        let c = spanish();
+        assert_eq!(c.tr("walk"), "caminar");
+        // Missing messages stay in English
+        assert_eq!(c.tr("bike"), "bike");
+        assert_eq!(
+            c.tr_args("riding bus {route}", vec![("route", "44".to_string())]),
+            "en el autobús 44"
+        );
+        assert_eq!(
+            Catalog::english().tr_args("riding bus {route}", vec![("route", "44".to_string())]),
+            "riding bus 44"
+        );
+    }
+
+    #[test]
+    fn plurals_and_numbers() {
+        let c = spanish();
+        assert_eq!(
+            c.plural("{n} trip", "{n} trips", 0, Vec::new()),
+            "ningún viaje"
+        );
+        assert_eq!(c.plural("{n} trip", "{n} trips", 1, Vec::new()), "1 viaje");
+        assert_eq!(
+            c.plural("{n} trip", "{n} trips", 12345, Vec::new()),
+            "12.345 viajes"
+        );
+        assert_eq!(c.decimal(2.5, 1), "2,5");
+
+        let en = Catalog::english();
+        assert_eq!(en.plural("{n} trip", "{n} trips", 0, Vec::new()), "0 trips");
+        assert_eq!(en.plural("{n} trip", "{n} trips", 1, Vec::new()), "1 trip");
+        assert_eq!(
+            en.plural("{n} trip", "{n} trips", 1234, Vec::new()),
+            "1,234 trips"
+        );
+    }
+}
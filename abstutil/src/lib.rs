@@ -2,6 +2,7 @@ mod cli;
 mod clone;
 mod collections;
 mod error;
+mod i18n;
 mod io;
 mod logs;
 mod random;
@@ -14,6 +15,7 @@ pub use crate::collections::{
     VecMap,
 };
 pub use crate::error::Error;
+pub use crate::i18n::{Catalog, PluralRule};
 pub use crate::io::{
     basename, deserialize_btreemap, deserialize_multimap, file_exists, find_next_file,
     find_prev_file, list_all_objects, list_dir, load_all_objects, maybe_read_binary,
@@ -94,12 +96,23 @@ pub fn path_all_synthetic_maps() -> String {
     format!("../data/system/synthetic_maps")
 }
 
+pub fn path_i18n(language: &str) -> String {
+    format!("../data/system/i18n/{}.json", language)
+}
+pub fn path_all_i18n() -> String {
+    format!("../data/system/i18n")
+}
+
 // Player data (Players edit this)
 
 pub fn path_camera_state(map_name: &str) -> String {
     format!("../data/player/camera_state/{}.json", map_name)
 }
 
+pub fn path_player_settings() -> String {
+    "../data/player/settings.json".to_string()
+}
+
 pub fn path_edits(map_name: &str, edits_name: &str) -> String {
     format!("../data/player/edits/{}/{}.json", map_name, edits_name)
 }
@@ -107,6 +120,10 @@ pub fn path_all_edits(map_name: &str) -> String {
     format!("../data/player/edits/{}", map_name)
 }
 
+pub fn path_csv_export(name: &str) -> String {
+    format!("../data/player/exports/{}.csv", name)
+}
+
 pub fn path_save(map_name: &str, edits_name: &str, run_name: &str, time: String) -> String {
     format!(
         "../data/player/saves/{}/{}_{}/{}.bin",
@@ -0,0 +1,562 @@
+use abstutil::{FileWithProgress, Timer};
+use geom::{GPSBounds, LonLat};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+// A format-neutral view of the OSM input, so the rest of the importer doesn't care whether it came
+// from .osm XML or .osm.pbf.
+#[derive(Serialize, Deserialize)]
+pub struct Document {
+    pub nodes: BTreeMap<i64, Node>,
+    pub ways: BTreeMap<i64, Way>,
+    pub relations: BTreeMap<i64, Relation>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Node {
+    pub id: i64,
+    pub pt: LonLat,
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Way {
+    pub id: i64,
+    pub nodes: Vec<i64>,
+    pub tags: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Relation {
+    pub id: i64,
+    pub tags: BTreeMap<String, String>,
+    // Role is the second member
+    pub members: Vec<(OsmID, String)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum OsmID {
+    Node(i64),
+    Way(i64),
+    Relation(i64),
+}
+
+impl Document {
+    fn new() -> Document {
+        Document {
+            nodes: BTreeMap::new(),
+            ways: BTreeMap::new(),
+            relations: BTreeMap::new(),
+        }
+    }
+}
+
+// Reads .osm or .osm.pbf files, depending on the extension. If a clipping polygon is specified,
+// only keeps nodes inside of it, ways with at least one node inside (along with all of their
+// nodes), and relations referring to anything kept. This is the same as osmconvert's
+// --complete-ways.
+//
+// Clipping a big .pbf extract is slow, and many maps are cut from the same one, so the clipped
+// result is cached next to the extract until either input changes.
+pub fn read(path: &str, clip_path: &Option<String>, timer: &mut Timer) -> Document {
+    let clip = clip_path
+        .as_ref()
+        .map(|p| ClipPolygon::new(LonLat::read_osmosis_polygon(p.to_string()).unwrap()));
+    if path.ends_with(".pbf") {
+        let cache = clip_path.as_ref().map(|p| {
+            format!(
+                "{}/clipped/{}_{}.bin",
+                std::path::Path::new(path)
+                    .parent()
+                    .unwrap()
+                    .to_str()
+                    .unwrap(),
+                abstutil::basename(path),
+                abstutil::basename(p)
+            )
+        });
+        if let (Some(cache), Some(clip_path)) = (&cache, clip_path) {
+            if is_newer_than(cache, &[path, clip_path]) {
+                match abstutil::maybe_read_binary(cache.clone(), timer) {
+                    Ok(doc) => {
+                        return doc;
+                    }
+                    Err(err) => {
+                        println!("Couldn't read cached {}, clipping again: {}", cache, err);
+                    }
+                }
+            }
+        }
+        let doc = read_pbf(path, &clip, timer);
+        if let Some(cache) = cache {
+            abstutil::write_binary(cache, &doc);
+        }
+        doc
+    } else {
+        let doc = read_xml(path, timer);
+        if let Some(ref clip) = clip {
+            timer.start("clip OSM input");
+            let doc = clip_document(doc, clip);
+            timer.stop("clip OSM input");
+            doc
+        } else {
+            doc
+        }
+    }
+}
+
+fn read_xml(path: &str, timer: &mut Timer) -> Document {
+    let (reader, done) = FileWithProgress::new(path).unwrap();
+    let raw = osm_xml::OSM::parse(reader).expect("OSM parsing failed");
+    println!(
+        "OSM doc has {} nodes, {} ways, {} relations",
+        raw.nodes.len(),
+        raw.ways.len(),
+        raw.relations.len()
+    );
+    done(timer);
+
+    let mut doc = Document::new();
+    timer.start("convert OSM XML");
+    for node in raw.nodes.values() {
+        doc.nodes.insert(
+            node.id,
+            Node {
+                id: node.id,
+                pt: LonLat::new(node.lon, node.lat),
+                tags: filter_tags(node.tags.iter().map(|t| (t.key.as_str(), t.val.as_str()))),
+            },
+        );
+    }
+    for way in raw.ways.values() {
+        let mut nodes = Vec::new();
+        let mut valid = true;
+        for node_ref in &way.nodes {
+            match node_ref {
+                osm_xml::UnresolvedReference::Node(id) => {
+                    nodes.push(*id);
+                }
+                // Don't handle nested ways/relations yet
+                _ => {
+                    valid = false;
+                }
+            }
+        }
+        if !valid {
+            continue;
+        }
+        doc.ways.insert(
+            way.id,
+            Way {
+                id: way.id,
+                nodes,
+                tags: filter_tags(way.tags.iter().map(|t| (t.key.as_str(), t.val.as_str()))),
+            },
+        );
+    }
+    for rel in raw.relations.values() {
+        let mut members = Vec::new();
+        for member in &rel.members {
+            members.push(match member {
+                osm_xml::Member::Node(osm_xml::UnresolvedReference::Node(id), role) => {
+                    (OsmID::Node(*id), role.clone())
+                }
+                osm_xml::Member::Way(osm_xml::UnresolvedReference::Way(id), role) => {
+                    (OsmID::Way(*id), role.clone())
+                }
+                osm_xml::Member::Relation(osm_xml::UnresolvedReference::Relation(id), role) => {
+                    (OsmID::Relation(*id), role.clone())
+                }
+                _ => {
+                    println!("Relation {} has weird member {:?}", rel.id, member);
+                    continue;
+                }
+            });
+        }
+        doc.relations.insert(
+            rel.id,
+            Relation {
+                id: rel.id,
+                tags: filter_tags(rel.tags.iter().map(|t| (t.key.as_str(), t.val.as_str()))),
+                members,
+            },
+        );
+    }
+    timer.stop("convert OSM XML");
+    doc
+}
+
+// Nodes, ways, and relations are all kept or dropped in one streaming pass, so that a huge extract
+// doesn't have to fit in memory when only a small piece of it is clipped out. This relies on the
+// usual sorted order of a .pbf (all nodes, then ways, then relations). Ways crossing the clipping
+// boundary need the nodes outside of it too; those are fetched in a second pass that stops at the
+// first way.
+fn read_pbf(path: &str, clip: &Option<ClipPolygon>, timer: &mut Timer) -> Document {
+    use osmpbfreader::{OsmId, OsmObj, OsmPbfReader};
+
+    let mut pbf = OsmPbfReader::new(std::fs::File::open(path).unwrap());
+    let mut doc = Document::new();
+
+    timer.start(format!("read {}", path));
+    let mut seen_ways = false;
+    let mut missing_nodes = HashSet::new();
+    for obj in pbf.iter() {
+        match obj.expect("PBF parsing failed") {
+            OsmObj::Node(node) => {
+                if seen_ways {
+                    panic!(
+                        "{} isn't sorted; node {} comes after some ways. Try osmium sort.",
+                        path, node.id.0
+                    );
+                }
+                let pt = LonLat::new(node.lon(), node.lat());
+                if clip.as_ref().map(|c| c.contains(pt)).unwrap_or(true) {
+                    doc.nodes.insert(
+                        node.id.0,
+                        Node {
+                            id: node.id.0,
+                            pt,
+                            tags: filter_tags(
+                                node.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                            ),
+                        },
+                    );
+                }
+            }
+            OsmObj::Way(way) => {
+                seen_ways = true;
+                let nodes: Vec<i64> = way.nodes.iter().map(|id| id.0).collect();
+                if !nodes.iter().any(|id| doc.nodes.contains_key(id)) {
+                    continue;
+                }
+                for id in &nodes {
+                    if !doc.nodes.contains_key(id) {
+                        missing_nodes.insert(*id);
+                    }
+                }
+                doc.ways.insert(
+                    way.id.0,
+                    Way {
+                        id: way.id.0,
+                        nodes,
+                        tags: filter_tags(way.tags.iter().map(|(k, v)| (k.as_str(), v.as_str()))),
+                    },
+                );
+            }
+            OsmObj::Relation(rel) => {
+                seen_ways = true;
+                let members: Vec<(OsmID, String)> = rel
+                    .refs
+                    .iter()
+                    .map(|r| {
+                        let id = match r.member {
+                            OsmId::Node(id) => OsmID::Node(id.0),
+                            OsmId::Way(id) => OsmID::Way(id.0),
+                            OsmId::Relation(id) => OsmID::Relation(id.0),
+                        };
+                        (id, r.role.to_string())
+                    })
+                    .collect();
+                // Missing nodes of crossing ways aren't in the doc yet, but they will be
+                if members.iter().any(|(id, _)| match id {
+                    OsmID::Node(n) => missing_nodes.contains(n) || doc.contains(*id),
+                    _ => doc.contains(*id),
+                }) {
+                    doc.relations.insert(
+                        rel.id.0,
+                        Relation {
+                            id: rel.id.0,
+                            tags: filter_tags(
+                                rel.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                            ),
+                            members,
+                        },
+                    );
+                }
+            }
+        }
+    }
+    timer.stop(format!("read {}", path));
+
+    if !missing_nodes.is_empty() {
+        timer.start(format!(
+            "read {} extra nodes from {}",
+            missing_nodes.len(),
+            path
+        ));
+        pbf.rewind().unwrap();
+        for obj in pbf.iter() {
+            match obj.expect("PBF parsing failed") {
+                OsmObj::Node(node) => {
+                    if missing_nodes.contains(&node.id.0) {
+                        doc.nodes.insert(
+                            node.id.0,
+                            Node {
+                                id: node.id.0,
+                                pt: LonLat::new(node.lon(), node.lat()),
+                                tags: filter_tags(
+                                    node.tags.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+                                ),
+                            },
+                        );
+                    }
+                }
+                // No more nodes
+                _ => {
+                    break;
+                }
+            }
+        }
+        timer.stop(format!(
+            "read {} extra nodes from {}",
+            missing_nodes.len(),
+            path
+        ));
+    }
+
+    println!(
+        "OSM doc has {} nodes, {} ways, {} relations",
+        doc.nodes.len(),
+        doc.ways.len(),
+        doc.relations.len()
+    );
+    doc
+}
+
+// Is the file at path newer than all of the inputs? False if anything can't be checked.
+fn is_newer_than(path: &str, inputs: &[&str]) -> bool {
+    let modified = |p: &str| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+    match modified(path) {
+        Some(t) => inputs
+            .iter()
+            .all(|input| modified(input).map(|x| x <= t).unwrap_or(false)),
+        None => false,
+    }
+}
+
+fn clip_document(mut doc: Document, clip: &ClipPolygon) -> Document {
+    let inside: HashSet<i64> = doc
+        .nodes
+        .values()
+        .filter(|n| clip.contains(n.pt))
+        .map(|n| n.id)
+        .collect();
+    abstutil::retain_btreemap(&mut doc.ways, |_, way| {
+        way.nodes.iter().any(|id| inside.contains(id))
+    });
+    let mut keep_nodes = inside;
+    for way in doc.ways.values() {
+        keep_nodes.extend(way.nodes.iter().cloned());
+    }
+    abstutil::retain_btreemap(&mut doc.nodes, |id, _| keep_nodes.contains(id));
+
+    let mut relations = std::mem::replace(&mut doc.relations, BTreeMap::new());
+    abstutil::retain_btreemap(&mut relations, |_, rel| {
+        rel.members.iter().any(|(id, _)| doc.contains(*id))
+    });
+    doc.relations = relations;
+
+    println!(
+        "After clipping, OSM doc has {} nodes, {} ways, {} relations",
+        doc.nodes.len(),
+        doc.ways.len(),
+        doc.relations.len()
+    );
+    doc
+}
+
+impl Document {
+    fn contains(&self, id: OsmID) -> bool {
+        match id {
+            OsmID::Node(n) => self.nodes.contains_key(&n),
+            OsmID::Way(w) => self.ways.contains_key(&w),
+            // Nested relations aren't handled by anything downstream, so only keep relations that
+            // refer to nodes or ways directly.
+            OsmID::Relation(_) => false,
+        }
+    }
+}
+
+fn filter_tags<'a, I: Iterator<Item = (&'a str, &'a str)>>(tags: I) -> BTreeMap<String, String> {
+    tags.filter_map(|(k, v)| {
+        // Toss out really useless metadata.
+        if k.starts_with("tiger:") || k.starts_with("old_name:") {
+            None
+        } else {
+            Some((k.to_string(), v.to_string()))
+        }
+    })
+    .collect()
+}
+
+struct ClipPolygon {
+    pts: Vec<LonLat>,
+    bounds: GPSBounds,
+}
+
+impl ClipPolygon {
+    fn new(pts: Vec<LonLat>) -> ClipPolygon {
+        let mut bounds = GPSBounds::new();
+        for pt in &pts {
+            bounds.update(*pt);
+        }
+        ClipPolygon { pts, bounds }
+    }
+
+    // Ray casting. Cheaper than triangulating the polygon, which matters for the millions of nodes
+    // in a big extract. Points exactly on the boundary count as inside, so a road ending right at
+    // the edge isn't lost.
+    fn contains(&self, pt: LonLat) -> bool {
+        if !self.bounds.contains(pt) {
+            return false;
+        }
+        let (x, y) = (pt.x(), pt.y());
+        let mut inside = false;
+        let mut j = self.pts.len() - 1;
+        for i in 0..self.pts.len() {
+            let (xi, yi) = (self.pts[i].x(), self.pts[i].y());
+            let (xj, yj) = (self.pts[j].x(), self.pts[j].y());
+            let cross = (xj - xi) * (y - yi) - (yj - yi) * (x - xi);
+            if cross == 0.0
+                && x >= xi.min(xj)
+                && x <= xi.max(xj)
+                && y >= yi.min(yj)
+                && y <= yi.max(yj)
+            {
+                return true;
+            }
+            if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+                inside = !inside;
+            }
+            j = i;
+        }
+        inside
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ll(x: f64, y: f64) -> LonLat {
+        LonLat::new(x, y)
+    }
+
+    fn square() -> ClipPolygon {
+        ClipPolygon::new(vec![ll(0.0, 0.0), ll(1.0, 0.0), ll(1.0, 1.0), ll(0.0, 1.0)])
+    }
+
+    // A U shape, open to the north
+    fn concave() -> ClipPolygon {
+        ClipPolygon::new(vec![
+            ll(0.0, 0.0),
+            ll(3.0, 0.0),
+            ll(3.0, 3.0),
+            ll(2.0, 3.0),
+            ll(2.0, 1.0),
+            ll(1.0, 1.0),
+            ll(1.0, 3.0),
+            ll(0.0, 3.0),
+        ])
+    }
+
+    #[test]
+    fn clip_polygon_square() {
+        let clip = square();
+        assert!(clip.contains(ll(0.5, 0.5)));
+        assert!(!clip.contains(ll(1.5, 0.5)));
+        assert!(!clip.contains(ll(-0.1, 0.5)));
+        // Every edge and corner counts as inside
+        for pt in &[
+            ll(0.5, 0.0),
+            ll(1.0, 0.5),
+            ll(0.5, 1.0),
+            ll(0.0, 0.5),
+            ll(0.0, 0.0),
+            ll(1.0, 1.0),
+        ] {
+            assert!(clip.contains(*pt), "{} should be inside", pt);
+        }
+    }
+
+    #[test]
+    fn clip_polygon_concave() {
+        let clip = concave();
+        // Both arms and the base
+        assert!(clip.contains(ll(0.5, 2.5)));
+        assert!(clip.contains(ll(2.5, 2.5)));
+        assert!(clip.contains(ll(1.5, 0.5)));
+        // The notch is inside the bounding box, but not the polygon
+        assert!(!clip.contains(ll(1.5, 2.0)));
+        // The notch's edges are still part of the boundary
+        assert!(clip.contains(ll(1.5, 1.0)));
+        assert!(clip.contains(ll(1.0, 2.0)));
+    }
+
+    fn node(doc: &mut Document, id: i64, x: f64, y: f64) {
+        doc.nodes.insert(
+            id,
+            Node {
+                id,
+                pt: ll(x, y),
+                tags: BTreeMap::new(),
+            },
+        );
+    }
+
+    fn way(doc: &mut Document, id: i64, nodes: Vec<i64>) {
+        doc.ways.insert(
+            id,
+            Way {
+                id,
+                nodes,
+                tags: BTreeMap::new(),
+            },
+        );
+    }
+
+    fn relation(doc: &mut Document, id: i64, members: Vec<OsmID>) {
+        doc.relations.insert(
+            id,
+            Relation {
+                id,
+                tags: BTreeMap::new(),
+                members: members.into_iter().map(|m| (m, String::new())).collect(),
+            },
+        );
+    }
+
+    #[test]
+    fn clip_document_complete_ways() {
+        let mut doc = Document::new();
+        node(&mut doc, 1, 0.5, 0.5);
+        node(&mut doc, 2, 2.0, 0.5);
+        node(&mut doc, 3, 3.0, 0.5);
+        node(&mut doc, 4, 1.0, 1.0);
+        node(&mut doc, 5, 2.0, 2.0);
+        // Crosses the boundary
+        way(&mut doc, 10, vec![1, 2]);
+        // Entirely outside
+        way(&mut doc, 11, vec![2, 3]);
+        // Touches the boundary at a corner
+        way(&mut doc, 12, vec![4, 5]);
+        relation(&mut doc, 20, vec![OsmID::Way(10)]);
+        relation(&mut doc, 21, vec![OsmID::Way(11), OsmID::Node(3)]);
+        relation(&mut doc, 22, vec![OsmID::Node(2)]);
+        relation(&mut doc, 23, vec![OsmID::Relation(20)]);
+
+        let doc = clip_document(doc, &square());
+        assert_eq!(doc.ways.keys().cloned().collect::<Vec<_>>(), vec![10, 12]);
+        // Nodes outside the polygon are kept when a kept way uses them; node 3 isn't
+        assert_eq!(
+            doc.nodes.keys().cloned().collect::<Vec<_>>(),
+            vec![1, 2, 4, 5]
+        );
+        // Node 2 survived as part of way 10, so relation 22 does too. Nested relations are
+        // dropped.
+        assert_eq!(
+            doc.relations.keys().cloned().collect::<Vec<_>>(),
+            vec![20, 22]
+        );
+    }
+}
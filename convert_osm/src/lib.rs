@@ -1,4 +1,5 @@
 mod clip;
+mod document;
 mod osm_reader;
 mod split_ways;
 mod srtm;
@@ -1,10 +1,10 @@
-use abstutil::{FileWithProgress, Timer};
+use crate::document::{Document, OsmID};
+use abstutil::Timer;
 use geom::{GPSBounds, HashablePt2D, LonLat, PolyLine, Polygon, Pt2D, Ring};
 use map_model::raw::{
     OriginalBuilding, RawArea, RawBuilding, RawMap, RawParkingLot, RawRoad, RestrictionType,
 };
 use map_model::{osm, AreaType};
-use osm_xml;
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 
 pub fn extract_osm(
@@ -28,18 +28,12 @@ pub fn extract_osm(
     // Amenities (location, name, amenity type)
     Vec<(Pt2D, String, String)>,
 ) {
-    let (reader, done) = FileWithProgress::new(osm_path).unwrap();
-    let doc = osm_xml::OSM::parse(reader).expect("OSM parsing failed");
-    println!(
-        "OSM doc has {} nodes, {} ways, {} relations",
-        doc.nodes.len(),
-        doc.ways.len(),
-        doc.relations.len()
-    );
-    done(timer);
+    let clip_pts = maybe_clip_path
+        .as_ref()
+        .map(|path| LonLat::read_osmosis_polygon(path.to_string()).unwrap());
+    let doc: Document = crate::document::read(osm_path, maybe_clip_path, timer);
 
-    let mut map = if let Some(path) = maybe_clip_path {
-        let pts = LonLat::read_osmosis_polygon(path.to_string()).unwrap();
+    let mut map = if let Some(pts) = clip_pts {
         let mut gps_bounds = GPSBounds::new();
         for pt in &pts {
             gps_bounds.update(*pt);
@@ -52,7 +46,7 @@ pub fn extract_osm(
     } else {
         let mut m = RawMap::blank(city_name, map_name);
         for node in doc.nodes.values() {
-            m.gps_bounds.update(LonLat::new(node.lon, node.lat));
+            m.gps_bounds.update(node.pt);
         }
         m.boundary_polygon = m.gps_bounds.to_bounds().get_rectangle();
         m
@@ -67,10 +61,10 @@ pub fn extract_osm(
     timer.start_iter("processing OSM nodes", doc.nodes.len());
     for node in doc.nodes.values() {
         timer.next();
-        let pt = Pt2D::forcibly_from_gps(LonLat::new(node.lon, node.lat), &map.gps_bounds);
+        let pt = Pt2D::forcibly_from_gps(node.pt, &map.gps_bounds);
         osm_node_ids.insert(pt.to_hashable(), node.id);
 
-        let tags = tags_to_map(&node.tags);
+        let tags = &node.tags;
         if tags.get(osm::HIGHWAY) == Some(&"traffic_signals".to_string()) {
             traffic_signals.insert(pt.to_hashable());
         }
@@ -101,22 +95,18 @@ pub fn extract_osm(
 
         let mut valid = true;
         let mut gps_pts = Vec::new();
-        for node_ref in &way.nodes {
-            match doc.resolve_reference(node_ref) {
-                osm_xml::Reference::Node(node) => {
-                    gps_pts.push(LonLat::new(node.lon, node.lat));
-                }
-                // Don't handle nested ways/relations yet
-                _ => {
-                    valid = false;
-                }
+        for id in &way.nodes {
+            if let Some(node) = doc.nodes.get(id) {
+                gps_pts.push(node.pt);
+            } else {
+                valid = false;
             }
         }
         if !valid || gps_pts.is_empty() {
             continue;
         }
         let pts = map.gps_bounds.forcibly_convert(&gps_pts);
-        let mut tags = tags_to_map(&way.tags);
+        let mut tags = way.tags.clone();
         tags.insert(osm::OSM_WAY_ID.to_string(), way.id.to_string());
 
         if is_road(&tags) {
@@ -235,15 +225,15 @@ pub fn extract_osm(
     timer.start_iter("processing OSM relations", doc.relations.len());
     for rel in doc.relations.values() {
         timer.next();
-        let mut tags = tags_to_map(&rel.tags);
+        let mut tags = rel.tags.clone();
         tags.insert(osm::OSM_REL_ID.to_string(), rel.id.to_string());
         if let Some(at) = get_area_type(&tags) {
             if tags.get("type") == Some(&"multipolygon".to_string()) {
                 let mut ok = true;
                 let mut pts_per_way: Vec<(i64, Vec<Pt2D>)> = Vec::new();
-                for member in &rel.members {
+                for (member, role) in &rel.members {
                     match member {
-                        osm_xml::Member::Way(osm_xml::UnresolvedReference::Way(id), ref role) => {
+                        OsmID::Way(id) => {
                             // If the way is clipped out, that's fine
                             if let Some(pts) = id_to_way.get(id) {
                                 if role == "outer" {
@@ -278,9 +268,9 @@ pub fn extract_osm(
             let mut via_node_id: Option<i64> = None;
             let mut via_way_id: Option<i64> = None;
             let mut to_way_id: Option<i64> = None;
-            for member in &rel.members {
+            for (member, role) in &rel.members {
                 match member {
-                    osm_xml::Member::Way(osm_xml::UnresolvedReference::Way(id), ref role) => {
+                    OsmID::Way(id) => {
                         if role == "from" {
                             from_way_id = Some(*id);
                         } else if role == "to" {
@@ -289,12 +279,12 @@ pub fn extract_osm(
                             via_way_id = Some(*id);
                         }
                     }
-                    osm_xml::Member::Node(osm_xml::UnresolvedReference::Node(id), ref role) => {
+                    OsmID::Node(id) => {
                         if role == "via" {
                             via_node_id = Some(*id);
                         }
                     }
-                    _ => unreachable!(),
+                    OsmID::Relation(_) => unreachable!(),
                 }
             }
             if let Some(restriction) = tags.get("restriction") {
@@ -320,8 +310,8 @@ pub fn extract_osm(
             if let Some(pts) = rel
                 .members
                 .iter()
-                .filter_map(|x| match x {
-                    osm_xml::Member::Way(osm_xml::UnresolvedReference::Way(id), ref role) => {
+                .filter_map(|(member, role)| match member {
+                    OsmID::Way(id) => {
                         if role == "outer" {
                             Some(*id)
                         } else {
@@ -404,20 +394,6 @@ pub fn extract_osm(
     )
 }
 
-fn tags_to_map(raw_tags: &[osm_xml::Tag]) -> BTreeMap<String, String> {
-    raw_tags
-        .iter()
-        .filter_map(|tag| {
-            // Toss out really useless metadata.
-            if tag.key.starts_with("tiger:") || tag.key.starts_with("old_name:") {
-                None
-            } else {
-                Some((tag.key.clone(), tag.val.clone()))
-            }
-        })
-        .collect()
-}
-
 fn is_road(tags: &BTreeMap<String, String>) -> bool {
     if !tags.contains_key(osm::HIGHWAY) {
         return false;
@@ -1,5 +1,5 @@
 use crate::assets::Assets;
-use crate::{hotkey, Key, ScreenDims, ScreenPt, ScreenRectangle, UserInput};
+use crate::{hotkey, Event, Key, ScreenDims, ScreenPt, ScreenRectangle, UserInput};
 use abstutil::Timer;
 use geom::{Bounds, Pt2D};
 use serde::{Deserialize, Serialize};
@@ -46,6 +46,14 @@ pub struct Canvas {
     // Kind of just ezgui state awkwardly stuck here...
     pub(crate) lctrl_held: bool,
     pub(crate) lshift_held: bool,
+    // Keyboard focus is shared by every Composite on the screen. This is the one that has it, and
+    // whether it's handled the current event yet. If it misses a whole event, it's probably been
+    // dropped, so anybody can take focus again.
+    pub(crate) keyboard_focus: Option<usize>,
+    pub(crate) keyboard_focus_seen: bool,
+    pub(crate) next_composite_id: usize,
+    // While recording or replaying input, always start from the default camera.
+    pub(crate) ignore_saved_camera: bool,
 }
 
 impl Canvas {
@@ -76,6 +84,10 @@ impl Canvas {
 
             lctrl_held: false,
             lshift_held: false,
+            keyboard_focus: None,
+            keyboard_focus_seen: false,
+            next_composite_id: 0,
+            ignore_saved_camera: false,
         }
     }
 
@@ -183,6 +195,39 @@ impl Canvas {
         self.cam_y = ((self.cam_zoom / old_zoom) * (focus.1 + self.cam_y)) - focus.1;
     }
 
+    // Remembers things like where the cursor is and which modifier keys are held.
+    pub(crate) fn track_input(&mut self, input: &UserInput) {
+        if input.event == Event::KeyPress(Key::LeftControl) {
+            self.lctrl_held = true;
+        }
+        if input.event == Event::KeyRelease(Key::LeftControl) {
+            self.lctrl_held = false;
+        }
+        if input.event == Event::KeyPress(Key::LeftShift) {
+            self.lshift_held = true;
+        }
+        if input.event == Event::KeyRelease(Key::LeftShift) {
+            self.lshift_held = false;
+        }
+
+        if !self.keyboard_focus_seen {
+            self.keyboard_focus = None;
+        }
+        self.keyboard_focus_seen = false;
+
+        if let Some(pt) = input.get_moved_mouse() {
+            self.cursor_x = pt.x;
+            self.cursor_y = pt.y;
+        }
+
+        if input.event == Event::WindowGainedCursor {
+            self.window_has_cursor = true;
+        }
+        if input.window_lost_cursor() {
+            self.window_has_cursor = false;
+        }
+    }
+
     pub(crate) fn start_drawing(&self) {
         self.covered_areas.borrow_mut().clear();
     }
@@ -277,6 +322,9 @@ impl Canvas {
     }
 
     pub fn save_camera_state(&self, map_name: &str) {
+        if self.ignore_saved_camera {
+            return;
+        }
         let state = CameraState {
             cam_x: self.cam_x,
             cam_y: self.cam_y,
@@ -287,6 +335,9 @@ impl Canvas {
 
     // True if this succeeds
     pub fn load_camera_state(&mut self, map_name: &str) -> bool {
+        if self.ignore_saved_camera {
+            return false;
+        }
         match abstutil::maybe_read_json::<CameraState>(
             abstutil::path_camera_state(map_name),
             &mut Timer::throwaway(),
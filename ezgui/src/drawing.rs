@@ -1,8 +1,7 @@
 use crate::assets::Assets;
-use crate::backend::{GfxCtxInnards, PrerenderInnards};
+use crate::backend::{self, GfxCtxInnards, PrerenderInnards};
 use crate::{
-    Canvas, Color, Drawable, FancyColor, GeomBatch, ScreenDims, ScreenPt, ScreenRectangle, Style,
-    Text,
+    Canvas, Color, FancyColor, GeomBatch, ScreenDims, ScreenPt, ScreenRectangle, Style, Text,
 };
 use geom::{ArrowCap, Bounds, Circle, Distance, Line, Polygon, Pt2D};
 use std::cell::Cell;
@@ -37,7 +36,8 @@ impl Uniforms {
 }
 
 pub struct GfxCtx<'a> {
-    pub(crate) inner: GfxCtxInnards<'a>,
+    // None when running without a window
+    inner: Option<GfxCtxInnards<'a>>,
     uniforms: Uniforms,
 
     screencap_mode: bool,
@@ -61,7 +61,7 @@ impl<'a> GfxCtx<'a> {
     ) -> GfxCtx<'a> {
         let uniforms = Uniforms::new(canvas);
         GfxCtx {
-            inner: prerender.inner.draw_new_frame(),
+            inner: prerender.inner.as_ref().map(|inner| inner.draw_new_frame()),
             uniforms,
             canvas,
             style,
@@ -112,7 +112,9 @@ impl<'a> GfxCtx<'a> {
     }
 
     pub fn clear(&mut self, color: Color) {
-        self.inner.clear(color);
+        if let Some(ref mut inner) = self.inner {
+            inner.clear(color);
+        }
     }
 
     pub fn draw_line(&mut self, color: Color, thickness: Distance, line: &Line) {
@@ -162,8 +164,9 @@ impl<'a> GfxCtx<'a> {
     }
 
     pub fn redraw(&mut self, obj: &Drawable) {
-        self.inner
-            .redraw(obj, &self.uniforms, &self.prerender.inner);
+        if let (Some(inner), Some(obj)) = (&mut self.inner, &obj.0) {
+            inner.redraw(obj, &self.uniforms, self.prerender.innards());
+        }
         self.num_draw_calls += 1;
 
         // println!("{:?}", backtrace::Backtrace::new());
@@ -177,11 +180,15 @@ impl<'a> GfxCtx<'a> {
 
     // TODO Stateful API :(
     pub fn enable_clipping(&mut self, rect: ScreenRectangle) {
-        self.inner.enable_clipping(rect, self.canvas);
+        if let Some(ref mut inner) = self.inner {
+            inner.enable_clipping(rect, self.canvas);
+        }
     }
 
     pub fn disable_clipping(&mut self) {
-        self.inner.disable_clipping(self.canvas);
+        if let Some(ref mut inner) = self.inner {
+            inner.disable_clipping(self.canvas);
+        }
     }
 
     // Canvas stuff.
@@ -216,10 +223,12 @@ impl<'a> GfxCtx<'a> {
         ];
         self.num_forks += 1;
         // Temporarily disable clipping if needed.
-        let clip = self.inner.take_clip();
+        let clip = self.inner.as_mut().map(|inner| inner.take_clip());
         batch.draw(self);
         self.unfork();
-        self.inner.restore_clip(clip);
+        if let (Some(inner), Some(clip)) = (&mut self.inner, clip) {
+            inner.restore_clip(clip);
+        }
     }
 
     pub fn get_screen_bounds(&self) -> Bounds {
@@ -260,12 +269,22 @@ impl<'a> GfxCtx<'a> {
     pub fn style(&self) -> &Style {
         &self.style
     }
+
+    pub(crate) fn finish(self) {
+        if let Some(inner) = self.inner {
+            inner.finish();
+        }
+    }
 }
 
+// Something that's been sent to the GPU already. Without a window, nothing is uploaded.
+pub struct Drawable(Option<backend::Drawable>);
+
 // TODO Don't expose this directly
 // TODO Rename or something maybe. This actually owns all the permanent state of everything.
 pub struct Prerender {
-    pub(crate) inner: PrerenderInnards,
+    // None when running without a window, like in tests
+    pub(crate) inner: Option<PrerenderInnards>,
     pub(crate) assets: Assets,
     pub(crate) num_uploads: Cell<usize>,
 }
@@ -277,7 +296,10 @@ impl Prerender {
     }
 
     pub fn get_total_bytes_uploaded(&self) -> usize {
-        self.inner.total_bytes_uploaded.get()
+        self.inner
+            .as_ref()
+            .map(|inner| inner.total_bytes_uploaded.get())
+            .unwrap_or(0)
     }
 
     pub(crate) fn upload_temporary(&self, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
@@ -287,10 +309,22 @@ impl Prerender {
     fn actually_upload(&self, permanent: bool, list: Vec<(FancyColor, &Polygon)>) -> Drawable {
         // println!("{:?}", backtrace::Backtrace::new());
         self.num_uploads.set(self.num_uploads.get() + 1);
-        self.inner.actually_upload(permanent, list)
+        Drawable(
+            self.inner
+                .as_ref()
+                .map(|inner| inner.actually_upload(permanent, list)),
+        )
     }
 
     pub fn request_redraw(&self) {
-        self.inner.request_redraw()
+        if let Some(ref inner) = self.inner {
+            inner.request_redraw();
+        }
+    }
+
+    pub(crate) fn innards(&self) -> &PrerenderInnards {
+        self.inner
+            .as_ref()
+            .expect("Drawing something without a window")
     }
 }
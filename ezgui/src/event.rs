@@ -1,10 +1,11 @@
 use crate::ScreenPt;
 use geom::Duration;
+use serde::{Deserialize, Serialize};
 use winit::event::{
     ElementState, KeyboardInput, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Event {
     // Used to initialize the application and also to recalculate menu state when some other event
     // is used.
@@ -96,7 +97,7 @@ fn scroll_wheel_multiplier() -> f64 {
     1.0
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
 pub enum Key {
     // Case is unspecified.
     // TODO Would be cool to represent A and UpperA, but then release semantics get weird... hold
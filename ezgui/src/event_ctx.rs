@@ -100,13 +100,17 @@ impl<'a> EventCtx<'a> {
     }
 
     pub fn monitor_scale_factor(&self) -> f64 {
-        self.prerender.inner.monitor_scale_factor()
+        self.prerender
+            .inner
+            .as_ref()
+            .map(|inner| inner.monitor_scale_factor())
+            .unwrap_or(1.0)
     }
 
     pub(crate) fn cursor_clickable(&mut self) {
-        self.prerender
-            .inner
-            .set_cursor_icon(winit::window::CursorIcon::Hand);
+        if let Some(ref inner) = self.prerender.inner {
+            inner.set_cursor_icon(winit::window::CursorIcon::Hand);
+        }
     }
 
     pub fn style(&self) -> &Style {
@@ -163,7 +167,7 @@ impl<'a> LoadingScreen<'a> {
 
     fn redraw(&mut self) {
         // TODO Ideally we wouldn't have to do this, but text rendering is still slow. :)
-        if elapsed_seconds(self.last_drawn) < 0.5 {
+        if self.prerender.inner.is_none() || elapsed_seconds(self.last_drawn) < 0.5 {
             return;
         }
         self.last_drawn = Instant::now();
@@ -187,7 +191,7 @@ impl<'a> LoadingScreen<'a> {
             &draw,
         );
 
-        g.inner.finish();
+        g.finish();
     }
 }
 
@@ -0,0 +1,308 @@
+use crate::assets::Assets;
+use crate::{text, Canvas, Event, EventCtx, GfxCtx, Key, Prerender, ScreenPt, Style, UserInput};
+use std::cell::Cell;
+
+// Runs widgets without a window, so panels can be unit tested. Nothing is uploaded to a GPU, but
+// text is still measured with the real fonts, so layout matches a real window of the same size.
+// Fonts are found relative to the current directory, like in the game.
+//
+// Each method that sends an event takes a callback that handles it, usually by calling event() on
+// a Composite or State, and returns what the callback returns. Like in a real window, the mouse is
+// only on top of a panel after the panel has been drawn once.
+pub struct Harness {
+    canvas: Canvas,
+    prerender: Prerender,
+    style: Style,
+}
+
+impl Harness {
+    pub fn new(window_width: f64, window_height: f64) -> Harness {
+        Harness {
+            canvas: Canvas::new(window_width, window_height),
+            prerender: Prerender {
+                assets: Assets::new(
+                    text::DEFAULT_FONT_SIZE,
+                    "../data/system/fonts".to_string(),
+                    1.0,
+                ),
+                num_uploads: Cell::new(0),
+                inner: None,
+            },
+            style: Style::standard(),
+        }
+    }
+
+    // The same sort of context that the GUI is first created with. Use this to build widgets.
+    pub fn setup<O, F: FnOnce(&mut EventCtx) -> O>(&mut self, f: F) -> O {
+        self.handle(Event::NoOp, true, f)
+    }
+
+    pub fn event<O, F: FnOnce(&mut EventCtx) -> O>(&mut self, ev: Event, f: F) -> O {
+        self.handle(ev, false, f)
+    }
+
+    pub fn move_mouse<O, F: FnOnce(&mut EventCtx) -> O>(&mut self, pt: ScreenPt, f: F) -> O {
+        self.event(Event::MouseMovedTo(pt), f)
+    }
+
+    // Moves the mouse somewhere, then presses and releases the left button. Returns the first
+    // thing the callback returns for any of those events.
+    pub fn click<O, F: FnMut(&mut EventCtx) -> Option<O>>(
+        &mut self,
+        pt: ScreenPt,
+        mut f: F,
+    ) -> Option<O> {
+        let mut result = None;
+        for ev in vec![
+            Event::MouseMovedTo(pt),
+            Event::LeftMouseButtonDown,
+            Event::LeftMouseButtonUp,
+        ] {
+            let x = self.event(ev, &mut f);
+            if result.is_none() {
+                result = x;
+            }
+        }
+        result
+    }
+
+    // Presses and releases a key. Returns the first thing the callback returns for either event.
+    pub fn press_key<O, F: FnMut(&mut EventCtx) -> Option<O>>(
+        &mut self,
+        key: Key,
+        mut f: F,
+    ) -> Option<O> {
+        let x = self.event(Event::KeyPress(key), &mut f);
+        let y = self.event(Event::KeyRelease(key), &mut f);
+        x.or(y)
+    }
+
+    pub fn resize<O, F: FnOnce(&mut EventCtx) -> O>(
+        &mut self,
+        window_width: f64,
+        window_height: f64,
+        f: F,
+    ) -> O {
+        self.canvas.window_width = window_width;
+        self.canvas.window_height = window_height;
+        self.event(Event::WindowResized(window_width, window_height), f)
+    }
+
+    // Nothing is actually drawn, but this runs the same code as drawing to a window.
+    pub fn draw<F: FnOnce(&mut GfxCtx)>(&self, f: F) {
+        let mut g = GfxCtx::new(&self.prerender, &self.canvas, &self.style, false);
+        self.canvas.start_drawing();
+        f(&mut g);
+        g.finish();
+    }
+
+    pub fn canvas(&self) -> &Canvas {
+        &self.canvas
+    }
+
+    fn handle<O, F: FnOnce(&mut EventCtx) -> O>(
+        &mut self,
+        ev: Event,
+        fake_mouseover: bool,
+        f: F,
+    ) -> O {
+        let input = UserInput::new(ev, &self.canvas);
+        self.canvas.track_input(&input);
+        f(&mut EventCtx {
+            fake_mouseover,
+            input,
+            canvas: &mut self.canvas,
+            prerender: &self.prerender,
+            style: &mut self.style,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Btn, Checkbox, Choice, Composite, Outcome, PersistentSplit, Widget};
+
+    fn panel(ctx: &mut EventCtx) -> Composite {
+        Composite::new(Widget::col(vec![
+            Btn::text_fg("first").build_def(ctx, None),
+            Btn::text_fg("second").build_def(ctx, crate::hotkey(Key::S)),
+            Checkbox::text(ctx, "toggle me", None, false),
+        ]))
+        .build(ctx)
+    }
+
+    #[test]
+    fn layout() {
+        let mut h = Harness::new(1920.0, 1080.0);
+        let c = h.setup(panel);
+        let first = c.rect_of("first").clone();
+        let second = c.rect_of("second").clone();
+        assert!(first.width() > 0.0 && first.height() > 0.0);
+        assert!(first.y2 <= second.y1);
+        // Centered by default
+        assert!(c.center_of_panel().x > 0.4 * 1920.0 && c.center_of_panel().x < 0.6 * 1920.0);
+    }
+
+    #[test]
+    fn click_buttons() {
+        let mut h = Harness::new(1920.0, 1080.0);
+        let mut c = h.setup(panel);
+        h.draw(|g| c.draw(g));
+        let pt = c.center_of("second");
+        assert_eq!(
+            h.click(pt, |ctx| c.event(ctx)),
+            Some(Outcome::Clicked("second".to_string()))
+        );
+        // Clicking outside of the panel does nothing
+        assert_eq!(h.click(ScreenPt::new(1.0, 1.0), |ctx| c.event(ctx)), None);
+        assert_eq!(
+            h.press_key(Key::S, |ctx| c.event(ctx)),
+            Some(Outcome::Clicked("second".to_string()))
+        );
+    }
+
+    #[test]
+    fn checkbox() {
+        let mut h = Harness::new(1920.0, 1080.0);
+        let mut c = h.setup(panel);
+        h.draw(|g| c.draw(g));
+        assert!(!c.is_checked("toggle me"));
+        let pt = c.center_of("toggle me");
+        assert_eq!(h.click(pt, |ctx| c.event(ctx)), None);
+        assert!(c.is_checked("toggle me"));
+    }
+
+    #[test]
+    fn keyboard_focus() {
+        let mut h = Harness::new(1920.0, 1080.0);
+        let mut c = h.setup(panel);
+        h.draw(|g| c.draw(g));
+        // Nothing has focus yet
+        assert_eq!(h.press_key(Key::Enter, |ctx| c.event(ctx)), None);
+
+        assert_eq!(h.press_key(Key::Tab, |ctx| c.event(ctx)), None);
+        assert_eq!(h.press_key(Key::Tab, |ctx| c.event(ctx)), None);
+        h.draw(|g| c.draw(g));
+        assert_eq!(
+            h.press_key(Key::Enter, |ctx| c.event(ctx)),
+            Some(Outcome::Clicked("second".to_string()))
+        );
+
+        assert_eq!(h.press_key(Key::Tab, |ctx| c.event(ctx)), None);
+        assert_eq!(h.press_key(Key::Space, |ctx| c.event(ctx)), None);
+        assert!(c.is_checked("toggle me"));
+
+        // Tab leaves the panel at the end, then starts again at the beginning
+        assert_eq!(h.press_key(Key::Tab, |ctx| c.event(ctx)), None);
+        assert_eq!(h.press_key(Key::Space, |ctx| c.event(ctx)), None);
+        assert!(c.is_checked("toggle me"));
+        assert_eq!(h.press_key(Key::Tab, |ctx| c.event(ctx)), None);
+        assert_eq!(
+            h.press_key(Key::Space, |ctx| c.event(ctx)),
+            Some(Outcome::Clicked("first".to_string()))
+        );
+
+        // Clicking somewhere clears focus
+        assert_eq!(h.click(ScreenPt::new(1.0, 1.0), |ctx| c.event(ctx)), None);
+        assert_eq!(h.press_key(Key::Space, |ctx| c.event(ctx)), None);
+    }
+
+    #[test]
+    fn focus_between_panels() {
+        let mut h = Harness::new(1920.0, 1080.0);
+        let mut c1 = h.setup(panel);
+        let mut c2 =
+            h.setup(|ctx| Composite::new(Btn::text_fg("other").build_def(ctx, None)).build(ctx));
+        {
+            let mut both = |ctx: &mut EventCtx| {
+                let x = c1.event(ctx);
+                let y = c2.event(ctx);
+                x.or(y)
+            };
+
+            for _ in 0..3 {
+                assert_eq!(h.press_key(Key::Tab, &mut both), None);
+            }
+            // Tab moves off the end of the first panel onto the second
+            assert_eq!(h.press_key(Key::Tab, &mut both), None);
+            assert_eq!(
+                h.press_key(Key::Enter, &mut both),
+                Some(Outcome::Clicked("other".to_string()))
+            );
+            // Then off the end of the second, and back to the first
+            assert_eq!(h.press_key(Key::Tab, &mut both), None);
+            assert_eq!(h.press_key(Key::Enter, &mut both), None);
+            assert_eq!(h.press_key(Key::Tab, &mut both), None);
+            assert_eq!(
+                h.press_key(Key::Enter, &mut both),
+                Some(Outcome::Clicked("first".to_string()))
+            );
+
+            // Shift+Tab goes backwards onto the second panel
+            h.event(Event::KeyPress(Key::LeftShift), &mut both);
+            assert_eq!(h.press_key(Key::Tab, &mut both), None);
+            h.event(Event::KeyRelease(Key::LeftShift), &mut both);
+            assert_eq!(
+                h.press_key(Key::Enter, &mut both),
+                Some(Outcome::Clicked("other".to_string()))
+            );
+        }
+
+        // If the panel with focus goes away, the other one can take it
+        drop(c2);
+        h.move_mouse(ScreenPt::new(1.0, 1.0), |ctx| c1.event(ctx));
+        assert_eq!(h.press_key(Key::Tab, |ctx| c1.event(ctx)), None);
+        assert_eq!(
+            h.press_key(Key::Enter, |ctx| c1.event(ctx)),
+            Some(Outcome::Clicked("first".to_string()))
+        );
+    }
+
+    #[test]
+    fn focus_persistent_split() {
+        let mut h = Harness::new(1920.0, 1080.0);
+        let mut c = h.setup(|ctx| {
+            Composite::new(PersistentSplit::new(
+                ctx,
+                "split",
+                1,
+                None,
+                vec![Choice::new("one", 1), Choice::new("two", 2)],
+            ))
+            .build(ctx)
+        });
+        assert_eq!(h.press_key(Key::Tab, |ctx| c.event(ctx)), None);
+        assert_eq!(h.press_key(Key::RightArrow, |ctx| c.event(ctx)), None);
+        assert_eq!(c.persistent_split_value::<i32>("split"), 2);
+        assert_eq!(
+            h.press_key(Key::Enter, |ctx| c.event(ctx)),
+            Some(Outcome::Clicked("split".to_string()))
+        );
+    }
+
+    #[test]
+    fn focus_after_replace() {
+        let mut h = Harness::new(1920.0, 1080.0);
+        let mut c = h.setup(|ctx| {
+            Composite::new(Widget::col(vec![
+                Btn::text_fg("first").build_def(ctx, None),
+                Widget::col(vec![
+                    Btn::text_fg("x").build_def(ctx, None),
+                    Btn::text_fg("y").build_def(ctx, None),
+                ])
+                .named("group"),
+            ]))
+            .build(ctx)
+        });
+        for _ in 0..3 {
+            assert_eq!(h.press_key(Key::Tab, |ctx| c.event(ctx)), None);
+        }
+        // "y" had focus, but there's one less widget now
+        h.setup(|ctx| c.replace(ctx, "group", Btn::text_fg("z").build_def(ctx, None)));
+        assert_eq!(
+            h.press_key(Key::Enter, |ctx| c.event(ctx)),
+            Some(Outcome::Clicked("z".to_string()))
+        );
+    }
+}
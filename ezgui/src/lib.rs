@@ -34,8 +34,10 @@ mod drawing;
 mod event;
 mod event_ctx;
 mod geom;
+mod harness;
 mod input;
 mod managed;
+mod raster;
 mod runner;
 mod screen_geom;
 mod style;
@@ -44,13 +46,14 @@ mod text;
 mod tools;
 mod widgets;
 
-pub use crate::backend::Drawable;
+pub use crate::assets::Assets;
 pub use crate::canvas::{Canvas, HorizontalAlignment, VerticalAlignment};
 pub use crate::color::{Color, FancyColor, LinearGradient};
-pub use crate::drawing::{GfxCtx, Prerender};
+pub use crate::drawing::{Drawable, GfxCtx, Prerender};
 pub use crate::event::{hotkey, hotkeys, lctrl, Event, Key, MultiKey};
 pub use crate::event_ctx::EventCtx;
 pub use crate::geom::{GeomBatch, RewriteColor};
+pub use crate::harness::Harness;
 pub use crate::input::UserInput;
 pub use crate::managed::{Composite, Widget};
 pub use crate::runner::{run, EventLoopMode, Settings, GUI};
@@ -60,6 +63,7 @@ pub use crate::text::{Line, Text, TextExt, TextSpan};
 pub use crate::tools::warper::Warper;
 pub use crate::tools::wizard::{Choice, Wizard, WrappedWizard};
 pub use crate::widgets::autocomplete::Autocomplete;
+pub use crate::widgets::bar_chart::{BarChart, BarLayout, BarSeries};
 pub use crate::widgets::button::Btn;
 pub(crate) use crate::widgets::button::Button;
 pub use crate::widgets::checkbox::Checkbox;
@@ -74,6 +78,8 @@ pub use crate::widgets::persistent_split::PersistentSplit;
 pub use crate::widgets::scatter_plot::ScatterPlot;
 pub use crate::widgets::slider::{AreaSlider, Slider};
 pub use crate::widgets::spinner::Spinner;
+pub use crate::widgets::stacked_area::StackedAreaPlot;
+pub use crate::widgets::table::{Col, Filter, Table};
 pub(crate) use crate::widgets::text_box::TextBox;
 pub use crate::widgets::{Outcome, WidgetImpl, WidgetOutput};
 
@@ -1,9 +1,9 @@
 use crate::widgets::containers::{Container, Nothing};
 use crate::{
-    AreaSlider, Autocomplete, Button, Checkbox, Choice, Color, Drawable, Dropdown, EventCtx,
-    GeomBatch, GfxCtx, HorizontalAlignment, JustDraw, Menu, Outcome, PersistentSplit, RewriteColor,
-    ScreenDims, ScreenPt, ScreenRectangle, Slider, Spinner, TextBox, VerticalAlignment, WidgetImpl,
-    WidgetOutput,
+    hotkey, AreaSlider, Autocomplete, Button, Checkbox, Choice, Color, Drawable, Dropdown,
+    EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, JustDraw, Key, Menu, Outcome,
+    PersistentSplit, RewriteColor, ScreenDims, ScreenPt, ScreenRectangle, Slider, Spinner, TextBox,
+    VerticalAlignment, WidgetImpl, WidgetOutput,
 };
 use geom::{Distance, Polygon};
 use std::collections::HashSet;
@@ -459,14 +459,43 @@ impl Widget {
         None
     }
 
+    // Everything that keyboard focus can move between, in the order Tab visits them
+    fn focusable<'a>(&'a self, list: &mut Vec<&'a Widget>) {
+        if let Some(container) = self.widget.downcast_ref::<Container>() {
+            for w in &container.members {
+                w.focusable(list);
+            }
+        } else if self.widget.can_focus() && !self.is_scrollbar() {
+            list.push(self);
+        }
+    }
+    fn focusable_mut<'a>(&'a mut self, list: &mut Vec<&'a mut Widget>) {
+        if self.widget.is::<Container>() {
+            let container = self.widget.downcast_mut::<Container>().unwrap();
+            for w in &mut container.members {
+                w.focusable_mut(list);
+            }
+        } else if self.widget.can_focus() && !self.is_scrollbar() {
+            list.push(self);
+        }
+    }
+
+    fn is_scrollbar(&self) -> bool {
+        self.id == Some("horiz scrollbar".to_string())
+            || self.id == Some("vert scrollbar".to_string())
+    }
+
     fn restore(&mut self, ctx: &mut EventCtx, prev: &Composite) {
         if let Some(container) = self.widget.downcast_mut::<Container>() {
             for w in &mut container.members {
                 w.restore(ctx, prev);
             }
         } else if self.widget.can_restore() {
-            if let Some(ref other) = prev.top_level.find(self.id.as_ref().unwrap()) {
-                self.widget.restore(ctx, &other.widget);
+            // Unnamed widgets can't be matched up with their previous version
+            if let Some(ref id) = self.id {
+                if let Some(ref other) = prev.top_level.find(id) {
+                    self.widget.restore(ctx, &other.widget);
+                }
             }
         }
     }
@@ -543,6 +572,12 @@ pub struct Composite {
     contents_dims: ScreenDims,
     container_dims: ScreenDims,
     clip_rect: Option<ScreenRectangle>,
+
+    // Which of the focusable widgets (in Tab order) has keyboard focus. Nothing does until the
+    // user presses Tab, and clicking the mouse anywhere clears it again. Only one Composite at a
+    // time has focus; the canvas tracks which one by this ID.
+    focus: Option<usize>,
+    id: usize,
 }
 
 impl Composite {
@@ -675,12 +710,42 @@ impl Composite {
             self.recompute_layout(ctx, false);
         }
 
+        if self.focus.is_some() {
+            if ctx.canvas.keyboard_focus == Some(self.id) {
+                ctx.canvas.keyboard_focus_seen = true;
+            } else {
+                // Another Composite took focus
+                self.set_focus(ctx, None);
+            }
+        }
+        if ctx.input.left_mouse_button_pressed() {
+            self.set_focus(ctx, None);
+        }
+        // Whoever has focus handles Tab. If nobody does, the first Composite with something
+        // focusable takes it.
+        if (self.focus.is_some() || ctx.canvas.keyboard_focus.is_none())
+            && ctx.input.new_was_pressed(&hotkey(Key::Tab).unwrap())
+            && !self.move_focus(ctx, !ctx.canvas.lshift_held)
+        {
+            // Let the next Composite have a turn
+            ctx.input.unconsume_event();
+        }
+
         let before = self.scroll_offset();
         let mut output = WidgetOutput {
             redo_layout: false,
             outcome: None,
         };
-        self.top_level.widget.event(ctx, &mut output);
+        if let Some(idx) = self.focus {
+            let mut list = Vec::new();
+            self.top_level.focusable_mut(&mut list);
+            if let Some(w) = list.into_iter().nth(idx) {
+                w.widget.focused_event(ctx, &mut output);
+            }
+        }
+        if output.outcome.is_none() {
+            self.top_level.widget.event(ctx, &mut output);
+        }
         if self.scroll_offset() != before || output.redo_layout {
             self.recompute_layout(ctx, true);
         }
@@ -688,6 +753,57 @@ impl Composite {
         output.outcome
     }
 
+    fn set_focus(&mut self, ctx: &mut EventCtx, focus: Option<usize>) {
+        if self.focus == focus {
+            return;
+        }
+        let mut list = Vec::new();
+        self.top_level.focusable_mut(&mut list);
+        if let Some(w) = self.focus.and_then(|idx| list.get_mut(idx)) {
+            w.widget.set_focus(false);
+        }
+        let mut rect = None;
+        if let Some(w) = focus.and_then(|idx| list.get_mut(idx)) {
+            w.widget.set_focus(true);
+            rect = Some(w.rect.clone());
+        }
+        self.focus = focus;
+        if focus.is_some() {
+            ctx.canvas.keyboard_focus = Some(self.id);
+            ctx.canvas.keyboard_focus_seen = true;
+        } else if ctx.canvas.keyboard_focus == Some(self.id) {
+            ctx.canvas.keyboard_focus = None;
+        }
+
+        // Scroll to make sure the focused widget is visible
+        if let (Some(rect), Some(clip)) = (rect, self.clip_rect.clone()) {
+            if rect.y1 < clip.y1 || rect.y2 > clip.y2 {
+                let (x, y) = self.scroll_offset();
+                self.set_scroll_offset(ctx, (x, y + rect.y1 - clip.y1));
+            }
+        }
+    }
+
+    // Tab and Shift+Tab move off either end of the Composite, so that the next one can take
+    // focus. Returns false when that happens, or if there's nothing to focus here.
+    fn move_focus(&mut self, ctx: &mut EventCtx, forwards: bool) -> bool {
+        let mut list = Vec::new();
+        self.top_level.focusable(&mut list);
+        let n = list.len();
+        if n == 0 {
+            return false;
+        }
+        let next = match (self.focus, forwards) {
+            (None, true) => Some(0),
+            (None, false) => Some(n - 1),
+            (Some(idx), true) if idx + 1 < n => Some(idx + 1),
+            (Some(idx), false) if idx > 0 => Some(idx - 1),
+            (Some(_), _) => None,
+        };
+        self.set_focus(ctx, next);
+        next.is_some()
+    }
+
     pub fn draw(&self, g: &mut GfxCtx) {
         if let Some(ref rect) = self.clip_rect {
             g.enable_clipping(rect.clone());
@@ -717,6 +833,24 @@ impl Composite {
         g.unfork();
 
         self.top_level.draw(g);
+        if let Some(idx) = self
+            .focus
+            .filter(|_| g.canvas.keyboard_focus == Some(self.id))
+        {
+            let mut list = Vec::new();
+            self.top_level.focusable(&mut list);
+            if let Some(w) = list.get(idx) {
+                let color = g.style().focus_ring;
+                g.fork_screenspace();
+                g.draw_polygon(
+                    color,
+                    &Polygon::rectangle(w.rect.width() + 4.0, w.rect.height() + 4.0)
+                        .translate(w.rect.x1 - 2.0, w.rect.y1 - 2.0)
+                        .to_outline(Distance::meters(2.0)),
+                );
+                g.unfork();
+            }
+        }
         if self.scrollable_x || self.scrollable_y {
             g.disable_clipping();
 
@@ -741,6 +875,17 @@ impl Composite {
         self.set_scroll_offset(ctx, prev.scroll_offset());
 
         self.top_level.restore(ctx, &prev);
+        // Keep keyboard focus in the same place, if the new Composite has a similar shape
+        if let Some(idx) = prev
+            .focus
+            .filter(|_| ctx.canvas.keyboard_focus == Some(prev.id))
+        {
+            let mut list = Vec::new();
+            self.top_level.focusable(&mut list);
+            if idx < list.len() {
+                self.set_focus(ctx, Some(idx));
+            }
+        }
 
         // Since we just moved things around, let all widgets respond to the mouse being somewhere
         ctx.no_op_event(true, |ctx| assert!(self.event(ctx).is_none()));
@@ -847,8 +992,21 @@ impl Composite {
     }
 
     pub fn replace(&mut self, ctx: &mut EventCtx, id: &str, new: Widget) {
+        // The focused widget might be the one replaced, so focus whatever is in the same place
+        // afterwards
+        let focus = self
+            .focus
+            .filter(|_| ctx.canvas.keyboard_focus == Some(self.id));
+        self.set_focus(ctx, None);
         *self.top_level.find_mut(id).unwrap() = new;
         self.recompute_layout(ctx, true);
+        if let Some(idx) = focus {
+            let mut list = Vec::new();
+            self.top_level.focusable(&mut list);
+            if !list.is_empty() {
+                self.set_focus(ctx, Some(idx.min(list.len() - 1)));
+            }
+        }
 
         // TODO Same no_op_event as align_above? Should we always do this in recompute_layout?
     }
@@ -877,7 +1035,10 @@ impl CompositeBuilder {
             contents_dims: ScreenDims::new(0.0, 0.0),
             container_dims: ScreenDims::new(0.0, 0.0),
             clip_rect: None,
+            focus: None,
+            id: ctx.canvas.next_composite_id,
         };
+        ctx.canvas.next_composite_id += 1;
         if let Dims::ExactPercent(w, h) = c.dims {
             // Don't set size, because then scrolling breaks -- the actual size has to be based on
             // the contents.
@@ -0,0 +1,295 @@
+//! Renders a [`GeomBatch`] on the CPU, for producing images without a window or GPU context --
+//! in CI, headless tools, or reports. Text can be included by rendering it into the batch first,
+//! using [`Text::render`] with some standalone [`Assets`].
+//!
+//! [`Assets`]: crate::Assets
+//! [`Text::render`]: crate::Text::render
+
+use crate::{Color, FancyColor, GeomBatch, ScreenDims};
+use geom::{Bounds, Pt2D};
+use std::fmt::Write;
+
+// Each pixel is covered by this many samples, arranged in a rotated grid, for antialiasing.
+const SAMPLES: [(f64, f64); 4] = [
+    (0.375, 0.125),
+    (0.875, 0.375),
+    (0.125, 0.625),
+    (0.625, 0.875),
+];
+
+impl GeomBatch {
+    /// Scales and translates the batch to fit inside the dimensions, preserving its aspect ratio.
+    pub fn fit_to(self, dims: ScreenDims) -> GeomBatch {
+        if self.is_empty() {
+            return self;
+        }
+        let mut bounds = Bounds::new();
+        for (_, poly) in &self.list {
+            bounds.union(poly.get_bounds());
+        }
+        // A batch that's only a horizontal or vertical line is fit along the other axis. A single
+        // point isn't scaled at all.
+        let zoom_x = if bounds.width() > 0.0 {
+            Some(dims.width / bounds.width())
+        } else {
+            None
+        };
+        let zoom_y = if bounds.height() > 0.0 {
+            Some(dims.height / bounds.height())
+        } else {
+            None
+        };
+        let zoom = match (zoom_x, zoom_y) {
+            (Some(x), Some(y)) => x.min(y),
+            (Some(z), None) | (None, Some(z)) => z,
+            (None, None) => 1.0,
+        };
+        self.translate(-bounds.min_x, -bounds.min_y).scale(zoom)
+    }
+
+    /// Rasterizes the batch into an image. The batch's coordinates are used as pixels, so scale
+    /// and translate it first; anything outside the image is clipped. Later polygons are drawn
+    /// over earlier ones.
+    pub fn rasterize(&self, width: u32, height: u32, background: Color) -> image::RgbaImage {
+        let (w, h) = (width as usize, height as usize);
+        // Premultiplied alpha
+        let bg = premultiply(background);
+        let mut pixels: Vec<[f32; 4]> = vec![bg; w * h];
+
+        for (color, poly) in &self.list {
+            if let FancyColor::RGBA(c) = color {
+                if c.a == 0.0 {
+                    continue;
+                }
+            }
+            let bounds = poly.get_bounds();
+            let x1 = bounds.min_x.floor().max(0.0) as usize;
+            let y1 = bounds.min_y.floor().max(0.0) as usize;
+            let x2 = (bounds.max_x.ceil().max(0.0) as usize).min(w);
+            let y2 = (bounds.max_y.ceil().max(0.0) as usize).min(h);
+            if x1 >= x2 || y1 >= y2 {
+                continue;
+            }
+
+            // Find which samples each triangle covers. Adjacent triangles in one polygon share
+            // edges, so combine their coverage before blending, to avoid seams.
+            let mut coverage = vec![0u8; (x2 - x1) * (y2 - y1)];
+            for tri in poly.triangles() {
+                let (pt1, pt2, pt3) = if cross(tri.pt1, tri.pt2, tri.pt3) < 0.0 {
+                    (tri.pt1, tri.pt3, tri.pt2)
+                } else {
+                    (tri.pt1, tri.pt2, tri.pt3)
+                };
+                let tx1 = (pt1.x().min(pt2.x()).min(pt3.x()).floor().max(0.0) as usize).max(x1);
+                let ty1 = (pt1.y().min(pt2.y()).min(pt3.y()).floor().max(0.0) as usize).max(y1);
+                let tx2 = (pt1.x().max(pt2.x()).max(pt3.x()).ceil().max(0.0) as usize).min(x2);
+                let ty2 = (pt1.y().max(pt2.y()).max(pt3.y()).ceil().max(0.0) as usize).min(y2);
+                for y in ty1..ty2 {
+                    for x in tx1..tx2 {
+                        let mut mask = 0;
+                        for (bit, (dx, dy)) in SAMPLES.iter().enumerate() {
+                            let pt = Pt2D::new(x as f64 + dx, y as f64 + dy);
+                            if cross(pt1, pt2, pt) >= 0.0
+                                && cross(pt2, pt3, pt) >= 0.0
+                                && cross(pt3, pt1, pt) >= 0.0
+                            {
+                                mask |= 1 << bit;
+                            }
+                        }
+                        coverage[(y - y1) * (x2 - x1) + (x - x1)] |= mask;
+                    }
+                }
+            }
+
+            for y in y1..y2 {
+                for x in x1..x2 {
+                    let mask = coverage[(y - y1) * (x2 - x1) + (x - x1)];
+                    if mask == 0 {
+                        continue;
+                    }
+                    let pct = (mask.count_ones() as f32) / (SAMPLES.len() as f32);
+                    let [r, g, b, a] = color.style(Pt2D::new(x as f64 + 0.5, y as f64 + 0.5));
+                    let src = premultiply(Color::rgba_f(r, g, b, a * pct));
+                    for (dst, value) in pixels[y * w + x].iter_mut().zip(src.iter()) {
+                        *dst = value + *dst * (1.0 - src[3]);
+                    }
+                }
+            }
+        }
+
+        let mut raw = Vec::with_capacity(w * h * 4);
+        for [r, g, b, a] in pixels {
+            let (r, g, b) = if a > 0.0 {
+                (r / a, g / a, b / a)
+            } else {
+                (0.0, 0.0, 0.0)
+            };
+            for value in &[r, g, b, a] {
+                raw.push((value.max(0.0).min(1.0) * 255.0).round() as u8);
+            }
+        }
+        image::RgbaImage::from_raw(width, height, raw).unwrap()
+    }
+
+    /// Rasterizes the batch and saves it as a PNG file.
+    pub fn save_png(
+        &self,
+        path: &str,
+        width: u32,
+        height: u32,
+        background: Color,
+    ) -> Result<(), String> {
+        self.rasterize(width, height, background)
+            .save(path)
+            .map_err(|err| format!("Couldn't save {}: {}", path, err))
+    }
+
+    /// Describes the batch as an SVG document. Like `rasterize`, the batch's coordinates are used
+    /// directly.
+    pub fn to_svg(&self, width: u32, height: u32, background: Color) -> String {
+        let mut defs = String::new();
+        let mut num_gradients = 0;
+        let mut body = String::new();
+        if background.a > 0.0 {
+            writeln!(
+                body,
+                r#"<rect width="100%" height="100%" fill="{}" fill-opacity="{}"/>"#,
+                background.to_hex(),
+                background.a
+            )
+            .unwrap();
+        }
+
+        for (color, poly) in &self.list {
+            let fill = match color {
+                FancyColor::RGBA(c) => {
+                    if c.a == 0.0 {
+                        continue;
+                    }
+                    format!(r#"fill="{}" fill-opacity="{}""#, c.to_hex(), c.a)
+                }
+                FancyColor::LinearGradient(lg) => {
+                    num_gradients += 1;
+                    let id = format!("gradient{}", num_gradients);
+                    write!(
+                        defs,
+                        "<linearGradient id=\"{}\" gradientUnits=\"userSpaceOnUse\" \
+                         x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\">",
+                        id,
+                        lg.line.pt1().x(),
+                        lg.line.pt1().y(),
+                        lg.line.pt2().x(),
+                        lg.line.pt2().y()
+                    )
+                    .unwrap();
+                    for (offset, c) in &lg.stops {
+                        write!(
+                            defs,
+                            r#"<stop offset="{}" stop-color="{}" stop-opacity="{}"/>"#,
+                            offset,
+                            c.to_hex(),
+                            c.a
+                        )
+                        .unwrap();
+                    }
+                    writeln!(defs, "</linearGradient>").unwrap();
+                    format!(r#"fill="url(#{})""#, id)
+                }
+            };
+
+            // The triangles don't overlap, so one path per polygon renders without seams.
+            let mut path = String::new();
+            for tri in poly.triangles() {
+                write!(
+                    path,
+                    "M{:.2} {:.2}L{:.2} {:.2}L{:.2} {:.2}Z",
+                    tri.pt1.x(),
+                    tri.pt1.y(),
+                    tri.pt2.x(),
+                    tri.pt2.y(),
+                    tri.pt3.x(),
+                    tri.pt3.y()
+                )
+                .unwrap();
+            }
+            writeln!(body, r#"<path d="{}" {}/>"#, path, fill).unwrap();
+        }
+
+        format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 \
+             {0} {1}\">\n<defs>\n{2}</defs>\n{3}</svg>\n",
+            width, height, defs, body
+        )
+    }
+
+    /// Saves the batch as an SVG file.
+    pub fn save_svg(
+        &self,
+        path: &str,
+        width: u32,
+        height: u32,
+        background: Color,
+    ) -> Result<(), String> {
+        std::fs::write(path, self.to_svg(width, height, background))
+            .map_err(|err| format!("Couldn't save {}: {}", path, err))
+    }
+}
+
+fn premultiply(c: Color) -> [f32; 4] {
+    [c.r * c.a, c.g * c.a, c.b * c.a, c.a]
+}
+
+// The sign says which side of the line from pt1 to pt2 that pt is on.
+fn cross(pt1: Pt2D, pt2: Pt2D, pt: Pt2D) -> f64 {
+    (pt2.x() - pt1.x()) * (pt.y() - pt1.y()) - (pt2.y() - pt1.y()) * (pt.x() - pt1.x())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::Polygon;
+
+    #[test]
+    fn fit_and_rasterize() {
+        // Twice as wide as it is tall, far from the origin
+        let batch = GeomBatch::from(vec![(
+            Color::RED,
+            Polygon::rectangle(200.0, 100.0).translate(1000.0, -500.0),
+        )])
+        .fit_to(ScreenDims::new(10.0, 10.0));
+        let img = batch.rasterize(10, 10, Color::BLACK);
+        // The top half is covered, and the aspect ratio is kept, so the bottom half isn't.
+        for x in 0..10 {
+            for y in 0..5 {
+                assert_eq!(img.get_pixel(x, y).0, [255, 0, 0, 255]);
+            }
+            for y in 5..10 {
+                assert_eq!(img.get_pixel(x, y).0, [0, 0, 0, 255]);
+            }
+        }
+    }
+
+    #[test]
+    fn fit_degenerate() {
+        // A horizontal line has no height
+        let line = GeomBatch::from(vec![(
+            Color::RED,
+            Polygon::rectangle(40.0, 0.0).translate(5.0, 5.0),
+        )])
+        .fit_to(ScreenDims::new(10.0, 10.0));
+        let dims = line.get_dims();
+        assert_eq!((dims.width, dims.height), (10.0, 0.0));
+        // Nothing has any area, so nothing is drawn
+        let img = line.rasterize(10, 10, Color::BLACK);
+        assert!(img.pixels().all(|p| p.0 == [0, 0, 0, 255]));
+
+        // A single point stays put
+        let pt = GeomBatch::from(vec![(
+            Color::RED,
+            Polygon::rectangle(0.0, 0.0).translate(5.0, 5.0),
+        )])
+        .fit_to(ScreenDims::new(10.0, 10.0));
+        let dims = pt.get_dims();
+        assert_eq!((dims.width, dims.height), (0.0, 0.0));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::assets::Assets;
+use crate::tools::recording::{Recorder, Replayer, Step};
 use crate::tools::screenshot::screenshot_everything;
-use crate::{text, Canvas, Event, EventCtx, GfxCtx, Key, Prerender, Style, UserInput};
+use crate::{text, Canvas, Event, EventCtx, GfxCtx, Prerender, Style, UserInput};
 use geom::Duration;
 use image::{GenericImageView, Pixel};
 use instant::Instant;
@@ -35,6 +36,7 @@ pub(crate) struct State<G: GUI> {
     pub(crate) gui: G,
     pub(crate) canvas: Canvas,
     style: Style,
+    recorder: Option<Recorder>,
 }
 
 impl<G: GUI> State<G> {
@@ -51,7 +53,7 @@ impl<G: GUI> State<G> {
         if let Event::Update(_) = ev {
         } else {
             prerender
-                .inner
+                .innards()
                 .set_cursor_icon(if self.canvas.drag_canvas_from.is_some() {
                     // We haven't run canvas_movement() yet, so we don't know if the button has been
                     // released. Bit of a hack to check this here, but better behavior.
@@ -72,7 +74,7 @@ impl<G: GUI> State<G> {
         // Update some ezgui state that's stashed in Canvas for sad reasons.
         {
             if let Event::WindowResized(width, height) = input.event {
-                let inner_size = prerender.inner.get_inner_size();
+                let inner_size = prerender.innards().get_inner_size();
                 println!(
                     "winit event says the window was resized from {}, {} to {}, {}. But inner \
                      size is {}, {}, so using that",
@@ -84,35 +86,12 @@ impl<G: GUI> State<G> {
                     inner_size.1
                 );
                 let (width, height) = inner_size;
-                prerender.inner.window_resized(width, height);
+                prerender.innards().window_resized(width, height);
                 self.canvas.window_width = width;
                 self.canvas.window_height = height;
             }
 
-            if input.event == Event::KeyPress(Key::LeftControl) {
-                self.canvas.lctrl_held = true;
-            }
-            if input.event == Event::KeyRelease(Key::LeftControl) {
-                self.canvas.lctrl_held = false;
-            }
-            if input.event == Event::KeyPress(Key::LeftShift) {
-                self.canvas.lshift_held = true;
-            }
-            if input.event == Event::KeyRelease(Key::LeftShift) {
-                self.canvas.lshift_held = false;
-            }
-
-            if let Some(pt) = input.get_moved_mouse() {
-                self.canvas.cursor_x = pt.x;
-                self.canvas.cursor_y = pt.y;
-            }
-
-            if input.event == Event::WindowGainedCursor {
-                self.canvas.window_has_cursor = true;
-            }
-            if input.window_lost_cursor() {
-                self.canvas.window_has_cursor = false;
-            }
+            self.canvas.track_input(&input);
         }
 
         match panic::catch_unwind(panic::AssertUnwindSafe(|| {
@@ -136,6 +115,7 @@ impl<G: GUI> State<G> {
             Ok(pair) => pair,
             Err(err) => {
                 self.gui.dump_before_abort(&self.canvas);
+                self.finish_recording();
                 panic::resume_unwind(err);
             }
         }
@@ -151,6 +131,7 @@ impl<G: GUI> State<G> {
             self.gui.draw(&mut g);
         })) {
             self.gui.dump_before_abort(&self.canvas);
+            self.finish_recording();
             panic::resume_unwind(err);
         }
         let naming_hint = g.naming_hint.take();
@@ -164,9 +145,21 @@ impl<G: GUI> State<G> {
             );
         }
 
-        g.inner.finish();
+        g.finish();
         naming_hint
     }
+
+    fn record(&mut self, step: Step) {
+        if let Some(ref mut recorder) = self.recorder {
+            recorder.record(step);
+        }
+    }
+
+    fn finish_recording(&self) {
+        if let Some(ref recorder) = self.recorder {
+            recorder.finish();
+        }
+    }
 }
 
 pub struct Settings {
@@ -177,6 +170,8 @@ pub struct Settings {
     dump_raw_events: bool,
     scale_factor: Option<f64>,
     window_icon: Option<String>,
+    record_input: Option<String>,
+    replay_input: Option<(String, bool)>,
 }
 
 impl Settings {
@@ -189,6 +184,8 @@ impl Settings {
             dump_raw_events: false,
             scale_factor: None,
             window_icon: None,
+            record_input: None,
+            replay_input: None,
         }
     }
 
@@ -213,6 +210,20 @@ impl Settings {
     pub fn window_icon(&mut self, path: &str) {
         self.window_icon = Some(path.to_string());
     }
+
+    // Saves every event and redraw to a JSON file when the window is closed (or the GUI
+    // crashes), so the session can be replayed later.
+    pub fn record_input(&mut self, path: &str) {
+        assert!(self.replay_input.is_none());
+        self.record_input = Some(path.to_string());
+    }
+
+    // Feeds a recorded session to the GUI with the original timing, ignoring real input until
+    // it's done. Then the window either closes or becomes interactive again.
+    pub fn replay_input(&mut self, path: &str, quit_when_done: bool) {
+        assert!(self.record_input.is_none());
+        self.replay_input = Some((path.to_string(), quit_when_done));
+    }
 }
 
 pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings, make_gui: F) -> ! {
@@ -240,10 +251,19 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
                 .unwrap_or_else(|| prerender_innards.monitor_scale_factor()),
         ),
         num_uploads: Cell::new(0),
-        inner: prerender_innards,
+        inner: Some(prerender_innards),
     };
     let mut style = Style::standard();
 
+    // The saved camera position would make mouse events land elsewhere.
+    canvas.ignore_saved_camera = settings.record_input.is_some() || settings.replay_input.is_some();
+    let recorder = settings
+        .record_input
+        .map(|path| Recorder::new(path, &canvas));
+    let mut replayer = settings
+        .replay_input
+        .map(|(path, quit_when_done)| Replayer::load(path, &canvas, quit_when_done));
+
     let gui = make_gui(&mut EventCtx {
         fake_mouseover: true,
         input: UserInput::new(Event::NoOp, &canvas),
@@ -252,7 +272,12 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
         style: &mut style,
     });
 
-    let mut state = State { canvas, gui, style };
+    let mut state = State {
+        canvas,
+        gui,
+        style,
+        recorder,
+    };
 
     if settings.profiling_enabled {
         abstutil::start_profiler();
@@ -267,6 +292,62 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
         if dump_raw_events {
             println!("Event: {:?}", event);
         }
+        if replayer.is_some() {
+            match event {
+                winit::event::Event::WindowEvent {
+                    event: winit::event::WindowEvent::CloseRequested,
+                    ..
+                } => {}
+                winit::event::Event::MainEventsCleared => {
+                    let (deadline, quit_when_done) = {
+                        let r = replayer.as_mut().unwrap();
+                        for step in r.ready_steps() {
+                            match step {
+                                Step::Event(ev) => {
+                                    if let EventLoopMode::ScreenCaptureEverything {
+                                        dir,
+                                        zoom,
+                                        max_x,
+                                        max_y,
+                                    } = state.event(ev, &prerender).0
+                                    {
+                                        screenshot_everything(
+                                            &mut state, &dir, &prerender, zoom, max_x, max_y,
+                                        );
+                                    }
+                                }
+                                Step::Draw => {
+                                    state.draw(&prerender, false);
+                                    prerender.num_uploads.set(0);
+                                }
+                            }
+                        }
+                        (r.next_deadline(), r.quit_when_done)
+                    };
+                    if let Some(deadline) = deadline {
+                        *control_flow = winit::event_loop::ControlFlow::WaitUntil(deadline);
+                    } else {
+                        println!("Finished replaying input");
+                        if quit_when_done {
+                            state.gui.before_quit(&state.canvas);
+                            std::process::exit(0);
+                        }
+                        replayer = None;
+                        running = true;
+                        last_update = Instant::now();
+                        *control_flow = winit::event_loop::ControlFlow::WaitUntil(
+                            Instant::now() + UPDATE_FREQUENCY,
+                        );
+                        prerender.request_redraw();
+                    }
+                    return;
+                }
+                // Real input and redraws would make the replay diverge
+                _ => {
+                    return;
+                }
+            }
+        }
         let ev = match event {
             winit::event::Event::WindowEvent {
                 event: winit::event::WindowEvent::CloseRequested,
@@ -279,6 +360,7 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
                     abstutil::stop_profiler();
                 }
                 state.gui.before_quit(&state.canvas);
+                state.finish_recording();
                 std::process::exit(0);
             }
             winit::event::Event::WindowEvent { event, .. } => {
@@ -290,6 +372,7 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
                 }
             }
             winit::event::Event::RedrawRequested(_) => {
+                state.record(Step::Draw);
                 state.draw(&prerender, false);
                 prerender.num_uploads.set(0);
                 return;
@@ -315,6 +398,7 @@ pub fn run<G: 'static + GUI, F: FnOnce(&mut EventCtx) -> G>(settings: Settings,
                 winit::event_loop::ControlFlow::WaitUntil(Instant::now() + UPDATE_FREQUENCY);
         }
 
+        state.record(Step::Event(ev));
         let (mode, input_used) = state.event(ev, &prerender);
         if input_used {
             prerender.request_redraw();
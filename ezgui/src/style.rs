@@ -7,6 +7,8 @@ pub struct Style {
     pub panel_bg: Color,
     pub hotkey_color: Color,
     pub hovering_color: Color,
+    pub section_bg: Color,
+    pub focus_ring: Color,
 }
 
 impl Style {
@@ -17,6 +19,8 @@ impl Style {
             panel_bg: Color::grey(0.4),
             hotkey_color: Color::GREEN,
             hovering_color: Color::ORANGE,
+            section_bg: Color::grey(0.5),
+            focus_ring: Color::hex("#4CA7E9"),
         }
     }
 }
@@ -1,3 +1,4 @@
+pub mod recording;
 pub mod screenshot;
 pub mod warper;
 pub mod wizard;
@@ -0,0 +1,177 @@
+use crate::{Canvas, Event};
+use geom::Duration;
+use instant::Instant;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+
+// Everything the GUI saw during one session, to reproduce it exactly later. The GUI has to be
+// started the same way (same flags, same files on disk) for a replay to make sense.
+//
+// The file has one JSON object per line: this header, then every step with the time it happened,
+// relative to when the GUI was created. Each step is written as soon as it's recorded, so nothing
+// is lost however the GUI exits -- including std::process::exit from the app.
+#[derive(Serialize, Deserialize)]
+struct Header {
+    window_width: f64,
+    window_height: f64,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) enum Step {
+    Event(Event),
+    // Drawing updates some state used to handle later events (like which parts of the screen are
+    // covered by panels), so the order of draws and events matters.
+    Draw,
+}
+
+pub(crate) struct Recorder {
+    path: String,
+    start: Instant,
+    // Unbuffered, so every line reaches the OS right away
+    file: File,
+    num_steps: usize,
+}
+
+impl Recorder {
+    pub fn new(path: String, canvas: &Canvas) -> Recorder {
+        Recorder::create(path, canvas.window_width, canvas.window_height)
+    }
+
+    fn create(path: String, window_width: f64, window_height: f64) -> Recorder {
+        let mut file =
+            File::create(&path).unwrap_or_else(|err| panic!("Can't record to {}: {}", path, err));
+        let header = Header {
+            window_width,
+            window_height,
+        };
+        writeln!(file, "{}", serde_json::to_string(&header).unwrap()).unwrap();
+        Recorder {
+            path,
+            start: Instant::now(),
+            file,
+            num_steps: 0,
+        }
+    }
+
+    pub fn record(&mut self, step: Step) {
+        let line = serde_json::to_string(&(Duration::realtime_elapsed(self.start), step)).unwrap();
+        writeln!(self.file, "{}", line)
+            .unwrap_or_else(|err| panic!("Can't record to {}: {}", self.path, err));
+        self.num_steps += 1;
+    }
+
+    // Everything's already written; this just reports it.
+    pub fn finish(&self) {
+        println!(
+            "Recorded {} steps to {}",
+            abstutil::prettyprint_usize(self.num_steps),
+            self.path
+        );
+    }
+}
+
+// If the GUI died in the middle of writing the last step, that step is skipped.
+fn read_recording(path: &str) -> (Header, Vec<(Duration, Step)>) {
+    let contents = std::fs::read_to_string(path)
+        .unwrap_or_else(|err| panic!("Can't read recording {}: {}", path, err));
+    let mut lines = contents.lines();
+    let header: Header = serde_json::from_str(lines.next().unwrap_or(""))
+        .unwrap_or_else(|err| panic!("{} has a bad header: {}", path, err));
+    let lines: Vec<&str> = lines.collect();
+    let mut steps = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        match serde_json::from_str(line) {
+            Ok(step) => steps.push(step),
+            Err(err) => {
+                if idx == lines.len() - 1 {
+                    println!("WARNING: skipping the incomplete last step of {}", path);
+                } else {
+                    panic!("{} line {} is a bad step: {}", path, idx + 2, err);
+                }
+            }
+        }
+    }
+    (header, steps)
+}
+
+pub(crate) struct Replayer {
+    start: Instant,
+    steps: VecDeque<(Duration, Step)>,
+    pub quit_when_done: bool,
+}
+
+impl Replayer {
+    pub fn load(path: String, canvas: &Canvas, quit_when_done: bool) -> Replayer {
+        let (header, steps) = read_recording(&path);
+        if header.window_width != canvas.window_width
+            || header.window_height != canvas.window_height
+        {
+            println!(
+                "WARNING: {} was recorded in a {}x{} window, but this one is {}x{}. Clicks might \
+                 not land in the same places.",
+                path,
+                header.window_width,
+                header.window_height,
+                canvas.window_width,
+                canvas.window_height
+            );
+        }
+        Replayer {
+            start: Instant::now(),
+            steps: steps.into_iter().collect(),
+            quit_when_done,
+        }
+    }
+
+    // Returns the steps that originally happened by this point in the session. Events keep the
+    // timing they were recorded with, so if the GUI runs slower than before, steps just bunch up.
+    pub fn ready_steps(&mut self) -> Vec<Step> {
+        let now = Duration::realtime_elapsed(self.start);
+        let mut steps = Vec::new();
+        while self.steps.front().map(|(t, _)| *t <= now).unwrap_or(false) {
+            steps.push(self.steps.pop_front().unwrap().1);
+        }
+        steps
+    }
+
+    // When the next step should happen, or None if the replay is finished
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.steps.front().map(|(t, _)| {
+            self.start + std::time::Duration::from_secs_f64(t.inner_seconds().max(0.0))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Key;
+
+    #[test]
+    fn steps_survive_exit() {
+        let path = std::env::temp_dir().join("ezgui_test_recording.json");
+        let path = path.to_str().unwrap().to_string();
+        let mut recorder = Recorder::create(path.clone(), 800.0, 600.0);
+        recorder.record(Step::Event(Event::KeyPress(Key::A)));
+        recorder.record(Step::Draw);
+        // Like std::process::exit, never run any cleanup
+        std::mem::forget(recorder);
+
+        let (header, steps) = read_recording(&path);
+        assert_eq!((header.window_width, header.window_height), (800.0, 600.0));
+        let steps: Vec<String> = steps.into_iter().map(|(_, s)| format!("{:?}", s)).collect();
+        assert_eq!(steps, vec!["Event(KeyPress(A))", "Draw"]);
+
+        // Dying partway through writing a step loses just that step
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"[{\"inner")
+            .unwrap();
+        assert_eq!(read_recording(&path).1.len(), 2);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,256 @@
+use crate::widgets::line_plot::{draw_y_grid, legend_entry, y_axis, Yvalue};
+use crate::{Color, DrawWithTooltips, EventCtx, GeomBatch, JustDraw, Line, Text, Widget};
+use geom::{Distance, Polygon};
+use std::ops::Add;
+
+pub struct BarChart;
+
+pub struct BarSeries<T> {
+    pub label: String,
+    pub color: Color,
+    // For BarChart::new, one value per category. For BarChart::histogram, the raw samples.
+    pub values: Vec<T>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum BarLayout {
+    // Each series gets its own bar, side-by-side within a category
+    Grouped,
+    // Series are piled on top of each other, so the height is the category's total
+    Stacked,
+}
+
+impl BarChart {
+    pub fn new<T: Yvalue<T> + Add<Output = T>>(
+        ctx: &EventCtx,
+        categories: Vec<String>,
+        series: Vec<BarSeries<T>>,
+        layout: BarLayout,
+    ) -> Widget {
+        assert!(!categories.is_empty());
+        for s in &series {
+            assert_eq!(
+                s.values.len(),
+                categories.len(),
+                "{} doesn't have one value per category",
+                s.label
+            );
+        }
+        let totals: Vec<T> = (0..categories.len())
+            .map(|idx| series.iter().fold(T::zero(), |sum, s| sum + s.values[idx]))
+            .collect();
+        let max_y = match layout {
+            BarLayout::Grouped => series
+                .iter()
+                .flat_map(|s| s.values.iter().cloned())
+                .max()
+                .unwrap_or(T::zero()),
+            BarLayout::Stacked => totals.iter().cloned().max().unwrap_or(T::zero()),
+        };
+
+        // TODO Tuned to fit the info panel, like LinePlot.
+        let width = 0.23 * ctx.canvas.window_width;
+        let height = 0.2 * ctx.canvas.window_height;
+        // Each category gets this much horizontal space, with a gap between categories
+        let slot_width = width / (categories.len() as f64);
+        let bar_width = 0.8 * slot_width;
+
+        let mut batch = GeomBatch::new();
+        batch.autocrop_dims = false;
+        // Keep the full size, even if the last categories are empty
+        batch.push(Color::INVISIBLE, Polygon::rectangle(width, height));
+        draw_y_grid(&mut batch, max_y, width, height);
+
+        let mut outlines = Vec::new();
+        let mut tooltips = Vec::new();
+        for (idx, category) in categories.iter().enumerate() {
+            let x1 = slot_width * (idx as f64) + (slot_width - bar_width) / 2.0;
+            let mut stacked_so_far = T::zero();
+            for (series_idx, s) in series.iter().enumerate() {
+                let value = s.values[idx];
+                if value == T::zero() {
+                    continue;
+                }
+                let bar_height = value.to_percent(max_y) * height;
+                let rect = match layout {
+                    BarLayout::Grouped => {
+                        let w = bar_width / (series.len() as f64);
+                        Polygon::rectangle(w, bar_height)
+                            .translate(x1 + w * (series_idx as f64), height - bar_height)
+                    }
+                    BarLayout::Stacked => {
+                        let y2 = (1.0 - stacked_so_far.to_percent(max_y)) * height;
+                        Polygon::rectangle(bar_width, bar_height).translate(x1, y2 - bar_height)
+                    }
+                };
+                stacked_so_far = stacked_so_far + value;
+
+                if let Some(o) = rect.maybe_to_outline(Distance::meters(1.5)) {
+                    outlines.push(o);
+                }
+                batch.push(s.color, rect.clone());
+                let mut txt = Text::from(Line(category));
+                txt.add(Line(format!("{}: {}", s.label, value.prettyprint())));
+                if layout == BarLayout::Stacked && series.len() > 1 {
+                    txt.add(Line(format!("Total: {}", totals[idx].prettyprint())).secondary());
+                }
+                tooltips.push((rect, txt));
+            }
+        }
+        batch.extend(Color::BLACK, outlines);
+
+        // Center a label under each category, skipping some if they'd overlap
+        let labels: Vec<GeomBatch> = categories
+            .iter()
+            .map(|c| Text::from(Line(c).small()).render_ctx(ctx))
+            .collect();
+        let max_label_width = labels
+            .iter()
+            .map(|l| l.get_dims().width)
+            .fold(0.0, f64::max);
+        let step = ((max_label_width + 5.0) / slot_width).ceil().max(1.0) as usize;
+        let mut x_axis = GeomBatch::new();
+        x_axis.autocrop_dims = false;
+        x_axis.push(Color::INVISIBLE, Polygon::rectangle(width, 1.0));
+        for (idx, lbl) in labels.into_iter().enumerate().step_by(step) {
+            let lbl_width = lbl.get_dims().width;
+            let x = (slot_width * ((idx as f64) + 0.5) - lbl_width / 2.0)
+                .max(0.0)
+                .min(width - lbl_width);
+            x_axis.append(lbl.translate(x, 5.0));
+        }
+
+        let legend = if series.len() > 1 {
+            Widget::row(
+                series
+                    .iter()
+                    .map(|s| legend_entry(ctx, &s.label, s.color))
+                    .collect(),
+            )
+            .flex_wrap(ctx, 24)
+            .margin_below(10)
+        } else {
+            Widget::nothing()
+        };
+
+        // Don't let the x-axis fill the parent container
+        Widget::row(vec![Widget::col(vec![
+            legend,
+            Widget::row(vec![
+                y_axis(ctx, max_y).evenly_spaced(),
+                DrawWithTooltips::new(ctx, batch, tooltips),
+            ]),
+            // The labels are exactly as wide as the plot, so this lines them up
+            JustDraw::wrap(ctx, x_axis).align_right(),
+        ])])
+    }
+
+    // Sorts samples into equal-width buckets from zero to the largest sample, and shows how many
+    // land in each. Counts from multiple series are stacked.
+    pub fn histogram<T: Yvalue<T>>(
+        ctx: &EventCtx,
+        series: Vec<BarSeries<T>>,
+        num_buckets: usize,
+    ) -> Widget {
+        let (categories, counts) = buckets(series, num_buckets);
+        BarChart::new(ctx, categories, counts, BarLayout::Stacked)
+    }
+}
+
+// For BarChart::histogram. Returns a label per bucket, and each series' count per bucket.
+fn buckets<T: Yvalue<T>>(
+    series: Vec<BarSeries<T>>,
+    num_buckets: usize,
+) -> (Vec<String>, Vec<BarSeries<usize>>) {
+    assert!(num_buckets > 0);
+    let max = series
+        .iter()
+        .flat_map(|s| s.values.iter().cloned())
+        .max()
+        .unwrap_or(T::zero());
+    let pct = |idx: usize| (idx as f64) / (num_buckets as f64);
+    let categories = (0..num_buckets)
+        .map(|idx| {
+            format!(
+                "{} - {}",
+                max.from_percent(pct(idx)).prettyprint(),
+                max.from_percent(pct(idx + 1)).prettyprint()
+            )
+        })
+        .collect();
+    let counts = series
+        .into_iter()
+        .map(|s| {
+            let mut values: Vec<usize> = vec![0; num_buckets];
+            for x in s.values {
+                // The largest sample belongs in the last bucket
+                let idx = (x.to_percent(max) * (num_buckets as f64)) as usize;
+                values[idx.min(num_buckets - 1)] += 1;
+            }
+            BarSeries {
+                label: s.label,
+                color: s.color,
+                values,
+            }
+        })
+        .collect();
+    (categories, counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn series(label: &str, values: Vec<usize>) -> BarSeries<usize> {
+        BarSeries {
+            label: label.to_string(),
+            color: Color::RED,
+            values,
+        }
+    }
+
+    fn counts(series: &Vec<BarSeries<usize>>) -> Vec<Vec<usize>> {
+        series.iter().map(|s| s.values.clone()).collect()
+    }
+
+    #[test]
+    fn bucket_edges() {
+        let (categories, result) = buckets(
+            vec![series("a", vec![0, 4, 5, 9, 10]), series("b", vec![1])],
+            2,
+        );
+        assert_eq!(categories, vec!["0 - 5", "5 - 10"]);
+        // A sample on an edge goes in the bucket above it, except for the largest
+        assert_eq!(counts(&result), vec![vec![2, 3], vec![1, 0]]);
+    }
+
+    #[test]
+    fn single_value() {
+        let (categories, result) = buckets(vec![series("a", vec![7])], 3);
+        assert_eq!(categories, vec!["0 - 2", "2 - 4", "4 - 7"]);
+        assert_eq!(counts(&result), vec![vec![0, 0, 1]]);
+
+        // If the only value is zero, the buckets have no width
+        let (categories, result) = buckets(vec![series("a", vec![0, 0])], 2);
+        assert_eq!(categories, vec!["0 - 0", "0 - 0"]);
+        assert_eq!(counts(&result), vec![vec![2, 0]]);
+    }
+
+    #[test]
+    fn empty() {
+        let (categories, result) = buckets(vec![series("a", Vec::new())], 2);
+        assert_eq!(categories, vec!["0 - 0", "0 - 0"]);
+        assert_eq!(counts(&result), vec![vec![0, 0]]);
+
+        let (categories, result) = buckets(Vec::<BarSeries<usize>>::new(), 2);
+        assert_eq!(categories.len(), 2);
+        assert!(result.is_empty());
+
+        // Drawing an empty histogram doesn't divide by zero anywhere
+        let mut h = crate::Harness::new(1920.0, 1080.0);
+        h.setup(|ctx| {
+            BarChart::histogram(ctx, vec![series("a", Vec::new())], 5);
+            BarChart::histogram(ctx, vec![series("a", vec![3])], 5);
+        });
+    }
+}
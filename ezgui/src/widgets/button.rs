@@ -1,6 +1,6 @@
 use crate::{
-    svg, Color, Drawable, EventCtx, GeomBatch, GfxCtx, JustDraw, Line, MultiKey, Outcome,
-    RewriteColor, ScreenDims, ScreenPt, Text, TextSpan, Widget, WidgetImpl, WidgetOutput,
+    hotkeys, svg, Color, Drawable, EventCtx, GeomBatch, GfxCtx, JustDraw, Key, Line, MultiKey,
+    Outcome, RewriteColor, ScreenDims, ScreenPt, Text, TextSpan, Widget, WidgetImpl, WidgetOutput,
 };
 use geom::Polygon;
 
@@ -108,6 +108,18 @@ impl WidgetImpl for Button {
             g.redraw_at(self.top_left, &self.draw_normal);
         }
     }
+
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focused_event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        if ctx
+            .input
+            .new_was_pressed(&hotkeys(vec![Key::Enter, Key::Space]).unwrap())
+        {
+            output.outcome = Some(Outcome::Clicked(self.action.clone()));
+        }
+    }
 }
 
 pub struct Btn {}
@@ -100,14 +100,31 @@ impl WidgetImpl for Checkbox {
     fn event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
         self.btn.event(ctx, output);
         if output.outcome.take().is_some() {
-            std::mem::swap(&mut self.btn, &mut self.other_btn);
-            self.btn.set_pos(self.other_btn.top_left);
-            self.enabled = !self.enabled;
-            output.redo_layout = true;
+            self.toggle(output);
         }
     }
 
     fn draw(&self, g: &mut GfxCtx) {
         self.btn.draw(g);
     }
+
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focused_event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        // Same keys as a button
+        self.btn.focused_event(ctx, output);
+        if output.outcome.take().is_some() {
+            self.toggle(output);
+        }
+    }
+}
+
+impl Checkbox {
+    fn toggle(&mut self, output: &mut WidgetOutput) {
+        std::mem::swap(&mut self.btn, &mut self.other_btn);
+        self.btn.set_pos(self.other_btn.top_left);
+        self.enabled = !self.enabled;
+        output.redo_layout = true;
+    }
 }
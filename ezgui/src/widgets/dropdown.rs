@@ -1,6 +1,6 @@
 use crate::{
-    Btn, Button, Choice, Color, EventCtx, GeomBatch, GfxCtx, InputResult, Menu, ScreenDims,
-    ScreenPt, ScreenRectangle, WidgetImpl, WidgetOutput,
+    hotkeys, Btn, Button, Choice, Color, EventCtx, GeomBatch, GfxCtx, InputResult, Key, Menu,
+    ScreenDims, ScreenPt, ScreenRectangle, WidgetImpl, WidgetOutput,
 };
 use geom::{Distance, Polygon, Pt2D};
 
@@ -45,6 +45,9 @@ impl<T: 'static + PartialEq + Clone> Dropdown<T> {
     pub(crate) fn current_value_label(&self) -> String {
         self.choices[self.current_idx].label.clone()
     }
+    pub(crate) fn is_open(&self) -> bool {
+        self.menu.is_some()
+    }
 }
 
 impl<T: 'static + Clone> Dropdown<T> {
@@ -72,6 +75,19 @@ impl<T: 'static + Clone> Dropdown<T> {
         ));
         self.menu = Some(menu);
     }
+
+    fn select(&mut self, ctx: &EventCtx, idx: usize, output: &mut WidgetOutput) {
+        self.current_idx = idx;
+        let top_left = self.btn.top_left;
+        self.btn = make_btn(
+            ctx,
+            &self.choices[self.current_idx].label,
+            &self.label,
+            self.blank_btn_label,
+        );
+        self.btn.set_pos(top_left);
+        output.redo_layout = true;
+    }
 }
 
 impl<T: 'static + Clone> WidgetImpl for Dropdown<T> {
@@ -93,16 +109,7 @@ impl<T: 'static + Clone> WidgetImpl for Dropdown<T> {
                 }
                 InputResult::Done(_, idx) => {
                     self.menu = None;
-                    self.current_idx = idx;
-                    let top_left = self.btn.top_left;
-                    self.btn = make_btn(
-                        ctx,
-                        &self.choices[self.current_idx].label,
-                        &self.label,
-                        self.blank_btn_label,
-                    );
-                    self.btn.set_pos(top_left);
-                    output.redo_layout = true;
+                    self.select(ctx, idx, output);
                 }
             }
         } else {
@@ -144,6 +151,36 @@ impl<T: 'static + Clone> WidgetImpl for Dropdown<T> {
         }
     }
 
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focused_event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        // The open menu already handles the keyboard
+        if self.menu.is_some() {
+            return;
+        }
+        if ctx
+            .input
+            .new_was_pressed(&hotkeys(vec![Key::Enter, Key::Space]).unwrap())
+        {
+            self.open_menu(ctx);
+        } else if ctx
+            .input
+            .new_was_pressed(&hotkeys(vec![Key::UpArrow, Key::LeftArrow]).unwrap())
+        {
+            if self.current_idx > 0 {
+                self.select(ctx, self.current_idx - 1, output);
+            }
+        } else if ctx
+            .input
+            .new_was_pressed(&hotkeys(vec![Key::DownArrow, Key::RightArrow]).unwrap())
+        {
+            if self.current_idx + 1 < self.choices.len() {
+                self.select(ctx, self.current_idx + 1, output);
+            }
+        }
+    }
+
     fn can_restore(&self) -> bool {
         true
     }
@@ -85,47 +85,8 @@ impl<T: Yvalue<T>> LinePlot<T> {
         let height = 0.2 * ctx.canvas.window_height;
 
         let mut batch = GeomBatch::new();
-        // Grid lines for the Y scale. Draw up to 10 lines max to cover the order of magnitude of
-        // the range.
-        // TODO This caps correctly, but if the max is 105, then suddenly we just have 2 grid
-        // lines.
-        {
-            let order_of_mag = 10.0_f64.powf(max_y.to_f64().log10().ceil());
-            for i in 0..10 {
-                let y = max_y.from_f64(order_of_mag / 10.0 * (i as f64));
-                let pct = y.to_percent(max_y);
-                if pct > 1.0 {
-                    break;
-                }
-                batch.push(
-                    Color::hex("#7C7C7C"),
-                    PolyLine::new(vec![
-                        Pt2D::new(0.0, (1.0 - pct) * height),
-                        Pt2D::new(width, (1.0 - pct) * height),
-                    ])
-                    .make_polygons(Distance::meters(1.0)),
-                );
-            }
-        }
-        // X axis grid
-        if max_x != Time::START_OF_DAY {
-            let order_of_mag = 10.0_f64.powf(max_x.inner_seconds().log10().ceil());
-            for i in 0..10 {
-                let x = Time::START_OF_DAY + Duration::seconds(order_of_mag / 10.0 * (i as f64));
-                let pct = x.to_percent(max_x);
-                if pct > 1.0 {
-                    break;
-                }
-                batch.push(
-                    Color::hex("#7C7C7C"),
-                    PolyLine::new(vec![
-                        Pt2D::new(pct * width, 0.0),
-                        Pt2D::new(pct * width, height),
-                    ])
-                    .make_polygons(Distance::meters(1.0)),
-                );
-            }
-        }
+        draw_y_grid(&mut batch, max_y, width, height);
+        draw_x_grid(&mut batch, max_x, width, height);
 
         let mut closest = FindClosest::new(&Bounds::from(&vec![
             Pt2D::new(0.0, 0.0),
@@ -163,29 +124,8 @@ impl<T: Yvalue<T>> LinePlot<T> {
             dims: ScreenDims::new(width, height),
         };
 
-        let num_x_labels = 3;
-        let mut row = Vec::new();
-        for i in 0..num_x_labels {
-            let percent_x = (i as f64) / ((num_x_labels - 1) as f64);
-            let t = max_x.percent_of(percent_x);
-            // TODO Need ticks now to actually see where this goes
-            let batch = Text::from(Line(t.to_string()))
-                .render_ctx(ctx)
-                .rotate(Angle::new_degs(-15.0))
-                .autocrop();
-            // The text is already scaled; don't use Widget::draw_batch and scale it again.
-            row.push(JustDraw::wrap(ctx, batch));
-        }
-        let x_axis = Widget::row(row).padding(10);
-
-        let num_y_labels = 4;
-        let mut col = Vec::new();
-        for i in 0..num_y_labels {
-            let percent_y = (i as f64) / ((num_y_labels - 1) as f64);
-            col.push(max_y.from_percent(percent_y).prettyprint().draw_text(ctx));
-        }
-        col.reverse();
-        let y_axis = Widget::col(col).padding(10);
+        let x_axis = time_axis(ctx, max_x);
+        let y_axis = y_axis(ctx, max_y);
 
         // Don't let the x-axis fill the parent container
         Widget::row(vec![Widget::col(vec![
@@ -327,24 +267,101 @@ pub fn make_legend<T: Yvalue<T>>(
                 Line(&s.label).draw(ctx),
             ]));
         } else {
-            let radius = 15.0;
-            row.push(Widget::row(vec![
-                Widget::draw_batch(
-                    ctx,
-                    GeomBatch::from(vec![(
-                        s.color,
-                        Circle::new(Pt2D::new(radius, radius), Distance::meters(radius))
-                            .to_polygon(),
-                    )]),
-                )
-                .margin(5),
-                s.label.clone().draw_text(ctx),
-            ]));
+            row.push(legend_entry(ctx, &s.label, s.color));
         }
     }
     Widget::row(row).flex_wrap(ctx, 24)
 }
 
+pub fn legend_entry(ctx: &EventCtx, label: &str, color: Color) -> Widget {
+    let radius = 15.0;
+    Widget::row(vec![
+        Widget::draw_batch(
+            ctx,
+            GeomBatch::from(vec![(
+                color,
+                Circle::new(Pt2D::new(radius, radius), Distance::meters(radius)).to_polygon(),
+            )]),
+        )
+        .margin(5),
+        label.draw_text(ctx),
+    ])
+}
+
+// Grid lines for the Y scale. Draw up to 10 lines max to cover the order of magnitude of the
+// range.
+// TODO This caps correctly, but if the max is 105, then suddenly we just have 2 grid lines.
+pub fn draw_y_grid<T: Yvalue<T>>(batch: &mut GeomBatch, max_y: T, width: f64, height: f64) {
+    let order_of_mag = 10.0_f64.powf(max_y.to_f64().log10().ceil());
+    for i in 0..10 {
+        let y = max_y.from_f64(order_of_mag / 10.0 * (i as f64));
+        let pct = y.to_percent(max_y);
+        if pct > 1.0 {
+            break;
+        }
+        batch.push(
+            Color::hex("#7C7C7C"),
+            PolyLine::new(vec![
+                Pt2D::new(0.0, (1.0 - pct) * height),
+                Pt2D::new(width, (1.0 - pct) * height),
+            ])
+            .make_polygons(Distance::meters(1.0)),
+        );
+    }
+}
+
+pub fn draw_x_grid(batch: &mut GeomBatch, max_x: Time, width: f64, height: f64) {
+    if max_x == Time::START_OF_DAY {
+        return;
+    }
+    let order_of_mag = 10.0_f64.powf(max_x.inner_seconds().log10().ceil());
+    for i in 0..10 {
+        let x = Time::START_OF_DAY + Duration::seconds(order_of_mag / 10.0 * (i as f64));
+        let pct = x.to_percent(max_x);
+        if pct > 1.0 {
+            break;
+        }
+        batch.push(
+            Color::hex("#7C7C7C"),
+            PolyLine::new(vec![
+                Pt2D::new(pct * width, 0.0),
+                Pt2D::new(pct * width, height),
+            ])
+            .make_polygons(Distance::meters(1.0)),
+        );
+    }
+}
+
+// Labels for an X axis from midnight to max_x. Use evenly_spaced() to stretch it under the plot.
+pub fn time_axis(ctx: &EventCtx, max_x: Time) -> Widget {
+    let num_x_labels = 3;
+    let mut row = Vec::new();
+    for i in 0..num_x_labels {
+        let percent_x = (i as f64) / ((num_x_labels - 1) as f64);
+        let t = max_x.percent_of(percent_x);
+        // TODO Need ticks now to actually see where this goes
+        let batch = Text::from(Line(t.to_string()))
+            .render_ctx(ctx)
+            .rotate(Angle::new_degs(-15.0))
+            .autocrop();
+        // The text is already scaled; don't use Widget::draw_batch and scale it again.
+        row.push(JustDraw::wrap(ctx, batch));
+    }
+    Widget::row(row).padding(10)
+}
+
+// Labels for a Y axis from zero to max_y. Use evenly_spaced() to stretch it beside the plot.
+pub fn y_axis<T: Yvalue<T>>(ctx: &EventCtx, max_y: T) -> Widget {
+    let num_y_labels = 4;
+    let mut col = Vec::new();
+    for i in 0..num_y_labels {
+        let percent_y = (i as f64) / ((num_y_labels - 1) as f64);
+        col.push(max_y.from_percent(percent_y).prettyprint().draw_text(ctx));
+    }
+    col.reverse();
+    Widget::col(col).padding(10)
+}
+
 // TODO If this proves useful, lift to geom
 fn thick_lineseries(pts: Vec<Pt2D>, width: Distance) -> Polygon {
     use lyon::math::{point, Point};
@@ -1,4 +1,5 @@
 pub mod autocomplete;
+pub mod bar_chart;
 pub mod button;
 pub mod checkbox;
 pub mod compare_times;
@@ -12,6 +13,8 @@ pub mod persistent_split;
 pub mod scatter_plot;
 pub mod slider;
 pub mod spinner;
+pub mod stacked_area;
+pub mod table;
 pub mod text_box;
 
 use crate::{EventCtx, GfxCtx, ScreenDims, ScreenPt};
@@ -39,8 +42,18 @@ pub trait WidgetImpl: downcast_rs::Downcast {
     fn restore(&mut self, _ctx: &mut EventCtx, _prev: &Box<dyn WidgetImpl>) {
         unreachable!()
     }
+    /// Can the user move keyboard focus to this widget with Tab?
+    fn can_focus(&self) -> bool {
+        false
+    }
+    /// Called when keyboard focus moves onto or away from this widget.
+    fn set_focus(&mut self, _focused: bool) {}
+    /// Only called while this widget has keyboard focus, before any widget in the Composite sees
+    /// the event. This lets keys like Enter and the arrows take priority over hotkeys.
+    fn focused_event(&mut self, _ctx: &mut EventCtx, _output: &mut WidgetOutput) {}
 }
 
+#[derive(Debug, PartialEq)]
 pub enum Outcome {
     Clicked(String),
 }
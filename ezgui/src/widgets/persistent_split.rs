@@ -74,6 +74,36 @@ impl<T: 'static + Clone + PartialEq> WidgetImpl for PersistentSplit<T> {
         }
 
         self.dropdown.event(ctx, output);
+        self.update_btn(ctx, output);
+    }
+
+    fn draw(&self, g: &mut GfxCtx) {
+        self.btn.draw(g);
+        self.spacer.draw(g);
+        self.dropdown.draw(g);
+    }
+
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focused_event(&mut self, ctx: &mut EventCtx, output: &mut WidgetOutput) {
+        // The open menu already handles the keyboard
+        if self.dropdown.is_open() {
+            return;
+        }
+        // Enter and space press the main button, and the arrows change the choice
+        self.btn.focused_event(ctx, output);
+        if output.outcome.is_some() {
+            return;
+        }
+        self.dropdown.focused_event(ctx, output);
+        self.update_btn(ctx, output);
+    }
+}
+
+impl<T: 'static + Clone + PartialEq> PersistentSplit<T> {
+    // The button always shows the current choice
+    fn update_btn(&mut self, ctx: &EventCtx, output: &mut WidgetOutput) {
         let new_value = self.dropdown.current_value();
         if new_value != self.current_value {
             self.current_value = new_value;
@@ -85,10 +115,4 @@ impl<T: 'static + Clone + PartialEq> WidgetImpl for PersistentSplit<T> {
             output.redo_layout = true;
         }
     }
-
-    fn draw(&self, g: &mut GfxCtx) {
-        self.btn.draw(g);
-        self.spacer.draw(g);
-        self.dropdown.draw(g);
-    }
 }
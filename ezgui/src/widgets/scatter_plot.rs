@@ -1,9 +1,9 @@
-use crate::widgets::line_plot::{make_legend, Yvalue};
+use crate::widgets::line_plot::{draw_x_grid, draw_y_grid, make_legend, time_axis, y_axis, Yvalue};
 use crate::{
-    Color, Drawable, EventCtx, GeomBatch, GfxCtx, JustDraw, Line, PlotOptions, ScreenDims,
-    ScreenPt, Series, Text, TextExt, Widget, WidgetImpl, WidgetOutput,
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, Line, PlotOptions, ScreenDims, ScreenPt, Series,
+    Text, Widget, WidgetImpl, WidgetOutput,
 };
-use geom::{Angle, Circle, Distance, Duration, PolyLine, Pt2D, Time};
+use geom::{Circle, Distance, PolyLine, Pt2D, Time};
 
 // The X is always time
 pub struct ScatterPlot {
@@ -57,47 +57,8 @@ impl ScatterPlot {
         let height = 0.2 * ctx.canvas.window_height;
 
         let mut batch = GeomBatch::new();
-        // Grid lines for the Y scale. Draw up to 10 lines max to cover the order of magnitude of
-        // the range.
-        // TODO This caps correctly, but if the max is 105, then suddenly we just have 2 grid
-        // lines.
-        {
-            let order_of_mag = 10.0_f64.powf(max_y.to_f64().log10().ceil());
-            for i in 0..10 {
-                let y = max_y.from_f64(order_of_mag / 10.0 * (i as f64));
-                let pct = y.to_percent(max_y);
-                if pct > 1.0 {
-                    break;
-                }
-                batch.push(
-                    Color::hex("#7C7C7C"),
-                    PolyLine::new(vec![
-                        Pt2D::new(0.0, (1.0 - pct) * height),
-                        Pt2D::new(width, (1.0 - pct) * height),
-                    ])
-                    .make_polygons(Distance::meters(1.0)),
-                );
-            }
-        }
-        // X axis grid
-        if max_x != Time::START_OF_DAY {
-            let order_of_mag = 10.0_f64.powf(max_x.inner_seconds().log10().ceil());
-            for i in 0..10 {
-                let x = Time::START_OF_DAY + Duration::seconds(order_of_mag / 10.0 * (i as f64));
-                let pct = x.to_percent(max_x);
-                if pct > 1.0 {
-                    break;
-                }
-                batch.push(
-                    Color::hex("#7C7C7C"),
-                    PolyLine::new(vec![
-                        Pt2D::new(pct * width, 0.0),
-                        Pt2D::new(pct * width, height),
-                    ])
-                    .make_polygons(Distance::meters(1.0)),
-                );
-            }
-        }
+        draw_y_grid(&mut batch, max_y, width, height);
+        draw_x_grid(&mut batch, max_x, width, height);
 
         let circle = Circle::new(Pt2D::new(0.0, 0.0), Distance::meters(4.0)).to_polygon();
         let mut sum = T::zero();
@@ -143,29 +104,8 @@ impl ScatterPlot {
             dims: ScreenDims::new(width, height),
         };
 
-        let num_x_labels = 3;
-        let mut row = Vec::new();
-        for i in 0..num_x_labels {
-            let percent_x = (i as f64) / ((num_x_labels - 1) as f64);
-            let t = max_x.percent_of(percent_x);
-            // TODO Need ticks now to actually see where this goes
-            let batch = Text::from(Line(t.to_string()))
-                .render_ctx(ctx)
-                .rotate(Angle::new_degs(-15.0))
-                .autocrop();
-            // The text is already scaled; don't use Widget::draw_batch and scale it again.
-            row.push(JustDraw::wrap(ctx, batch));
-        }
-        let x_axis = Widget::row(row).padding(10);
-
-        let num_y_labels = 4;
-        let mut col = Vec::new();
-        for i in 0..num_y_labels {
-            let percent_y = (i as f64) / ((num_y_labels - 1) as f64);
-            col.push(max_y.from_percent(percent_y).prettyprint().draw_text(ctx));
-        }
-        col.reverse();
-        let y_axis = Widget::col(col).padding(10);
+        let x_axis = time_axis(ctx, max_x);
+        let y_axis = y_axis(ctx, max_y);
 
         // Don't let the x-axis fill the parent container
         Widget::row(vec![Widget::col(vec![
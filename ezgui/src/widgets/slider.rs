@@ -1,6 +1,6 @@
 use crate::{
-    Color, Drawable, EventCtx, GeomBatch, GfxCtx, ScreenDims, ScreenPt, ScreenRectangle, Widget,
-    WidgetImpl, WidgetOutput,
+    hotkey, Color, Drawable, EventCtx, GeomBatch, GfxCtx, Key, ScreenDims, ScreenPt,
+    ScreenRectangle, Widget, WidgetImpl, WidgetOutput,
 };
 use geom::{Circle, Distance, Polygon, Pt2D};
 
@@ -20,6 +20,8 @@ pub struct Slider {
 }
 
 const BG_CROSS_AXIS_LEN: f64 = 20.0;
+// How far the arrow keys move a slider
+const KEYBOARD_STEP: f64 = 0.05;
 
 impl Slider {
     pub fn horizontal(
@@ -217,6 +219,20 @@ impl WidgetImpl for Slider {
         g.canvas
             .mark_covered_area(ScreenRectangle::top_left(self.top_left, self.dims));
     }
+
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focused_event(&mut self, ctx: &mut EventCtx, _: &mut WidgetOutput) {
+        let (decrease, increase) = if self.horiz {
+            (Key::LeftArrow, Key::RightArrow)
+        } else {
+            (Key::UpArrow, Key::DownArrow)
+        };
+        if let Some(percent) = arrow_keys(ctx, decrease, increase, self.current_percent) {
+            self.set_percent(ctx, percent);
+        }
+    }
 }
 
 // TODO Try to dedupe code maybe
@@ -380,4 +396,26 @@ impl WidgetImpl for AreaSlider {
     fn draw(&self, g: &mut GfxCtx) {
         g.redraw_at(self.top_left, &self.draw);
     }
+
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focused_event(&mut self, ctx: &mut EventCtx, _: &mut WidgetOutput) {
+        if let Some(percent) =
+            arrow_keys(ctx, Key::LeftArrow, Key::RightArrow, self.current_percent)
+        {
+            self.set_percent(ctx, percent);
+        }
+    }
+}
+
+// If one of the keys was pressed, returns the new percent
+fn arrow_keys(ctx: &mut EventCtx, decrease: Key, increase: Key, percent: f64) -> Option<f64> {
+    if ctx.input.new_was_pressed(&hotkey(decrease).unwrap()) {
+        Some((percent - KEYBOARD_STEP).max(0.0))
+    } else if ctx.input.new_was_pressed(&hotkey(increase).unwrap()) {
+        Some((percent + KEYBOARD_STEP).min(1.0))
+    } else {
+        None
+    }
 }
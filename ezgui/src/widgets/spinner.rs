@@ -1,6 +1,6 @@
 use crate::{
-    text, Btn, Button, EventCtx, GeomBatch, GfxCtx, Line, ScreenDims, ScreenPt, ScreenRectangle,
-    Text, Widget, WidgetImpl, WidgetOutput,
+    hotkeys, text, Btn, Button, EventCtx, GeomBatch, GfxCtx, Key, Line, ScreenDims, ScreenPt,
+    ScreenRectangle, Text, Widget, WidgetImpl, WidgetOutput,
 };
 use geom::{Polygon, Pt2D};
 
@@ -116,4 +116,25 @@ impl WidgetImpl for Spinner {
         self.up.draw(g);
         self.down.draw(g);
     }
+
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn focused_event(&mut self, ctx: &mut EventCtx, _: &mut WidgetOutput) {
+        if ctx
+            .input
+            .new_was_pressed(&hotkeys(vec![Key::UpArrow, Key::RightArrow]).unwrap())
+        {
+            if self.current != self.high {
+                self.current += 1;
+            }
+        } else if ctx
+            .input
+            .new_was_pressed(&hotkeys(vec![Key::DownArrow, Key::LeftArrow]).unwrap())
+        {
+            if self.current != self.low {
+                self.current -= 1;
+            }
+        }
+    }
 }
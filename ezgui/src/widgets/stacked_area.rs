@@ -0,0 +1,176 @@
+use crate::widgets::line_plot::{draw_x_grid, draw_y_grid, make_legend, time_axis, y_axis, Yvalue};
+use crate::{
+    Color, Drawable, EventCtx, GeomBatch, GfxCtx, Line, PlotOptions, ScreenDims, ScreenPt,
+    ScreenRectangle, Series, Text, Widget, WidgetImpl, WidgetOutput,
+};
+use geom::{Distance, PolyLine, Polygon, Pt2D, Time};
+use std::ops::Add;
+
+// The X is always time. Each series is piled on top of the previous ones, so the top edge is the
+// total. Every series must have values at the same times.
+pub struct StackedAreaPlot<T: Yvalue<T>> {
+    draw: Drawable,
+
+    max_x: Time,
+    times: Vec<Time>,
+    // Per series, in the original order
+    values: Vec<(String, Vec<T>)>,
+    totals: Vec<T>,
+
+    top_left: ScreenPt,
+    dims: ScreenDims,
+}
+
+impl<T: Yvalue<T> + Add<Output = T>> StackedAreaPlot<T> {
+    pub fn new(ctx: &EventCtx, mut series: Vec<Series<T>>, opts: PlotOptions<T>) -> Widget {
+        let legend = make_legend(ctx, &series, &opts);
+        series.retain(|s| !opts.disabled.contains(&s.label));
+
+        let times: Vec<Time> = series
+            .get(0)
+            .map(|s| s.pts.iter().map(|(t, _)| *t).collect())
+            .unwrap_or_else(Vec::new);
+        for s in &series {
+            assert!(
+                s.pts.iter().map(|(t, _)| *t).eq(times.iter().cloned()),
+                "{} doesn't have values at the same times as the other series",
+                s.label
+            );
+        }
+
+        // Assume min_x is Time::START_OF_DAY and min_y is T::zero()
+        let max_x = opts
+            .max_x
+            .unwrap_or_else(|| times.last().cloned().unwrap_or(Time::START_OF_DAY));
+        let mut totals = vec![T::zero(); times.len()];
+        let mut bands = Vec::new();
+        let mut values = Vec::new();
+        for s in series {
+            let lower = totals.clone();
+            for (total, (_, y)) in totals.iter_mut().zip(s.pts.iter()) {
+                *total = *total + *y;
+            }
+            bands.push((s.color, lower, totals.clone()));
+            values.push((s.label, s.pts.into_iter().map(|(_, y)| y).collect()));
+        }
+        let max_y = opts
+            .max_y
+            .unwrap_or_else(|| totals.iter().cloned().max().unwrap_or(T::zero()));
+
+        // TODO Tuned to fit the info panel, like LinePlot.
+        let width = 0.23 * ctx.canvas.window_width;
+        let height = 0.2 * ctx.canvas.window_height;
+
+        let mut batch = GeomBatch::new();
+        draw_y_grid(&mut batch, max_y, width, height);
+        draw_x_grid(&mut batch, max_x, width, height);
+
+        if max_x != Time::START_OF_DAY && times.len() >= 2 {
+            let to_pt = |t: Time, y: T| {
+                Pt2D::new(
+                    t.to_percent(max_x) * width,
+                    // Y inversion
+                    (1.0 - y.to_percent(max_y)) * height,
+                )
+            };
+            for (color, lower, upper) in bands {
+                // Two triangles between each pair of times, even where the band is empty
+                let mut pts = Vec::new();
+                let mut indices = Vec::new();
+                for (idx, t) in times.iter().enumerate() {
+                    pts.push(to_pt(*t, lower[idx]));
+                    pts.push(to_pt(*t, upper[idx]));
+                    if idx > 0 {
+                        let (l1, u1, l2, u2) = (2 * idx - 2, 2 * idx - 1, 2 * idx, 2 * idx + 1);
+                        indices.extend(vec![l1, u1, u2, l1, u2, l2]);
+                    }
+                }
+                batch.push(color, Polygon::precomputed(pts, indices));
+            }
+        }
+
+        let plot = StackedAreaPlot {
+            draw: ctx.upload(batch),
+            max_x,
+            times,
+            values,
+            totals,
+
+            top_left: ScreenPt::new(0.0, 0.0),
+            dims: ScreenDims::new(width, height),
+        };
+
+        let x_axis = time_axis(ctx, max_x);
+        let y_axis = y_axis(ctx, max_y);
+
+        // Don't let the x-axis fill the parent container
+        Widget::row(vec![Widget::col(vec![
+            legend.margin_below(10),
+            Widget::row(vec![y_axis.evenly_spaced(), Widget::new(Box::new(plot))]),
+            x_axis.evenly_spaced(),
+        ])])
+    }
+}
+
+impl<T: Yvalue<T>> WidgetImpl for StackedAreaPlot<T> {
+    fn get_dims(&self) -> ScreenDims {
+        self.dims
+    }
+
+    fn set_pos(&mut self, top_left: ScreenPt) {
+        self.top_left = top_left;
+    }
+
+    fn event(&mut self, _ctx: &mut EventCtx, _output: &mut WidgetOutput) {}
+
+    fn draw(&self, g: &mut GfxCtx) {
+        g.redraw_at(self.top_left, &self.draw);
+
+        if self.times.is_empty() || self.max_x == Time::START_OF_DAY {
+            return;
+        }
+        if let Some(cursor) = g.canvas.get_cursor_in_screen_space() {
+            if !ScreenRectangle::top_left(self.top_left, self.dims).contains(cursor) {
+                return;
+            }
+            // Describe the sample closest to the cursor
+            let t = self
+                .max_x
+                .percent_of((cursor.x - self.top_left.x) / self.dims.width);
+            let idx = match self.times.binary_search(&t) {
+                Ok(idx) => idx,
+                Err(0) => 0,
+                Err(idx) if idx == self.times.len() => idx - 1,
+                Err(idx) => {
+                    if t - self.times[idx - 1] < self.times[idx] - t {
+                        idx - 1
+                    } else {
+                        idx
+                    }
+                }
+            };
+
+            let mut txt = Text::from(Line(self.times[idx].ampm_tostring()));
+            // List from the top of the pile down, to match the picture
+            for (label, values) in self.values.iter().rev() {
+                txt.add(Line(format!("{}: {}", label, values[idx].prettyprint())));
+            }
+            if self.values.len() > 1 {
+                txt.add(Line(format!("Total: {}", self.totals[idx].prettyprint())).secondary());
+            }
+
+            let x = self.times[idx].to_percent(self.max_x) * self.dims.width;
+            g.fork_screenspace();
+            g.draw_polygon(
+                Color::WHITE,
+                &PolyLine::new(vec![
+                    Pt2D::new(self.top_left.x + x, self.top_left.y),
+                    Pt2D::new(self.top_left.x + x, self.top_left.y + self.dims.height),
+                ])
+                .make_polygons(Distance::meters(2.0)),
+            );
+            g.draw_mouse_tooltip(txt);
+            g.unfork();
+        }
+    }
+}
@@ -0,0 +1,538 @@
+use crate::{Btn, Composite, EventCtx, GeomBatch, Line, Text, TextExt, Widget};
+use abstutil::prettyprint_usize;
+use geom::Polygon;
+use std::cell::RefCell;
+use std::cmp::Ordering;
+
+// Rows that can be sorted by clicking column headers, filtered, paged through, and exported to
+// CSV. This isn't a widget by itself, since it's made of many buttons. render() produces widgets
+// to put in a Composite; whoever owns that Composite passes along clicks (clicked) and other
+// changes (panel_changed), then rebuilds the Composite when either returns true.
+pub struct Table<T, F> {
+    id: String,
+    data: Vec<T>,
+    label_per_row: Box<dyn Fn(&T) -> String>,
+    columns: Vec<Column<T>>,
+    filter: Filter<T, F>,
+    // Rows containing this in any column are shown. Case-insensitive.
+    text_filter: String,
+
+    sort_by: String,
+    descending: bool,
+    skip: usize,
+    rows_per_page: usize,
+    // The label and index in data of every row last rendered. Only these are buttons, so looking
+    // up clicked or hovered rows doesn't have to search everything.
+    page: RefCell<Vec<(String, usize)>>,
+}
+
+pub enum Col<T> {
+    Static,
+    Sortable(Box<dyn Fn(&T, &T) -> Ordering>),
+}
+
+impl<T: 'static> Col<T> {
+    pub fn sort_by_key<K: Ord, G: 'static + Fn(&T) -> K>(key: G) -> Col<T> {
+        Col::Sortable(Box::new(move |a, b| key(a).cmp(&key(b))))
+    }
+}
+
+struct Column<T> {
+    name: String,
+    // Used for the text filter and CSV export, and drawn if there's no custom render
+    value: Box<dyn Fn(&T) -> String>,
+    render: Option<Box<dyn Fn(&EventCtx, &T) -> GeomBatch>>,
+    col: Col<T>,
+}
+
+// Controls for choosing which rows to show, beyond the text filter. The state is read back from
+// the controls every time the Composite changes.
+pub struct Filter<T, F> {
+    pub state: F,
+    pub to_controls: Box<dyn Fn(&mut EventCtx, &F) -> Widget>,
+    pub from_controls: Box<dyn Fn(&Composite) -> F>,
+    pub apply: Box<dyn Fn(&F, &T) -> bool>,
+}
+
+impl<T> Filter<T, ()> {
+    pub fn empty() -> Filter<T, ()> {
+        Filter {
+            state: (),
+            to_controls: Box::new(|_, _| Widget::nothing()),
+            from_controls: Box::new(|_| ()),
+            apply: Box::new(|_, _| true),
+        }
+    }
+}
+
+impl<T: 'static, F: PartialEq> Table<T, F> {
+    // The id names the table's own buttons, so it should be unique in the Composite. Each row
+    // also becomes a button, labeled by label_per_row.
+    pub fn new<L: 'static + Fn(&T) -> String>(
+        id: &str,
+        data: Vec<T>,
+        label_per_row: L,
+        default_sort_by: &str,
+        filter: Filter<T, F>,
+    ) -> Table<T, F> {
+        Table {
+            id: id.to_string(),
+            data,
+            label_per_row: Box::new(label_per_row),
+            columns: Vec::new(),
+            filter,
+            text_filter: String::new(),
+
+            sort_by: default_sort_by.to_string(),
+            descending: true,
+            skip: 0,
+            rows_per_page: 10,
+            page: RefCell::new(Vec::new()),
+        }
+    }
+
+    pub fn rows_per_page(&mut self, n: usize) {
+        assert!(n > 0);
+        self.rows_per_page = n;
+    }
+
+    pub fn column<V: 'static + Fn(&T) -> String>(&mut self, name: &str, value: V, col: Col<T>) {
+        self.columns.push(Column {
+            name: name.to_string(),
+            value: Box::new(value),
+            render: None,
+            col,
+        });
+    }
+
+    // Like column, but drawn some other way than the plain value.
+    pub fn custom_column<
+        V: 'static + Fn(&T) -> String,
+        R: 'static + Fn(&EventCtx, &T) -> GeomBatch,
+    >(
+        &mut self,
+        name: &str,
+        value: V,
+        render: R,
+        col: Col<T>,
+    ) {
+        self.columns.push(Column {
+            name: name.to_string(),
+            value: Box::new(value),
+            render: Some(Box::new(render)),
+            col,
+        });
+    }
+
+    // Returns true if the action belonged to the table, meaning the Composite should be rebuilt.
+    pub fn clicked(&mut self, action: &str) -> bool {
+        if action == self.prev_action() {
+            self.skip -= self.rows_per_page;
+            return true;
+        }
+        if action == self.next_action() {
+            self.skip += self.rows_per_page;
+            return true;
+        }
+        if action == self.export_action() {
+            let path = abstutil::path_csv_export(&self.id.replace(' ', "_"));
+            match std::fs::create_dir_all(std::path::Path::new(&path).parent().unwrap())
+                .and_then(|_| std::fs::write(&path, self.to_csv()))
+            {
+                Ok(()) => println!("Exported {}", path),
+                Err(err) => println!("Couldn't export {}: {}", path, err),
+            }
+            return true;
+        }
+        let sortable = self.columns.iter().any(|c| {
+            c.name == action
+                && match c.col {
+                    Col::Sortable(_) => true,
+                    Col::Static => false,
+                }
+        });
+        if sortable {
+            self.skip = 0;
+            if self.sort_by == action {
+                self.descending = !self.descending;
+            } else {
+                self.sort_by = action.to_string();
+                self.descending = true;
+            }
+            return true;
+        }
+        false
+    }
+
+    // Call when the Composite produces no outcome, to notice filter changes. Returns true if the
+    // Composite should be rebuilt.
+    pub fn panel_changed(&mut self, composite: &Composite) -> bool {
+        let state = (self.filter.from_controls)(composite);
+        let text_filter = composite.text_box(&self.text_filter_name());
+        if state != self.filter.state || text_filter != self.text_filter {
+            self.filter.state = state;
+            self.text_filter = text_filter;
+            self.skip = 0;
+            return true;
+        }
+        false
+    }
+
+    // If the action is a row of the table, returns the row.
+    pub fn selected(&self, action: &str) -> Option<&T> {
+        let idx = self
+            .page
+            .borrow()
+            .iter()
+            .find(|(label, _)| label == action)
+            .map(|(_, idx)| *idx)?;
+        Some(&self.data[idx])
+    }
+
+    pub fn hovering(&self, composite: &Composite) -> Option<&T> {
+        composite
+            .currently_hovering()
+            .and_then(|action| self.selected(action))
+    }
+
+    // Every row passing the filters, not just the current page, in the current order
+    pub fn to_csv(&self) -> String {
+        let mut out = self
+            .columns
+            .iter()
+            .map(|c| escape_csv(&c.name))
+            .collect::<Vec<_>>()
+            .join(",");
+        out.push('\n');
+        for idx in self.visible_rows() {
+            let x = &self.data[idx];
+            out.push_str(
+                &self
+                    .columns
+                    .iter()
+                    .map(|c| escape_csv(&(c.value)(x)))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+            out.push('\n');
+        }
+        out
+    }
+
+    pub fn render(&self, ctx: &mut EventCtx) -> Widget {
+        let rows = self.visible_rows();
+        let total_rows = rows.len();
+
+        let mut col = vec![
+            Widget::row(vec![
+                (self.filter.to_controls)(ctx, &self.filter.state),
+                Widget::row(vec![
+                    "Search:".draw_text(ctx).margin_right(5),
+                    Widget::text_entry(ctx, self.text_filter.clone(), false)
+                        .named(self.text_filter_name()),
+                ])
+                .align_right(),
+            ])
+            .margin_below(5),
+            Widget::row(vec![
+                if self.skip > 0 {
+                    Btn::text_fg("<").build(ctx, self.prev_action(), None)
+                } else {
+                    Btn::text_fg("<").inactive(ctx)
+                }
+                .margin_right(10),
+                format!(
+                    "{}-{} of {}",
+                    if total_rows > 0 {
+                        prettyprint_usize(self.skip + 1)
+                    } else {
+                        "0".to_string()
+                    },
+                    prettyprint_usize((self.skip + self.rows_per_page).min(total_rows)),
+                    prettyprint_usize(total_rows)
+                )
+                .draw_text(ctx)
+                .margin_right(10),
+                if self.skip + self.rows_per_page < total_rows {
+                    Btn::text_fg(">").build(ctx, self.next_action(), None)
+                } else {
+                    Btn::text_fg(">").inactive(ctx)
+                },
+                Btn::text_bg2("export to CSV")
+                    .build(ctx, self.export_action(), None)
+                    .align_right(),
+            ])
+            .margin_below(5),
+        ];
+
+        let headers = self
+            .columns
+            .iter()
+            .map(|c| match c.col {
+                Col::Static => Line(&c.name).draw(ctx),
+                Col::Sortable(_) => {
+                    if self.sort_by == c.name {
+                        Btn::text_bg2(format!(
+                            "{} {}",
+                            c.name,
+                            if self.descending { "↓" } else { "↑" }
+                        ))
+                        .build(ctx, &c.name, None)
+                    } else {
+                        Btn::text_bg2(&c.name).build_def(ctx, None)
+                    }
+                }
+            })
+            .collect();
+        let page: Vec<(String, usize)> = rows
+            .into_iter()
+            .skip(self.skip)
+            .take(self.rows_per_page)
+            .map(|idx| ((self.label_per_row)(&self.data[idx]), idx))
+            .collect();
+        let cells = page
+            .iter()
+            .map(|(label, idx)| {
+                let x = &self.data[*idx];
+                (
+                    label.clone(),
+                    self.columns
+                        .iter()
+                        .map(|c| {
+                            if let Some(ref render) = c.render {
+                                render(ctx, x)
+                            } else {
+                                Text::from(Line((c.value)(x))).render_ctx(ctx)
+                            }
+                        })
+                        .collect(),
+                )
+            })
+            .collect();
+        *self.page.borrow_mut() = page;
+        col.extend(make_table(
+            ctx,
+            headers,
+            cells,
+            0.88 * ctx.canvas.window_width,
+        ));
+
+        Widget::col(col).named(&self.id)
+    }
+
+    // Filtered and sorted, as indices into data
+    fn visible_rows(&self) -> Vec<usize> {
+        let query = self.text_filter.to_lowercase();
+        let mut rows: Vec<usize> = self
+            .data
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| (self.filter.apply)(&self.filter.state, *x))
+            .filter(|(_, x)| {
+                query.is_empty()
+                    || self
+                        .columns
+                        .iter()
+                        .any(|c| (c.value)(*x).to_lowercase().contains(&query))
+            })
+            .map(|(idx, _)| idx)
+            .collect();
+        if let Some(Col::Sortable(cmp)) = self
+            .columns
+            .iter()
+            .find(|c| c.name == self.sort_by)
+            .map(|c| &c.col)
+        {
+            if self.descending {
+                rows.sort_by(|a, b| cmp(&self.data[*a], &self.data[*b]).reverse());
+            } else {
+                rows.sort_by(|a, b| cmp(&self.data[*a], &self.data[*b]));
+            }
+        }
+        rows
+    }
+
+    fn prev_action(&self) -> String {
+        format!("previous page of {}", self.id)
+    }
+    fn next_action(&self) -> String {
+        format!("next page of {}", self.id)
+    }
+    fn export_action(&self) -> String {
+        format!("export {} to CSV", self.id)
+    }
+    fn text_filter_name(&self) -> String {
+        format!("search {}", self.id)
+    }
+}
+
+fn make_table(
+    ctx: &mut EventCtx,
+    headers: Vec<Widget>,
+    rows: Vec<(String, Vec<GeomBatch>)>,
+    total_width: f64,
+) -> Vec<Widget> {
+    let total_width = total_width / ctx.get_scale_factor();
+    let mut width_per_col: Vec<f64> = headers
+        .iter()
+        .map(|w| w.get_width_for_forcing() / ctx.get_scale_factor())
+        .collect();
+    for (_, row) in &rows {
+        for (col, width) in row.iter().zip(width_per_col.iter_mut()) {
+            *width = width.max(col.get_dims().width / ctx.get_scale_factor());
+        }
+    }
+    // Spread leftover space between the columns. With only one column, there's nowhere to put it.
+    let extra_margin = if width_per_col.len() <= 1 {
+        0.0
+    } else {
+        ((total_width - width_per_col.iter().sum::<f64>()) / (width_per_col.len() - 1) as f64)
+            .max(0.0)
+    };
+
+    let mut col = vec![Widget::row(
+        headers
+            .into_iter()
+            .enumerate()
+            .map(|(idx, w)| {
+                let margin = extra_margin + width_per_col[idx]
+                    - (w.get_width_for_forcing() / ctx.get_scale_factor());
+                if idx == width_per_col.len() - 1 {
+                    w.margin_right((margin - extra_margin) as usize)
+                } else {
+                    w.margin_right(margin as usize)
+                }
+            })
+            .collect(),
+    )
+    .bg(ctx.style().section_bg)];
+
+    for (label, row) in rows {
+        let mut batch = GeomBatch::new();
+        batch.autocrop_dims = false;
+        let mut x1 = 0.0;
+        for (col, width) in row.into_iter().zip(width_per_col.iter()) {
+            batch.append(col.scale(1.0 / ctx.get_scale_factor()).translate(x1, 0.0));
+            x1 += *width + extra_margin;
+        }
+
+        let rect = Polygon::rectangle(total_width, batch.get_dims().height);
+        let mut hovered = GeomBatch::new();
+        hovered.push(ctx.style().hovering_color, rect.clone());
+        hovered.append(batch.clone());
+
+        col.push(
+            Btn::custom(batch, hovered, rect)
+                .tooltip(Text::new())
+                .build(ctx, label, None),
+        );
+    }
+
+    col
+}
+
+fn escape_csv(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table<(usize, &'static str), bool> {
+        let filter = Filter {
+            state: true,
+            to_controls: Box::new(|_, _| Widget::nothing()),
+            from_controls: Box::new(|_| true),
+            // When false, hide odd rows
+            apply: Box::new(|show_odd, x| *show_odd || x.0 % 2 == 0),
+        };
+        let mut table = Table::new(
+            "test",
+            vec![(1, "one"), (2, "two"), (3, "three, etc"), (4, "four")],
+            |x: &(usize, &str)| x.0.to_string(),
+            "Number",
+            filter,
+        );
+        table.column(
+            "Number",
+            |x| x.0.to_string(),
+            Col::sort_by_key(|x: &(usize, &str)| x.0),
+        );
+        table.column("Name", |x| x.1.to_string(), Col::Static);
+        table
+    }
+
+    fn numbers(table: &Table<(usize, &'static str), bool>) -> Vec<usize> {
+        table
+            .visible_rows()
+            .into_iter()
+            .map(|idx| table.data[idx].0)
+            .collect()
+    }
+
+    #[test]
+    fn sort_and_filter() {
+        let mut t = table();
+        assert_eq!(numbers(&t), vec![4, 3, 2, 1]);
+        assert!(t.clicked("Number"));
+        assert_eq!(numbers(&t), vec![1, 2, 3, 4]);
+        // Static columns aren't sortable
+        assert!(!t.clicked("Name"));
+
+        t.text_filter = "T".to_string();
+        assert_eq!(numbers(&t), vec![2, 3]);
+        t.filter.state = false;
+        assert_eq!(numbers(&t), vec![2]);
+    }
+
+    #[test]
+    fn select_rendered_rows() {
+        let mut t = table();
+        t.rows_per_page(2);
+        // Nothing's a button until the table is rendered
+        assert!(t.selected("4").is_none());
+
+        let mut h = crate::Harness::new(1920.0, 1080.0);
+        h.setup(|ctx| t.render(ctx));
+        assert_eq!(t.selected("3").map(|x| x.1), Some("three, etc"));
+        assert!(t.selected("2").is_none());
+        assert!(t.selected("5").is_none());
+
+        assert!(t.clicked(&t.next_action()));
+        h.setup(|ctx| t.render(ctx));
+        assert_eq!(t.selected("2").map(|x| x.1), Some("two"));
+        assert!(t.selected("3").is_none());
+    }
+
+    #[test]
+    fn few_columns() {
+        let mut h = crate::Harness::new(1920.0, 1080.0);
+        h.setup(|ctx| {
+            let rows = vec![(
+                "row".to_string(),
+                vec![GeomBatch::from(vec![(
+                    crate::Color::RED,
+                    Polygon::rectangle(10.0, 10.0),
+                )])],
+            )];
+            // The header row, then one button per row
+            assert_eq!(
+                make_table(ctx, vec!["Only".draw_text(ctx)], rows, 500.0).len(),
+                2
+            );
+            assert_eq!(make_table(ctx, Vec::new(), Vec::new(), 500.0).len(), 1);
+        });
+    }
+
+    #[test]
+    fn csv() {
+        let mut t = table();
+        t.text_filter = "three".to_string();
+        assert_eq!(t.to_csv(), "Number,Name\n3,\"three, etc\"\n");
+    }
+}
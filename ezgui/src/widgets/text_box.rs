@@ -120,4 +120,21 @@ impl WidgetImpl for TextBox {
         let draw = g.upload(batch);
         g.redraw_at(self.top_left, &draw);
     }
+
+    fn can_restore(&self) -> bool {
+        true
+    }
+    fn restore(&mut self, _: &mut EventCtx, prev: &Box<dyn WidgetImpl>) {
+        // Keep typing, if the Composite was rebuilt in response to this textbox changing
+        let prev = prev.downcast_ref::<TextBox>().unwrap();
+        self.has_focus = prev.has_focus;
+        self.cursor_x = prev.cursor_x.min(self.line.len());
+    }
+
+    fn can_focus(&self) -> bool {
+        true
+    }
+    fn set_focus(&mut self, focused: bool) {
+        self.has_focus = focused;
+    }
 }
@@ -5,7 +5,7 @@ use crate::layer::Layer;
 use crate::options::Options;
 use crate::render::{AgentCache, AgentColorScheme, DrawMap, DrawOptions, Renderable};
 use crate::sandbox::{GameplayMode, TutorialState};
-use abstutil::{MeasureMemory, Timer};
+use abstutil::{Catalog, MeasureMemory, Timer};
 use ezgui::{EventCtx, GfxCtx, Prerender};
 use geom::{Bounds, Circle, Distance, Duration, Pt2D, Time};
 use map_model::{IntersectionID, Map, Traversable};
@@ -25,6 +25,8 @@ pub struct App {
     // It has no meaning in edit mode.
     pub agent_cs: AgentColorScheme,
     pub opts: Options,
+    // Translations for UI text, matching opts.language
+    pub catalog: Catalog,
 
     pub per_obj: PerObjectActions,
     pub layer: Option<Box<dyn Layer>>,
@@ -38,7 +40,7 @@ pub struct App {
 
 impl App {
     pub fn new(flags: Flags, opts: Options, ctx: &mut EventCtx, splash: bool) -> App {
-        let cs = ColorScheme::new(opts.color_scheme);
+        let cs = ColorScheme::new(opts.color_scheme, opts.data_palette);
         ctx.set_style(cs.gui_style.clone());
 
         let primary = ctx.loading_screen("load map", |ctx, mut timer| {
@@ -76,6 +78,7 @@ impl App {
             prebaked: None,
             agent_cs: AgentColorScheme::new(&cs),
             cs,
+            catalog: Catalog::load_or_english(&opts.language),
             opts,
             per_obj: PerObjectActions::new(),
             layer: None,
@@ -112,6 +115,14 @@ impl App {
                     "- bus_passengers_waiting: {} bytes",
                     prettyprint_usize(serialized_size_bytes(&a.bus_passengers_waiting))
                 );
+                println!(
+                    "- bus_loads: {} bytes",
+                    prettyprint_usize(serialized_size_bytes(&a.bus_loads))
+                );
+                println!(
+                    "- bus_denied_boardings: {} bytes",
+                    prettyprint_usize(serialized_size_bytes(&a.bus_denied_boardings))
+                );
                 println!(
                     "- started_trips: {} bytes",
                     prettyprint_usize(serialized_size_bytes(&a.started_trips))
@@ -120,6 +131,10 @@ impl App {
                     "- finished_trips: {} bytes",
                     prettyprint_usize(serialized_size_bytes(&a.finished_trips))
                 );
+                println!(
+                    "- cancelled_trips: {} bytes",
+                    prettyprint_usize(serialized_size_bytes(&a.cancelled_trips))
+                );
                 println!(
                     "- trip_log: {} bytes",
                     prettyprint_usize(serialized_size_bytes(&a.trip_log))
@@ -136,6 +151,14 @@ impl App {
                     "- parking_lot_changes: {} bytes",
                     prettyprint_usize(serialized_size_bytes(&a.parking_lot_changes))
                 );
+                println!(
+                    "- pandemic_counts: {} bytes",
+                    prettyprint_usize(serialized_size_bytes(&a.pandemic_counts))
+                );
+                println!(
+                    "- pandemic_exposures: {} bytes",
+                    prettyprint_usize(serialized_size_bytes(&a.pandemic_exposures))
+                );
             }
         }
     }
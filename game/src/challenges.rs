@@ -222,7 +222,7 @@ impl Tab {
             let challenge = Challenge::all().remove(name).unwrap().remove(current);
             let mut txt = Text::new();
             for l in &challenge.description {
-                txt.add(Line(l));
+                txt.add(Line(app.catalog.tr(l)));
             }
 
             let mut inner_col = vec![
@@ -1,5 +1,6 @@
 use crate::common::ColorScale;
 use ezgui::{Choice, Color, Style};
+use serde::{Deserialize, Serialize};
 
 // I've gone back and forth how to organize color scheme code. I was previously against having one
 // centralized place with all definitions, because careful naming or comments are needed to explain
@@ -37,6 +38,29 @@ impl ColorSchemeChoice {
     }
 }
 
+// Independent of the ColorSchemeChoice, this picks the colors that data layers use to encode
+// values. The standard palette leans on red vs green, which is hard to tell apart for many people.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DataPalette {
+    Standard,
+    // Red-green, with weak green
+    Deuteranopia,
+    // Red-green, with weak red, so dark reds look nearly black
+    Protanopia,
+    HighContrast,
+}
+
+impl DataPalette {
+    pub fn choices() -> Vec<Choice<DataPalette>> {
+        vec![
+            Choice::new("default", DataPalette::Standard),
+            Choice::new("deuteranopia (red-green)", DataPalette::Deuteranopia),
+            Choice::new("protanopia (red-green)", DataPalette::Protanopia),
+            Choice::new("high contrast", DataPalette::HighContrast),
+        ]
+    }
+}
+
 pub struct ColorScheme {
     // UI
     pub hovering: Color,
@@ -110,8 +134,21 @@ pub struct ColorScheme {
     // Layers
     pub good_to_bad_red: ColorScale,
     pub good_to_bad_green: ColorScale,
+    // Low is good, middle is neutral, high is bad. Used to compare before and after edits.
+    pub good_to_bad_diverging: ColorScale,
     pub bus_layer: Color,
     pub edits_layer: Color,
+    // Individual points, like people, drawn instead of a heatmap
+    pub layer_dots: Color,
+    // The area of a traffic jam, and the intersection where it started
+    pub traffic_jam: Color,
+    pub jam_epicenter: Color,
+    // Arrows pointing uphill
+    pub elevation_arrow: Color,
+    // Distinct colors for categories, like types of amenities
+    category_colors: Vec<Color>,
+    // The name of a gradient that HeatmapOptions understands
+    pub default_heatmap: &'static str,
 
     // Misc
     pub parking_trip: Color,
@@ -120,8 +157,8 @@ pub struct ColorScheme {
 }
 
 impl ColorScheme {
-    pub fn new(scheme: ColorSchemeChoice) -> ColorScheme {
-        match scheme {
+    pub fn new(scheme: ColorSchemeChoice, palette: DataPalette) -> ColorScheme {
+        let mut cs = match scheme {
             ColorSchemeChoice::Standard => ColorScheme::standard(),
             ColorSchemeChoice::NightMode => ColorScheme::night_mode(),
             ColorSchemeChoice::SAMGreenDay => ColorScheme::sam_green_day(),
@@ -129,6 +166,63 @@ impl ColorScheme {
             ColorSchemeChoice::BAP => ColorScheme::bap(),
             ColorSchemeChoice::OSM => ColorScheme::osm(),
             ColorSchemeChoice::Starcat => ColorScheme::starcat(),
+        };
+        cs.use_palette(palette);
+        cs
+    }
+
+    // Palettes are picked from ColorBrewer and the IBM design library, avoiding pairs that the
+    // relevant kind of color blindness confuses.
+    fn use_palette(&mut self, palette: DataPalette) {
+        match palette {
+            DataPalette::Standard => {}
+            DataPalette::Deuteranopia => {
+                // Purple vs orange
+                self.good_to_bad_red = ColorScale(vec![hex("#FDB863"), hex("#B35806")]);
+                self.good_to_bad_green = ColorScale(vec![hex("#B2ABD2"), hex("#542788")]);
+                self.good_to_bad_diverging =
+                    ColorScale(vec![hex("#542788"), Color::WHITE, hex("#B35806")]);
+                self.layer_dots = hex("#E66101").alpha(0.8);
+                self.traffic_jam = hex("#B35806");
+                self.category_colors = okabe_ito();
+                self.default_heatmap = "Cividis";
+            }
+            DataPalette::Protanopia => {
+                // Blue vs yellow-brown
+                self.good_to_bad_red = ColorScale(vec![hex("#FEE08B"), hex("#8C510A")]);
+                self.good_to_bad_green = ColorScale(vec![hex("#92C5DE"), hex("#2166AC")]);
+                self.good_to_bad_diverging =
+                    ColorScale(vec![hex("#2166AC"), Color::WHITE, hex("#8C510A")]);
+                self.layer_dots = hex("#D8B365").alpha(0.9);
+                self.traffic_jam = hex("#8C510A");
+                self.category_colors = okabe_ito();
+                self.default_heatmap = "Viridis";
+            }
+            DataPalette::HighContrast => {
+                self.good_to_bad_red = ColorScale(vec![hex("#FFB000"), hex("#DC267F")]);
+                self.good_to_bad_green = ColorScale(vec![hex("#648FFF"), hex("#002DB3")]);
+                self.good_to_bad_diverging =
+                    ColorScale(vec![hex("#002DB3"), Color::WHITE, hex("#DC267F")]);
+                self.bus_layer = hex("#0000FF");
+                self.edits_layer = hex("#FF00FF");
+                self.layer_dots = Color::BLACK;
+                self.traffic_jam = hex("#DC267F");
+                self.jam_epicenter = Color::BLACK;
+                self.category_colors = vec![
+                    Color::BLACK,
+                    hex("#DC267F"),
+                    hex("#648FFF"),
+                    hex("#785EF0"),
+                    hex("#198038"),
+                    hex("#002DB3"),
+                    hex("#FFB000"),
+                    hex("#9F1853"),
+                    hex("#FE6100"),
+                    Color::WHITE,
+                    hex("#808080"),
+                ];
+                self.default_heatmap = "Inferno";
+            }
         }
     }
 
@@ -138,7 +232,7 @@ impl ColorScheme {
             // UI
             hovering: gui_style.hovering_color,
             panel_bg: gui_style.panel_bg,
-            section_bg: Color::grey(0.5),
+            section_bg: gui_style.section_bg,
             inner_panel: hex("#4C4C4C"),
             day_time_slider: hex("#F4DA22"),
             night_time_slider: hex("#12409D"),
@@ -213,8 +307,27 @@ impl ColorScheme {
             // Layers
             good_to_bad_red: ColorScale(vec![hex("#F19A93"), hex("#A32015")]),
             good_to_bad_green: ColorScale(vec![hex("#BEDB92"), hex("#397A4C")]),
+            good_to_bad_diverging: ColorScale(vec![hex("#5D9630"), Color::WHITE, hex("#A32015")]),
             bus_layer: hex("#4CA7E9"),
             edits_layer: hex("#12409D"),
+            layer_dots: Color::RED.alpha(0.8),
+            traffic_jam: Color::RED,
+            jam_epicenter: Color::WHITE,
+            elevation_arrow: Color::BLACK,
+            category_colors: vec![
+                Color::BLACK,
+                Color::RED,
+                Color::BLUE,
+                Color::PURPLE,
+                Color::GREEN,
+                Color::CYAN,
+                Color::YELLOW,
+                Color::PINK,
+                Color::ORANGE,
+                Color::WHITE,
+                hex("#96322F"),
+            ],
+            default_heatmap: "Turbo",
 
             // Misc
             parking_trip: hex("#4E30A6"),
@@ -239,6 +352,10 @@ impl ColorScheme {
     pub fn rotating_color_agents(&self, idx: usize) -> Color {
         modulo_color(&self.agent_colors, idx)
     }
+
+    pub fn category_color(&self, idx: usize) -> Color {
+        modulo_color(&self.category_colors, idx)
+    }
 }
 
 fn modulo_color(colors: &Vec<Color>, idx: usize) -> Color {
@@ -250,6 +367,24 @@ fn hex(x: &str) -> Color {
     Color::hex(x)
 }
 
+// Okabe and Ito's categorical palette is distinguishable with any kind of color blindness. It only
+// has 8 colors, so grey, white, and a dark red are added.
+fn okabe_ito() -> Vec<Color> {
+    vec![
+        Color::BLACK,
+        hex("#D55E00"),
+        hex("#0072B2"),
+        hex("#CC79A7"),
+        hex("#009E73"),
+        hex("#56B4E9"),
+        hex("#F0E442"),
+        hex("#882255"),
+        hex("#E69F00"),
+        Color::WHITE,
+        hex("#999999"),
+    ]
+}
+
 // Alternate, in-progress schemes
 impl ColorScheme {
     fn night_mode() -> ColorScheme {
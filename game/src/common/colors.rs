@@ -1,4 +1,5 @@
 use crate::app::App;
+use crate::colors::ColorScheme;
 use abstutil::Counter;
 use ezgui::{Color, Drawable, EventCtx, FancyColor, GeomBatch, Line, LinearGradient, Text, Widget};
 use geom::{Circle, Distance, Line, Polygon, Pt2D};
@@ -118,29 +119,7 @@ impl ColorLegend {
         scale: &ColorScale,
         labels: Vec<I>,
     ) -> Widget {
-        assert!(scale.0.len() >= 2);
-        let width = 300.0;
-        let n = scale.0.len();
-        let mut batch = GeomBatch::new();
-        let width_each = width / ((n - 1) as f64);
-        batch.fancy_push(
-            FancyColor::LinearGradient(LinearGradient {
-                line: Line::new(Pt2D::new(0.0, 0.0), Pt2D::new(width, 0.0)),
-                stops: scale
-                    .0
-                    .iter()
-                    .enumerate()
-                    .map(|(idx, color)| ((idx as f64) / ((n - 1) as f64), *color))
-                    .collect(),
-            }),
-            Polygon::union_all(
-                (0..n - 1)
-                    .map(|i| {
-                        Polygon::rectangle(width_each, 32.0).translate((i as f64) * width_each, 0.0)
-                    })
-                    .collect(),
-            ),
-        );
+        let batch = scale.gradient_bar(300.0, 32.0);
         // Extra wrapping to make the labels stretch against just the scale, not everything else
         // TODO Long labels aren't nicely lined up with the boundaries between buckets
         Widget::row(vec![Widget::col(vec![
@@ -231,13 +210,14 @@ pub struct ColorNetwork<'a> {
 
 impl<'a> ColorNetwork<'a> {
     pub fn new(app: &'a App) -> ColorNetwork {
+        ColorNetwork::for_map(&app.primary.map, &app.cs)
+    }
+
+    pub fn for_map(map: &'a Map, cs: &ColorScheme) -> ColorNetwork<'a> {
         let mut unzoomed = GeomBatch::new();
-        unzoomed.push(
-            app.cs.fade_map_dark,
-            app.primary.map.get_boundary_polygon().clone(),
-        );
+        unzoomed.push(cs.fade_map_dark, map.get_boundary_polygon().clone());
         ColorNetwork {
-            map: &app.primary.map,
+            map,
             unzoomed,
             zoomed: GeomBatch::new(),
         }
@@ -302,6 +282,34 @@ impl<'a> ColorNetwork<'a> {
 pub struct ColorScale(pub Vec<Color>);
 
 impl ColorScale {
+    // A horizontal bar blending through all of the colors, for legends.
+    pub fn gradient_bar(&self, width: f64, height: f64) -> GeomBatch {
+        assert!(self.0.len() >= 2);
+        let n = self.0.len();
+        let mut batch = GeomBatch::new();
+        let width_each = width / ((n - 1) as f64);
+        batch.fancy_push(
+            FancyColor::LinearGradient(LinearGradient {
+                line: Line::new(Pt2D::new(0.0, 0.0), Pt2D::new(width, 0.0)),
+                stops: self
+                    .0
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, color)| ((idx as f64) / ((n - 1) as f64), *color))
+                    .collect(),
+            }),
+            Polygon::union_all(
+                (0..n - 1)
+                    .map(|i| {
+                        Polygon::rectangle(width_each, height)
+                            .translate((i as f64) * width_each, 0.0)
+                    })
+                    .collect(),
+            ),
+        );
+        batch
+    }
+
     pub fn eval(&self, pct: f64) -> Color {
         let (low, pct) = self.inner_eval(pct);
         self.0[low].lerp(self.0[low + 1], pct)
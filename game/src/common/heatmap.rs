@@ -1,3 +1,4 @@
+use crate::colors::ColorScheme;
 use crate::common::{ColorLegend, ColorScale};
 use ezgui::{Checkbox, Choice, Color, Composite, EventCtx, GeomBatch, Spinner, TextExt, Widget};
 use geom::{Bounds, Histogram, Polygon, Pt2D, Statistic};
@@ -24,12 +25,13 @@ pub struct HeatmapOptions {
 }
 
 impl HeatmapOptions {
-    pub fn new() -> HeatmapOptions {
+    // The gradient comes from the data palette
+    pub fn new(cs: &ColorScheme) -> HeatmapOptions {
         HeatmapOptions {
             resolution: 10,
             radius: 3,
             smoothing: true,
-            color_scheme: "Turbo".to_string(),
+            color_scheme: cs.default_heatmap.to_string(),
         }
     }
 
@@ -66,10 +68,13 @@ impl HeatmapOptions {
                     ctx,
                     "Color scheme",
                     self.color_scheme.clone(),
-                    vec!["Turbo", "Inferno", "Warm", "Cool", "Oranges", "Spectral"]
-                        .into_iter()
-                        .map(|x| Choice::string(x))
-                        .collect(),
+                    vec![
+                        "Turbo", "Inferno", "Warm", "Cool", "Oranges", "Spectral", "Viridis",
+                        "Cividis",
+                    ]
+                    .into_iter()
+                    .map(|x| Choice::string(x))
+                    .collect(),
                 ),
             ])
             .margin_below(5),
@@ -77,7 +82,7 @@ impl HeatmapOptions {
         ]
     }
 
-    pub fn from_controls(c: &Composite) -> HeatmapOptions {
+    pub fn from_controls(c: &Composite, cs: &ColorScheme) -> HeatmapOptions {
         // Did we just change?
         if c.has_widget("resolution") {
             HeatmapOptions {
@@ -87,7 +92,7 @@ impl HeatmapOptions {
                 color_scheme: c.dropdown_value("Color scheme"),
             }
         } else {
-            HeatmapOptions::new()
+            HeatmapOptions::new(cs)
         }
     }
 }
@@ -100,6 +105,17 @@ pub fn make_heatmap(
     pts: Vec<Pt2D>,
     opts: &HeatmapOptions,
 ) -> Widget {
+    let (scale, labels) = draw_heatmap(batch, bounds, pts, opts);
+    ColorLegend::gradient(ctx, &scale, labels)
+}
+
+// Like make_heatmap, but without a GUI. Returns the colors and labels for a legend.
+pub fn draw_heatmap(
+    batch: &mut GeomBatch,
+    bounds: &Bounds,
+    pts: Vec<Pt2D>,
+    opts: &HeatmapOptions,
+) -> (ColorScale, Vec<String>) {
     // 7 colors, 8 labels
     let num_colors = 7;
     let gradient = match opts.color_scheme.as_ref() {
@@ -109,6 +125,8 @@ pub fn make_heatmap(
         "Cool" => colorous::COOL,
         "Oranges" => colorous::ORANGES,
         "Spectral" => colorous::SPECTRAL,
+        "Viridis" => colorous::VIRIDIS,
+        "Cividis" => colorous::CIVIDIS,
         _ => unreachable!(),
     };
     let colors: Vec<Color> = (0..num_colors)
@@ -122,7 +140,7 @@ pub fn make_heatmap(
         let labels = std::iter::repeat("0".to_string())
             .take(num_colors + 1)
             .collect();
-        return ColorLegend::gradient(ctx, &ColorScale(colors), labels);
+        return (ColorScale(colors), labels);
     }
 
     // At each point, add a 2D Gaussian kernel centered at the point.
@@ -221,7 +239,7 @@ pub fn make_heatmap(
                 .to_string(),
         );
     }
-    ColorLegend::gradient(ctx, &ColorScale(colors), labels)
+    (ColorScale(colors), labels)
 }
 
 struct Grid<T> {
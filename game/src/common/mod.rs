@@ -8,7 +8,7 @@ mod warp;
 
 pub use self::city_picker::CityPicker;
 pub use self::colors::{ColorDiscrete, ColorLegend, ColorNetwork, ColorScale, DivergingScale};
-pub use self::heatmap::{make_heatmap, HeatmapOptions};
+pub use self::heatmap::{draw_heatmap, make_heatmap, HeatmapOptions};
 pub use self::minimap::Minimap;
 pub use self::panels::tool_panel;
 pub use self::warp::Warping;
@@ -145,7 +145,7 @@ impl State for PopularDestinations {
         }
 
         let opts = if self.composite.is_checked("Show heatmap") {
-            Some(HeatmapOptions::from_controls(&self.composite))
+            Some(HeatmapOptions::from_controls(&self.composite, &app.cs))
         } else {
             None
         };
@@ -0,0 +1,201 @@
+use crate::app::App;
+use crate::common::ColorDiscrete;
+use crate::edit::apply_map_edits;
+use crate::game::{msg, State, Transition};
+use ezgui::{
+    hotkey, Btn, Color, Composite, Drawable, EventCtx, GfxCtx, HorizontalAlignment, Key, Line,
+    Outcome, TextExt, VerticalAlignment, Widget,
+};
+use map_model::MapEdits;
+
+// Browse every change made so far (and any that could be redone), see what each one touches, and
+// jump between named checkpoints.
+pub struct EditHistory {
+    composite: Composite,
+    // The hovered change, and its (unzoomed, zoomed) preview
+    preview: Option<(String, Drawable, Drawable)>,
+}
+
+impl EditHistory {
+    pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
+        Box::new(EditHistory {
+            composite: make_panel(ctx, app),
+            preview: None,
+        })
+    }
+
+    fn change_edits(&mut self, ctx: &mut EventCtx, app: &mut App, edits: MapEdits) {
+        apply_map_edits(ctx, app, edits);
+        self.composite = make_panel(ctx, app);
+        self.preview = None;
+    }
+}
+
+impl State for EditHistory {
+    fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
+        match self.composite.event(ctx) {
+            Some(Outcome::Clicked(x)) => match x.as_ref() {
+                "close" => {
+                    return Transition::Pop;
+                }
+                "save checkpoint" => {
+                    let name = self
+                        .composite
+                        .text_box("new checkpoint name")
+                        .trim()
+                        .to_string();
+                    if !name.is_empty() {
+                        let mut edits = app.primary.map.get_edits().clone();
+                        edits.save_checkpoint(name);
+                        self.change_edits(ctx, app, edits);
+                    }
+                }
+                x => {
+                    let mut edits = app.primary.map.get_edits().clone();
+                    if x.starts_with("change #") {
+                        // Undo or redo until just after this change
+                        let num = x["change #".len()..].parse::<usize>().unwrap();
+                        while edits.commands.len() > num {
+                            edits.undo();
+                        }
+                        while edits.commands.len() < num {
+                            edits.redo();
+                        }
+                    } else if x.starts_with("delete checkpoint ") {
+                        let name = &x["delete checkpoint ".len()..];
+                        edits.checkpoints.retain(|cp| cp.name != name);
+                    } else if let Err(err) = edits.jump_to_checkpoint(&x["checkpoint ".len()..]) {
+                        return Transition::Push(msg("Error", vec![err]));
+                    }
+                    self.change_edits(ctx, app, edits);
+                }
+            },
+            None => {}
+        }
+
+        if ctx.redo_mouseover() {
+            let hovering = self
+                .composite
+                .currently_hovering()
+                .filter(|x| x.starts_with("change #") && x.as_str() != "change #0")
+                .cloned();
+            if hovering != self.preview.as_ref().map(|(x, _, _)| x.clone()) {
+                self.preview = hovering.map(|x| {
+                    let (unzoomed, zoomed) = preview_change(ctx, app, &x);
+                    (x, unzoomed, zoomed)
+                });
+            }
+        }
+
+        Transition::Keep
+    }
+
+    fn draw(&self, g: &mut GfxCtx, app: &App) {
+        if let Some((_, ref unzoomed, ref zoomed)) = self.preview {
+            if g.canvas.cam_zoom < app.opts.min_zoom_for_detail {
+                g.redraw(unzoomed);
+            } else {
+                g.redraw(zoomed);
+            }
+        }
+        self.composite.draw(g);
+    }
+}
+
+fn make_panel(ctx: &mut EventCtx, app: &App) -> Composite {
+    let edits = app.primary.map.get_edits();
+    let mut col = vec![Widget::row(vec![
+        Line("Edit history").small_heading().draw(ctx),
+        Btn::plaintext("X")
+            .build(ctx, "close", hotkey(Key::Escape))
+            .align_right(),
+    ])
+    .margin_below(10)];
+
+    col.push("Checkpoints".draw_text(ctx));
+    for cp in &edits.checkpoints {
+        col.push(Widget::row(vec![
+            if cp.commands == edits.commands {
+                Btn::text_fg(format!("{} (current)", cp.name)).inactive(ctx)
+            } else {
+                Btn::text_fg(&cp.name).build(ctx, format!("checkpoint {}", cp.name), None)
+            },
+            Btn::svg_def("../data/system/assets/tools/delete.svg")
+                .build(ctx, format!("delete checkpoint {}", cp.name), None)
+                .align_right(),
+        ]));
+    }
+    col.push(
+        Widget::row(vec![
+            Widget::text_entry(
+                ctx,
+                format!("checkpoint {}", edits.checkpoints.len() + 1),
+                false,
+            )
+            .named("new checkpoint name")
+            .margin_right(10),
+            Btn::text_bg2("save checkpoint").build_def(ctx, None),
+        ])
+        .margin_below(10),
+    );
+
+    col.push("Changes (hover to see what they affect)".draw_text(ctx));
+    col.push(if edits.commands.is_empty() {
+        Btn::text_fg("0) original map (current)").inactive(ctx)
+    } else {
+        Btn::plaintext("0) original map").build(ctx, "change #0", None)
+    });
+    let num_applied = edits.commands.len();
+    for (idx, cmd) in edits.commands.iter().enumerate() {
+        let label = format!("{}) {}", idx + 1, cmd.short_name());
+        col.push(if idx + 1 == num_applied {
+            Btn::text_fg(format!("{} (current)", label)).build(
+                ctx,
+                format!("change #{}", idx + 1),
+                None,
+            )
+        } else {
+            Btn::plaintext(label).build(ctx, format!("change #{}", idx + 1), None)
+        });
+    }
+    // Changes that were undone come next, greyed out, in the order they'd be redone
+    for (idx, cmd) in edits.redo_stack.iter().rev().enumerate() {
+        let num = num_applied + idx + 1;
+        let label = format!("{}) {}", num, cmd.short_name());
+        col.push(
+            Btn::text_fg_line(label.clone(), Line(label).fg(Color::grey(0.6))).build(
+                ctx,
+                format!("change #{}", num),
+                None,
+            ),
+        );
+    }
+
+    Composite::new(Widget::col(col).padding(16).bg(app.cs.panel_bg))
+        .max_size_percent(30, 80)
+        .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
+        .build(ctx)
+}
+
+// Highlight the roads and intersections touched by a change, from either the applied commands or
+// the redo stack.
+fn preview_change(ctx: &mut EventCtx, app: &App, action: &str) -> (Drawable, Drawable) {
+    let edits = app.primary.map.get_edits();
+    let num = action["change #".len()..].parse::<usize>().unwrap();
+    let cmd = if num <= edits.commands.len() {
+        &edits.commands[num - 1]
+    } else {
+        &edits.redo_stack[edits.redo_stack.len() - (num - edits.commands.len())]
+    };
+    let effects = cmd.preview_effects(&app.primary.map);
+
+    let mut colorer = ColorDiscrete::new(app, vec![("changed", Color::CYAN)]);
+    for r in effects.changed_roads {
+        colorer.add_r(r, "changed");
+    }
+    for i in effects.changed_intersections {
+        colorer.add_i(i, "changed");
+    }
+    let (unzoomed, zoomed, _) = colorer.build(ctx);
+    (unzoomed, zoomed)
+}
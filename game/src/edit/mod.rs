@@ -1,5 +1,6 @@
 mod bulk;
 mod cluster_traffic_signals;
+mod history;
 mod lanes;
 mod stop_signs;
 mod traffic_signals;
@@ -40,8 +41,8 @@ pub struct EditMode {
     // Retained state from the SandboxMode that spawned us
     mode: GameplayMode,
 
-    // edits name, number of commands
-    changelist_key: (String, usize),
+    // edits name, number of commands, number of redoable commands, number of checkpoints
+    changelist_key: (String, usize, usize, usize),
 
     unzoomed: Drawable,
     zoomed: Drawable,
@@ -61,7 +62,7 @@ impl EditMode {
             orig_edits: edits.clone(),
             orig_dirty,
             mode,
-            changelist_key: (edits.edits_name.clone(), edits.commands.len()),
+            changelist_key: changelist_key(edits),
             unzoomed: layer.unzoomed,
             zoomed: layer.zoomed,
         }
@@ -70,8 +71,11 @@ impl EditMode {
     fn quit(&self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         let old_sim = app.suspended_sim.take().unwrap();
 
-        // If nothing changed, short-circuit
-        if app.primary.map.get_edits() == &self.orig_edits {
+        // If nothing changed, short-circuit. Just undoing and redoing doesn't count.
+        let edits = app.primary.map.get_edits();
+        if edits.commands == self.orig_edits.commands
+            && edits.edits_name == self.orig_edits.edits_name
+        {
             app.primary.sim = old_sim;
             app.primary.dirty_from_edits = self.orig_dirty;
             // Could happen if we load some edits, then load whatever we entered edit mode with.
@@ -110,8 +114,7 @@ impl EditMode {
 impl State for EditMode {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         {
-            let edits = app.primary.map.get_edits();
-            let changelist_key = (edits.edits_name.clone(), edits.commands.len());
+            let changelist_key = changelist_key(app.primary.map.get_edits());
             if self.changelist_key != changelist_key {
                 self.changelist_key = changelist_key;
                 self.changelist = make_changelist(ctx, app);
@@ -179,9 +182,14 @@ impl State for EditMode {
                         Some(Transition::Pop)
                     })));
                 }
-                "undo" => {
+                "undo" | "redo" => {
                     let mut edits = app.primary.map.get_edits().clone();
-                    let id = cmd_to_id(&edits.commands.pop().unwrap());
+                    let cmd = if x == "undo" {
+                        edits.undo()
+                    } else {
+                        edits.redo()
+                    };
+                    let id = cmd_to_id(cmd.unwrap());
                     apply_map_edits(ctx, app, edits);
                     return Transition::Push(Warping::new(
                         ctx,
@@ -191,6 +199,9 @@ impl State for EditMode {
                         &mut app.primary,
                     ));
                 }
+                "history" => {
+                    return Transition::Push(history::EditHistory::new(ctx, app));
+                }
                 x => {
                     let idx = x["most recent change #".len()..].parse::<usize>().unwrap();
                     let id = cmd_to_id(
@@ -632,9 +643,16 @@ pub fn maybe_edit_intersection(
     None
 }
 
+fn changelist_key(edits: &MapEdits) -> (String, usize, usize, usize) {
+    (
+        edits.edits_name.clone(),
+        edits.commands.len(),
+        edits.redo_stack.len(),
+        edits.checkpoints.len(),
+    )
+}
+
 fn make_changelist(ctx: &mut EventCtx, app: &App) -> Composite {
-    // TODO Support redo. Bit harder here to reset the redo_stack when the edits
-    // change, because nested other places modify it too.
     let edits = app.primary.map.get_edits();
     let mut col = vec![
         Widget::row(vec![
@@ -658,6 +676,21 @@ fn make_changelist(ctx: &mut EventCtx, app: &App) -> Composite {
                     RewriteColor::ChangeAll(Color::WHITE.alpha(0.5)),
                 )
             })
+            .centered_vert()
+            .margin_right(10),
+            (if !edits.redo_stack.is_empty() {
+                Btn::svg_def("../data/system/assets/tools/redo.svg").build(
+                    ctx,
+                    "redo",
+                    lctrl(Key::Y),
+                )
+            } else {
+                Widget::draw_svg_transform(
+                    ctx,
+                    "../data/system/assets/tools/redo.svg",
+                    RewriteColor::ChangeAll(Color::WHITE.alpha(0.5)),
+                )
+            })
             .centered_vert(),
         ])
         .margin_below(10),
@@ -678,6 +711,10 @@ fn make_changelist(ctx: &mut EventCtx, app: &App) -> Composite {
                 "{} intersections changed",
                 edits.original_intersections.len()
             )),
+            Line(format!(
+                "{} intersections with transit priority",
+                edits.changed_transit_priority.len()
+            )),
         ])
         .draw(ctx)
         .margin_below(10),
@@ -695,6 +732,11 @@ fn make_changelist(ctx: &mut EventCtx, app: &App) -> Composite {
     if edits.commands.len() > 5 {
         col.push(format!("{} more...", edits.commands.len()).draw_text(ctx));
     }
+    col.push(
+        Btn::text_fg(format!("History ({} checkpoints)", edits.checkpoints.len()))
+            .build(ctx, "history", lctrl(Key::H))
+            .margin_above(10),
+    );
 
     Composite::new(Widget::col(col).padding(16).bg(app.cs.panel_bg))
         .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
@@ -707,5 +749,6 @@ fn cmd_to_id(cmd: &EditCmd) -> ID {
         EditCmd::ReverseLane { l, .. } => ID::Lane(*l),
         EditCmd::ChangeSpeedLimit { id, .. } => ID::Road(*id),
         EditCmd::ChangeIntersection { i, .. } => ID::Intersection(*i),
+        EditCmd::ChangeTransitPriority { i, .. } => ID::Intersection(*i),
     }
 }
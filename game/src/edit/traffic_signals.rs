@@ -504,6 +504,7 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
         .iter()
         .any(|t| t.between_sidewalks());
     let current_offset = app.primary.map.get_traffic_signal(i).offset;
+    let current_priority = app.primary.map.get_i(i).transit_priority;
 
     WizardState::new(Box::new(move |wiz, ctx, app| {
         let use_template = "use template";
@@ -511,6 +512,16 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
         let stop_sign = "convert to stop signs";
         let close = "close intersection for construction";
         let offset = "edit signal offset";
+        let signal_priority = if current_priority.signal_priority {
+            "turn off transit signal priority"
+        } else {
+            "turn on transit signal priority"
+        };
+        let queue_jump = if current_priority.queue_jump {
+            "turn off bus queue jumps"
+        } else {
+            "turn on bus queue jumps"
+        };
         let reset = "reset to default";
 
         let mut choices = vec![use_template];
@@ -523,6 +534,8 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
             choices.push(close);
         }
         choices.push(offset);
+        choices.push(signal_priority);
+        choices.push(queue_jump);
         choices.push(reset);
 
         let mut wizard = wiz.wrap(ctx);
@@ -593,6 +606,22 @@ fn edit_entire_signal(app: &App, i: IntersectionID, mode: GameplayMode) -> Box<d
                     editor.change_phase(editor.current_phase, ctx, app);
                 })))
             }
+            x if x == signal_priority || x == queue_jump => {
+                let mut new = current_priority;
+                if x == signal_priority {
+                    new.signal_priority = !new.signal_priority;
+                } else {
+                    new.queue_jump = !new.queue_jump;
+                }
+                let mut edits = app.primary.map.get_edits().clone();
+                edits.commands.push(EditCmd::ChangeTransitPriority {
+                    i,
+                    old: current_priority,
+                    new,
+                });
+                apply_map_edits(ctx, app, edits);
+                Some(Transition::Pop)
+            }
             x if x == reset => {
                 Some(Transition::PopWithData(Box::new(move |state, ctx, app| {
                     let editor = state.downcast_mut::<TrafficSignalEditor>().unwrap();
@@ -29,13 +29,7 @@ impl Game {
         let mut app = App::new(flags, opts, ctx, title);
 
         // Handle savestates
-        let savestate = if app
-            .primary
-            .current_flags
-            .sim_flags
-            .load
-            .starts_with("../data/player/saves/")
-        {
+        let savestate = if app.primary.current_flags.sim_flags.is_savestate() {
             assert!(maybe_mode.is_none());
             Some(app.primary.clear_sim())
         } else {
@@ -318,10 +312,11 @@ impl State for WizardState {
 }
 
 // TODO Word wrap
-pub fn msg<S: Into<String>>(title: &'static str, lines: Vec<S>) -> Box<dyn State> {
+pub fn msg<T: Into<String>, S: Into<String>>(title: T, lines: Vec<S>) -> Box<dyn State> {
+    let title = title.into();
     let str_lines: Vec<String> = lines.into_iter().map(|l| l.into()).collect();
     WizardState::new(Box::new(move |wiz, ctx, _| {
-        wiz.wrap(ctx).acknowledge(title, || str_lines.clone())?;
+        wiz.wrap(ctx).acknowledge(&title, || str_lines.clone())?;
         Some(Transition::Pop)
     }))
 }
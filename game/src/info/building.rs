@@ -111,7 +111,7 @@ pub fn people(ctx: &mut EventCtx, app: &App, details: &mut Details, id: Building
                     // TODO What to do here? This is meant for building callers right now
                     break;
                 }
-                TripResult::TripDone | TripResult::TripAborted => {}
+                TripResult::TripDone | TripResult::TripAborted | TripResult::TripCancelled => {}
                 TripResult::TripDoesntExist | TripResult::RemoteTrip => unreachable!(),
             }
         }
@@ -122,14 +122,20 @@ pub fn people(ctx: &mut EventCtx, app: &App, details: &mut Details, id: Building
         let widget = Widget::col(vec![
             Btn::text_bg1(p.to_string()).build_def(ctx, None),
             if let Some((t, mode)) = next_trip {
-                format!(
-                    "Leaving in {} to {}",
-                    t - app.primary.sim.time(),
-                    mode.verb()
-                )
-                .draw_text(ctx)
+                app.catalog
+                    .tr_args(
+                        "Leaving in {duration} to {mode}",
+                        vec![
+                            (
+                                "duration",
+                                (t - app.primary.sim.time()).to_localized_string(&app.catalog),
+                            ),
+                            ("mode", app.catalog.tr(mode.verb())),
+                        ],
+                    )
+                    .draw_text(ctx)
             } else {
-                "Staying inside".draw_text(ctx)
+                app.catalog.tr("Staying inside").draw_text(ctx)
             },
         ]);
         ppl.push((
@@ -2,10 +2,10 @@ use crate::app::App;
 use crate::helpers::ID;
 use crate::info::{header_btns, make_table, make_tabs, Details, Tab};
 use ezgui::{
-    Btn, Color, EventCtx, GeomBatch, Line, LinePlot, PlotOptions, RewriteColor, Series, Text,
-    TextExt, Widget,
+    BarChart, BarLayout, BarSeries, Btn, Color, EventCtx, GeomBatch, Line, LinePlot, PlotOptions,
+    RewriteColor, Series, Text, TextExt, Widget,
 };
-use geom::{Circle, Distance, Polygon, Pt2D, Statistic, Time};
+use geom::{Circle, Distance, Duration, Histogram, Polygon, Pt2D, Statistic, Time};
 use map_model::{BusRouteID, BusStopID};
 use sim::{AgentID, CarID};
 
@@ -68,6 +68,7 @@ pub fn bus_status(ctx: &mut EventCtx, app: &App, details: &mut Details, id: CarI
 
     let route = app.primary.sim.bus_route_id(id).unwrap();
     rows.push(passenger_delay(ctx, app, details, route));
+    rows.push(crowding(ctx, app, route));
 
     rows
 }
@@ -143,9 +144,138 @@ fn delays_over_time(ctx: &mut EventCtx, app: &App, id: BusRouteID) -> Widget {
                 .unwrap_or_else(Vec::new),
         });
     }
-    Widget::col(vec![
+    let mut col = vec![
         Line("Delays between stops").small_heading().draw(ctx),
         LinePlot::new(ctx, series, PlotOptions::fixed()).margin(10),
+    ];
+
+    // The typical delay per segment, next to the baseline if there is one
+    if !route.stops.is_empty() {
+        let now = app.primary.sim.time();
+        let median = |hgram: Option<&Histogram<Duration>>| {
+            hgram
+                .map(|h| h.select(Statistic::P50))
+                .unwrap_or(Duration::ZERO)
+        };
+        let mut all_arrivals = Vec::new();
+        if app.has_prebaked().is_some() {
+            all_arrivals.push((
+                format!("Before \"{}\"", app.primary.map.get_edits().edits_name),
+                app.cs.before_changes,
+                app.prebaked().bus_arrivals(now, id),
+            ));
+        }
+        all_arrivals.push((
+            format!("After \"{}\"", app.primary.map.get_edits().edits_name),
+            app.cs.after_changes,
+            app.primary.sim.get_analytics().bus_arrivals(now, id),
+        ));
+
+        let mut categories = Vec::new();
+        let mut bars: Vec<BarSeries<Duration>> = all_arrivals
+            .iter()
+            .map(|(label, color, _)| BarSeries {
+                label: label.clone(),
+                color: *color,
+                values: Vec::new(),
+            })
+            .collect();
+        for idx1 in 0..route.stops.len() {
+            let idx2 = if idx1 == route.stops.len() - 1 {
+                0
+            } else {
+                idx1 + 1
+            };
+            categories.push(format!("{}->{}", idx1 + 1, idx2 + 1));
+            for (series, (_, _, arrivals)) in bars.iter_mut().zip(all_arrivals.iter()) {
+                series.values.push(median(arrivals.get(&route.stops[idx2])));
+            }
+        }
+        col.push(
+            Line("Typical delay between stops")
+                .small_heading()
+                .draw(ctx),
+        );
+        col.push(BarChart::new(ctx, categories, bars, BarLayout::Grouped).margin(10));
+    }
+
+    // Compare reliability against the baseline, to see the effect of things like transit signal
+    // priority.
+    if app.has_prebaked().is_some() {
+        let now = app.primary.sim.time();
+        let mut after = app.primary.sim.get_analytics().bus_arrivals(now, id);
+        let mut before = app.prebaked().bus_arrivals(now, id);
+        let mut txt = Text::from(Line("Typical / worst 10% delay, compared to before edits"));
+        for idx1 in 0..route.stops.len() {
+            let idx2 = if idx1 == route.stops.len() - 1 {
+                0
+            } else {
+                idx1 + 1
+            };
+            let stop = route.stops[idx2];
+            let describe = |hgram: Option<Histogram<Duration>>| {
+                if let Some(h) = hgram {
+                    format!(
+                        "{} / {}",
+                        h.select(Statistic::P50),
+                        h.select(Statistic::P90)
+                    )
+                } else {
+                    "no data".to_string()
+                }
+            };
+            txt.add(
+                Line(format!(
+                    "Stop {}->{}: {} (was {})",
+                    idx1 + 1,
+                    idx2 + 1,
+                    describe(after.remove(&stop)),
+                    describe(before.remove(&stop))
+                ))
+                .secondary(),
+            );
+        }
+        col.push(txt.draw(ctx));
+    }
+
+    Widget::col(col)
+}
+
+fn crowding(ctx: &mut EventCtx, app: &App, id: BusRouteID) -> Widget {
+    let route = app.primary.map.get_br(id);
+    let analytics = app.primary.sim.get_analytics();
+    let now = app.primary.sim.time();
+    let mut loads = analytics.bus_load_factors(now, id);
+    let denied = analytics.bus_denied_boardings(now, id);
+
+    let mut txt = Text::new();
+    for idx1 in 0..route.stops.len() {
+        let idx2 = if idx1 == route.stops.len() - 1 {
+            0
+        } else {
+            idx1 + 1
+        };
+        let stop = route.stops[idx1];
+        let mut line = format!("Stop {}->{}: ", idx1 + 1, idx2 + 1);
+        if let Some(hgram) = loads.remove(&stop) {
+            line.push_str(&format!(
+                "{}% full on average, {}% at worst",
+                hgram.select(Statistic::Mean),
+                hgram.select(Statistic::Max)
+            ));
+        } else {
+            line.push_str("no buses yet");
+        }
+        let cnt = denied.get(stop);
+        if cnt > 0 {
+            line.push_str(&format!(", {} left behind", cnt));
+        }
+        txt.add(Line(line).secondary());
+    }
+
+    Widget::col(vec![
+        Line("Crowding").small_heading().draw(ctx),
+        txt.draw(ctx),
     ])
 }
 
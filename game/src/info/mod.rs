@@ -30,6 +30,7 @@ pub struct InfoPanel {
     tab: Tab,
     time: Time,
     is_paused: bool,
+    language: String,
     composite: Composite,
 
     unzoomed: Drawable,
@@ -318,6 +319,7 @@ impl InfoPanel {
             tab,
             time: app.primary.sim.time(),
             is_paused: ctx_actions.is_paused(),
+            language: app.catalog.language.clone(),
             composite: Composite::new(Widget::col(col).bg(Color::hex("#5B5B5B")).padding(16))
                 .aligned(
                     HorizontalAlignment::Percent(0.02),
@@ -351,7 +353,10 @@ impl InfoPanel {
         }
 
         // Live update?
-        if app.primary.sim.time() != self.time || ctx_actions.is_paused() != self.is_paused {
+        if app.primary.sim.time() != self.time
+            || ctx_actions.is_paused() != self.is_paused
+            || app.catalog.language != self.language
+        {
             let mut new = InfoPanel::new(ctx, app, self.tab.clone(), ctx_actions);
             new.composite.restore(ctx, &self.composite);
             *self = new;
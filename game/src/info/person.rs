@@ -115,6 +115,14 @@ pub fn trips(
                     open_trips.get(t).map(|_| trip::aborted(ctx, app, *t)),
                 )
             }
+            TripResult::TripCancelled => {
+                // Cancelled trips can happen anywhere in the schedule too
+                (
+                    "cancelled",
+                    Color::hex("#A3A3A3"),
+                    open_trips.get(t).map(|_| Widget::nothing()),
+                )
+            }
             TripResult::TripDoesntExist => unreachable!(),
         };
         let (_, _, _, trip_mode) = sim.trip_info(*t);
@@ -262,14 +270,24 @@ pub fn bio(
             // TODO More info here? Make these public too?
             "Other (hospitalized or quarantined)".to_string()
         };
-        rows.push(
-            Text::from_all(vec![
-                Line("Pandemic model state: ").secondary(),
-                Line(status),
-            ])
-            .draw(ctx)
-            .margin_below(5),
-        );
+        let mut txt = Text::from_all(vec![
+            Line("Pandemic model state: ").secondary(),
+            Line(status),
+        ]);
+        if p.is_masked(id) {
+            txt.add(Line("Wears a mask").secondary());
+        }
+        if p.is_quarantined(id) {
+            txt.add(Line("Quarantined").secondary());
+        }
+        rows.push(txt.draw(ctx).margin_below(5));
+    }
+
+    if let Some(b) = person.home {
+        rows.push(Btn::text_bg2(format!("Lives at {}", b)).build_def(ctx, None));
+        details
+            .hyperlinks
+            .insert(format!("Lives at {}", b), Tab::BldgInfo(b));
     }
 
     let mut has_bike = false;
@@ -277,24 +295,29 @@ pub fn bio(
         if v.vehicle_type == VehicleType::Bike {
             has_bike = true;
         } else {
+            let verb = if v.owner == Some(id) {
+                "Owner of"
+            } else {
+                "Shares"
+            };
             if app.primary.sim.lookup_parked_car(v.id).is_some() {
                 rows.push(
-                    Btn::text_bg2(format!("Owner of {} (parked)", v.id)).build_def(ctx, None),
+                    Btn::text_bg2(format!("{} {} (parked)", verb, v.id)).build_def(ctx, None),
                 );
                 details
                     .hyperlinks
-                    .insert(format!("Owner of {} (parked)", v.id), Tab::ParkedCar(v.id));
-            } else if let PersonState::Trip(t) = person.state {
-                match app.primary.sim.trip_to_agent(t) {
-                    TripResult::Ok(AgentID::Car(x)) if x == v.id => {
-                        rows.push(format!("Owner of {} (currently driving)", v.id).draw_text(ctx));
-                    }
-                    _ => {
-                        rows.push(format!("Owner of {} (off-map)", v.id).draw_text(ctx));
-                    }
+                    .insert(format!("{} {} (parked)", verb, v.id), Tab::ParkedCar(v.id));
+            } else if let Some(driver) = app.primary.sim.agent_to_person(AgentID::Car(v.id)) {
+                // A shared car might be in use by somebody else in the household
+                if driver == id {
+                    rows.push(format!("{} {} (currently driving)", verb, v.id).draw_text(ctx));
+                } else {
+                    let label = format!("{} {} (driven by {})", verb, v.id, driver);
+                    rows.push(Btn::text_bg2(&label).build_def(ctx, None));
+                    details.hyperlinks.insert(label, Tab::PersonBio(driver));
                 }
             } else {
-                rows.push(format!("Owner of {} (off-map)", v.id).draw_text(ctx));
+                rows.push(format!("{} {} (off-map)", verb, v.id).draw_text(ctx));
             }
         }
     }
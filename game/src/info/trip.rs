@@ -441,22 +441,28 @@ fn make_timeline(
     for (idx, p) in phases.into_iter().enumerate() {
         let color = color_for_trip_phase(app, p.phase_type).alpha(0.7);
 
-        let mut txt = Text::from(Line(&p.phase_type.describe(map)));
-        txt.add(Line(format!(
-            "- Started at {}",
-            p.start_time.ampm_tostring()
+        let c = &app.catalog;
+        let mut txt = Text::from(Line(&p.phase_type.describe(map, c)));
+        txt.add(Line(c.tr_args(
+            "- Started at {time}",
+            vec![("time", p.start_time.to_localized_string(c))],
         )));
         let duration = if let Some(t2) = p.end_time {
             let d = t2 - p.start_time;
-            txt.add(Line(format!(
-                "- Ended at {} (duration: {})",
-                t2.ampm_tostring(),
-                d
+            txt.add(Line(c.tr_args(
+                "- Ended at {time} (duration: {duration})",
+                vec![
+                    ("time", t2.to_localized_string(c)),
+                    ("duration", d.to_localized_string(c)),
+                ],
             )));
             d
         } else {
             let d = sim.time() - p.start_time;
-            txt.add(Line(format!("- Ongoing (duration so far: {})", d)));
+            txt.add(Line(c.tr_args(
+                "- Ongoing (duration so far: {duration})",
+                vec![("duration", d.to_localized_string(c))],
+            )));
             d
         };
         // TODO Problems when this is really low?
@@ -465,9 +471,9 @@ fn make_timeline(
         } else {
             duration / total_duration_so_far
         };
-        txt.add(Line(format!(
-            "- {}% of trip duration",
-            (100.0 * percent_duration) as usize
+        txt.add(Line(c.tr_args(
+            "- {percent}% of trip duration",
+            vec![("percent", ((100.0 * percent_duration) as usize).to_string())],
         )));
 
         let phase_width = total_width * percent_duration;
@@ -2,8 +2,8 @@ use crate::app::App;
 use crate::common::ColorDiscrete;
 use crate::layer::{Layer, LayerOutcome};
 use ezgui::{
-    hotkey, Btn, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key,
-    Line, Text, TextExt, VerticalAlignment, Widget,
+    hotkey, Btn, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line,
+    Text, TextExt, VerticalAlignment, Widget,
 };
 use geom::{Circle, Distance, Pt2D, Time};
 use map_model::{BusRouteID, PathConstraints, PathRequest, PathStep};
@@ -62,7 +62,7 @@ impl Layer for ShowBusRoute {
         let mut batch = GeomBatch::new();
         let radius = Distance::meters(20.0) / g.canvas.cam_zoom;
         for pt in &self.bus_locations {
-            batch.push(Color::BLUE, Circle::new(*pt, radius).to_polygon());
+            batch.push(app.cs.bus_layer, Circle::new(*pt, radius).to_polygon());
         }
         batch.draw(g);
     }
@@ -2,8 +2,8 @@ use crate::app::App;
 use crate::common::{ColorLegend, ColorNetwork};
 use crate::layer::{Layer, LayerOutcome};
 use ezgui::{
-    hotkey, Btn, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key,
-    Line, Text, TextExt, VerticalAlignment, Widget,
+    hotkey, Btn, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment, Key, Line,
+    Text, TextExt, VerticalAlignment, Widget,
 };
 use geom::{ArrowCap, Distance, PolyLine};
 
@@ -79,7 +79,7 @@ impl Elevation {
             while dist + arrow_len <= len {
                 let (pt, angle) = pl.dist_along(dist);
                 batch.push(
-                    Color::BLACK,
+                    app.cs.elevation_arrow,
                     PolyLine::new(vec![
                         pt.project_away(arrow_len / 2.0, angle.opposite()),
                         pt.project_away(arrow_len / 2.0, angle),
@@ -0,0 +1,291 @@
+use crate::colors::{ColorScheme, ColorSchemeChoice, DataPalette};
+use crate::common::{draw_heatmap, ColorNetwork, ColorScale, HeatmapOptions};
+use crate::layer::{parking, population, traffic};
+use crate::render::{DrawArea, DrawMap};
+use abstutil::Timer;
+use ezgui::{Assets, GeomBatch, Line, ScreenDims, Text};
+use geom::{Bounds, Polygon, Time};
+use map_model::Map;
+use sim::SimFlags;
+
+// Pixels; the height follows the map's aspect ratio.
+const IMAGE_WIDTH: f64 = 2000.0;
+const LEGEND_WIDTH: f64 = 300.0;
+const PADDING: f64 = 10.0;
+
+// Writes a PNG per layer (throughput, delay, parking occupancy, and population) to a directory,
+// without opening a window. A savestate is drawn as-is; anything else is simulated through the end
+// of the day first.
+pub fn export_layers(
+    sim_flags: SimFlags,
+    color_scheme: ColorSchemeChoice,
+    data_palette: DataPalette,
+    dir: String,
+) {
+    let mut timer = Timer::new("export layers");
+    let (map, mut sim, _) = sim_flags.load(&mut timer);
+    if !sim_flags.is_savestate() {
+        sim.timed_step(
+            &map,
+            sim.get_end_of_day() - Time::START_OF_DAY,
+            &mut None,
+            &mut timer,
+        );
+    }
+
+    let cs = ColorScheme::new(color_scheme, data_palette);
+    let assets = Assets::new(21, "../data/system/fonts".to_string(), 1.0);
+    let base = draw_map(&map, &cs, &mut timer);
+    std::fs::create_dir_all(&dir).unwrap();
+    let exporter = Exporter {
+        bounds: map.get_bounds().clone(),
+        cs: &cs,
+        assets: &assets,
+        base,
+        dir,
+        time: sim.time(),
+    };
+
+    {
+        let mut colorer = ColorNetwork::for_map(&map, &cs);
+        traffic::color_throughput(&mut colorer, sim.get_analytics(), &cs);
+        exporter.save(
+            "throughput",
+            "Throughput",
+            colorer.unzoomed,
+            &cs.good_to_bad_red,
+            vec!["lowest count", "highest"],
+        );
+    }
+    {
+        let mut colorer = ColorNetwork::for_map(&map, &cs);
+        // By the end of the day, hardly anybody is still waiting anywhere, so use every delay
+        // measured so far.
+        traffic::color_average_delay(&mut colorer, sim.get_analytics(), &cs);
+        exporter.save(
+            "delay",
+            "Average intersection delay (minutes)",
+            colorer.unzoomed,
+            &cs.good_to_bad_red,
+            vec!["0", "1", "2", "3", "4", "5+"],
+        );
+    }
+    {
+        let mut colorer = ColorNetwork::for_map(&map, &cs);
+        let (filled, avail) = sim.get_all_parking_spots();
+        parking::color_occupancy(&mut colorer, filled, avail, &map, &cs);
+        exporter.save(
+            "parking_occupancy",
+            "Parking occupancy",
+            colorer.unzoomed,
+            &cs.good_to_bad_red,
+            vec!["0%", "100%"],
+        );
+    }
+    {
+        let (mut pts, repeat_pts) = population::population_pts(&sim, &map);
+        pts.extend(repeat_pts);
+        let mut batch = GeomBatch::new();
+        let (scale, labels) =
+            draw_heatmap(&mut batch, map.get_bounds(), pts, &HeatmapOptions::new(&cs));
+        exporter.save("population", "Population", batch, &scale, labels);
+    }
+    timer.done();
+}
+
+struct Exporter<'a> {
+    // Of the map
+    bounds: Bounds,
+    cs: &'a ColorScheme,
+    assets: &'a Assets,
+    // The map itself, drawn underneath every layer
+    base: GeomBatch,
+    dir: String,
+    time: Time,
+}
+
+impl<'a> Exporter<'a> {
+    fn save<I: Into<String>>(
+        &self,
+        name: &str,
+        title: &str,
+        layer: GeomBatch,
+        scale: &ColorScale,
+        labels: Vec<I>,
+    ) {
+        let (batch, dims) = self.render(title, layer, scale, labels);
+        let path = format!("{}/{}.png", self.dir, name);
+        batch
+            .save_png(
+                &path,
+                dims.width as u32,
+                dims.height as u32,
+                self.cs.void_background,
+            )
+            .unwrap();
+        println!("Wrote {}", path);
+    }
+
+    // The layer over the map, scaled to the image size, with the legend in the top-left corner
+    fn render<I: Into<String>>(
+        &self,
+        title: &str,
+        layer: GeomBatch,
+        scale: &ColorScale,
+        labels: Vec<I>,
+    ) -> (GeomBatch, ScreenDims) {
+        let dims = ScreenDims::new(
+            IMAGE_WIDTH,
+            (IMAGE_WIDTH * self.bounds.height() / self.bounds.width()).ceil(),
+        );
+        let mut batch = self.base.clone();
+        batch.append(layer);
+        let mut batch = batch.fit_to(dims);
+        batch.append(
+            self.legend(&format!("{} at {}", title, self.time), scale, labels)
+                .translate(2.0 * PADDING, 2.0 * PADDING),
+        );
+        (batch, dims)
+    }
+
+    // Like ColorLegend::gradient, with a title above
+    fn legend<I: Into<String>>(
+        &self,
+        title: &str,
+        scale: &ColorScale,
+        labels: Vec<I>,
+    ) -> GeomBatch {
+        let mut contents = Text::from(Line(title)).render(self.assets);
+        let mut y = contents.get_dims().height + PADDING;
+        let bar_height = 32.0;
+        contents.append(
+            scale
+                .gradient_bar(LEGEND_WIDTH, bar_height)
+                .translate(0.0, y),
+        );
+        y += bar_height;
+
+        let labels: Vec<GeomBatch> = labels
+            .into_iter()
+            .map(|lbl| Text::from(Line(lbl).small()).render(self.assets))
+            .collect();
+        let n = labels.len();
+        let mut label_height: f64 = 0.0;
+        for (idx, lbl) in labels.into_iter().enumerate() {
+            let dims = lbl.get_dims();
+            // Center each label on its position along the bar, but keep it inside the legend
+            let pct = if n == 1 {
+                0.5
+            } else {
+                (idx as f64) / ((n - 1) as f64)
+            };
+            let x = (pct * LEGEND_WIDTH - dims.width / 2.0)
+                .max(0.0)
+                .min(LEGEND_WIDTH - dims.width);
+            label_height = label_height.max(dims.height);
+            contents.append(lbl.translate(x, y));
+        }
+        y += label_height;
+
+        let mut batch = GeomBatch::new();
+        batch.push(
+            self.cs.panel_bg,
+            Polygon::rectangle(
+                contents.get_dims().width.max(LEGEND_WIDTH) + 2.0 * PADDING,
+                y + 2.0 * PADDING,
+            ),
+        );
+        batch.append(contents.translate(PADDING, PADDING));
+        batch
+    }
+}
+
+// The unzoomed view of the map
+fn draw_map(map: &Map, cs: &ColorScheme, timer: &mut Timer) -> GeomBatch {
+    let mut batch = GeomBatch::new();
+    batch.push(cs.map_background, map.get_boundary_polygon().clone());
+    for a in map.all_areas() {
+        batch.push(DrawArea::color(a.area_type, cs), a.polygon.clone());
+    }
+    batch.append(DrawMap::zoomed_out_roads(map, cs, timer));
+    batch.append(DrawMap::zoomed_out_intersections(map, cs));
+    for b in map.all_buildings() {
+        batch.push(cs.building, b.polygon.clone());
+    }
+    batch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ezgui::Color;
+    use geom::Pt2D;
+
+    // A map 100m wide and 50m tall, colored blue, without a GPU
+    fn exporter<'a>(cs: &'a ColorScheme, assets: &'a Assets) -> Exporter<'a> {
+        let mut bounds = Bounds::new();
+        bounds.update(Pt2D::new(0.0, 0.0));
+        bounds.update(Pt2D::new(100.0, 50.0));
+        Exporter {
+            bounds,
+            cs,
+            assets,
+            base: GeomBatch::from(vec![(Color::BLUE, Polygon::rectangle(100.0, 50.0))]),
+            dir: String::new(),
+            time: Time::START_OF_DAY,
+        }
+    }
+
+    #[test]
+    fn render_layer() {
+        let cs = ColorScheme::new(ColorSchemeChoice::Standard, DataPalette::Standard);
+        let assets = Assets::new(21, "../data/system/fonts".to_string(), 1.0);
+        let exporter = exporter(&cs, &assets);
+
+        // Color the right half of the map
+        let layer = GeomBatch::from(vec![(
+            Color::RED,
+            Polygon::rectangle(50.0, 50.0).translate(50.0, 0.0),
+        )]);
+        let (batch, dims) = exporter.render(
+            "Test",
+            layer,
+            &ColorScale(vec![Color::BLACK, Color::WHITE]),
+            vec!["low", "high"],
+        );
+        // The image keeps the map's aspect ratio
+        assert_eq!((dims.width, dims.height), (IMAGE_WIDTH, IMAGE_WIDTH / 2.0));
+
+        let img = batch.rasterize(dims.width as u32, dims.height as u32, Color::BLACK);
+        let (w, h) = (img.width(), img.height());
+        assert_eq!(img.get_pixel(w / 4, h - 10).0, [0, 0, 255, 255]);
+        assert_eq!(img.get_pixel(3 * w / 4, h - 10).0, [255, 0, 0, 255]);
+        // The legend covers the top-left corner
+        assert_ne!(
+            img.get_pixel(3 * PADDING as u32, 3 * PADDING as u32).0,
+            [0, 0, 255, 255]
+        );
+    }
+
+    #[test]
+    fn legend_labels() {
+        let cs = ColorScheme::new(ColorSchemeChoice::Standard, DataPalette::Standard);
+        let assets = Assets::new(21, "../data/system/fonts".to_string(), 1.0);
+        let exporter = exporter(&cs, &assets);
+        let scale = ColorScale(vec![Color::BLACK, Color::WHITE]);
+
+        let many = exporter
+            .legend("Delay", &scale, vec!["0", "1", "2", "3", "4", "5+"])
+            .get_dims();
+        // Labels stay inside the legend
+        assert_eq!(many.width, LEGEND_WIDTH + 2.0 * PADDING);
+
+        // One label or none don't divide by zero
+        let one = exporter.legend("Delay", &scale, vec!["all"]).get_dims();
+        assert_eq!(one.width, LEGEND_WIDTH + 2.0 * PADDING);
+        let none = exporter
+            .legend("Delay", &scale, Vec::<String>::new())
+            .get_dims();
+        assert!(none.height < many.height);
+    }
+}
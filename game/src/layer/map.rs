@@ -4,8 +4,8 @@ use crate::helpers::amenity_type;
 use crate::layer::{Layer, LayerOutcome};
 use abstutil::Counter;
 use ezgui::{
-    hotkey, Btn, Color, Composite, Drawable, EventCtx, GfxCtx, HorizontalAlignment, Key, Line,
-    Text, TextExt, VerticalAlignment, Widget,
+    hotkey, Btn, Composite, Drawable, EventCtx, GfxCtx, HorizontalAlignment, Key, Line, Text,
+    TextExt, VerticalAlignment, Widget,
 };
 use geom::{Distance, Time};
 use map_model::LaneType;
@@ -263,6 +263,9 @@ impl Static {
         for r in &edits.changed_speed_limits {
             colorer.add_r(*r, "modified lane/intersection");
         }
+        for i in &edits.changed_transit_priority {
+            colorer.add_i(*i, "modified lane/intersection");
+        }
 
         Static::new(
             ctx,
@@ -281,6 +284,10 @@ impl Static {
                     "{} intersections changed",
                     edits.original_intersections.len()
                 )),
+                Line(format!(
+                    "{} intersections with transit priority",
+                    edits.changed_transit_priority.len()
+                )),
             ])
             .draw(ctx),
         )
@@ -290,18 +297,22 @@ impl Static {
         let mut colorer = ColorDiscrete::new(
             app,
             vec![
-                ("groceries", Color::BLACK),
-                ("food", Color::RED),
-                ("bar", Color::BLUE),
-                ("medical", Color::PURPLE),
-                ("church / temple", Color::GREEN),
-                ("education", Color::CYAN),
-                ("bank / post office", Color::YELLOW),
-                ("media", Color::PINK),
-                ("childcare", Color::ORANGE),
-                ("shopping", Color::WHITE),
-                ("other", Color::hex("#96322F")),
-            ],
+                "groceries",
+                "food",
+                "bar",
+                "medical",
+                "church / temple",
+                "education",
+                "bank / post office",
+                "media",
+                "childcare",
+                "shopping",
+                "other",
+            ]
+            .into_iter()
+            .enumerate()
+            .map(|(idx, category)| (category, app.cs.category_color(idx)))
+            .collect(),
         );
 
         for b in app.primary.map.all_buildings() {
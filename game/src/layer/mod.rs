@@ -1,5 +1,6 @@
 pub mod bus;
 mod elevation;
+pub mod export;
 pub mod map;
 mod pandemic;
 mod parking;
@@ -182,7 +183,7 @@ impl State for PickLayer {
                         ctx,
                         app,
                         population::Options {
-                            heatmap: Some(HeatmapOptions::new()),
+                            heatmap: Some(HeatmapOptions::new(&app.cs)),
                         },
                     )));
                 }
@@ -191,7 +192,7 @@ impl State for PickLayer {
                         ctx,
                         app,
                         pandemic::Options {
-                            heatmap: Some(HeatmapOptions::new()),
+                            heatmap: Some(HeatmapOptions::new(&app.cs)),
                             state: pandemic::SEIR::Infected,
                         },
                     )));
@@ -217,3 +218,27 @@ impl State for PickLayer {
         self.composite.draw(g);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    // Layers should take every color from the ColorScheme, so the colorblind-safe and high
+    // contrast palettes apply to them.
+    #[test]
+    fn no_hardcoded_colors() {
+        for entry in std::fs::read_dir("src/layer").unwrap() {
+            let path = entry.unwrap().path();
+            let code = std::fs::read_to_string(&path).unwrap();
+            // Tests can use whatever they like
+            let code = code.split("#[cfg(test)]").next().unwrap();
+            for (idx, line) in code.lines().enumerate() {
+                assert!(
+                    !line.contains("Color::"),
+                    "{}:{} hardcodes a color: {}",
+                    path.display(),
+                    idx + 1,
+                    line.trim()
+                );
+            }
+        }
+    }
+}
@@ -3,7 +3,7 @@ use crate::common::{make_heatmap, HeatmapOptions};
 use crate::layer::{Layer, LayerOutcome};
 use abstutil::prettyprint_usize;
 use ezgui::{
-    hotkey, Btn, Checkbox, Choice, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
+    hotkey, Btn, Checkbox, Choice, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
     HorizontalAlignment, Key, Outcome, TextExt, VerticalAlignment, Widget,
 };
 use geom::{Circle, Distance, Pt2D, Time};
@@ -44,7 +44,7 @@ impl Layer for Pandemic {
                 _ => unreachable!(),
             },
             None => {
-                let new_opts = self.options();
+                let new_opts = self.options(app);
                 if self.opts != new_opts {
                     *self = Pandemic::new(ctx, app, new_opts);
                     self.composite.align_above(ctx, minimap);
@@ -126,7 +126,7 @@ impl Pandemic {
             // It's quite silly to produce triangles for the same circle over and over again. ;)
             let circle = Circle::new(Pt2D::new(0.0, 0.0), Distance::meters(10.0)).to_polygon();
             for pt in pts {
-                batch.push(Color::RED.alpha(0.8), circle.translate(pt.x(), pt.y()));
+                batch.push(app.cs.layer_dots, circle.translate(pt.x(), pt.y()));
             }
             None
         };
@@ -139,9 +139,9 @@ impl Pandemic {
         }
     }
 
-    fn options(&self) -> Options {
+    fn options(&self, app: &App) -> Options {
         let heatmap = if self.composite.is_checked("Show heatmap") {
-            Some(HeatmapOptions::from_controls(&self.composite))
+            Some(HeatmapOptions::from_controls(&self.composite, &app.cs))
         } else {
             None
         };
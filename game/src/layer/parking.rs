@@ -1,4 +1,5 @@
 use crate::app::App;
+use crate::colors::ColorScheme;
 use crate::common::{ColorLegend, ColorNetwork};
 use crate::layer::{Layer, LayerOutcome};
 use abstutil::{prettyprint_usize, Counter};
@@ -207,32 +208,14 @@ impl Occupancy {
         .aligned(HorizontalAlignment::Right, VerticalAlignment::Center)
         .build(ctx);
 
-        let mut filled = Counter::new();
-        let mut avail = Counter::new();
-        let mut keys = HashSet::new();
-        for spot in filled_spots {
-            let loc = Loc::new(spot, &app.primary.map);
-            keys.insert(loc);
-            filled.inc(loc);
-        }
-        for spot in avail_spots {
-            let loc = Loc::new(spot, &app.primary.map);
-            keys.insert(loc);
-            avail.inc(loc);
-        }
-
         let mut colorer = ColorNetwork::new(app);
-        for loc in keys {
-            let open = avail.get(loc);
-            let closed = filled.get(loc);
-            let percent = (closed as f64) / ((open + closed) as f64);
-            let color = app.cs.good_to_bad_red.eval(percent);
-            match loc {
-                Loc::Road(r) => colorer.add_r(r, color),
-                Loc::Bldg(b) => colorer.add_b(b, color),
-                Loc::Lot(pl) => colorer.add_pl(pl, color),
-            }
-        }
+        color_occupancy(
+            &mut colorer,
+            filled_spots,
+            avail_spots,
+            &app.primary.map,
+            &app.cs,
+        );
         let (unzoomed, zoomed) = colorer.build(ctx);
 
         Occupancy {
@@ -248,6 +231,41 @@ impl Occupancy {
     }
 }
 
+// Colors each road, building, and parking lot by the percent of its spots that are filled.
+pub fn color_occupancy(
+    colorer: &mut ColorNetwork,
+    filled_spots: Vec<ParkingSpot>,
+    avail_spots: Vec<ParkingSpot>,
+    map: &Map,
+    cs: &ColorScheme,
+) {
+    let mut filled = Counter::new();
+    let mut avail = Counter::new();
+    let mut keys = HashSet::new();
+    for spot in filled_spots {
+        let loc = Loc::new(spot, map);
+        keys.insert(loc);
+        filled.inc(loc);
+    }
+    for spot in avail_spots {
+        let loc = Loc::new(spot, map);
+        keys.insert(loc);
+        avail.inc(loc);
+    }
+
+    for loc in keys {
+        let open = avail.get(loc);
+        let closed = filled.get(loc);
+        let percent = (closed as f64) / ((open + closed) as f64);
+        let color = cs.good_to_bad_red.eval(percent);
+        match loc {
+            Loc::Road(r) => colorer.add_r(r, color),
+            Loc::Bldg(b) => colorer.add_b(b, color),
+            Loc::Lot(pl) => colorer.add_pl(pl, color),
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 enum Loc {
     Road(RoadID),
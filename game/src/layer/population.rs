@@ -3,11 +3,12 @@ use crate::common::{make_heatmap, HeatmapOptions};
 use crate::layer::{Layer, LayerOutcome};
 use abstutil::prettyprint_usize;
 use ezgui::{
-    hotkey, Btn, Checkbox, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
-    HorizontalAlignment, Key, Line, Outcome, VerticalAlignment, Widget,
+    hotkey, Btn, Checkbox, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment,
+    Key, Line, Outcome, VerticalAlignment, Widget,
 };
 use geom::{Circle, Distance, Pt2D, Time};
-use sim::{GetDrawAgents, PersonState};
+use map_model::Map;
+use sim::{GetDrawAgents, PersonState, Sim};
 use std::collections::HashSet;
 
 // TODO Disable drawing unzoomed agents... or alternatively, implement this by asking Sim to
@@ -44,7 +45,7 @@ impl Layer for PopulationMap {
                 _ => unreachable!(),
             },
             None => {
-                let new_opts = self.options();
+                let new_opts = self.options(app);
                 if self.opts != new_opts {
                     *self = PopulationMap::new(ctx, app, new_opts);
                     self.composite.align_above(ctx, minimap);
@@ -66,35 +67,7 @@ impl Layer for PopulationMap {
 
 impl PopulationMap {
     pub fn new(ctx: &mut EventCtx, app: &App, opts: Options) -> PopulationMap {
-        let mut pts = Vec::new();
-        // Faster to grab all agent positions than individually map trips to agent positions.
-        for a in app.primary.sim.get_unzoomed_agents(&app.primary.map) {
-            if a.person.is_some() {
-                pts.push(a.pos);
-            }
-        }
-
-        // Many people are probably in the same building. If we're building a heatmap, we
-        // absolutely care about these repeats! If we're just drawing the simple dot map, avoid
-        // drawing repeat circles.
-        let mut seen_bldgs = HashSet::new();
-        let mut repeat_pts = Vec::new();
-        for person in app.primary.sim.get_all_people() {
-            match person.state {
-                // Already covered above
-                PersonState::Trip(_) => {}
-                PersonState::Inside(b) => {
-                    let pt = app.primary.map.get_b(b).polygon.center();
-                    if seen_bldgs.contains(&b) {
-                        repeat_pts.push(pt);
-                    } else {
-                        seen_bldgs.insert(b);
-                        pts.push(pt);
-                    }
-                }
-                PersonState::OffMap => {}
-            }
-        }
+        let (mut pts, repeat_pts) = population_pts(&app.primary.sim, &app.primary.map);
 
         let mut batch = GeomBatch::new();
         let legend = if let Some(ref o) = opts.heatmap {
@@ -110,7 +83,7 @@ impl PopulationMap {
             // It's quite silly to produce triangles for the same circle over and over again. ;)
             let circle = Circle::new(Pt2D::new(0.0, 0.0), Distance::meters(10.0)).to_polygon();
             for pt in pts {
-                batch.push(Color::RED.alpha(0.8), circle.translate(pt.x(), pt.y()));
+                batch.push(app.cs.layer_dots, circle.translate(pt.x(), pt.y()));
             }
             None
         };
@@ -123,9 +96,9 @@ impl PopulationMap {
         }
     }
 
-    fn options(&self) -> Options {
+    fn options(&self, app: &App) -> Options {
         let heatmap = if self.composite.is_checked("Show heatmap") {
-            Some(HeatmapOptions::from_controls(&self.composite))
+            Some(HeatmapOptions::from_controls(&self.composite, &app.cs))
         } else {
             None
         };
@@ -133,6 +106,39 @@ impl PopulationMap {
     }
 }
 
+// Returns where everybody on the map is. Many people are probably in the same building; the
+// second list has all of the repeats. If we're building a heatmap, we absolutely care about these
+// repeats! If we're just drawing the simple dot map, avoid drawing repeat circles.
+pub fn population_pts(sim: &Sim, map: &Map) -> (Vec<Pt2D>, Vec<Pt2D>) {
+    let mut pts = Vec::new();
+    // Faster to grab all agent positions than individually map trips to agent positions.
+    for a in sim.get_unzoomed_agents(map) {
+        if a.person.is_some() {
+            pts.push(a.pos);
+        }
+    }
+
+    let mut seen_bldgs = HashSet::new();
+    let mut repeat_pts = Vec::new();
+    for person in sim.get_all_people() {
+        match person.state {
+            // Already covered above
+            PersonState::Trip(_) => {}
+            PersonState::Inside(b) => {
+                let pt = map.get_b(b).polygon.center();
+                if seen_bldgs.contains(&b) {
+                    repeat_pts.push(pt);
+                } else {
+                    seen_bldgs.insert(b);
+                    pts.push(pt);
+                }
+            }
+            PersonState::OffMap => {}
+        }
+    }
+    (pts, repeat_pts)
+}
+
 #[derive(Clone, PartialEq)]
 pub struct Options {
     // If None, just a dot map
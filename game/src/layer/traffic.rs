@@ -1,14 +1,16 @@
 use crate::app::App;
-use crate::common::{ColorLegend, ColorNetwork, ColorScale, DivergingScale};
+use crate::colors::ColorScheme;
+use crate::common::{ColorLegend, ColorNetwork, DivergingScale};
 use crate::layer::{Layer, LayerOutcome};
 use abstutil::Counter;
 use ezgui::{
-    hotkey, Btn, Checkbox, Color, Composite, Drawable, EventCtx, GeomBatch, GfxCtx,
-    HorizontalAlignment, Key, Line, Outcome, Text, TextExt, VerticalAlignment, Widget,
+    hotkey, Btn, Checkbox, Composite, Drawable, EventCtx, GeomBatch, GfxCtx, HorizontalAlignment,
+    Key, Line, Outcome, Text, TextExt, VerticalAlignment, Widget,
 };
 use geom::{Distance, Duration, Polygon, Time};
 use map_model::{IntersectionID, Map, Traversable};
 use maplit::btreeset;
+use sim::{Analytics, Sim};
 use std::collections::BTreeSet;
 
 pub struct Backpressure {
@@ -197,15 +199,7 @@ impl Throughput {
         .build(ctx);
 
         let mut colorer = ColorNetwork::new(app);
-        let stats = &app.primary.sim.get_analytics();
-        colorer.ranked_roads(
-            stats.road_thruput.all_total_counts(),
-            &app.cs.good_to_bad_red,
-        );
-        colorer.ranked_intersections(
-            stats.intersection_thruput.all_total_counts(),
-            &app.cs.good_to_bad_red,
-        );
+        color_throughput(&mut colorer, app.primary.sim.get_analytics(), &app.cs);
         let (unzoomed, zoomed) = colorer.build(ctx);
 
         Throughput {
@@ -251,7 +245,8 @@ impl Throughput {
 
         let mut colorer = ColorNetwork::new(app);
 
-        let scale = DivergingScale::new(Color::hex("#5D9630"), Color::WHITE, Color::hex("#A32015"))
+        let stops = &app.cs.good_to_bad_diverging.0;
+        let scale = DivergingScale::new(stops[0], stops[1], stops[2])
             .range(0.0, 2.0)
             .ignore(0.7, 1.3);
 
@@ -357,28 +352,7 @@ impl Delay {
         }
 
         let mut colorer = ColorNetwork::new(app);
-
-        let (per_road, per_intersection) = app.primary.sim.worst_delay(&app.primary.map);
-        for (r, d) in per_road {
-            if d < Duration::minutes(1) {
-                continue;
-            }
-            let color = app
-                .cs
-                .good_to_bad_red
-                .eval(((d - Duration::minutes(1)) / Duration::minutes(15)).min(1.0));
-            colorer.add_r(r, color);
-        }
-        for (i, d) in per_intersection {
-            if d < Duration::minutes(1) {
-                continue;
-            }
-            let color = app
-                .cs
-                .good_to_bad_red
-                .eval(((d - Duration::minutes(1)) / Duration::minutes(15)).min(1.0));
-            colorer.add_i(i, color);
-        }
+        color_delay(&mut colorer, &app.primary.sim, &app.primary.map, &app.cs);
 
         let composite = Composite::new(
             Widget::col(vec![
@@ -416,8 +390,7 @@ impl Delay {
     // TODO Needs work.
     fn compare_delay(ctx: &mut EventCtx, app: &App) -> Delay {
         let mut colorer = ColorNetwork::new(app);
-        let red = Color::hex("#A32015");
-        let green = Color::hex("#5D9630");
+        let scale = &app.cs.good_to_bad_diverging;
 
         let results = app
             .primary
@@ -429,10 +402,11 @@ impl Delay {
             let slowest = results.iter().max_by_key(|(_, dt)| *dt).unwrap().1;
 
             for (i, dt) in results {
+                // Faster is the lower half of the scale, slower the upper half
                 let color = if dt < Duration::ZERO {
-                    green.lerp(Color::WHITE, 1.0 - (dt / fastest))
+                    scale.eval(0.5 - 0.5 * (dt / fastest))
                 } else {
-                    Color::WHITE.lerp(red, dt / slowest)
+                    scale.eval(0.5 + 0.5 * (dt / slowest))
                 };
                 colorer.add_i(i, color);
             }
@@ -449,11 +423,7 @@ impl Delay {
                         .align_right(),
                 ]),
                 Checkbox::text(ctx, "Compare before edits", None, true).margin_below(5),
-                ColorLegend::gradient(
-                    ctx,
-                    &ColorScale(vec![green, Color::WHITE, red]),
-                    vec!["faster", "same", "slower"],
-                ),
+                ColorLegend::gradient(ctx, scale, vec!["faster", "same", "slower"]),
             ])
             .padding(5)
             .bg(app.cs.panel_bg),
@@ -472,6 +442,58 @@ impl Delay {
     }
 }
 
+// Ranks roads and intersections by how many people have crossed them.
+pub fn color_throughput(colorer: &mut ColorNetwork, analytics: &Analytics, cs: &ColorScheme) {
+    colorer.ranked_roads(
+        analytics.road_thruput.all_total_counts(),
+        &cs.good_to_bad_red,
+    );
+    colorer.ranked_intersections(
+        analytics.intersection_thruput.all_total_counts(),
+        &cs.good_to_bad_red,
+    );
+}
+
+// Colors roads and intersections by the worst delay anybody is currently experiencing there,
+// ignoring anything less than a minute.
+pub fn color_delay(colorer: &mut ColorNetwork, sim: &Sim, map: &Map, cs: &ColorScheme) {
+    let (per_road, per_intersection) = sim.worst_delay(map);
+    for (r, d) in per_road {
+        if d < Duration::minutes(1) {
+            continue;
+        }
+        let color = cs
+            .good_to_bad_red
+            .eval(((d - Duration::minutes(1)) / Duration::minutes(15)).min(1.0));
+        colorer.add_r(r, color);
+    }
+    for (i, d) in per_intersection {
+        if d < Duration::minutes(1) {
+            continue;
+        }
+        let color = cs
+            .good_to_bad_red
+            .eval(((d - Duration::minutes(1)) / Duration::minutes(15)).min(1.0));
+        colorer.add_i(i, color);
+    }
+}
+
+// Colors intersections by the average delay measured there so far, up to 5 minutes. Unlike
+// color_delay, this covers the whole day, not just the agents currently waiting.
+pub fn color_average_delay(colorer: &mut ColorNetwork, analytics: &Analytics, cs: &ColorScheme) {
+    for (i, list) in &analytics.intersection_delays {
+        if list.is_empty() {
+            continue;
+        }
+        let avg = list.iter().map(|(_, d, _)| *d).sum::<Duration>() / (list.len() as f64);
+        colorer.add_i(
+            *i,
+            cs.good_to_bad_red
+                .eval((avg / Duration::minutes(5)).min(1.0)),
+        );
+    }
+}
+
 pub struct TrafficJams {
     time: Time,
     unzoomed: Drawable,
@@ -523,16 +545,19 @@ impl TrafficJams {
             app.primary.sim.delayed_intersections(Duration::minutes(5)),
         ) {
             cnt += 1;
-            unzoomed.push(Color::RED, boundary.to_outline(Distance::meters(5.0)));
-            unzoomed.push(Color::RED.alpha(0.5), boundary.clone());
-            unzoomed.push(Color::WHITE, epicenter.clone());
+            unzoomed.push(
+                app.cs.traffic_jam,
+                boundary.to_outline(Distance::meters(5.0)),
+            );
+            unzoomed.push(app.cs.traffic_jam.alpha(0.5), boundary.clone());
+            unzoomed.push(app.cs.jam_epicenter, epicenter.clone());
 
             zoomed.push(
-                Color::RED.alpha(0.4),
+                app.cs.traffic_jam.alpha(0.4),
                 boundary.to_outline(Distance::meters(5.0)),
             );
-            zoomed.push(Color::RED.alpha(0.3), boundary);
-            zoomed.push(Color::WHITE.alpha(0.4), epicenter);
+            zoomed.push(app.cs.traffic_jam.alpha(0.3), boundary);
+            zoomed.push(app.cs.jam_epicenter.alpha(0.4), epicenter);
         }
 
         let composite = Composite::new(
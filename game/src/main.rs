@@ -34,6 +34,7 @@ fn main() {
         num_agents: args.optional_parse("--num_agents", |s| s.parse()),
     };
     let mut opts = options::Options::default();
+    opts.load_saved();
     opts.dev = args.enabled("--dev");
     if args.enabled("--lowzoom") {
         opts.min_zoom_for_detail = 1.0;
@@ -58,6 +59,14 @@ fn main() {
             );
         }
     }
+    // Just write images of some layers and quit, without a window. --load should point to a
+    // savestate, scenario, or map.
+    if let Some(dir) = args.optional("--export_layers") {
+        args.done();
+        layer::export::export_layers(flags.sim_flags, opts.color_scheme, opts.data_palette, dir);
+        return;
+    }
+
     let mut settings = ezgui::Settings::new("A/B Street", "../data/system/fonts");
     settings.window_icon("../data/system/assets/pregame/icon.png");
     if args.enabled("--enable_profiler") {
@@ -66,6 +75,12 @@ fn main() {
     if args.enabled("--dump_raw_events") {
         settings.dump_raw_events();
     }
+    if let Some(path) = args.optional("--record_input") {
+        settings.record_input(&path);
+    }
+    if let Some(path) = args.optional("--replay_input") {
+        settings.replay_input(&path, args.enabled("--quit_after_replay"));
+    }
     if let Some(n) = args.optional_parse("--font_size", |s| s.parse::<usize>()) {
         settings.default_font_size(n);
     }
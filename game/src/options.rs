@@ -1,11 +1,13 @@
 use crate::app::App;
-use crate::colors::ColorSchemeChoice;
+use crate::colors::{ColorScheme, ColorSchemeChoice, DataPalette};
 use crate::game::{State, Transition};
+use abstutil::{Catalog, Timer};
 use ezgui::{
     hotkey, Btn, Checkbox, Choice, Composite, EventCtx, GfxCtx, Key, Line, Outcome, Spinner,
     TextExt, Widget,
 };
 use geom::Duration;
+use serde::{Deserialize, Serialize};
 
 // TODO SimOptions stuff too
 #[derive(Clone)]
@@ -15,6 +17,9 @@ pub struct Options {
     pub label_roads: bool,
     pub traffic_signal_style: TrafficSignalStyle,
     pub color_scheme: ColorSchemeChoice,
+    pub data_palette: DataPalette,
+    // Which message catalog to use, like "en" or "es"
+    pub language: String,
     pub min_zoom_for_detail: f64,
     pub large_unzoomed_agents: bool,
 
@@ -30,6 +35,8 @@ impl Options {
             label_roads: true,
             traffic_signal_style: TrafficSignalStyle::BAP,
             color_scheme: ColorSchemeChoice::Standard,
+            data_palette: DataPalette::Standard,
+            language: "en".to_string(),
             min_zoom_for_detail: 4.0,
             large_unzoomed_agents: false,
 
@@ -37,6 +44,41 @@ impl Options {
             resume_after_edit: true,
         }
     }
+
+    // Restores whatever the player picked in a previous session
+    pub fn load_saved(&mut self) {
+        if let Ok(saved) = abstutil::maybe_read_json::<SavedOptions>(
+            abstutil::path_player_settings(),
+            &mut Timer::throwaway(),
+        ) {
+            self.data_palette = saved.data_palette;
+            self.language = saved.language;
+        }
+    }
+
+    pub fn save(&self) {
+        abstutil::write_json(
+            abstutil::path_player_settings(),
+            &SavedOptions {
+                data_palette: self.data_palette,
+                language: self.language.clone(),
+            },
+        );
+    }
+}
+
+// The subset of Options that persists between sessions. Most options are just for debugging or
+// are set by flags.
+#[derive(Serialize, Deserialize)]
+struct SavedOptions {
+    data_palette: DataPalette,
+    // Settings saved before this existed won't have it
+    #[serde(default = "default_language")]
+    language: String,
+}
+
+fn default_language() -> String {
+    "en".to_string()
 }
 
 #[derive(Clone, PartialEq)]
@@ -156,6 +198,30 @@ impl OptionsPanel {
                             ),
                         ])
                         .margin_below(10),
+                        Widget::row(vec![
+                            "Colors for data layers:".draw_text(ctx).margin_right(5),
+                            Widget::dropdown(
+                                ctx,
+                                "Data palette",
+                                app.opts.data_palette,
+                                DataPalette::choices(),
+                            ),
+                        ])
+                        .margin_below(10),
+                        Widget::row(vec![
+                            "Language:".draw_text(ctx).margin_right(5),
+                            Widget::dropdown(
+                                ctx,
+                                "Language",
+                                // Not opts.language, in case that catalog failed to load
+                                app.catalog.language.clone(),
+                                Catalog::all_languages()
+                                    .into_iter()
+                                    .map(|(language, name)| Choice::new(name, language))
+                                    .collect(),
+                            ),
+                        ])
+                        .margin_below(10),
                         Widget::row(vec![
                             format!(
                                 "Scale factor for text / UI elements (your monitor is {}):",
@@ -249,10 +315,29 @@ impl State for OptionsPanel {
                         }
                     }
 
+                    let palette = self.composite.dropdown_value("Data palette");
+                    let palette_changed = app.opts.data_palette != palette;
+                    let language: String = self.composite.dropdown_value("Language");
+                    let language_changed = app.opts.language != language;
+                    if language_changed {
+                        // Open panels notice the new language and rebuild themselves
+                        app.catalog = Catalog::load_or_english(&language);
+                        app.opts.language = language;
+                    }
+                    if palette_changed || language_changed {
+                        app.opts.data_palette = palette;
+                        app.opts.save();
+                    }
+
                     let scheme = self.composite.dropdown_value("Color scheme");
                     if app.opts.color_scheme != scheme {
                         app.opts.color_scheme = scheme;
                         app.switch_map(ctx, app.primary.current_flags.sim_flags.load.clone());
+                    } else if palette_changed {
+                        // The map itself looks the same, so just swap colors. Layers bake in
+                        // their colors, so close the current one.
+                        app.cs = ColorScheme::new(app.opts.color_scheme, app.opts.data_palette);
+                        app.layer = None;
                     }
 
                     let factor = self.composite.dropdown_value("Scale factor");
@@ -61,22 +61,7 @@ impl DrawMap {
         }
 
         timer.start("generate thick roads");
-        let mut road_refs: Vec<&Road> = map.all_roads().iter().collect();
-        road_refs.sort_by_key(|r| r.zorder);
-        let mut all_roads = GeomBatch::new();
-        for r in road_refs {
-            all_roads.push(
-                osm_rank_to_color(cs, r.get_rank()),
-                r.get_thick_polygon(map).get(timer),
-            );
-            /*if false {
-                all_roads.push(
-                    color,
-                    roads[r.id.0].get_outline(map),
-                );
-            }*/
-        }
-        let draw_all_thick_roads = all_roads.upload(ctx);
+        let draw_all_thick_roads = DrawMap::zoomed_out_roads(map, cs, timer).upload(ctx);
         timer.stop("generate thick roads");
 
         let almost_lanes =
@@ -107,25 +92,8 @@ impl DrawMap {
         }
 
         timer.start("generate unzoomed intersections");
-        let mut intersection_refs: Vec<&Intersection> = map.all_intersections().iter().collect();
-        intersection_refs.sort_by_key(|i| i.get_zorder(map));
-        let mut all_intersections = GeomBatch::new();
-        for i in intersection_refs {
-            // TODO Would be neat to show closed intersections here, but then edits need to
-            // regenerate this
-            if i.is_stop_sign() {
-                all_intersections.push(osm_rank_to_color(cs, i.get_rank(map)), i.polygon.clone());
-            /*if false {
-                all_intersections.push(
-                    color,
-                    intersections[i.id.0].get_outline(map),
-                );
-            }*/
-            } else {
-                all_intersections.push(cs.unzoomed_interesting_intersection, i.polygon.clone());
-            }
-        }
-        let draw_all_unzoomed_intersections = all_intersections.upload(ctx);
+        let draw_all_unzoomed_intersections =
+            DrawMap::zoomed_out_intersections(map, cs).upload(ctx);
         timer.stop("generate unzoomed intersections");
 
         let mut buildings: Vec<DrawBuilding> = Vec::new();
@@ -243,6 +211,50 @@ impl DrawMap {
         }
     }
 
+    // Every road's thick polygon, colored by rank, for drawing while unzoomed. Also used to draw the
+    // map without a GPU.
+    pub fn zoomed_out_roads(map: &Map, cs: &ColorScheme, timer: &mut Timer) -> GeomBatch {
+        let mut road_refs: Vec<&Road> = map.all_roads().iter().collect();
+        road_refs.sort_by_key(|r| r.zorder);
+        let mut all_roads = GeomBatch::new();
+        for r in road_refs {
+            all_roads.push(
+                osm_rank_to_color(cs, r.get_rank()),
+                r.get_thick_polygon(map).get(timer),
+            );
+            /*if false {
+                all_roads.push(
+                    color,
+                    roads[r.id.0].get_outline(map),
+                );
+            }*/
+        }
+        all_roads
+    }
+
+    // Like zoomed_out_roads, for intersections
+    pub fn zoomed_out_intersections(map: &Map, cs: &ColorScheme) -> GeomBatch {
+        let mut intersection_refs: Vec<&Intersection> = map.all_intersections().iter().collect();
+        intersection_refs.sort_by_key(|i| i.get_zorder(map));
+        let mut all_intersections = GeomBatch::new();
+        for i in intersection_refs {
+            // TODO Would be neat to show closed intersections here, but then edits need to
+            // regenerate this
+            if i.is_stop_sign() {
+                all_intersections.push(osm_rank_to_color(cs, i.get_rank(map)), i.polygon.clone());
+            /*if false {
+                all_intersections.push(
+                    color,
+                    intersections[i.id.0].get_outline(map),
+                );
+            }*/
+            } else {
+                all_intersections.push(cs.unzoomed_interesting_intersection, i.polygon.clone());
+            }
+        }
+        all_intersections
+    }
+
     // The alt to these is implementing std::ops::Index, but that's way more verbose!
     pub fn get_r(&self, id: RoadID) -> &DrawRoad {
         &self.roads[id.0]
@@ -1,74 +1,49 @@
 use crate::app::App;
 use crate::game::{DrawBaselayer, State, Transition};
-use crate::info::{OpenTrip, Tab};
-use crate::sandbox::dashboards::trip_table::{make_table, preview_trip};
+use crate::sandbox::dashboards::trip_table::{open_trip, preview_trip};
 use crate::sandbox::dashboards::DashTab;
-use crate::sandbox::SandboxMode;
-use abstutil::prettyprint_usize;
 use ezgui::{
-    Btn, Checkbox, Composite, EventCtx, Filler, GfxCtx, Line, Outcome, ScreenDims, Text, TextExt,
-    Widget,
+    Checkbox, Col, Composite, EventCtx, Filler, Filter, GfxCtx, Line, Outcome, ScreenDims, Table,
+    Text, Widget,
 };
 use geom::Duration;
 use sim::{TripEndpoint, TripID, TripPhaseType};
 
-const ROWS: usize = 20;
-
-// TODO Mostly dupliclated code with trip_table. Find the right generalization.
 // TODO Compare all of these things before/after
-// TODO Filter out border trips
 
 pub struct ParkingOverhead {
     composite: Composite,
-    opts: Options,
-}
-
-struct Options {
-    sort_by: SortBy,
-    descending: bool,
-    off_map_starts: bool,
-    off_map_ends: bool,
-    skip: usize,
+    table: Table<Entry, Filters>,
 }
 
-impl Options {
-    fn change(&mut self, value: SortBy) {
-        self.skip = 0;
-        if self.sort_by == value {
-            self.descending = !self.descending;
-        } else {
-            self.sort_by = value;
-            self.descending = true;
-        }
-    }
+struct Entry {
+    trip: TripID,
+    starts_off_map: bool,
+    ends_off_map: bool,
+    total_duration: Duration,
+    driving_duration: Duration,
+    parking_duration: Duration,
+    walking_duration: Duration,
+    percent_overhead: usize,
 }
 
-#[derive(Clone, Copy, PartialEq)]
-enum SortBy {
-    TotalDuration,
-    DrivingDuration,
-    ParkingDuration,
-    WalkingDuration,
-    PercentOverhead,
+#[derive(PartialEq)]
+struct Filters {
+    off_map_starts: bool,
+    off_map_ends: bool,
 }
 
 impl ParkingOverhead {
     pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
-        let opts = Options {
-            sort_by: SortBy::PercentOverhead,
-            descending: true,
-            off_map_starts: true,
-            off_map_ends: true,
-            skip: 0,
-        };
+        let table = make_table(gather(app));
         Box::new(ParkingOverhead {
-            composite: make(ctx, app, &opts),
-            opts,
+            composite: make(ctx, app, &table),
+            table,
         })
     }
 
     fn recalc(&mut self, ctx: &mut EventCtx, app: &App) {
-        let mut new = make(ctx, app, &self.opts);
+        let mut new = make(ctx, app, &self.table);
         new.restore(ctx, &self.composite);
         self.composite = new;
     }
@@ -77,62 +52,17 @@ impl ParkingOverhead {
 impl State for ParkingOverhead {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self.composite.event(ctx) {
-            Some(Outcome::Clicked(x)) => match x.as_ref() {
-                "Total duration" => {
-                    self.opts.change(SortBy::TotalDuration);
+            Some(Outcome::Clicked(x)) => {
+                if self.table.clicked(&x) {
                     self.recalc(ctx, app);
+                } else if let Some(entry) = self.table.selected(&x) {
+                    return open_trip(app, entry.trip);
+                } else {
+                    return DashTab::ParkingOverhead.transition(ctx, app, &x);
                 }
-                "Driving duration" => {
-                    self.opts.change(SortBy::DrivingDuration);
-                    self.recalc(ctx, app);
-                }
-                "Parking duration" => {
-                    self.opts.change(SortBy::ParkingDuration);
-                    self.recalc(ctx, app);
-                }
-                "Walking duration" => {
-                    self.opts.change(SortBy::WalkingDuration);
-                    self.recalc(ctx, app);
-                }
-                "Percent overhead" => {
-                    self.opts.change(SortBy::PercentOverhead);
-                    self.recalc(ctx, app);
-                }
-                "previous trips" => {
-                    self.opts.skip -= ROWS;
-                    self.recalc(ctx, app);
-                }
-                "next trips" => {
-                    self.opts.skip += ROWS;
-                    self.recalc(ctx, app);
-                }
-                x => {
-                    if let Ok(idx) = x.parse::<usize>() {
-                        let trip = TripID(idx);
-                        let person = app.primary.sim.trip_to_person(trip);
-                        return Transition::PopWithData(Box::new(move |state, ctx, app| {
-                            let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
-                            let mut actions = sandbox.contextual_actions();
-                            sandbox.controls.common.as_mut().unwrap().launch_info_panel(
-                                ctx,
-                                app,
-                                Tab::PersonTrips(person, OpenTrip::single(trip)),
-                                &mut actions,
-                            );
-                        }));
-                    }
-                    return DashTab::ParkingOverhead.transition(ctx, app, x);
-                }
-            },
+            }
             None => {
-                let off_map_starts = self.composite.is_checked("starting off-map");
-                let off_map_ends = self.composite.is_checked("ending off-map");
-                if self.opts.off_map_starts != off_map_starts
-                    || self.opts.off_map_ends != off_map_ends
-                {
-                    self.opts.off_map_starts = off_map_starts;
-                    self.opts.off_map_ends = off_map_ends;
-                    self.opts.skip = 0;
+                if self.table.panel_changed(&self.composite) {
                     self.recalc(ctx, app);
                 }
             }
@@ -148,34 +78,19 @@ impl State for ParkingOverhead {
     fn draw(&self, g: &mut GfxCtx, app: &App) {
         g.clear(app.cs.grass);
         self.composite.draw(g);
-        preview_trip(g, app, &self.composite);
+        preview_trip(
+            g,
+            app,
+            &self.composite,
+            self.table.hovering(&self.composite).map(|x| x.trip),
+        );
     }
 }
 
-struct Entry {
-    trip: TripID,
-    total_duration: Duration,
-    driving_duration: Duration,
-    parking_duration: Duration,
-    walking_duration: Duration,
-    percent_overhead: usize,
-}
-
-fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
-    // Gather raw data
+fn gather(app: &App) -> Vec<Entry> {
     let mut data = Vec::new();
     for (id, phases) in app.primary.sim.get_analytics().get_all_trip_phases() {
         let (_, start, end, _) = app.primary.sim.trip_info(id);
-        if !opts.off_map_starts {
-            if let TripEndpoint::Border(_, _) = start {
-                continue;
-            }
-        }
-        if !opts.off_map_ends {
-            if let TripEndpoint::Border(_, _) = end {
-                continue;
-            }
-        }
 
         let mut total_duration = Duration::ZERO;
         let mut driving_duration = Duration::ZERO;
@@ -209,6 +124,14 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
 
         data.push(Entry {
             trip: id,
+            starts_off_map: match start {
+                TripEndpoint::Border(_, _) => true,
+                _ => false,
+            },
+            ends_off_map: match end {
+                TripEndpoint::Border(_, _) => true,
+                _ => false,
+            },
             total_duration,
             driving_duration,
             parking_duration,
@@ -216,59 +139,71 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
             percent_overhead: (100.0 * (1.0 - (driving_duration / total_duration))) as usize,
         });
     }
+    data
+}
 
-    // Sort
-    match opts.sort_by {
-        SortBy::TotalDuration => data.sort_by_key(|x| x.total_duration),
-        SortBy::DrivingDuration => data.sort_by_key(|x| x.driving_duration),
-        SortBy::ParkingDuration => data.sort_by_key(|x| x.parking_duration),
-        SortBy::WalkingDuration => data.sort_by_key(|x| x.walking_duration),
-        SortBy::PercentOverhead => data.sort_by_key(|x| x.percent_overhead),
-    }
-    if opts.descending {
-        data.reverse();
-    }
-    let total_rows = data.len();
-
-    // Render data
-    let mut rows = Vec::new();
-    for x in data.into_iter().skip(opts.skip).take(ROWS) {
-        rows.push((
-            x.trip.0.to_string(),
-            vec![
-                Text::from(Line(x.trip.0.to_string())).render_ctx(ctx),
-                Text::from(Line(x.total_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(x.driving_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(x.parking_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(x.walking_duration.to_string())).render_ctx(ctx),
-                Text::from(Line(format!("{}%", x.percent_overhead))).render_ctx(ctx),
-            ],
-        ));
-    }
-
-    let btn = |value, name| {
-        if opts.sort_by == value {
-            Btn::text_bg2(format!(
-                "{} {}",
-                name,
-                if opts.descending { "↓" } else { "↑" }
-            ))
-            .build(ctx, name, None)
-        } else {
-            Btn::text_bg2(name).build_def(ctx, None)
-        }
+fn make_table(data: Vec<Entry>) -> Table<Entry, Filters> {
+    let filter = Filter {
+        state: Filters {
+            off_map_starts: true,
+            off_map_ends: true,
+        },
+        to_controls: Box::new(|ctx, state| {
+            Widget::row(vec![
+                Checkbox::text(ctx, "starting off-map", None, state.off_map_starts)
+                    .margin_right(10),
+                Checkbox::text(ctx, "ending off-map", None, state.off_map_ends),
+            ])
+        }),
+        from_controls: Box::new(|composite| Filters {
+            off_map_starts: composite.is_checked("starting off-map"),
+            off_map_ends: composite.is_checked("ending off-map"),
+        }),
+        apply: Box::new(|state, x| {
+            (state.off_map_starts || !x.starts_off_map) && (state.off_map_ends || !x.ends_off_map)
+        }),
     };
-    let headers = vec![
-        Line("Trip ID").draw(ctx),
-        btn(SortBy::TotalDuration, "Total duration"),
-        btn(SortBy::DrivingDuration, "Driving duration"),
-        btn(SortBy::ParkingDuration, "Parking duration"),
-        btn(SortBy::WalkingDuration, "Walking duration"),
-        btn(SortBy::PercentOverhead, "Percent overhead"),
-    ];
 
-    let mut col = vec![DashTab::ParkingOverhead.picker(ctx, app)];
-    col.push(
+    let mut table = Table::new(
+        "trips",
+        data,
+        |x: &Entry| x.trip.0.to_string(),
+        "Percent overhead",
+        filter,
+    );
+    table.rows_per_page(20);
+    table.column("Trip ID", |x| x.trip.0.to_string(), Col::Static);
+    table.column(
+        "Total duration",
+        |x| x.total_duration.to_string(),
+        Col::sort_by_key(|x: &Entry| x.total_duration),
+    );
+    table.column(
+        "Driving duration",
+        |x| x.driving_duration.to_string(),
+        Col::sort_by_key(|x: &Entry| x.driving_duration),
+    );
+    table.column(
+        "Parking duration",
+        |x| x.parking_duration.to_string(),
+        Col::sort_by_key(|x: &Entry| x.parking_duration),
+    );
+    table.column(
+        "Walking duration",
+        |x| x.walking_duration.to_string(),
+        Col::sort_by_key(|x: &Entry| x.walking_duration),
+    );
+    table.column(
+        "Percent overhead",
+        |x| format!("{}%", x.percent_overhead),
+        Col::sort_by_key(|x: &Entry| x.percent_overhead),
+    );
+    table
+}
+
+fn make(ctx: &mut EventCtx, app: &App, table: &Table<Entry, Filters>) -> Composite {
+    let col = vec![
+        DashTab::ParkingOverhead.picker(ctx, app),
         Widget::row(vec![
             Text::from_multiline(vec![
                 Line(
@@ -298,50 +233,8 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
         ])
         .evenly_spaced()
         .margin_below(10),
-    );
-    col.push(
-        Widget::row(vec![
-            Checkbox::text(ctx, "starting off-map", None, opts.off_map_starts).margin_right(10),
-            Checkbox::text(ctx, "ending off-map", None, opts.off_map_ends),
-        ])
-        .margin_below(5),
-    );
-    col.push(
-        Widget::row(vec![
-            if opts.skip > 0 {
-                Btn::text_fg("<").build(ctx, "previous trips", None)
-            } else {
-                Btn::text_fg("<").inactive(ctx)
-            }
-            .margin_right(10),
-            format!(
-                "{}-{} of {}",
-                if total_rows > 0 {
-                    prettyprint_usize(opts.skip + 1)
-                } else {
-                    "0".to_string()
-                },
-                prettyprint_usize((opts.skip + 1 + ROWS).min(total_rows)),
-                prettyprint_usize(total_rows)
-            )
-            .draw_text(ctx)
-            .margin_right(10),
-            if opts.skip + 1 + ROWS < total_rows {
-                Btn::text_fg(">").build(ctx, "next trips", None)
-            } else {
-                Btn::text_fg(">").inactive(ctx)
-            },
-        ])
-        .margin_below(5),
-    );
-
-    col.extend(make_table(
-        ctx,
-        app,
-        headers,
-        rows,
-        0.88 * ctx.canvas.window_width,
-    ));
+        table.render(ctx),
+    ];
 
     Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(10))
         .exact_size_percent(90, 90)
@@ -4,12 +4,13 @@ use crate::helpers::color_for_mode;
 use crate::sandbox::dashboards::DashTab;
 use abstutil::prettyprint_usize;
 use ezgui::{
-    Checkbox, Choice, Color, CompareTimes, Composite, DrawWithTooltips, EventCtx, GeomBatch,
-    GfxCtx, Line, Outcome, Text, TextExt, Widget,
+    BarChart, BarSeries, Checkbox, Choice, Color, CompareTimes, Composite, DrawWithTooltips,
+    EventCtx, GeomBatch, GfxCtx, Line, Outcome, PlotOptions, Series, StackedAreaPlot, Text,
+    TextExt, Widget,
 };
-use geom::{Distance, Duration, Polygon, Pt2D};
+use geom::{Distance, Duration, Polygon, Pt2D, Time};
 use sim::TripMode;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub struct TripSummaries {
     composite: Composite,
@@ -40,7 +41,12 @@ impl TripSummaries {
                 )
                 .margin_right(5),
             );
-            filters.push(m.ongoing_verb().draw_text(ctx).margin_right(10));
+            filters.push(
+                app.catalog
+                    .tr(m.ongoing_verb())
+                    .draw_text(ctx)
+                    .margin_right(10),
+            );
         }
 
         Box::new(TripSummaries {
@@ -55,6 +61,12 @@ impl TripSummaries {
                             .margin_right(20),
                         scatter_plot(ctx, app, &filter),
                     ])
+                    .evenly_spaced()
+                    .margin_below(10),
+                    Widget::row(vec![
+                        trip_time_histogram(ctx, app, &filter).margin_right(20),
+                        mode_split(ctx, app, &filter),
+                    ])
                     .evenly_spaced(),
                 ])
                 .bg(app.cs.panel_bg)
@@ -331,6 +343,72 @@ fn contingency_table(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
         .padding(10)
 }
 
+fn trip_time_histogram(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
+    let mut series: Vec<BarSeries<Duration>> = filter
+        .modes
+        .iter()
+        .map(|m| BarSeries {
+            label: app.catalog.tr(m.ongoing_verb()),
+            color: color_for_mode(app, *m),
+            values: Vec::new(),
+        })
+        .collect();
+    let mut any = false;
+    for (_, _, maybe_mode, dt) in &app.primary.sim.get_analytics().finished_trips {
+        if let Some(idx) = maybe_mode.and_then(|m| filter.modes.iter().position(|x| *x == m)) {
+            series[idx].values.push(*dt);
+            any = true;
+        }
+    }
+    if !any {
+        return Widget::nothing();
+    }
+
+    Widget::col(vec![
+        Line("Trip time distribution").small_heading().draw(ctx),
+        BarChart::histogram(ctx, series, 10),
+    ])
+    .outline(2.0, Color::WHITE)
+    .padding(10)
+}
+
+// How many trips of each mode finished every hour
+fn mode_split(ctx: &mut EventCtx, app: &App, filter: &Filter) -> Widget {
+    let num_hours = app.primary.sim.time().get_hours() + 1;
+    let mut counts: BTreeMap<TripMode, Vec<usize>> = filter
+        .modes
+        .iter()
+        .map(|m| (*m, vec![0; num_hours]))
+        .collect();
+    for (t, _, maybe_mode, _) in &app.primary.sim.get_analytics().finished_trips {
+        if let Some(hourly) = maybe_mode.and_then(|m| counts.get_mut(&m)) {
+            hourly[t.get_hours()] += 1;
+        }
+    }
+    if counts.is_empty() {
+        return Widget::nothing();
+    }
+
+    let series = counts
+        .into_iter()
+        .map(|(m, hourly)| Series {
+            label: app.catalog.tr(m.ongoing_verb()),
+            color: color_for_mode(app, m),
+            pts: hourly
+                .into_iter()
+                .enumerate()
+                .map(|(hour, cnt)| (Time::START_OF_DAY + Duration::hours(hour), cnt))
+                .collect(),
+        })
+        .collect();
+    Widget::col(vec![
+        Line("Trips finished per hour").small_heading().draw(ctx),
+        StackedAreaPlot::new(ctx, series, PlotOptions::fixed()),
+    ])
+    .outline(2.0, Color::WHITE)
+    .padding(10)
+}
+
 #[derive(PartialEq)]
 pub struct Filter {
     changes_pct: Option<f64>,
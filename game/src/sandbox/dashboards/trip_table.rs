@@ -6,70 +6,51 @@ use crate::sandbox::dashboards::DashTab;
 use crate::sandbox::SandboxMode;
 use abstutil::prettyprint_usize;
 use ezgui::{
-    Btn, Checkbox, Color, Composite, EventCtx, Filler, GeomBatch, GfxCtx, Line, Outcome,
-    RewriteColor, ScreenDims, ScreenPt, Text, TextExt, Widget,
+    Checkbox, Col, Color, Composite, EventCtx, Filler, Filter, GeomBatch, GfxCtx, Line, Outcome,
+    RewriteColor, ScreenDims, ScreenPt, Table, Text, TextExt, Widget,
 };
-use geom::{Distance, Duration, Polygon, Pt2D, Time};
+use geom::{Distance, Duration, Pt2D, Time};
 use sim::{TripEndpoint, TripID, TripMode};
-use std::collections::BTreeSet;
-
-const ROWS: usize = 10;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub struct TripTable {
     composite: Composite,
-    opts: Options,
+    table: Table<Entry, Filters>,
+    aborted: usize,
 }
 
-struct Options {
-    sort_by: SortBy,
-    descending: bool,
+struct Entry {
+    trip: TripID,
+    mode: TripMode,
+    starts_off_map: bool,
+    ends_off_map: bool,
+    departure: Time,
+    duration_after: Duration,
+    duration_before: Duration,
+    waiting: Duration,
+    percent_waiting: usize,
+}
+
+#[derive(PartialEq)]
+struct Filters {
     modes: BTreeSet<TripMode>,
     off_map_starts: bool,
     off_map_ends: bool,
-    skip: usize,
-}
-
-impl Options {
-    fn change(&mut self, value: SortBy) {
-        self.skip = 0;
-        if self.sort_by == value {
-            self.descending = !self.descending;
-        } else {
-            self.sort_by = value;
-            self.descending = true;
-        }
-    }
-}
-
-// TODO Is there a heterogenously typed table crate somewhere?
-#[derive(Clone, Copy, PartialEq)]
-enum SortBy {
-    Departure,
-    Duration,
-    RelativeDuration,
-    PercentChangeDuration,
-    Waiting,
-    PercentWaiting,
 }
 
 impl TripTable {
     pub fn new(ctx: &mut EventCtx, app: &App) -> Box<dyn State> {
-        let opts = Options {
-            sort_by: SortBy::PercentWaiting,
-            descending: true,
-            modes: TripMode::all().into_iter().collect(),
-            off_map_starts: true,
-            off_map_ends: true,
-            skip: 0,
-        };
+        let (data, aborted) = gather(app);
+        let table = make_table(app, data);
         Box::new(TripTable {
-            composite: make(ctx, app, &opts),
-            opts,
+            composite: make(ctx, app, &table, aborted),
+            table,
+            aborted,
         })
     }
 
     fn recalc(&mut self, ctx: &mut EventCtx, app: &App) {
-        let mut new = make(ctx, app, &self.opts);
+        let mut new = make(ctx, app, &self.table, self.aborted);
         new.restore(ctx, &self.composite);
         self.composite = new;
     }
@@ -78,77 +59,17 @@ impl TripTable {
 impl State for TripTable {
     fn event(&mut self, ctx: &mut EventCtx, app: &mut App) -> Transition {
         match self.composite.event(ctx) {
-            Some(Outcome::Clicked(x)) => match x.as_ref() {
-                "Departure" => {
-                    self.opts.change(SortBy::Departure);
-                    self.recalc(ctx, app);
-                }
-                "Duration" => {
-                    self.opts.change(SortBy::Duration);
-                    self.recalc(ctx, app);
-                }
-                "Comparison" => {
-                    self.opts.change(SortBy::RelativeDuration);
-                    self.recalc(ctx, app);
-                }
-                "Normalized" => {
-                    self.opts.change(SortBy::PercentChangeDuration);
-                    self.recalc(ctx, app);
-                }
-                "Time spent waiting" => {
-                    self.opts.change(SortBy::Waiting);
-                    self.recalc(ctx, app);
-                }
-                "Percent waiting" => {
-                    self.opts.change(SortBy::PercentWaiting);
-                    self.recalc(ctx, app);
-                }
-                "previous trips" => {
-                    self.opts.skip -= ROWS;
-                    self.recalc(ctx, app);
-                }
-                "next trips" => {
-                    self.opts.skip += ROWS;
+            Some(Outcome::Clicked(x)) => {
+                if self.table.clicked(&x) {
                     self.recalc(ctx, app);
+                } else if let Some(entry) = self.table.selected(&x) {
+                    return open_trip(app, entry.trip);
+                } else {
+                    return DashTab::TripTable.transition(ctx, app, &x);
                 }
-                x => {
-                    if let Ok(idx) = x.parse::<usize>() {
-                        let trip = TripID(idx);
-                        let person = app.primary.sim.trip_to_person(trip);
-                        return Transition::PopWithData(Box::new(move |state, ctx, app| {
-                            let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
-                            let mut actions = sandbox.contextual_actions();
-                            sandbox.controls.common.as_mut().unwrap().launch_info_panel(
-                                ctx,
-                                app,
-                                Tab::PersonTrips(person, OpenTrip::single(trip)),
-                                &mut actions,
-                            );
-                        }));
-                    }
-                    return DashTab::TripTable.transition(ctx, app, x);
-                }
-            },
+            }
             None => {
-                let mut modes = BTreeSet::new();
-                for m in TripMode::all() {
-                    if self.composite.is_checked(m.ongoing_verb()) {
-                        modes.insert(m);
-                    }
-                }
-                if modes != self.opts.modes {
-                    self.opts.skip = 0;
-                    self.opts.modes = modes;
-                    self.recalc(ctx, app);
-                }
-                let off_map_starts = self.composite.is_checked("starting off-map");
-                let off_map_ends = self.composite.is_checked("ending off-map");
-                if self.opts.off_map_starts != off_map_starts
-                    || self.opts.off_map_ends != off_map_ends
-                {
-                    self.opts.off_map_starts = off_map_starts;
-                    self.opts.off_map_ends = off_map_ends;
-                    self.opts.skip = 0;
+                if self.table.panel_changed(&self.composite) {
                     self.recalc(ctx, app);
                 }
             }
@@ -164,49 +85,28 @@ impl State for TripTable {
     fn draw(&self, g: &mut GfxCtx, app: &App) {
         g.clear(app.cs.grass);
         self.composite.draw(g);
-        preview_trip(g, app, &self.composite);
+        preview_trip(
+            g,
+            app,
+            &self.composite,
+            self.table.hovering(&self.composite).map(|x| x.trip),
+        );
     }
 }
 
-struct Entry {
-    trip: TripID,
-    mode: TripMode,
-    departure: Time,
-    duration_after: Duration,
-    duration_before: Duration,
-    waiting: Duration,
-    percent_waiting: usize,
-}
-
-fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
-    // Gather raw data
+fn gather(app: &App) -> (Vec<Entry>, usize) {
     let mut data = Vec::new();
     let sim = &app.primary.sim;
     let mut aborted = 0;
     for (_, id, maybe_mode, duration_after) in &sim.get_analytics().finished_trips {
         let mode = if let Some(m) = maybe_mode {
-            if !opts.modes.contains(m) {
-                continue;
-            }
             *m
         } else {
             aborted += 1;
             continue;
         };
-        let (_, start, end, _) = sim.trip_info(*id);
-        if !opts.off_map_starts {
-            if let TripEndpoint::Border(_, _) = start {
-                continue;
-            }
-        }
-        if !opts.off_map_ends {
-            if let TripEndpoint::Border(_, _) = end {
-                continue;
-            }
-        }
-
+        let (departure, start, end, _) = sim.trip_info(*id);
         let (_, waiting) = sim.finished_trip_time(*id).unwrap();
-        let (departure, _, _, _) = sim.trip_info(*id);
         let duration_before = if app.has_prebaked().is_some() {
             if let Some(dt) = app.prebaked().finished_trip_time(*id) {
                 dt
@@ -222,6 +122,14 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
         data.push(Entry {
             trip: *id,
             mode,
+            starts_off_map: match start {
+                TripEndpoint::Border(_, _) => true,
+                _ => false,
+            },
+            ends_off_map: match end {
+                TripEndpoint::Border(_, _) => true,
+                _ => false,
+            },
             departure,
             duration_after: *duration_after,
             duration_before,
@@ -229,155 +137,150 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
             percent_waiting: (100.0 * waiting / *duration_after) as usize,
         });
     }
+    (data, aborted)
+}
+
+fn make_table(app: &App, data: Vec<Entry>) -> Table<Entry, Filters> {
+    let mode_colors: BTreeMap<TripMode, Color> = TripMode::all()
+        .into_iter()
+        .map(|m| (m, color_for_mode(app, m)))
+        .collect();
+    // Checkboxes are still named by the English verb
+    let mode_labels: BTreeMap<TripMode, String> = TripMode::all()
+        .into_iter()
+        .map(|m| (m, app.catalog.tr(m.ongoing_verb())))
+        .collect();
 
-    // Sort
-    match opts.sort_by {
-        SortBy::Departure => data.sort_by_key(|x| x.departure),
-        SortBy::Duration => data.sort_by_key(|x| x.duration_after),
-        SortBy::RelativeDuration => data.sort_by_key(|x| x.duration_after - x.duration_before),
-        SortBy::PercentChangeDuration => {
-            data.sort_by_key(|x| (100.0 * (x.duration_after / x.duration_before)) as isize)
+    let filter = {
+        let mode_colors = mode_colors.clone();
+        let mode_labels = mode_labels.clone();
+        Filter {
+            state: Filters {
+                modes: TripMode::all().into_iter().collect(),
+                off_map_starts: true,
+                off_map_ends: true,
+            },
+            to_controls: Box::new(move |ctx, state| {
+                let mut modes = Vec::new();
+                for m in TripMode::all() {
+                    modes.push(
+                        Checkbox::colored(
+                            ctx,
+                            m.ongoing_verb(),
+                            mode_colors[&m],
+                            state.modes.contains(&m),
+                        )
+                        .margin_right(5),
+                    );
+                    modes.push(mode_labels[&m].draw_text(ctx).margin_right(10));
+                }
+                Widget::col(vec![
+                    Widget::row(modes).margin_below(5),
+                    Widget::row(vec![
+                        Checkbox::text(ctx, "starting off-map", None, state.off_map_starts)
+                            .margin_right(10),
+                        Checkbox::text(ctx, "ending off-map", None, state.off_map_ends),
+                    ]),
+                ])
+            }),
+            from_controls: Box::new(|composite| Filters {
+                modes: TripMode::all()
+                    .into_iter()
+                    .filter(|m| composite.is_checked(m.ongoing_verb()))
+                    .collect(),
+                off_map_starts: composite.is_checked("starting off-map"),
+                off_map_ends: composite.is_checked("ending off-map"),
+            }),
+            apply: Box::new(|state, x| {
+                state.modes.contains(&x.mode)
+                    && (state.off_map_starts || !x.starts_off_map)
+                    && (state.off_map_ends || !x.ends_off_map)
+            }),
         }
-        SortBy::Waiting => data.sort_by_key(|x| x.waiting),
-        SortBy::PercentWaiting => data.sort_by_key(|x| x.percent_waiting),
-    }
-    if opts.descending {
-        data.reverse();
-    }
-    let total_rows = data.len();
+    };
 
-    // Render data
-    let mut rows = Vec::new();
-    for x in data.into_iter().skip(opts.skip).take(ROWS) {
-        let mut row = vec![
-            Text::from(Line(x.trip.0.to_string())).render_ctx(ctx),
-            Text::from(Line(x.mode.ongoing_verb()).fg(color_for_mode(app, x.mode))).render_ctx(ctx),
-            Text::from(Line(x.departure.ampm_tostring())).render_ctx(ctx),
-            Text::from(Line(x.duration_after.to_string())).render_ctx(ctx),
-        ];
-        if app.has_prebaked().is_some() {
-            row.push(
+    let mut table = Table::new(
+        "trips",
+        data,
+        |x: &Entry| x.trip.0.to_string(),
+        "Percent waiting",
+        filter,
+    );
+    table.column("Trip ID", |x| x.trip.0.to_string(), Col::Static);
+    table.custom_column(
+        "Type",
+        {
+            let mode_labels = mode_labels.clone();
+            move |x: &Entry| mode_labels[&x.mode].clone()
+        },
+        move |ctx, x| {
+            Text::from(Line(&mode_labels[&x.mode]).fg(mode_colors[&x.mode])).render_ctx(ctx)
+        },
+        Col::Static,
+    );
+    table.column(
+        "Departure",
+        |x| x.departure.ampm_tostring(),
+        Col::sort_by_key(|x: &Entry| x.departure),
+    );
+    table.column(
+        "Duration",
+        |x| x.duration_after.to_string(),
+        Col::sort_by_key(|x: &Entry| x.duration_after),
+    );
+    if app.has_prebaked().is_some() {
+        table.custom_column(
+            "Comparison",
+            |x| (x.duration_after - x.duration_before).to_string(),
+            |ctx, x| {
                 Text::from_all(cmp_duration_shorter(x.duration_after, x.duration_before))
-                    .render_ctx(ctx),
-            );
-            if x.duration_after == x.duration_before {
-                row.push(Text::from(Line("same")).render_ctx(ctx));
-            } else if x.duration_after < x.duration_before {
-                row.push(
-                    Text::from(Line(format!(
+                    .render_ctx(ctx)
+            },
+            Col::sort_by_key(|x: &Entry| x.duration_after - x.duration_before),
+        );
+        table.column(
+            "Normalized",
+            |x| {
+                if x.duration_after == x.duration_before {
+                    "same".to_string()
+                } else if x.duration_after < x.duration_before {
+                    format!(
                         "{}% faster",
                         (100.0 * (1.0 - (x.duration_after / x.duration_before))) as usize
-                    )))
-                    .render_ctx(ctx),
-                );
-            } else {
-                row.push(
-                    Text::from(Line(format!(
-                        "{}% slower ",
+                    )
+                } else {
+                    format!(
+                        "{}% slower",
                         (100.0 * ((x.duration_after / x.duration_before) - 1.0)) as usize
-                    )))
-                    .render_ctx(ctx),
-                );
-            }
-        }
-        row.push(Text::from(Line(x.waiting.to_string())).render_ctx(ctx));
-        row.push(Text::from(Line(format!("{}%", x.percent_waiting))).render_ctx(ctx));
-
-        rows.push((x.trip.0.to_string(), row));
-    }
-
-    let btn = |value, name| {
-        if opts.sort_by == value {
-            Btn::text_bg2(format!(
-                "{} {}",
-                name,
-                if opts.descending { "↓" } else { "↑" }
-            ))
-            .build(ctx, name, None)
-        } else {
-            Btn::text_bg2(name).build_def(ctx, None)
-        }
-    };
-    let mut headers = vec![
-        Line("Trip ID").draw(ctx),
-        Line("Type").draw(ctx),
-        btn(SortBy::Departure, "Departure"),
-        btn(SortBy::Duration, "Duration"),
-    ];
-    if app.has_prebaked().is_some() {
-        headers.push(btn(SortBy::RelativeDuration, "Comparison"));
-        headers.push(btn(SortBy::PercentChangeDuration, "Normalized"));
-    }
-    headers.push(btn(SortBy::Waiting, "Time spent waiting"));
-    headers.push(btn(SortBy::PercentWaiting, "Percent waiting"));
-
-    let mut col = vec![DashTab::TripTable.picker(ctx, app)];
-    let mut filters = Vec::new();
-    for m in TripMode::all() {
-        filters.push(
-            Checkbox::colored(
-                ctx,
-                m.ongoing_verb(),
-                color_for_mode(app, m),
-                opts.modes.contains(&m),
-            )
-            .margin_right(5),
+                    )
+                }
+            },
+            Col::sort_by_key(|x: &Entry| (100.0 * (x.duration_after / x.duration_before)) as isize),
         );
-        filters.push(m.ongoing_verb().draw_text(ctx).margin_right(10));
     }
-    col.push(Widget::row(filters).margin_below(5));
-    col.push(
-        Widget::row(vec![
-            Checkbox::text(ctx, "starting off-map", None, opts.off_map_starts).margin_right(10),
-            Checkbox::text(ctx, "ending off-map", None, opts.off_map_ends),
-        ])
-        .margin_below(5),
+    table.column(
+        "Time spent waiting",
+        |x| x.waiting.to_string(),
+        Col::sort_by_key(|x: &Entry| x.waiting),
+    );
+    table.column(
+        "Percent waiting",
+        |x| format!("{}%", x.percent_waiting),
+        Col::sort_by_key(|x: &Entry| x.percent_waiting),
     );
-    col.push(
+    table
+}
+
+fn make(ctx: &mut EventCtx, app: &App, table: &Table<Entry, Filters>, aborted: usize) -> Composite {
+    let col = vec![
+        DashTab::TripTable.picker(ctx, app),
         format!(
             "{} trips aborted due to simulation glitch",
             prettyprint_usize(aborted)
         )
         .draw_text(ctx)
         .margin_below(5),
-    );
-    col.push(
-        Widget::row(vec![
-            if opts.skip > 0 {
-                Btn::text_fg("<").build(ctx, "previous trips", None)
-            } else {
-                Btn::text_fg("<").inactive(ctx)
-            }
-            .margin_right(10),
-            format!(
-                "{}-{} of {}",
-                if total_rows > 0 {
-                    prettyprint_usize(opts.skip + 1)
-                } else {
-                    "0".to_string()
-                },
-                prettyprint_usize((opts.skip + 1 + ROWS).min(total_rows)),
-                prettyprint_usize(total_rows)
-            )
-            .draw_text(ctx)
-            .margin_right(10),
-            if opts.skip + 1 + ROWS < total_rows {
-                Btn::text_fg(">").build(ctx, "next trips", None)
-            } else {
-                Btn::text_fg(">").inactive(ctx)
-            },
-        ])
-        .margin_below(5),
-    );
-
-    col.extend(make_table(
-        ctx,
-        app,
-        headers,
-        rows,
-        0.88 * ctx.canvas.window_width,
-    ));
-    col.push(
+        table.render(ctx),
         Filler::new(ScreenDims::new(
             0.15 * ctx.canvas.window_width,
             0.15 * ctx.canvas.window_width,
@@ -385,77 +288,29 @@ fn make(ctx: &mut EventCtx, app: &App, opts: &Options) -> Composite {
         .named("preview")
         .centered_horiz()
         .margin_above(10),
-    );
+    ];
 
     Composite::new(Widget::col(col).bg(app.cs.panel_bg).padding(10))
         .exact_size_percent(90, 90)
         .build(ctx)
 }
 
-// TODO Figure out a nicer API to construct generic sortable tables.
-pub fn make_table(
-    ctx: &mut EventCtx,
-    app: &App,
-    headers: Vec<Widget>,
-    rows: Vec<(String, Vec<GeomBatch>)>,
-    total_width: f64,
-) -> Vec<Widget> {
-    let total_width = total_width / ctx.get_scale_factor();
-    let mut width_per_col: Vec<f64> = headers
-        .iter()
-        .map(|w| w.get_width_for_forcing() / ctx.get_scale_factor())
-        .collect();
-    for (_, row) in &rows {
-        for (col, width) in row.iter().zip(width_per_col.iter_mut()) {
-            *width = width.max(col.get_dims().width / ctx.get_scale_factor());
-        }
-    }
-    let extra_margin = ((total_width - width_per_col.clone().into_iter().sum::<f64>())
-        / (width_per_col.len() - 1) as f64)
-        .max(0.0);
-
-    let mut col = vec![Widget::row(
-        headers
-            .into_iter()
-            .enumerate()
-            .map(|(idx, w)| {
-                let margin = extra_margin + width_per_col[idx]
-                    - (w.get_width_for_forcing() / ctx.get_scale_factor());
-                if idx == width_per_col.len() - 1 {
-                    w.margin_right((margin - extra_margin) as usize)
-                } else {
-                    w.margin_right(margin as usize)
-                }
-            })
-            .collect(),
-    )
-    .bg(app.cs.section_bg)];
-
-    for (label, row) in rows {
-        let mut batch = GeomBatch::new();
-        batch.autocrop_dims = false;
-        let mut x1 = 0.0;
-        for (col, width) in row.into_iter().zip(width_per_col.iter()) {
-            batch.append(col.scale(1.0 / ctx.get_scale_factor()).translate(x1, 0.0));
-            x1 += *width + extra_margin;
-        }
-
-        let rect = Polygon::rectangle(total_width, batch.get_dims().height);
-        let mut hovered = GeomBatch::new();
-        hovered.push(app.cs.hovering, rect.clone());
-        hovered.append(batch.clone());
-
-        col.push(
-            Btn::custom(batch, hovered, rect)
-                .tooltip(Text::new())
-                .build(ctx, label, None),
+// Closes the dashboard and shows the trip in the info panel
+pub fn open_trip(app: &App, trip: TripID) -> Transition {
+    let person = app.primary.sim.trip_to_person(trip);
+    Transition::PopWithData(Box::new(move |state, ctx, app| {
+        let sandbox = state.downcast_mut::<SandboxMode>().unwrap();
+        let mut actions = sandbox.contextual_actions();
+        sandbox.controls.common.as_mut().unwrap().launch_info_panel(
+            ctx,
+            app,
+            Tab::PersonTrips(person, OpenTrip::single(trip)),
+            &mut actions,
         );
-    }
-
-    col
+    }))
 }
 
-pub fn preview_trip(g: &mut GfxCtx, app: &App, composite: &Composite) {
+pub fn preview_trip(g: &mut GfxCtx, app: &App, composite: &Composite, trip: Option<TripID>) {
     let inner_rect = composite.rect_of("preview").clone();
     let map_bounds = app.primary.map.get_bounds().clone();
     let zoom = 0.15 * g.canvas.window_width / map_bounds.width().max(map_bounds.height());
@@ -472,11 +327,8 @@ pub fn preview_trip(g: &mut GfxCtx, app: &App, composite: &Composite) {
     g.redraw(&app.primary.draw_map.draw_all_unzoomed_intersections);
     g.redraw(&app.primary.draw_map.draw_all_thick_roads);
 
-    if let Some(x) = composite.currently_hovering() {
-        if let Ok(idx) = x.parse::<usize>() {
-            let trip = TripID(idx);
-            preview_route(g, app, trip).draw(g);
-        }
+    if let Some(trip) = trip {
+        preview_route(g, app, trip).draw(g);
     }
 
     g.disable_clipping();
@@ -159,6 +159,13 @@ pub fn make_change_traffic(btn: ScreenRectangle, current: String) -> Box<dyn Sta
                         list.push(Choice::new(name.clone(), name));
                     }
                 }
+                list.push(
+                    Choice::new("generated from land use", "land use".to_string()).tooltip(
+                        "People live in homes, go to work or school, and sometimes run errands \
+                         afterwards. Their destinations and modes are guessed from the map's \
+                         buildings, so this works for any city.",
+                    ),
+                );
                 list.push(
                     Choice::new("random unrealistic trips", "random".to_string()).tooltip(
                         "Lots of trips will start at midnight, but not constantly appear through \
@@ -225,7 +232,7 @@ impl AgentSpawner {
                             TripMode::Drive,
                             TripMode::all()
                                 .into_iter()
-                                .map(|m| Choice::new(m.ongoing_verb(), m))
+                                .map(|m| Choice::new(app.catalog.tr(m.ongoing_verb()), m))
                                 .collect(),
                         ),
                     ]),
@@ -10,7 +10,7 @@ use crate::sandbox::{
     maybe_exit_sandbox, spawn_agents_around, AgentMeter, SandboxControls, SandboxMode,
     SpeedControls, TimePanel,
 };
-use abstutil::Timer;
+use abstutil::{Catalog, Timer};
 use ezgui::{
     hotkey, hotkeys, lctrl, Btn, Color, Composite, EventCtx, GeomBatch, GfxCtx,
     HorizontalAlignment, Key, Line, Outcome, RewriteColor, ScreenPt, Text, TextExt,
@@ -167,24 +167,24 @@ impl Tutorial {
                 Some(ID::Lane(l)) => {
                     if app.primary.map.get_l(l).is_biking() && !tut.inspected_bike_lane {
                         tut.inspected_bike_lane = true;
-                        self.top_center = tut.make_top_center(ctx, &app.cs, false);
+                        self.top_center = tut.make_top_center(ctx, &app.cs, &app.catalog, false);
                     }
                 }
                 Some(ID::Building(_)) => {
                     if !tut.inspected_building {
                         tut.inspected_building = true;
-                        self.top_center = tut.make_top_center(ctx, &app.cs, false);
+                        self.top_center = tut.make_top_center(ctx, &app.cs, &app.catalog, false);
                     }
                 }
                 Some(ID::Intersection(i)) => {
                     let i = app.primary.map.get_i(i);
                     if i.is_stop_sign() && !tut.inspected_stop_sign {
                         tut.inspected_stop_sign = true;
-                        self.top_center = tut.make_top_center(ctx, &app.cs, false);
+                        self.top_center = tut.make_top_center(ctx, &app.cs, &app.catalog, false);
                     }
                     if i.is_border() && !tut.inspected_border {
                         tut.inspected_border = true;
-                        self.top_center = tut.make_top_center(ctx, &app.cs, false);
+                        self.top_center = tut.make_top_center(ctx, &app.cs, &app.catalog, false);
                     }
                 }
                 _ => {}
@@ -210,7 +210,7 @@ impl Tutorial {
             if !tut.was_paused && is_paused {
                 tut.num_pauses += 1;
                 tut.was_paused = true;
-                self.top_center = tut.make_top_center(ctx, &app.cs, false);
+                self.top_center = tut.make_top_center(ctx, &app.cs, &app.catalog, false);
             }
             if tut.num_pauses == 3 {
                 tut.next();
@@ -226,14 +226,14 @@ impl Tutorial {
                 .is_none();
             if !tut.car_parked && is_parked && tut.following_car {
                 tut.car_parked = true;
-                self.top_center = tut.make_top_center(ctx, &app.cs, false);
+                self.top_center = tut.make_top_center(ctx, &app.cs, &app.catalog, false);
             }
 
             if following_car && !tut.following_car {
                 // TODO There's a delay of one event before the checklist updates, because the
                 // info panel opening happens at the end of the event. Not a big deal.
                 tut.following_car = true;
-                self.top_center = tut.make_top_center(ctx, &app.cs, false);
+                self.top_center = tut.make_top_center(ctx, &app.cs, &app.catalog, false);
             }
 
             if tut.prank_done {
@@ -265,48 +265,55 @@ impl Tutorial {
                 }
                 if !tut.score_delivered {
                     tut.score_delivered = true;
+                    let c = &app.catalog;
+                    let times = vec![
+                        ("before", before.to_localized_string(c)),
+                        ("after", after.to_localized_string(c)),
+                    ];
                     if before == after {
                         return Some(Transition::Push(msg(
-                            "All trips completed",
+                            c.tr("All trips completed"),
                             vec![
-                                "Your changes didn't affect anything!",
-                                "Try editing the map to create some bike lanes.",
+                                c.tr("Your changes didn't affect anything!"),
+                                c.tr("Try editing the map to create some bike lanes."),
                             ],
                         )));
                     }
                     if after > before {
                         return Some(Transition::Push(msg(
-                            "All trips completed",
+                            c.tr("All trips completed"),
                             vec![
-                                "Your changes made things worse!".to_string(),
-                                format!(
-                                    "All trips originally finished in {}, but now they took {}",
-                                    before, after
+                                c.tr("Your changes made things worse!"),
+                                c.tr_args(
+                                    "All trips originally finished in {before}, but now they took \
+                                     {after}",
+                                    times,
                                 ),
                                 "".to_string(),
-                                "Try again!".to_string(),
+                                c.tr("Try again!"),
                             ],
                         )));
                     }
                     if before - after < CAR_BIKE_CONTENTION_GOAL {
                         return Some(Transition::Push(msg(
-                            "All trips completed",
+                            c.tr("All trips completed"),
                             vec![
-                                "Nice, you helped things a bit!".to_string(),
-                                format!(
-                                    "All trips originally took {}, but now they took {}",
-                                    before, after
+                                c.tr("Nice, you helped things a bit!"),
+                                c.tr_args(
+                                    "All trips originally took {before}, but now they took {after}",
+                                    times,
                                 ),
                                 "".to_string(),
-                                "See if you can do a little better though.".to_string(),
+                                c.tr("See if you can do a little better though."),
                             ],
                         )));
                     }
                     return Some(Transition::Push(msg(
-                        "All trips completed",
-                        vec![format!(
-                            "Awesome! All trips originally took {}, but now they only took {}",
-                            before, after
+                        c.tr("All trips completed"),
+                        vec![c.tr_args(
+                            "Awesome! All trips originally took {before}, but now they only took \
+                             {after}",
+                            times,
                         )],
                     )));
                 }
@@ -342,6 +349,19 @@ impl GameplayState for Tutorial {
             tut.window_dims = window_dims;
         }
 
+        // Everything with text in it is stale after the language changes.
+        if app.catalog.language != tut.language {
+            tut.stages = TutorialState::new(ctx, app).stages;
+            tut.language = app.catalog.language.clone();
+            self.top_center = tut.make_top_center(
+                ctx,
+                &app.cs,
+                &app.catalog,
+                self.last_finished_task >= Task::WatchBikes,
+            );
+            self.msg_panel = tut.make_msg_panel(ctx, app);
+        }
+
         let result = self.inner_event(ctx, app, controls, &mut tut);
         app.session.tutorial = Some(tut);
         result
@@ -430,12 +450,12 @@ enum Task {
 }
 
 impl Task {
-    fn top_txt(self, state: &TutorialState) -> Text {
+    fn top_txt(self, state: &TutorialState, catalog: &Catalog) -> Text {
         let simple = match self {
             Task::Nil => unreachable!(),
             Task::Camera => "Put out the fire at the fire station",
             Task::InspectObjects => {
-                let mut txt = Text::from(Line("Find one of each:"));
+                let mut txt = Text::from(Line(catalog.tr("Find one of each:")));
                 for (name, done) in vec![
                     ("bike lane", state.inspected_bike_lane),
                     ("building", state.inspected_building),
@@ -443,62 +463,80 @@ impl Task {
                     ("intersection on the map border", state.inspected_border),
                 ] {
                     if done {
-                        txt.add(Line(format!("[X] {}", name)).fg(Color::GREEN));
+                        txt.add(Line(format!("[X] {}", catalog.tr(name))).fg(Color::GREEN));
                     } else {
-                        txt.add(Line(format!("[ ] {}", name)));
+                        txt.add(Line(format!("[ ] {}", catalog.tr(name))));
                     }
                 }
                 return txt;
             }
             Task::TimeControls => "Wait until after 5pm",
             Task::PauseResume => {
-                let mut txt = Text::from(Line("[ ] Pause/resume "));
-                txt.append(Line(format!("{} times", 3 - state.num_pauses)).fg(Color::GREEN));
+                let mut txt = Text::from(Line(format!("[ ] {} ", catalog.tr("Pause/resume"))));
+                txt.append(
+                    Line(catalog.plural("{n} time", "{n} times", 3 - state.num_pauses, Vec::new()))
+                        .fg(Color::GREEN),
+                );
                 return txt;
             }
             Task::Escort => {
                 // Inspect the target car, wait for them to park, draw WASH ME on the window
                 let mut txt = Text::new();
                 if state.following_car {
-                    txt.add(Line("[X] follow the target car").fg(Color::GREEN));
+                    txt.add(
+                        Line(format!("[X] {}", catalog.tr("follow the target car")))
+                            .fg(Color::GREEN),
+                    );
                 } else {
-                    txt.add(Line("[ ] follow the target car"));
+                    txt.add(Line(format!("[ ] {}", catalog.tr("follow the target car"))));
                 }
                 if state.car_parked {
-                    txt.add(Line("[X] wait for them to park").fg(Color::GREEN));
+                    txt.add(
+                        Line(format!("[X] {}", catalog.tr("wait for them to park")))
+                            .fg(Color::GREEN),
+                    );
                 } else {
-                    txt.add(Line("[ ] wait for them to park"));
+                    txt.add(Line(format!("[ ] {}", catalog.tr("wait for them to park"))));
                 }
                 if state.prank_done {
-                    txt.add(Line("[X] click car and press c to draw WASH ME").fg(Color::GREEN));
+                    txt.add(
+                        Line(format!(
+                            "[X] {}",
+                            catalog.tr("click car and press c to draw WASH ME")
+                        ))
+                        .fg(Color::GREEN),
+                    );
                 } else {
-                    txt.add(Line("[ ] click car and press "));
+                    txt.add(Line(format!("[ ] {} ", catalog.tr("click car and press"))));
                     // TODO ctx.style().hotkey_color
                     txt.append(Line(Key::C.describe()).fg(Color::GREEN));
-                    txt.append(Line(" to draw WASH ME"));
+                    txt.append(Line(format!(" {}", catalog.tr("to draw WASH ME"))));
                 }
                 return txt;
             }
             Task::LowParking => {
                 let mut txt = Text::from(Line(
-                    "1) Find a road with almost no parking spots available",
+                    catalog.tr("1) Find a road with almost no parking spots available"),
                 ));
-                txt.add(Line("2) Click it and press "));
+                txt.add(Line(format!("{} ", catalog.tr("2) Click it and press"))));
                 // TODO ctx.style().hotkey_color
                 txt.append(Line(Key::C.describe()).fg(Color::GREEN));
-                txt.append(Line(" to check the occupancy"));
+                txt.append(Line(format!(" {}", catalog.tr("to check the occupancy"))));
                 return txt;
             }
             Task::WatchBikes => "Watch for 3 minutes",
             Task::FixBikes => {
-                return Text::from(Line(format!(
-                    "[ ] Complete all trips {} faster",
-                    CAR_BIKE_CONTENTION_GOAL
+                return Text::from(Line(catalog.tr_args(
+                    "[ ] Complete all trips {goal} faster",
+                    vec![(
+                        "goal",
+                        CAR_BIKE_CONTENTION_GOAL.to_localized_string(catalog),
+                    )],
                 )));
             }
             Task::Done => "Tutorial complete!",
         };
-        Text::from(Line(simple))
+        Text::from(Line(catalog.tr(simple)))
     }
 
     fn label(self) -> &'static str {
@@ -612,6 +650,7 @@ pub struct TutorialState {
     pub current: TutorialPointer,
 
     window_dims: (f64, f64),
+    language: String,
 
     // Goofy state for just some stages.
     inspected_bike_lane: bool,
@@ -718,9 +757,18 @@ impl TutorialState {
         }
     }
 
-    fn make_top_center(&self, ctx: &mut EventCtx, cs: &ColorScheme, edit_map: bool) -> Composite {
+    fn make_top_center(
+        &self,
+        ctx: &mut EventCtx,
+        cs: &ColorScheme,
+        catalog: &Catalog,
+        edit_map: bool,
+    ) -> Composite {
         let mut col = vec![Widget::row(vec![
-            Line("Tutorial").small_heading().draw(ctx).margin(5),
+            Line(catalog.tr("Tutorial"))
+                .small_heading()
+                .draw(ctx)
+                .margin(5),
             Widget::draw_batch(
                 ctx,
                 GeomBatch::from(vec![(Color::WHITE, Polygon::rectangle(2.0, 50.0))]),
@@ -733,7 +781,10 @@ impl TutorialState {
             }
             .margin(5),
             {
-                let mut txt = Text::from(Line(format!("Task {}", self.current.stage + 1)));
+                let mut txt = Text::from(Line(catalog.tr_args(
+                    "Task {n}",
+                    vec![("n", (self.current.stage + 1).to_string())],
+                )));
                 // TODO Smaller font and use alpha for the "/9" part
                 txt.append(Line(format!("/{}", self.stages.len())).fg(Color::grey(0.7)));
                 txt.draw(ctx).margin(5)
@@ -744,7 +795,9 @@ impl TutorialState {
                 Btn::text_fg(">").build(ctx, "next tutorial", None)
             }
             .margin(5),
-            Btn::text_fg("Quit").build_def(ctx, None).margin(5),
+            Btn::text_fg(catalog.tr("Quit"))
+                .build(ctx, "Quit", None)
+                .margin(5),
         ])
         .centered()];
         {
@@ -752,10 +805,12 @@ impl TutorialState {
             if task != Task::Nil {
                 col.push(Widget::row(vec![
                     Text::from(
-                        Line(format!(
-                            "Task {}: {}",
-                            self.current.stage + 1,
-                            self.stage().task.label()
+                        Line(catalog.tr_args(
+                            "Task {n}: {label}",
+                            vec![
+                                ("n", (self.current.stage + 1).to_string()),
+                                ("label", catalog.tr(self.stage().task.label())),
+                            ],
                         ))
                         .small_heading(),
                     )
@@ -768,7 +823,7 @@ impl TutorialState {
                         .centered_vert()
                         .align_right(),
                 ]));
-                col.push(task.top_txt(self).draw(ctx).margin(5));
+                col.push(task.top_txt(self, catalog).draw(ctx).margin(5));
             }
         }
         if edit_map {
@@ -803,87 +858,92 @@ impl TutorialState {
         };
 
         Box::new(Tutorial {
-            top_center: self.make_top_center(ctx, &app.cs, last_finished_task >= Task::WatchBikes),
+            top_center: self.make_top_center(
+                ctx,
+                &app.cs,
+                &app.catalog,
+                last_finished_task >= Task::WatchBikes,
+            ),
             last_finished_task,
+            msg_panel: self.make_msg_panel(ctx, app),
+            warped: false,
+        })
+    }
 
-            msg_panel: if let Some((ref lines, horiz_align, _)) = self.lines() {
-                let mut col = vec![{
-                    let mut txt = Text::new();
-                    txt.add(Line(self.stage().task.label()).small_heading());
-                    txt.add(Line(""));
+    // The message panel for the current part of the stage, if it's not time to try the task yet
+    fn make_msg_panel(&self, ctx: &mut EventCtx, app: &App) -> Option<Composite> {
+        if let Some((ref lines, horiz_align, _)) = self.lines() {
+            let mut col = vec![{
+                let mut txt = Text::new();
+                txt.add(Line(app.catalog.tr(self.stage().task.label())).small_heading());
+                txt.add(Line(""));
 
-                    for l in lines {
-                        txt.add(Line(l));
-                    }
-                    txt.wrap_to_pct(ctx, 30).draw(ctx)
-                }];
-                let mut controls = vec![Widget::row(vec![
-                    if self.current.part > 0 {
-                        Btn::svg(
-                            "../data/system/assets/tools/prev.svg",
-                            RewriteColor::Change(Color::WHITE, app.cs.hovering),
-                        )
-                        .build(
-                            ctx,
-                            "previous message",
-                            hotkey(Key::LeftArrow),
-                        )
-                    } else {
-                        Widget::draw_svg_transform(
-                            ctx,
-                            "../data/system/assets/tools/prev.svg",
-                            RewriteColor::ChangeAll(Color::WHITE.alpha(0.5)),
-                        )
-                    }
+                for l in lines {
+                    txt.add(Line(app.catalog.tr(l)));
+                }
+                txt.wrap_to_pct(ctx, 30).draw(ctx)
+            }];
+            let mut controls = vec![Widget::row(vec![
+                if self.current.part > 0 {
+                    Btn::svg(
+                        "../data/system/assets/tools/prev.svg",
+                        RewriteColor::Change(Color::WHITE, app.cs.hovering),
+                    )
+                    .build(ctx, "previous message", hotkey(Key::LeftArrow))
+                } else {
+                    Widget::draw_svg_transform(
+                        ctx,
+                        "../data/system/assets/tools/prev.svg",
+                        RewriteColor::ChangeAll(Color::WHITE.alpha(0.5)),
+                    )
+                }
+                .margin_right(15),
+                format!("{}/{}", self.current.part + 1, self.stage().messages.len())
+                    .draw_text(ctx)
+                    .centered_vert()
                     .margin_right(15),
-                    format!("{}/{}", self.current.part + 1, self.stage().messages.len())
-                        .draw_text(ctx)
-                        .centered_vert()
-                        .margin_right(15),
-                    if self.current.part == self.stage().messages.len() - 1 {
-                        Widget::draw_svg_transform(
-                            ctx,
-                            "../data/system/assets/tools/next.svg",
-                            RewriteColor::ChangeAll(Color::WHITE.alpha(0.5)),
-                        )
-                        .named("next message")
-                    } else {
-                        Btn::svg(
-                            "../data/system/assets/tools/next.svg",
-                            RewriteColor::Change(Color::WHITE, app.cs.hovering),
-                        )
-                        .build(
-                            ctx,
-                            "next message",
-                            hotkeys(vec![Key::RightArrow, Key::Space, Key::Enter]),
-                        )
-                    },
-                ])];
                 if self.current.part == self.stage().messages.len() - 1 {
-                    controls.push(
-                        Btn::text_bg2("Try it")
-                            .build_def(ctx, hotkeys(vec![Key::RightArrow, Key::Space, Key::Enter]))
-                            .margin_above(10),
-                    );
-                }
-                col.push(Widget::col(controls).align_bottom());
-
-                Some(
-                    Composite::new(
-                        Widget::col(col)
-                            .bg(app.cs.panel_bg)
-                            .outline(5.0, Color::WHITE)
-                            .padding(16),
+                    Widget::draw_svg_transform(
+                        ctx,
+                        "../data/system/assets/tools/next.svg",
+                        RewriteColor::ChangeAll(Color::WHITE.alpha(0.5)),
+                    )
+                    .named("next message")
+                } else {
+                    Btn::svg(
+                        "../data/system/assets/tools/next.svg",
+                        RewriteColor::Change(Color::WHITE, app.cs.hovering),
+                    )
+                    .build(
+                        ctx,
+                        "next message",
+                        hotkeys(vec![Key::RightArrow, Key::Space, Key::Enter]),
                     )
-                    .exact_size_percent(40, 40)
-                    .aligned(*horiz_align, VerticalAlignment::Center)
-                    .build(ctx),
+                },
+            ])];
+            if self.current.part == self.stage().messages.len() - 1 {
+                controls.push(
+                    Btn::text_bg2("Try it")
+                        .build_def(ctx, hotkeys(vec![Key::RightArrow, Key::Space, Key::Enter]))
+                        .margin_above(10),
+                );
+            }
+            col.push(Widget::col(controls).align_bottom());
+
+            Some(
+                Composite::new(
+                    Widget::col(col)
+                        .bg(app.cs.panel_bg)
+                        .outline(5.0, Color::WHITE)
+                        .padding(16),
                 )
-            } else {
-                None
-            },
-            warped: false,
-        })
+                .exact_size_percent(40, 40)
+                .aligned(*horiz_align, VerticalAlignment::Center)
+                .build(ctx),
+            )
+        } else {
+            None
+        }
     }
 
     fn new(ctx: &mut EventCtx, app: &App) -> TutorialState {
@@ -891,6 +951,7 @@ impl TutorialState {
             stages: Vec::new(),
             current: TutorialPointer::new(0, 0),
             window_dims: (ctx.canvas.window_width, ctx.canvas.window_height),
+            language: app.catalog.language.clone(),
 
             inspected_bike_lane: false,
             inspected_building: false,
@@ -1248,7 +1309,7 @@ impl TutorialState {
                 ),
         );
 
-        let top_center = state.make_top_center(ctx, &app.cs, true);
+        let top_center = state.make_top_center(ctx, &app.cs, &app.catalog, true);
         state.stages.push(
             Stage::new(Task::FixBikes)
                 .spawn_scenario(bike_lane_scenario)
@@ -1291,9 +1352,14 @@ impl TutorialState {
                 .msg(
                     // TODO Deliberately vague with the measurement.
                     vec![
-                        format!(
-                            "So adjust lanes and speed up the slowest trip by at least {}.",
-                            CAR_BIKE_CONTENTION_GOAL
+                        // Lines are translated when they're shown, but this one needs the goal
+                        // filled in first
+                        app.catalog.tr_args(
+                            "So adjust lanes and speed up the slowest trip by at least {goal}.",
+                            vec![(
+                                "goal",
+                                CAR_BIKE_CONTENTION_GOAL.to_localized_string(&app.catalog),
+                            )],
                         ),
                         "".to_string(),
                         "You can explore results as trips finish. When everyone's finished, \
@@ -1344,6 +1410,9 @@ pub fn actions(app: &App, id: ID) -> Vec<(Key, String)> {
 
 pub fn execute(_: &mut EventCtx, app: &mut App, id: ID, action: String) -> Transition {
     let mut tut = app.session.tutorial.as_mut().unwrap();
+    let catalog = &app.catalog;
+    let tr =
+        |lines: Vec<&str>| -> Vec<String> { lines.into_iter().map(|l| catalog.tr(l)).collect() };
     let response = match (id, action.as_ref()) {
         (ID::Car(c), "draw WASH ME") => {
             let is_parked = app
@@ -1355,37 +1424,37 @@ pub fn execute(_: &mut EventCtx, app: &mut App, id: ID, action: String) -> Trans
                 if is_parked {
                     tut.prank_done = true;
                     msg(
-                        "Prank in progress",
-                        vec!["You quickly scribble on the window..."],
+                        catalog.tr("Prank in progress"),
+                        tr(vec!["You quickly scribble on the window..."]),
                     )
                 } else {
                     msg(
-                        "Not yet!",
-                        vec![
+                        catalog.tr("Not yet!"),
+                        tr(vec![
                             "You're going to run up to an occupied car and draw on their windows?",
                             "Sounds like we should be friends.",
                             "But, er, wait for the car to park. (You can speed up time!)",
-                        ],
+                        ]),
                     )
                 }
             } else if c.1 == VehicleType::Bike {
                 msg(
-                    "That's a bike",
-                    vec![
+                    catalog.tr("That's a bike"),
+                    tr(vec![
                         "Achievement unlocked: You attempted to draw WASH ME on a cyclist.",
                         "This game is PG-13 or something, so I can't really describe what happens \
                          next.",
                         "But uh, don't try this at home.",
-                    ],
+                    ]),
                 )
             } else {
                 msg(
-                    "Wrong car",
-                    vec![
+                    catalog.tr("Wrong car"),
+                    tr(vec![
                         "You're looking at the wrong car.",
                         "Use the 'reset to midnight' (key binding 'X') to start over, if you lost \
                          the car to follow.",
-                    ],
+                    ]),
                 )
             }
         }
@@ -1396,19 +1465,29 @@ pub fn execute(_: &mut EventCtx, app: &mut App, id: ID, action: String) -> Trans
                     / (lane.number_parking_spots() as f64);
                 if percent > 0.1 {
                     msg(
-                        "Not quite",
+                        catalog.tr("Not quite"),
                         vec![
-                            format!("This lane has {:.0}% spots free", percent * 100.0),
-                            "Try using the 'parking occupancy' layer from the minimap controls"
-                                .to_string(),
+                            catalog.tr_args(
+                                "This lane has {percent}% spots free",
+                                vec![("percent", format!("{:.0}", percent * 100.0))],
+                            ),
+                            catalog.tr(
+                                "Try using the 'parking occupancy' layer from the minimap controls",
+                            ),
                         ],
                     )
                 } else {
                     tut.parking_found = true;
-                    msg("Noice", vec!["Yup, parallel parking would be tough here!"])
+                    msg(
+                        catalog.tr("Noice"),
+                        tr(vec!["Yup, parallel parking would be tough here!"]),
+                    )
                 }
             } else {
-                msg("Uhh..", vec!["That's not even a parking lane"])
+                msg(
+                    catalog.tr("Uhh.."),
+                    tr(vec!["That's not even a parking lane"]),
+                )
             }
         }
         _ => unreachable!(),
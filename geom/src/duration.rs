@@ -119,6 +119,53 @@ impl Duration {
         }
     }
 
+    // Like Display, but with the units and decimal separator from the catalog
+    pub fn to_localized_string(self, catalog: &abstutil::Catalog) -> String {
+        self.describe(
+            &catalog.decimal_separator,
+            |n| catalog.number(n),
+            |unit, n| {
+                let msg = match unit {
+                    'h' => "{n}h",
+                    'm' => "{n}m",
+                    _ => "{n}s",
+                };
+                catalog.tr_args(msg, vec![("n", n)])
+            },
+        )
+    }
+
+    // Shared by Display and to_localized_string. unit gets 'h', 'm', or 's' and the number to
+    // put in front of it; hours are large enough to need their own formatting.
+    fn describe<H: Fn(usize) -> String, U: Fn(char, String) -> String>(
+        self,
+        decimal_separator: &str,
+        hours: H,
+        unit: U,
+    ) -> String {
+        if self == Duration::ZERO {
+            return unit('s', "0".to_string());
+        }
+        let mut s = String::new();
+        if self < Duration::ZERO {
+            s.push('-');
+        }
+        let (h, minutes, seconds, remainder) = self.get_parts();
+        if h != 0 {
+            s.push_str(&unit('h', hours(h)));
+        }
+        if h != 0 || minutes != 0 {
+            s.push_str(&unit('m', minutes.to_string()));
+        }
+        if remainder != 0 {
+            let n = format!("{}{}{:01}", seconds, decimal_separator, remainder);
+            s.push_str(&unit('s', n));
+        } else if seconds != 0 {
+            s.push_str(&unit('s', seconds.to_string()));
+        }
+        s
+    }
+
     // If two durations are within this amount, they'll print as if they're the same.
     pub fn epsilon_eq(self, other: Duration) -> bool {
         let eps = Duration::seconds(0.1);
@@ -191,26 +238,11 @@ impl Duration {
 
 impl std::fmt::Display for Duration {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        if *self == Duration::ZERO {
-            write!(f, "0s")?;
-            return Ok(());
-        }
-        if *self < Duration::ZERO {
-            write!(f, "-")?;
-        }
-        let (hours, minutes, seconds, remainder) = self.get_parts();
-        if hours != 0 {
-            write!(f, "{}h", hours)?;
-        }
-        if hours != 0 || minutes != 0 {
-            write!(f, "{}m", minutes)?;
-        }
-        if remainder != 0 {
-            write!(f, "{}.{:01}s", seconds, remainder)?;
-        } else if seconds != 0 {
-            write!(f, "{}s", seconds)?;
-        }
-        Ok(())
+        write!(
+            f,
+            "{}",
+            self.describe(".", |n| n.to_string(), |unit, n| format!("{}{}", n, unit))
+        )
     }
 }
 
@@ -310,3 +342,28 @@ impl std::iter::Sum for Duration {
         sum
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use abstutil::Catalog;
+
+    #[test]
+    fn localized_matches_display() {
+        let english = Catalog::english();
+        let mut comma = Catalog::english();
+        comma.decimal_separator = ",".to_string();
+
+        for (d, expected) in vec![
+            (Duration::ZERO, "0s"),
+            (Duration::seconds(5.0), "5s"),
+            (Duration::seconds(-90.0), "-1m30s"),
+            (Duration::seconds(3723.4), "1h2m3.4s"),
+            (Duration::hours(2), "2h0m"),
+        ] {
+            assert_eq!(d.to_string(), expected);
+            assert_eq!(d.to_localized_string(&english), expected);
+            assert_eq!(d.to_localized_string(&comma), expected.replace('.', ","));
+        }
+    }
+}
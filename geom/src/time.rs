@@ -103,6 +103,28 @@ impl Time {
         )
     }
 
+    // Like ampm_tostring, but following the conventions of the catalog's language
+    pub fn to_localized_string(self, catalog: &abstutil::Catalog) -> String {
+        if !catalog.clock_24h {
+            return self.ampm_tostring();
+        }
+        let (mut hours, minutes, seconds, remainder) = self.get_parts();
+        let next_day = if hours >= 24 {
+            let days = hours / 24;
+            hours = hours % 24;
+            format!(
+                " {}",
+                catalog.plural("(+{n} day)", "(+{n} days)", days, Vec::new())
+            )
+        } else {
+            "".to_string()
+        };
+        format!(
+            "{:02}:{:02}:{:02}{}{:01}{}",
+            hours, minutes, seconds, catalog.decimal_separator, remainder, next_day
+        )
+    }
+
     pub fn as_filename(self) -> String {
         let (hours, minutes, seconds, remainder) = self.get_parts();
         format!(
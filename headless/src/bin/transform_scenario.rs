@@ -0,0 +1,140 @@
+use abstutil::{CmdArgs, Timer};
+use geom::{Duration, LonLat};
+use map_model::Map;
+use rand::SeedableRng;
+use rand_xorshift::XorShiftRng;
+use sim::{Scenario, ScenarioModifier, TripMode};
+
+// Builds a what-if scenario from an existing one. Modifiers are applied in the order they're given
+// on the command line.
+fn main() {
+    let mut args = CmdArgs::new();
+    let map_name = args.required("--map");
+    let input = args.required("--input");
+    let output = args.required("--output");
+    let rng_seed = args
+        .optional_parse("--rng_seed", |s| s.parse::<u8>())
+        .unwrap_or(42);
+
+    let mut modifiers = Vec::new();
+    // Another scenario's name
+    if let Some(name) = args.optional("--merge") {
+        modifiers.push((position("--merge"), ScenarioModifier::Merge(name)));
+    }
+    // An Osmosis .poly file
+    if let Some(path) = args.optional("--only_touching") {
+        modifiers.push((
+            position("--only_touching"),
+            ScenarioModifier::OnlyTouching(LonLat::read_osmosis_polygon(path).unwrap()),
+        ));
+    }
+    // Like 30,drive,bike, meaning 30% of people who drive will bike instead
+    if let Some(x) = args.optional("--change_mode") {
+        modifiers.push((
+            position("--change_mode"),
+            parse_or_quit("--change_mode", x, parse_change_mode),
+        ));
+    }
+    if let Some(x) = args.optional("--scale") {
+        modifiers.push((
+            position("--scale"),
+            ScenarioModifier::ScaleDemand(parse_or_quit("--scale", x, parse_scale)),
+        ));
+    }
+    // Like 1:00:00 or -0:30:00
+    if let Some(by) = args.optional_parse("--shift", parse_signed_duration) {
+        modifiers.push((position("--shift"), ScenarioModifier::ShiftDepartures(by)));
+    }
+    if let Some(n) = args.optional_parse("--repeat_days", |s| s.parse::<usize>()) {
+        modifiers.push((position("--repeat_days"), ScenarioModifier::RepeatDays(n)));
+    }
+    args.done();
+    if modifiers.is_empty() {
+        println!(
+            "Nothing to do! Pass some combination of --merge, --only_touching, --change_mode, \
+             --scale, --shift or --repeat_days"
+        );
+        std::process::exit(1);
+    }
+
+    let mut timer = Timer::new("transform scenario");
+    let map = Map::new(abstutil::path_map(&map_name), &mut timer);
+    let mut scenario: Scenario =
+        abstutil::read_binary(abstutil::path_scenario(&map_name, &input), &mut timer);
+    let mut rng = XorShiftRng::from_seed([rng_seed; 16]);
+    modifiers.sort_by_key(|(pos, _)| *pos);
+    for (_, m) in modifiers {
+        timer.start(m.describe());
+        scenario = m.apply(scenario, &map, &mut rng, &mut timer);
+        timer.stop(m.describe());
+    }
+    scenario.scenario_name = output;
+    scenario.save();
+    timer.done();
+    println!(
+        "{} has been created, with {} people",
+        abstutil::path_scenario(&map_name, &scenario.scenario_name),
+        abstutil::prettyprint_usize(scenario.people.len())
+    );
+}
+
+// Where a flag appears on the command line. CmdArgs doesn't remember the order.
+fn position(flag: &str) -> usize {
+    let prefix = format!("{}=", flag);
+    std::env::args()
+        .position(|arg| arg.starts_with(&prefix))
+        .unwrap()
+}
+
+// Like CmdArgs::optional_parse, but explains what's wrong with the value
+fn parse_or_quit<T, F: Fn(&str) -> Result<T, String>>(key: &str, value: String, parser: F) -> T {
+    match parser(&value) {
+        Ok(x) => x,
+        Err(err) => {
+            println!("Bad argument {}={}: {}", key, value, err);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn parse_scale(x: &str) -> Result<f64, String> {
+    let factor = x.parse::<f64>().map_err(|err| err.to_string())?;
+    if !factor.is_finite() || factor < 0.0 {
+        return Err("the factor must be a number, 0 or more".to_string());
+    }
+    Ok(factor)
+}
+
+fn parse_change_mode(x: &str) -> Result<ScenarioModifier, String> {
+    let parts: Vec<&str> = x.split(',').collect();
+    if parts.len() != 3 {
+        return Err(format!("{} isn't pct,from,to", x));
+    }
+    let pct = parts[0].parse::<usize>().map_err(|err| err.to_string())?;
+    if pct > 100 {
+        return Err(format!("{} isn't a percent from 0 to 100", parts[0]));
+    }
+    Ok(ScenarioModifier::ChangeMode {
+        pct,
+        from: parse_mode(parts[1])?,
+        to: parse_mode(parts[2])?,
+    })
+}
+
+fn parse_mode(x: &str) -> Result<TripMode, String> {
+    match x {
+        "walk" => Ok(TripMode::Walk),
+        "bike" => Ok(TripMode::Bike),
+        "transit" => Ok(TripMode::Transit),
+        "drive" => Ok(TripMode::Drive),
+        _ => Err(format!("unknown mode {}", x)),
+    }
+}
+
+fn parse_signed_duration(x: &str) -> Result<Duration, abstutil::Error> {
+    if x.starts_with('-') {
+        Ok(Duration::ZERO - Duration::parse(&x[1..])?)
+    } else {
+        Duration::parse(x)
+    }
+}
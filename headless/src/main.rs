@@ -2,7 +2,7 @@ use abstutil::{prettyprint_usize, CmdArgs, Timer};
 use map_model::Map;
 use rand::SeedableRng;
 use rand_xorshift::XorShiftRng;
-use sim::{AlertHandler, Scenario, Sim, SimFlags};
+use sim::{AlertHandler, Interventions, Scenario, Sim, SimFlags};
 
 // This is specialized to experiment with running the pandemic model over long time periods.
 // Original functionality for profiling and debugging gridlock have been removed.
@@ -12,10 +12,12 @@ fn main() {
     let num_days = args
         .optional_parse("--days", |s| s.parse::<usize>())
         .unwrap_or(1);
+    let interventions = Interventions::from_args(&mut args);
     args.done();
 
     let mut sim_flags = SimFlags::synthetic_test("montlake", "pandemic");
     sim_flags.opts.enable_pandemic_model = Some(XorShiftRng::from_seed([sim_flags.rng_seed; 16]));
+    sim_flags.opts.pandemic_interventions = interventions;
     // Less spam
     sim_flags.opts.alerts = AlertHandler::Silence;
     let mut timer = Timer::new("setup headless");
@@ -55,4 +57,33 @@ fn run_experiment(map: &Map, sim: &mut Sim) {
     );
     timer.done();
     println!("Done at {}", sim.time());
+
+    let analytics = sim.get_analytics();
+    for (t, sane, exposed, infectious, recovered, dead) in &analytics.pandemic_counts {
+        println!(
+            "At {}: {} sane, {} exposed, {} infected, {} recovered, {} dead",
+            t,
+            prettyprint_usize(*sane),
+            prettyprint_usize(*exposed),
+            prettyprint_usize(*infectious),
+            prettyprint_usize(*recovered),
+            prettyprint_usize(*dead)
+        );
+    }
+    for (day, cnt) in analytics
+        .pandemic_exposures_per_day(sim.time())
+        .into_iter()
+        .enumerate()
+    {
+        let per_space: Vec<String> = cnt
+            .consume()
+            .into_iter()
+            .map(|(space, n)| format!("{} in {:?}", prettyprint_usize(n), space))
+            .collect();
+        println!("Day {} exposures: {}", day + 1, per_space.join(", "));
+    }
+    println!(
+        "{} trips cancelled by interventions",
+        prettyprint_usize(analytics.cancelled_trips.len())
+    );
 }
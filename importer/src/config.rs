@@ -0,0 +1,96 @@
+use crate::utils::download;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Everything needed to import the maps for one city, read from data/input/$city/config.json.
+// Onboarding a new city should just mean writing one of these and some clipping polygons.
+//
+// All paths are relative to the importer/ directory, like everything else here.
+#[derive(Serialize, Deserialize)]
+pub struct CityConfig {
+    // A .osm or .osm.pbf file covering every map in the city. Each map is clipped out of this.
+    pub osm_input: String,
+    // A directory with one Osmosis .poly file per map. The map is named after the file.
+    pub clipping_polygons: String,
+    pub drive_on_right: bool,
+
+    // A directory with an unzipped GTFS feed
+    #[serde(default)]
+    pub gtfs: Option<String>,
+    // An SRTM .hgt file
+    #[serde(default)]
+    pub elevation: Option<String>,
+    // The rest are ExtraShapes .bin files, usually produced from KML by download().
+    #[serde(default)]
+    pub parking_shapes: Option<String>,
+    #[serde(default)]
+    pub public_offstreet_parking: Option<String>,
+    #[serde(default)]
+    pub sidewalks: Option<String>,
+
+    // If a building doesn't have anything from public_offstreet_parking, how many private spots
+    // should it have?
+    pub private_parking_per_bldg: usize,
+    // Per map name, overrides private_parking_per_bldg.
+    #[serde(default)]
+    pub private_parking_per_bldg_overrides: BTreeMap<String, usize>,
+
+    // Local path to URL. Anything missing is fetched before importing; if all of the files already
+    // exist, the import runs entirely from local files. Can be empty.
+    #[serde(default)]
+    pub downloads: BTreeMap<String, String>,
+}
+
+impl CityConfig {
+    pub fn load(city: &str) -> CityConfig {
+        abstutil::read_json(
+            format!("../data/input/{}/config.json", city),
+            &mut abstutil::Timer::throwaway(),
+        )
+    }
+
+    // The names of all maps in this city, one per clipping polygon.
+    pub fn all_maps(&self) -> Vec<String> {
+        abstutil::list_all_objects(self.clipping_polygons.clone())
+    }
+
+    fn clipping_polygon(&self, name: &str) -> String {
+        format!("{}/{}.poly", self.clipping_polygons, name)
+    }
+}
+
+// Fetches anything missing, then converts OSM and the other input to a RawMap.
+pub fn osm_to_raw(city: &str, config: &CityConfig, name: &str) {
+    for (output, url) in &config.downloads {
+        download(output, url);
+    }
+
+    println!("- Running convert_osm");
+    let map = convert_osm::convert(
+        convert_osm::Options {
+            // The clipping polygon is applied while reading this
+            osm_input: config.osm_input.clone(),
+            city_name: city.to_string(),
+            name: name.to_string(),
+
+            parking_shapes: config.parking_shapes.clone(),
+            public_offstreet_parking: config.public_offstreet_parking.clone(),
+            private_offstreet_parking: convert_osm::PrivateOffstreetParking::FixedPerBldg(
+                config
+                    .private_parking_per_bldg_overrides
+                    .get(name)
+                    .cloned()
+                    .unwrap_or(config.private_parking_per_bldg),
+            ),
+            sidewalks: config.sidewalks.clone(),
+            gtfs: config.gtfs.clone(),
+            elevation: config.elevation.clone(),
+            clip: Some(config.clipping_polygon(name)),
+            drive_on_right: config.drive_on_right,
+        },
+        &mut abstutil::Timer::throwaway(),
+    );
+    let output = format!("../data/input/raw_maps/{}.bin", name);
+    println!("- Saving {}", output);
+    abstutil::write_binary(output, &map);
+}
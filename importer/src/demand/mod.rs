@@ -0,0 +1,162 @@
+// Builds scenarios from generic travel demand data, for cities without a Soundcast-style model.
+// Two inputs are supported: zone-to-zone OD matrices and trip tables with GPS endpoints.
+
+mod od;
+mod trip_table;
+
+pub use self::od::import_od_matrix;
+pub use self::trip_table::import_trip_table;
+
+use crate::utils::{nearest_border, Borders};
+use geom::Time;
+use map_model::BuildingID;
+use sim::{OffMapLocation, TripEndpoint, TripMode};
+
+// Where one end of a trip happens, before it's matched to something in the map
+#[derive(Clone)]
+enum Endpoint {
+    Bldg(BuildingID),
+    OffMap(OffMapLocation),
+}
+
+// None if neither end is on the map, if both ends are the same building, or if no border is usable
+// by the mode.
+fn endpoints(
+    borders: &Borders,
+    from: &Endpoint,
+    to: &Endpoint,
+    mode: TripMode,
+) -> Option<(TripEndpoint, TripEndpoint)> {
+    match (from, to) {
+        (Endpoint::Bldg(b1), Endpoint::Bldg(b2)) => {
+            if b1 == b2 {
+                None
+            } else {
+                Some((TripEndpoint::Bldg(*b1), TripEndpoint::Bldg(*b2)))
+            }
+        }
+        (Endpoint::Bldg(b), Endpoint::OffMap(loc)) => {
+            let i = nearest_border(&borders.outgoing[&mode], loc.gps)?;
+            Some((
+                TripEndpoint::Bldg(*b),
+                TripEndpoint::Border(i, Some(loc.clone())),
+            ))
+        }
+        (Endpoint::OffMap(loc), Endpoint::Bldg(b)) => {
+            let i = nearest_border(&borders.incoming[&mode], loc.gps)?;
+            Some((
+                TripEndpoint::Border(i, Some(loc.clone())),
+                TripEndpoint::Bldg(*b),
+            ))
+        }
+        // TODO Detect and handle pass-through trips
+        (Endpoint::OffMap(_), Endpoint::OffMap(_)) => None,
+    }
+}
+
+fn parse_time(time: &str, csv_path: &str, row: usize) -> Result<Time, String> {
+    Time::parse(time)
+        .map_err(|err| format!("{} line {}: bad time {:?}: {}", csv_path, row, time, err))
+}
+
+// Accepts the names most travel models use.
+fn parse_mode(mode: &str) -> Option<TripMode> {
+    match mode.trim().to_lowercase().as_str() {
+        "walk" | "walking" | "foot" => Some(TripMode::Walk),
+        "bike" | "biking" | "bicycle" | "cycling" => Some(TripMode::Bike),
+        "transit" | "bus" | "pt" | "public_transport" => Some(TripMode::Transit),
+        "drive" | "driving" | "car" | "sov" | "hov" => Some(TripMode::Drive),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::{Duration, LonLat};
+    use map_model::IntersectionID;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn modes() {
+        assert_eq!(parse_mode("Walk"), Some(TripMode::Walk));
+        assert_eq!(parse_mode(" bicycle "), Some(TripMode::Bike));
+        assert_eq!(parse_mode("PT"), Some(TripMode::Transit));
+        assert_eq!(parse_mode("hov"), Some(TripMode::Drive));
+        assert_eq!(parse_mode("teleport"), None);
+        assert_eq!(parse_mode(""), None);
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(
+            parse_time("7:30:00", "trips.csv", 2),
+            Ok(Time::START_OF_DAY + Duration::minutes(7 * 60 + 30))
+        );
+        let err = parse_time("half past seven", "trips.csv", 5).unwrap_err();
+        assert!(err.starts_with("trips.csv line 5: bad time \"half past seven\""));
+    }
+
+    #[test]
+    fn endpoint_matching() {
+        // Cars can enter in the west and leave in the east. Nobody else can use any border.
+        let west = (IntersectionID(0), LonLat::new(-1.0, 0.0));
+        let east = (IntersectionID(1), LonLat::new(1.0, 0.0));
+        let mut incoming = BTreeMap::new();
+        let mut outgoing = BTreeMap::new();
+        for mode in TripMode::all() {
+            incoming.insert(mode, Vec::new());
+            outgoing.insert(mode, Vec::new());
+        }
+        incoming.insert(TripMode::Drive, vec![west, east]);
+        outgoing.insert(TripMode::Drive, vec![west, east]);
+        let borders = Borders { incoming, outgoing };
+
+        let home = Endpoint::Bldg(BuildingID(0));
+        let work = Endpoint::Bldg(BuildingID(1));
+        let off_map = |x| {
+            Endpoint::OffMap(OffMapLocation {
+                parcel_id: 0,
+                gps: LonLat::new(x, 0.0),
+            })
+        };
+
+        assert_eq!(
+            endpoints(&borders, &home, &work, TripMode::Walk),
+            Some((
+                TripEndpoint::Bldg(BuildingID(0)),
+                TripEndpoint::Bldg(BuildingID(1))
+            ))
+        );
+        assert_eq!(endpoints(&borders, &home, &home, TripMode::Drive), None);
+        assert_eq!(
+            endpoints(&borders, &home, &off_map(5.0), TripMode::Drive).map(|(_, to)| to),
+            Some(TripEndpoint::Border(
+                IntersectionID(1),
+                Some(OffMapLocation {
+                    parcel_id: 0,
+                    gps: LonLat::new(5.0, 0.0)
+                })
+            ))
+        );
+        assert_eq!(
+            endpoints(&borders, &off_map(-5.0), &work, TripMode::Drive).map(|(from, _)| from),
+            Some(TripEndpoint::Border(
+                IntersectionID(0),
+                Some(OffMapLocation {
+                    parcel_id: 0,
+                    gps: LonLat::new(-5.0, 0.0)
+                })
+            ))
+        );
+        // No border works for biking
+        assert_eq!(
+            endpoints(&borders, &home, &off_map(5.0), TripMode::Bike),
+            None
+        );
+        assert_eq!(
+            endpoints(&borders, &off_map(-5.0), &off_map(5.0), TripMode::Drive),
+            None
+        );
+    }
+}
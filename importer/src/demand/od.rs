@@ -0,0 +1,273 @@
+use crate::demand::{endpoints, parse_mode, parse_time, Endpoint};
+use crate::utils::Borders;
+use abstutil::{prettyprint_usize, FileWithProgress, Timer};
+use geom::{Duration, LonLat, Polygon, Pt2D, Time};
+use map_model::{BuildingID, Map};
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use serde::Deserialize;
+use sim::{IndividTrip, OffMapLocation, PersonID, PersonSpec, Scenario, SpawnTrip};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+// One row of the OD matrix. depart_from and depart_to are optional; by default, trips are spread
+// over the whole day.
+#[derive(Debug, Deserialize)]
+struct RawOD {
+    origin: String,
+    destination: String,
+    mode: String,
+    // Can be fractional; the remainder becomes one more trip with that probability.
+    trips: f64,
+    #[serde(default)]
+    depart_from: Option<String>,
+    #[serde(default)]
+    depart_to: Option<String>,
+}
+
+struct Zone {
+    name: String,
+    // Buildings whose center is in the zone, weighted by area
+    bldgs: Vec<(BuildingID, f64)>,
+    // Used for zones without any buildings in the map
+    off_map: OffMapLocation,
+}
+
+// Each trip in the matrix becomes one person making one trip. Trips between zones are
+// disaggregated to random buildings in each zone. Zones that don't cover any buildings in the map
+// are treated as off-map, and trips to or from them use the nearest border. Fails on malformed
+// rows.
+pub fn import_od_matrix(
+    csv_path: &str,
+    zones_path: &str,
+    scenario_name: &str,
+    map: &Map,
+    timer: &mut Timer,
+) -> Result<Scenario, String> {
+    let zones = read_zones(zones_path, map)?;
+    timer.note(format!(
+        "{} zones, {} of them off-map",
+        prettyprint_usize(zones.len()),
+        prettyprint_usize(zones.iter().filter(|z| z.bldgs.is_empty()).count())
+    ));
+    let borders = Borders::new(map);
+    let mut rng = XorShiftRng::from_seed([42; 16]);
+
+    let mut people = Vec::new();
+    let mut skipped_trips = 0;
+    let (reader, done) = FileWithProgress::new(csv_path).unwrap();
+    for (idx, rec) in csv::Reader::from_reader(reader).deserialize().enumerate() {
+        // The header is the first line
+        let row = idx + 2;
+        let rec: RawOD = rec.map_err(|err| format!("{} line {}: {}", csv_path, row, err))?;
+        let from = zones.iter().find(|z| z.name == rec.origin);
+        let to = zones.iter().find(|z| z.name == rec.destination);
+        let mode = parse_mode(&rec.mode);
+        let (from, to, mode) = match (from, to, mode) {
+            (Some(from), Some(to), Some(mode)) => (from, to, mode),
+            _ => {
+                timer.warn(format!("Skipping unknown zone or mode in {:?}", rec));
+                continue;
+            }
+        };
+        if !rec.trips.is_finite() {
+            return Err(format!(
+                "{} line {}: trips must be a number, not {}",
+                csv_path, row, rec.trips
+            ));
+        }
+        if rec.trips < 0.0 {
+            timer.warn(format!("Skipping negative number of trips in {:?}", rec));
+            continue;
+        }
+        let depart_from = match rec.depart_from {
+            Some(ref t) => parse_time(t, csv_path, row)?,
+            None => Time::START_OF_DAY,
+        };
+        let depart_to = match rec.depart_to {
+            Some(ref t) => parse_time(t, csv_path, row)?,
+            None => Time::START_OF_DAY + Duration::hours(24),
+        };
+
+        let mut num_trips = rec.trips.floor() as usize;
+        if rng.gen_bool(rec.trips.fract()) {
+            num_trips += 1;
+        }
+        for _ in 0..num_trips {
+            let endpts = endpoints(
+                &borders,
+                &pick_endpoint(from, &mut rng),
+                &pick_endpoint(to, &mut rng),
+                mode,
+            );
+            if let Some((start, end)) = endpts {
+                let depart = if depart_to > depart_from {
+                    depart_from + (depart_to - depart_from) * rng.gen_range(0.0, 1.0)
+                } else {
+                    depart_from
+                };
+                people.push(PersonSpec {
+                    id: PersonID(people.len()),
+                    orig_id: None,
+                    trips: vec![IndividTrip {
+                        depart,
+                        trip: SpawnTrip::new(start, end, mode, map),
+                    }],
+                });
+            } else {
+                skipped_trips += 1;
+            }
+        }
+    }
+    done(timer);
+    timer.note(format!(
+        "{} trips, skipped {} that don't start or end in the map",
+        prettyprint_usize(people.len()),
+        prettyprint_usize(skipped_trips)
+    ));
+
+    let mut scenario = Scenario::empty(map, scenario_name);
+    scenario.people = people;
+    // People might take any bus
+    scenario.only_seed_buses = None;
+    Ok(scenario)
+}
+
+fn pick_endpoint(zone: &Zone, rng: &mut XorShiftRng) -> Endpoint {
+    if let Ok((b, _)) = zone.bldgs.choose_weighted(rng, |(_, area)| *area) {
+        Endpoint::Bldg(*b)
+    } else {
+        Endpoint::OffMap(zone.off_map.clone())
+    }
+}
+
+fn read_zones(path: &str, map: &Map) -> Result<Vec<Zone>, String> {
+    let mut zones = Vec::new();
+    for (idx, (name, pts)) in read_osmosis_polygons(path)?.into_iter().enumerate() {
+        let polygon = Polygon::new(
+            &pts.iter()
+                .map(|gps| Pt2D::forcibly_from_gps(*gps, map.get_gps_bounds()))
+                .collect(),
+        );
+        let bldgs = map
+            .all_buildings()
+            .iter()
+            .filter(|b| polygon.contains_pt(b.polygon.center()))
+            .map(|b| (b.id, b.polygon.area()))
+            .collect();
+        let centroid = LonLat::new(
+            pts.iter().map(|pt| pt.x()).sum::<f64>() / (pts.len() as f64),
+            pts.iter().map(|pt| pt.y()).sum::<f64>() / (pts.len() as f64),
+        );
+        zones.push(Zone {
+            name,
+            bldgs,
+            off_map: OffMapLocation {
+                parcel_id: idx,
+                gps: centroid,
+            },
+        });
+    }
+    Ok(zones)
+}
+
+// Like LonLat::read_osmosis_polygon, but the file can have many sections. Each section is one
+// zone, named by the line before its points. Fails on malformed files.
+fn read_osmosis_polygons(path: &str) -> Result<Vec<(String, Vec<LonLat>)>, String> {
+    let f = File::open(path).map_err(|err| format!("{}: {}", path, err))?;
+    let mut lines = BufReader::new(f).lines();
+    // The first line names the whole file
+    lines.next();
+
+    let mut zones = Vec::new();
+    while let Some(name) = lines.next() {
+        let name = name.map_err(|err| format!("{}: {}", path, err))?;
+        let name = name.trim().to_string();
+        if name == "END" {
+            break;
+        }
+        let mut pts = Vec::new();
+        for line in &mut lines {
+            let line = line.map_err(|err| format!("{}: {}", path, err))?;
+            let line = line.trim();
+            if line == "END" {
+                break;
+            }
+            let bad_line = || format!("{}: bad line in zone {}: {}", path, name, line);
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() != 2 {
+                return Err(bad_line());
+            }
+            pts.push(LonLat::new(
+                parts[0].parse::<f64>().map_err(|_| bad_line())?,
+                parts[1].parse::<f64>().map_err(|_| bad_line())?,
+            ));
+        }
+        if pts.len() < 3 {
+            return Err(format!("{}: zone {} has fewer than 3 points", path, name));
+        }
+        zones.push((name, pts));
+    }
+    Ok(zones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn osmosis_zones() {
+        let path = std::env::temp_dir().join("importer_test_zones.poly");
+        std::fs::write(
+            &path,
+            "zones\n\
+             downtown\n\
+             \t-122.3 47.6\n\
+             \t-122.2 47.6\n\
+             \t-122.2 47.7\n\
+             END\n\
+             suburbs \n\
+             -122.1 47.5\n\
+             -122.0 47.5\n\
+             -122.0 47.6\n\
+             -122.1 47.6\n\
+             END\n\
+             END\n",
+        )
+        .unwrap();
+        let zones = read_osmosis_polygons(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(zones.len(), 2);
+        assert_eq!(zones[0].0, "downtown");
+        assert_eq!(
+            zones[0].1,
+            vec![
+                LonLat::new(-122.3, 47.6),
+                LonLat::new(-122.2, 47.6),
+                LonLat::new(-122.2, 47.7)
+            ]
+        );
+        assert_eq!(zones[1].0, "suburbs");
+        assert_eq!(zones[1].1.len(), 4);
+    }
+
+    #[test]
+    fn bad_osmosis_zones() {
+        let path = std::env::temp_dir().join("importer_test_bad_zones.poly");
+        std::fs::write(&path, "zones\ntiny\n0.0 0.0\n1.0 1.0\nEND\nEND\n").unwrap();
+        let too_small = read_osmosis_polygons(path.to_str().unwrap());
+        std::fs::write(&path, "zones\nweird\n0.0 zero\nEND\nEND\n").unwrap();
+        let bad_number = read_osmosis_polygons(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(too_small
+            .unwrap_err()
+            .ends_with("zone tiny has fewer than 3 points"));
+        assert!(bad_number
+            .unwrap_err()
+            .ends_with("bad line in zone weird: 0.0 zero"));
+        assert!(read_osmosis_polygons("/does/not/exist.poly").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::demand::{endpoints, parse_mode, parse_time, Endpoint};
+use crate::utils::Borders;
+use abstutil::{prettyprint_usize, FileWithProgress, Timer};
+use geom::{Distance, FindClosest, LonLat, Pt2D, Time};
+use map_model::{BuildingID, Map};
+use serde::Deserialize;
+use sim::{IndividTrip, OffMapLocation, PersonID, PersonSpec, Scenario, SpawnTrip, TripMode};
+use std::collections::BTreeMap;
+
+// Endpoints inside the map snap to the closest building within this distance.
+const MAX_DIST_TO_BLDG: Distance = Distance::const_meters(100.0);
+
+#[derive(Debug, Deserialize)]
+struct RawTrip {
+    // Any string; trips with the same person are chained together.
+    person: String,
+    // HH:MM:SS
+    depart: String,
+    origin_lon: f64,
+    origin_lat: f64,
+    destination_lon: f64,
+    destination_lat: f64,
+    mode: String,
+}
+
+// Each row of the CSV is one trip. A person's trips are ordered by departure time, and people whose
+// trips don't form a sensible chain are removed. Fails on malformed rows.
+pub fn import_trip_table(
+    csv_path: &str,
+    scenario_name: &str,
+    map: &Map,
+    timer: &mut Timer,
+) -> Result<Scenario, String> {
+    let mut matcher = Matcher::new(map);
+    let borders = Borders::new(map);
+
+    let mut trips_per_person: BTreeMap<String, Vec<(Time, Endpoint, Endpoint, TripMode)>> =
+        BTreeMap::new();
+    let mut total_trips = 0;
+    let (reader, done) = FileWithProgress::new(csv_path).unwrap();
+    for (idx, rec) in csv::Reader::from_reader(reader).deserialize().enumerate() {
+        // The header is the first line
+        let row = idx + 2;
+        let rec: RawTrip = rec.map_err(|err| format!("{} line {}: {}", csv_path, row, err))?;
+        total_trips += 1;
+        let mode = if let Some(m) = parse_mode(&rec.mode) {
+            m
+        } else {
+            timer.warn(format!("Skipping unknown mode in {:?}", rec));
+            continue;
+        };
+        let depart = parse_time(&rec.depart, csv_path, row)?;
+        let from = matcher.match_pt(LonLat::new(rec.origin_lon, rec.origin_lat));
+        let to = matcher.match_pt(LonLat::new(rec.destination_lon, rec.destination_lat));
+        if let (Some(from), Some(to)) = (from, to) {
+            trips_per_person
+                .entry(rec.person)
+                .or_insert_with(Vec::new)
+                .push((depart, from, to, mode));
+        }
+    }
+    done(timer);
+
+    let mut people = Vec::new();
+    let mut kept_trips = 0;
+    for (_, mut trips) in trips_per_person {
+        trips.sort_by_key(|(depart, _, _, _)| *depart);
+        let trips: Vec<IndividTrip> = trips
+            .into_iter()
+            .filter_map(|(depart, from, to, mode)| {
+                let (start, end) = endpoints(&borders, &from, &to, mode)?;
+                Some(IndividTrip {
+                    depart,
+                    trip: SpawnTrip::new(start, end, mode, map),
+                })
+            })
+            .collect();
+        if trips.is_empty() {
+            continue;
+        }
+        kept_trips += trips.len();
+        people.push(PersonSpec {
+            id: PersonID(people.len()),
+            orig_id: None,
+            trips,
+        });
+    }
+    timer.note(format!(
+        "{} trips clipped down to {}, over {} people",
+        prettyprint_usize(total_trips),
+        prettyprint_usize(kept_trips),
+        prettyprint_usize(people.len())
+    ));
+
+    let mut scenario = Scenario::empty(map, scenario_name);
+    scenario.people = people;
+    // People might take any bus
+    scenario.only_seed_buses = None;
+    Ok(scenario.remove_weird_schedules(map))
+}
+
+struct Matcher<'a> {
+    map: &'a Map,
+    closest_bldg: FindClosest<BuildingID>,
+    // Every distinct off-map point is its own parcel.
+    parcels: BTreeMap<LonLat, usize>,
+}
+
+impl<'a> Matcher<'a> {
+    fn new(map: &'a Map) -> Matcher<'a> {
+        let mut closest_bldg = FindClosest::new(map.get_bounds());
+        for b in map.all_buildings() {
+            closest_bldg.add(b.id, b.polygon.points());
+        }
+        Matcher {
+            map,
+            closest_bldg,
+            parcels: BTreeMap::new(),
+        }
+    }
+
+    // None if the point is inside the map, but nowhere near a building.
+    fn match_pt(&mut self, gps: LonLat) -> Option<Endpoint> {
+        if let Some(pt) = Pt2D::from_gps(gps, self.map.get_gps_bounds()) {
+            if self.map.get_boundary_polygon().contains_pt(pt) {
+                return self
+                    .closest_bldg
+                    .closest_pt(pt, MAX_DIST_TO_BLDG)
+                    .map(|(b, _)| Endpoint::Bldg(b));
+            }
+        }
+        let next_id = self.parcels.len();
+        let parcel_id = *self.parcels.entry(gps).or_insert(next_id);
+        Some(Endpoint::OffMap(OffMapLocation { parcel_id, gps }))
+    }
+}
@@ -1,19 +1,25 @@
-mod austin;
+mod config;
+mod demand;
+mod pipeline;
 mod seattle;
 #[cfg(feature = "scenarios")]
 mod soundcast;
 mod utils;
 
-// TODO Might be cleaner to express as a dependency graph?
-
 struct Job {
     city: String,
     osm_to_raw: bool,
     raw_to_map: bool,
     scenario: bool,
     scenario_everyone: bool,
+    od_matrix: Option<String>,
+    zones: Option<String>,
+    trip_table: Option<String>,
+    scenario_name: Option<String>,
 
     skip_ch: bool,
+    incremental: bool,
+    dry_run: bool,
 
     only_map: Option<String>,
 
@@ -25,7 +31,8 @@ fn main() {
     let mut args = abstutil::CmdArgs::new();
     let job = Job {
         city: args.optional("--city").unwrap_or("seattle".to_string()),
-        // Download all raw input files, then convert OSM to the intermediate RawMap.
+        // Download any missing raw input files, then convert OSM to the intermediate RawMap. What
+        // to do is described by data/input/$city/config.json.
         osm_to_raw: args.enabled("--raw"),
         // Convert the RawMap to the final Map format.
         raw_to_map: args.enabled("--map"),
@@ -33,12 +40,27 @@ fn main() {
         scenario: args.enabled("--scenario"),
         // Produce a variation of the weekday scenario including off-map trips.
         scenario_everyone: args.enabled("--scenario_everyone"),
+        // Produce a scenario from a zone-to-zone OD matrix (CSV with origin, destination, mode,
+        // trips, and optionally depart_from and depart_to). Requires --zones, an Osmosis .poly file
+        // with one section per zone.
+        od_matrix: args.optional("--od_matrix"),
+        zones: args.optional("--zones"),
+        // Produce a scenario from a CSV trip table (person, depart, origin_lon, origin_lat,
+        // destination_lon, destination_lat, mode).
+        trip_table: args.optional("--trip_table"),
+        // The name of the scenario produced by --od_matrix or --trip_table. Required, so these
+        // never overwrite the weekday scenario from --scenario by accident.
+        scenario_name: args.optional("--scenario_name"),
         // Skip the most expensive step of --map, building contraction hierarchies. The resulting
         // map won't be usable for simulation; as soon as you try to pathfind, it'll crash.
         skip_ch: args.enabled("--skip_ch"),
+        // Only run the stages above whose input files changed since the last import.
+        incremental: args.enabled("--incremental"),
+        // Like --incremental, but just print what would be rebuilt and why.
+        dry_run: args.enabled("--dry_run"),
 
         // Only process one map. If not specified, process all maps defined by clipping polygons in
-        // data/input/$city/polygons/.
+        // the city's config.
         only_map: args.optional_free(),
 
         // Ignore other arguments and just convert the given .osm file to a Map.
@@ -50,31 +72,43 @@ fn main() {
         && !job.raw_to_map
         && !job.scenario
         && !job.scenario_everyone
+        && job.od_matrix.is_none()
+        && job.trip_table.is_none()
         && job.oneshot.is_none()
     {
         println!(
             "Nothing to do! Pass some combination of --raw, --map, --scenario, \
-             --scenario_everyone or --oneshot"
+             --scenario_everyone, --od_matrix, --trip_table or --oneshot"
         );
         std::process::exit(1);
     }
+    if job.od_matrix.is_some() != job.zones.is_some() {
+        println!("--od_matrix and --zones must be used together");
+        std::process::exit(1);
+    }
+    if (job.od_matrix.is_some() || job.trip_table.is_some()) && job.scenario_name.is_none() {
+        println!("--od_matrix and --trip_table need --scenario_name");
+        std::process::exit(1);
+    }
 
     if let Some(path) = job.oneshot {
         oneshot(path, job.oneshot_clip);
         return;
     }
 
+    let config = config::CityConfig::load(&job.city);
     let names = if let Some(n) = job.only_map {
         println!("- Just working on {}", n);
         vec![n]
     } else {
         println!("- Working on all {} maps", job.city);
-        abstutil::list_all_objects(format!("../data/input/{}/polygons", job.city))
+        config.all_maps()
     };
 
     let mut timer = abstutil::Timer::new("import map data");
 
-    let (maybe_popdat, maybe_huge_map) = if job.scenario || job.scenario_everyone {
+    let (maybe_popdat, maybe_huge_map) = if (job.scenario || job.scenario_everyone) && !job.dry_run
+    {
         assert_eq!(job.city, "seattle");
 
         #[cfg(feature = "scenarios")]
@@ -96,53 +130,132 @@ fn main() {
         (None, None)
     };
 
+    let mut pipeline = pipeline::Pipeline::load(&job.city, job.incremental, job.dry_run);
     for name in names {
         if job.osm_to_raw {
-            match job.city.as_ref() {
-                "austin" => austin::osm_to_raw(&name),
-                "seattle" => seattle::osm_to_raw(&name),
-                x => panic!("Unknown city {}", x),
+            let stage = pipeline::Stage::raw(&job.city, &config, &name);
+            if pipeline.should_run(&name, &stage) {
+                config::osm_to_raw(&job.city, &config, &name);
+                pipeline.finished(&name, &stage);
             }
         }
 
-        let mut maybe_map = if job.raw_to_map {
-            Some(utils::raw_to_map(&name, !job.skip_ch, &mut timer))
-        } else if job.scenario || job.scenario_everyone {
-            Some(map_model::Map::new(abstutil::path_map(&name), &mut timer))
-        } else {
-            None
-        };
+        let mut maybe_map = None;
+        if job.raw_to_map {
+            let stage = pipeline::Stage::map(&name, !job.skip_ch);
+            if pipeline.should_run(&name, &stage) {
+                maybe_map = Some(utils::raw_to_map(&name, !job.skip_ch, &mut timer));
+                pipeline.finished(&name, &stage);
+            }
+        }
 
         #[cfg(feature = "scenarios")]
         if job.scenario {
-            timer.start(format!("scenario for {}", name));
-            let scenario = soundcast::make_weekday_scenario(
-                maybe_map.as_ref().unwrap(),
-                maybe_popdat.as_ref().unwrap(),
-                maybe_huge_map.as_ref().unwrap(),
-                &mut timer,
-            );
-            scenario.save();
-            timer.stop(format!("scenario for {}", name));
-
-            // This is a strange ordering.
-            if name == "downtown" {
-                timer.start(format!("adjust parking for {}", name));
-                seattle::adjust_private_parking(maybe_map.as_mut().unwrap(), &scenario);
-                timer.stop(format!("adjust parking for {}", name));
+            let stage = pipeline::Stage::scenario(&name, "weekday");
+            if pipeline.should_run(&name, &stage) {
+                if maybe_map.is_none() {
+                    maybe_map = Some(map_model::Map::new(abstutil::path_map(&name), &mut timer));
+                }
+
+                timer.start(format!("scenario for {}", name));
+                let scenario = match soundcast::make_weekday_scenario(
+                    maybe_map.as_ref().unwrap(),
+                    maybe_popdat.as_ref().unwrap(),
+                    maybe_huge_map.as_ref().unwrap(),
+                    &mut timer,
+                ) {
+                    Ok(s) => s,
+                    Err(err) => panic!("Can't make the weekday scenario for {}: {}", name, err),
+                };
+                scenario.save();
+                timer.stop(format!("scenario for {}", name));
+
+                // This is a strange ordering.
+                if name == "downtown" {
+                    timer.start(format!("adjust parking for {}", name));
+                    seattle::adjust_private_parking(maybe_map.as_mut().unwrap(), &scenario);
+                    timer.stop(format!("adjust parking for {}", name));
+                }
+                // Record after adjusting parking, since that modifies the map.
+                pipeline.finished(&name, &stage);
             }
         }
 
         #[cfg(feature = "scenarios")]
         if job.scenario_everyone {
-            timer.start(format!("scenario_everyone for {}", name));
-            soundcast::make_weekday_scenario_with_everyone(
-                maybe_map.as_ref().unwrap(),
-                maybe_popdat.as_ref().unwrap(),
-                &mut timer,
-            )
-            .save();
-            timer.stop(format!("scenario_everyone for {}", name));
+            let stage = pipeline::Stage::scenario(&name, "everyone_weekday");
+            if pipeline.should_run(&name, &stage) {
+                if maybe_map.is_none() {
+                    maybe_map = Some(map_model::Map::new(abstutil::path_map(&name), &mut timer));
+                }
+
+                timer.start(format!("scenario_everyone for {}", name));
+                soundcast::make_weekday_scenario_with_everyone(
+                    maybe_map.as_ref().unwrap(),
+                    maybe_popdat.as_ref().unwrap(),
+                    &mut timer,
+                )
+                .save();
+                timer.stop(format!("scenario_everyone for {}", name));
+                pipeline.finished(&name, &stage);
+            }
+        }
+
+        if let (Some(od_matrix), Some(zones)) = (&job.od_matrix, &job.zones) {
+            let scenario_name = job.scenario_name.as_ref().unwrap();
+            let stage = pipeline::Stage::demand(
+                "od_matrix",
+                &name,
+                scenario_name,
+                vec![od_matrix.clone(), zones.clone()],
+            );
+            if pipeline.should_run(&name, &stage) {
+                if maybe_map.is_none() {
+                    maybe_map = Some(map_model::Map::new(abstutil::path_map(&name), &mut timer));
+                }
+
+                timer.start(format!("OD matrix for {}", name));
+                match demand::import_od_matrix(
+                    od_matrix,
+                    zones,
+                    scenario_name,
+                    maybe_map.as_ref().unwrap(),
+                    &mut timer,
+                ) {
+                    Ok(scenario) => scenario.save(),
+                    Err(err) => panic!("Can't import OD matrix for {}: {}", name, err),
+                }
+                timer.stop(format!("OD matrix for {}", name));
+                pipeline.finished(&name, &stage);
+            }
+        }
+
+        if let Some(ref trip_table) = job.trip_table {
+            let scenario_name = job.scenario_name.as_ref().unwrap();
+            let stage = pipeline::Stage::demand(
+                "trip_table",
+                &name,
+                scenario_name,
+                vec![trip_table.clone()],
+            );
+            if pipeline.should_run(&name, &stage) {
+                if maybe_map.is_none() {
+                    maybe_map = Some(map_model::Map::new(abstutil::path_map(&name), &mut timer));
+                }
+
+                timer.start(format!("trip table for {}", name));
+                match demand::import_trip_table(
+                    trip_table,
+                    scenario_name,
+                    maybe_map.as_ref().unwrap(),
+                    &mut timer,
+                ) {
+                    Ok(scenario) => scenario.save(),
+                    Err(err) => panic!("Can't import trip table for {}: {}", name, err),
+                }
+                timer.stop(format!("trip table for {}", name));
+                pipeline.finished(&name, &stage);
+            }
         }
     }
 }
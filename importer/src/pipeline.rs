@@ -0,0 +1,483 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+// Remembers what each stage of the import was last built from, so that stages whose inputs haven't
+// changed can be skipped. The stages form a dependency graph just by virtue of one stage's output
+// being another's input: the RawMap feeds the Map, which feeds the scenarios.
+//
+// Only input files are tracked, not the importer code itself. After changing convert_osm or
+// map_model, run without --incremental to force a rebuild.
+pub struct Pipeline {
+    state_path: String,
+    state: PipelineState,
+    // If false, every requested stage runs, and nothing is hashed. Records for those stages are
+    // dropped, since they no longer describe the output.
+    incremental: bool,
+    dry_run: bool,
+    // In a dry run, the outputs of stages that would've been rebuilt. Anything depending on these is
+    // stale too.
+    pending_outputs: HashSet<String>,
+    // Inputs already checked during this run, so finished() doesn't hash them again. A stage might
+    // rewrite its own inputs (the scenario stages adjust parking in the map), so these're still
+    // checked against the file's size and time.
+    fingerprints: HashMap<String, Fingerprint>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct PipelineState {
+    // Map name, then stage name
+    maps: BTreeMap<String, BTreeMap<String, StageRecord>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StageRecord {
+    inputs: BTreeMap<String, Fingerprint>,
+    // Anything else affecting the output, like whether contraction hierarchies were built
+    params: String,
+}
+
+// Describes a file or directory. If the size and modification time match the last build, the
+// contents are assumed to match too, and aren't hashed again. Big inputs like OSM extracts take a
+// while to hash.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Fingerprint {
+    // For directories, summed over every file inside
+    size: u64,
+    // Seconds and nanoseconds since the Unix epoch. For directories, the newest file inside.
+    modified: (u64, u32),
+    hash: String,
+}
+
+pub struct Stage {
+    pub name: &'static str,
+    pub inputs: Vec<String>,
+    pub params: String,
+    pub output: String,
+}
+
+impl Stage {
+    pub fn raw(city: &str, config: &crate::config::CityConfig, map: &str) -> Stage {
+        let mut inputs = vec![
+            format!("../data/input/{}/config.json", city),
+            config.osm_input.clone(),
+            format!("{}/{}.poly", config.clipping_polygons, map),
+        ];
+        for path in vec![
+            &config.gtfs,
+            &config.elevation,
+            &config.parking_shapes,
+            &config.public_offstreet_parking,
+            &config.sidewalks,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            inputs.push(path.clone());
+        }
+        Stage {
+            name: "raw",
+            inputs,
+            params: String::new(),
+            output: abstutil::path_raw_map(map),
+        }
+    }
+
+    pub fn map(map: &str, build_ch: bool) -> Stage {
+        Stage {
+            name: "map",
+            inputs: vec![abstutil::path_raw_map(map)],
+            params: format!("contraction hierarchies: {}", build_ch),
+            output: abstutil::path_map(map),
+        }
+    }
+
+    pub fn scenario(map: &str, scenario_name: &'static str) -> Stage {
+        Stage {
+            name: scenario_name,
+            inputs: vec![abstutil::path_map(map), abstutil::path_popdat()],
+            params: String::new(),
+            output: abstutil::path_scenario(map, scenario_name),
+        }
+    }
+
+    // A scenario built from generic travel demand files, like an OD matrix
+    pub fn demand(
+        name: &'static str,
+        map: &str,
+        scenario_name: &str,
+        demand_inputs: Vec<String>,
+    ) -> Stage {
+        let mut inputs = vec![abstutil::path_map(map)];
+        inputs.extend(demand_inputs);
+        Stage {
+            name,
+            inputs,
+            params: format!("scenario name: {}", scenario_name),
+            output: abstutil::path_scenario(map, scenario_name),
+        }
+    }
+}
+
+impl Pipeline {
+    pub fn load(city: &str, incremental: bool, dry_run: bool) -> Pipeline {
+        let state_path = format!("../data/input/{}/import_state.json", city);
+        let state =
+            abstutil::maybe_read_json(state_path.clone(), &mut abstutil::Timer::throwaway())
+                .unwrap_or_else(|_| PipelineState::default());
+        Pipeline {
+            state_path,
+            state,
+            incremental: incremental || dry_run,
+            dry_run,
+            pending_outputs: HashSet::new(),
+            fingerprints: HashMap::new(),
+        }
+    }
+
+    // Should this stage be run? In a dry run, always false, but the reason is printed.
+    pub fn should_run(&mut self, map: &str, stage: &Stage) -> bool {
+        if !self.incremental {
+            return true;
+        }
+        let reason = match self.why_stale(map, stage) {
+            Some(r) => r,
+            None => {
+                println!("- {} for {} is up-to-date, skipping", stage.name, map);
+                return false;
+            }
+        };
+        if self.dry_run {
+            println!("- Would rebuild {} for {}: {}", stage.name, map, reason);
+            self.pending_outputs.insert(stage.output.clone());
+            return false;
+        }
+        println!("- Rebuilding {} for {}: {}", stage.name, map, reason);
+        true
+    }
+
+    // Call after successfully running a stage.
+    pub fn finished(&mut self, map: &str, stage: &Stage) {
+        // The output is probably another stage's input, and it just changed
+        self.fingerprints.remove(&stage.output);
+
+        if !self.incremental {
+            let removed = self
+                .state
+                .maps
+                .get_mut(map)
+                .and_then(|m| m.remove(stage.name))
+                .is_some();
+            if removed {
+                abstutil::write_json(self.state_path.clone(), &self.state);
+            }
+            return;
+        }
+
+        let mut inputs = BTreeMap::new();
+        for input in &stage.inputs {
+            inputs.insert(input.clone(), self.fingerprint(map, stage.name, input));
+        }
+        self.state
+            .maps
+            .entry(map.to_string())
+            .or_insert_with(BTreeMap::new)
+            .insert(
+                stage.name.to_string(),
+                StageRecord {
+                    inputs,
+                    params: stage.params.clone(),
+                },
+            );
+        abstutil::write_json(self.state_path.clone(), &self.state);
+    }
+
+    fn why_stale(&mut self, map: &str, stage: &Stage) -> Option<String> {
+        if !Path::new(&stage.output).exists() {
+            return Some(format!("{} doesn't exist", stage.output));
+        }
+        for input in &stage.inputs {
+            if self.pending_outputs.contains(input) {
+                return Some(format!("{} would be rebuilt", input));
+            }
+        }
+        let record = match self.state.maps.get(map).and_then(|m| m.get(stage.name)) {
+            Some(r) => r,
+            None => {
+                return Some("no record of a previous build".to_string());
+            }
+        };
+        if record.params != stage.params {
+            return Some(format!(
+                "settings changed from \"{}\" to \"{}\"",
+                record.params, stage.params
+            ));
+        }
+        for input in &stage.inputs {
+            if !self.state.maps[map][stage.name].inputs.contains_key(input) {
+                return Some(format!("{} is a new input", input));
+            }
+            let current = self.fingerprint(map, stage.name, input);
+            if self.state.maps[map][stage.name].inputs[input].hash != current.hash {
+                return Some(format!("{} changed", input));
+            }
+        }
+        None
+    }
+
+    // Describe an input as it is now, hashing it only if it doesn't match what was seen earlier in
+    // this run, or what this stage was last built from.
+    fn fingerprint(&mut self, map: &str, stage: &str, input: &str) -> Fingerprint {
+        let f = if let Some(seen) = self.fingerprints.get(input) {
+            fingerprint(input, Some(seen))
+        } else {
+            let old = self
+                .state
+                .maps
+                .get(map)
+                .and_then(|m| m.get(stage))
+                .and_then(|r| r.inputs.get(input));
+            fingerprint(input, old)
+        };
+        self.fingerprints.insert(input.to_string(), f.clone());
+        f
+    }
+}
+
+fn fingerprint(path: &str, old: Option<&Fingerprint>) -> Fingerprint {
+    let (size, modified) = match stat_path(Path::new(path)) {
+        Some(x) => x,
+        None => {
+            return Fingerprint {
+                size: 0,
+                modified: (0, 0),
+                hash: "missing".to_string(),
+            };
+        }
+    };
+    if let Some(old) = old {
+        if old.size == size && old.modified == modified {
+            return old.clone();
+        }
+    }
+    Fingerprint {
+        size,
+        modified,
+        hash: hash_path(path),
+    }
+}
+
+// The total size and newest modification time of a file, or a directory and everything inside.
+// None if the path is missing.
+fn stat_path(path: &Path) -> Option<(u64, (u64, u32))> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let since_epoch = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?;
+    // A directory's own time changes when files are added, removed, or renamed
+    let mut modified = (since_epoch.as_secs(), since_epoch.subsec_nanos());
+    if !metadata.is_dir() {
+        return Some((metadata.len(), modified));
+    }
+    let mut size = 0;
+    for entry in std::fs::read_dir(path).ok()?.filter_map(|e| e.ok()) {
+        if let Some((s, m)) = stat_path(&entry.path()) {
+            size += s;
+            modified = modified.max(m);
+        }
+    }
+    Some((size, modified))
+}
+
+// A hash of a file's contents, or every file in a directory. Missing paths hash to "missing".
+fn hash_path(path: &str) -> String {
+    let mut context = md5::Context::new();
+    if !hash_into(Path::new(path), &mut context) {
+        return "missing".to_string();
+    }
+    format!("{:x}", context.compute())
+}
+
+fn hash_into(path: &Path, context: &mut md5::Context) -> bool {
+    if path.is_dir() {
+        let mut entries: Vec<_> = match std::fs::read_dir(path) {
+            Ok(list) => list.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+            Err(_) => {
+                return false;
+            }
+        };
+        entries.sort();
+        for entry in entries {
+            // Include the name, so renaming a file counts as a change
+            context.consume(entry.display().to_string().as_bytes());
+            hash_into(&entry, context);
+        }
+        return true;
+    }
+
+    let mut file = match File::open(path) {
+        Ok(f) => f,
+        Err(_) => {
+            return false;
+        }
+    };
+    // These files can be very large, so read in chunks
+    let mut buffer = [0 as u8; 16 * 1024];
+    while let Ok(n) = file.read(&mut buffer) {
+        if n == 0 {
+            break;
+        }
+        context.consume(&buffer[..n]);
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A scratch directory with one input file and one output file
+    fn setup(name: &str) -> (String, Stage) {
+        let dir = std::env::temp_dir().join(format!("importer_pipeline_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let dir = dir.display().to_string();
+        std::fs::write(format!("{}/input.txt", dir), "original").unwrap();
+        std::fs::write(format!("{}/output.bin", dir), "built").unwrap();
+        let stage = Stage {
+            name: "map",
+            inputs: vec![format!("{}/input.txt", dir)],
+            params: "contraction hierarchies: true".to_string(),
+            output: format!("{}/output.bin", dir),
+        };
+        (dir, stage)
+    }
+
+    fn pipeline(dir: &str, incremental: bool) -> Pipeline {
+        Pipeline {
+            state_path: format!("{}/import_state.json", dir),
+            state: PipelineState::default(),
+            incremental,
+            dry_run: false,
+            pending_outputs: HashSet::new(),
+            fingerprints: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn staleness() {
+        let (dir, mut stage) = setup("staleness");
+        let mut p = pipeline(&dir, true);
+        assert_eq!(
+            p.why_stale("m", &stage),
+            Some("no record of a previous build".to_string())
+        );
+        p.finished("m", &stage);
+        assert_eq!(p.why_stale("m", &stage), None);
+
+        // Each new run starts without remembered fingerprints
+        std::fs::write(&stage.inputs[0], "changed, and a different size").unwrap();
+        let mut p = pipeline(&dir, true);
+        p.state = abstutil::maybe_read_json(
+            format!("{}/import_state.json", dir),
+            &mut abstutil::Timer::throwaway(),
+        )
+        .unwrap();
+        assert_eq!(
+            p.why_stale("m", &stage),
+            Some(format!("{} changed", stage.inputs[0]))
+        );
+        p.finished("m", &stage);
+        assert_eq!(p.why_stale("m", &stage), None);
+
+        stage.params = "contraction hierarchies: false".to_string();
+        assert!(p
+            .why_stale("m", &stage)
+            .unwrap()
+            .starts_with("settings changed"));
+        stage.params = "contraction hierarchies: true".to_string();
+
+        stage.inputs.push(format!("{}/another.txt", dir));
+        assert_eq!(
+            p.why_stale("m", &stage),
+            Some(format!("{}/another.txt is a new input", dir))
+        );
+        stage.inputs.pop();
+
+        std::fs::remove_file(&stage.output).unwrap();
+        assert_eq!(
+            p.why_stale("m", &stage),
+            Some(format!("{} doesn't exist", stage.output))
+        );
+    }
+
+    #[test]
+    fn unchanged_files_arent_rehashed() {
+        let (dir, stage) = setup("rehash");
+        let mut p = pipeline(&dir, true);
+        p.finished("m", &stage);
+        let input = &stage.inputs[0];
+
+        // If the size and time match, the old hash is trusted
+        let mut old = p.state.maps["m"]["map"].inputs[input].clone();
+        old.hash = "not really a hash".to_string();
+        assert_eq!(fingerprint(input, Some(&old)), old);
+
+        // But not otherwise
+        old.modified.0 += 1;
+        assert_eq!(fingerprint(input, Some(&old)).hash, hash_path(input));
+
+        // Rewriting the same contents is still fresh
+        std::fs::write(input, "original").unwrap();
+        let mut p2 = pipeline(&dir, true);
+        p2.state = std::mem::replace(&mut p.state, PipelineState::default());
+        assert_eq!(p2.why_stale("m", &stage), None);
+    }
+
+    #[test]
+    fn stage_rewrites_input() {
+        let (dir, stage) = setup("rewrites_input");
+        let mut p = pipeline(&dir, true);
+        p.finished("m", &stage);
+        // The input's fingerprint is remembered for the rest of the run now
+        assert_eq!(p.why_stale("m", &stage), None);
+        // Like the scenario stages adjusting parking and saving the map again
+        std::fs::write(&stage.inputs[0], "modified by the stage itself").unwrap();
+        p.finished("m", &stage);
+        assert_eq!(
+            p.state.maps["m"]["map"].inputs[&stage.inputs[0]].hash,
+            hash_path(&stage.inputs[0])
+        );
+
+        let mut p = pipeline(&dir, true);
+        p.state = abstutil::maybe_read_json(
+            format!("{}/import_state.json", dir),
+            &mut abstutil::Timer::throwaway(),
+        )
+        .unwrap();
+        assert_eq!(p.why_stale("m", &stage), None);
+    }
+
+    #[test]
+    fn full_rebuild_drops_record() {
+        let (dir, stage) = setup("full_rebuild");
+        let mut p = pipeline(&dir, true);
+        p.finished("m", &stage);
+        assert!(p.state.maps["m"].contains_key("map"));
+
+        let mut p = pipeline(&dir, false);
+        p.state = abstutil::maybe_read_json(
+            format!("{}/import_state.json", dir),
+            &mut abstutil::Timer::throwaway(),
+        )
+        .unwrap();
+        assert!(p.should_run("m", &stage));
+        p.finished("m", &stage);
+        assert!(p.fingerprints.is_empty());
+        assert!(!p.state.maps["m"].contains_key("map"));
+    }
+}
@@ -1,12 +1,13 @@
 use crate::soundcast::popdat::{Endpoint, OrigTrip, PopDat};
-use abstutil::{prettyprint_usize, MultiMap, Timer};
+use crate::utils::{nearest_border, Borders};
+use abstutil::{prettyprint_usize, Counter, MultiMap, Timer};
 use geom::LonLat;
 use map_model::{BuildingID, IntersectionID, Map, PathConstraints, PathRequest, PathStep};
 use sim::{
-    IndividTrip, OffMapLocation, OrigPersonID, PersonID, PersonSpec, Scenario, SpawnTrip,
-    TripEndpoint, TripMode,
+    HouseholdSpec, IndividTrip, OffMapLocation, OrigPersonID, PersonID, PersonSpec, Scenario,
+    SpawnTrip, TripEndpoint,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Clone, Debug)]
 struct Trip {
@@ -115,12 +116,8 @@ fn endpoints(
         None
     };
     // Fallback to finding the nearest border with straight-line distance
-    let border_i = maybe_other_border.or_else(|| {
-        usable_borders
-            .iter()
-            .min_by_key(|(_, pt)| pt.fast_dist(border_endpt.pos))
-            .map(|(id, _)| *id)
-    })?;
+    let border_i =
+        maybe_other_border.or_else(|| nearest_border(usable_borders, border_endpt.pos))?;
     let border = TripEndpoint::Border(
         border_i,
         Some(OffMapLocation {
@@ -150,50 +147,7 @@ fn clip_trips(map: &Map, popdat: &PopDat, huge_map: &Map, timer: &mut Timer) ->
     for b in map.all_buildings() {
         osm_id_to_bldg.insert(b.osm_way_id, b.id);
     }
-    let bounds = map.get_gps_bounds();
-    // TODO Figure out why some polygon centers are broken
-    let incoming_borders_walking: Vec<(IntersectionID, LonLat)> = map
-        .all_incoming_borders()
-        .into_iter()
-        .filter(|i| {
-            !i.get_outgoing_lanes(map, PathConstraints::Pedestrian)
-                .is_empty()
-        })
-        .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
-        .collect();
-    let incoming_borders_driving: Vec<(IntersectionID, LonLat)> = map
-        .all_incoming_borders()
-        .into_iter()
-        .filter(|i| !i.get_outgoing_lanes(map, PathConstraints::Car).is_empty())
-        .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
-        .collect();
-    let incoming_borders_biking: Vec<(IntersectionID, LonLat)> = map
-        .all_incoming_borders()
-        .into_iter()
-        .filter(|i| !i.get_outgoing_lanes(map, PathConstraints::Bike).is_empty())
-        .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
-        .collect();
-    let outgoing_borders_walking: Vec<(IntersectionID, LonLat)> = map
-        .all_outgoing_borders()
-        .into_iter()
-        .filter(|i| {
-            !i.get_incoming_lanes(map, PathConstraints::Pedestrian)
-                .is_empty()
-        })
-        .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
-        .collect();
-    let outgoing_borders_driving: Vec<(IntersectionID, LonLat)> = map
-        .all_outgoing_borders()
-        .into_iter()
-        .filter(|i| !i.get_incoming_lanes(map, PathConstraints::Car).is_empty())
-        .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
-        .collect();
-    let outgoing_borders_biking: Vec<(IntersectionID, LonLat)> = map
-        .all_outgoing_borders()
-        .into_iter()
-        .filter(|i| !i.get_incoming_lanes(map, PathConstraints::Bike).is_empty())
-        .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
-        .collect();
+    let borders = Borders::new(map);
 
     let total_trips = popdat.trips.len();
     let maybe_results: Vec<Option<Trip>> =
@@ -203,18 +157,8 @@ fn clip_trips(map: &Map, popdat: &PopDat, huge_map: &Map, timer: &mut Timer) ->
                 &orig.to,
                 map,
                 &osm_id_to_bldg,
-                match orig.mode {
-                    TripMode::Walk | TripMode::Transit => {
-                        (&incoming_borders_walking, &outgoing_borders_walking)
-                    }
-                    TripMode::Drive => (&incoming_borders_driving, &outgoing_borders_driving),
-                    TripMode::Bike => (&incoming_borders_biking, &outgoing_borders_biking),
-                },
-                match orig.mode {
-                    TripMode::Walk | TripMode::Transit => PathConstraints::Pedestrian,
-                    TripMode::Drive => PathConstraints::Car,
-                    TripMode::Bike => PathConstraints::Bike,
-                },
+                (&borders.incoming[&orig.mode], &borders.outgoing[&orig.mode]),
+                orig.mode.to_constraints(),
                 maybe_huge_map.as_ref(),
             )?;
             Some(Trip {
@@ -239,7 +183,7 @@ pub fn make_weekday_scenario(
     popdat: &PopDat,
     huge_map: &Map,
     timer: &mut Timer,
-) -> Scenario {
+) -> Result<Scenario, String> {
     let trips = clip_trips(map, popdat, huge_map, timer);
     let orig_trips = trips.len();
 
@@ -291,13 +235,17 @@ pub fn make_weekday_scenario(
         }
     }
 
-    Scenario {
+    let households = make_households(&people, map);
+    let scenario = Scenario {
         scenario_name: "weekday".to_string(),
         map_name: map.get_name().to_string(),
         people,
+        households,
         only_seed_buses: None,
     }
-    .remove_weird_schedules(map)
+    .remove_weird_schedules(map);
+    scenario.check_households()?;
+    Ok(scenario)
 }
 
 pub fn make_weekday_scenario_with_everyone(
@@ -361,11 +309,57 @@ pub fn make_weekday_scenario_with_everyone(
         }
     }
 
+    // Nobody's home is on the map
     Scenario {
         scenario_name: "everyone_weekday".to_string(),
         map_name: map.get_name().to_string(),
         people,
+        households: Vec::new(),
         only_seed_buses: None,
     }
     .remove_weird_schedules(map)
 }
+
+// Group people by their Soundcast household. Assume the household lives wherever most members
+// start their day.
+// TODO Soundcast has the real home parcel and number of vehicles per household, but we don't
+// import those yet. For now, assume one car for every member who drives from home.
+fn make_households(people: &Vec<PersonSpec>, map: &Map) -> Vec<HouseholdSpec> {
+    let mut members_per_household: BTreeMap<usize, Vec<&PersonSpec>> = BTreeMap::new();
+    for p in people {
+        members_per_household
+            .entry(p.orig_id.unwrap().0)
+            .or_insert_with(Vec::new)
+            .push(p);
+    }
+
+    let mut households = Vec::new();
+    for (_, members) in members_per_household {
+        let mut starts: Counter<BuildingID> = Counter::new();
+        for p in &members {
+            if let TripEndpoint::Bldg(b) = p.trips[0].trip.start(map) {
+                starts.inc(b);
+            }
+        }
+        let home = if let Some(b) = starts.sorted_asc().pop().map(|group| group[0]) {
+            b
+        } else {
+            continue;
+        };
+        let num_cars = members
+            .iter()
+            .filter(|p| {
+                p.trips.iter().any(|t| match t.trip {
+                    SpawnTrip::UsingParkedCar(b, _) => b == home,
+                    _ => false,
+                })
+            })
+            .count();
+        households.push(HouseholdSpec {
+            home,
+            members: members.into_iter().map(|p| p.id).collect(),
+            num_cars,
+        });
+    }
+    households
+}
@@ -1,4 +1,8 @@
 use abstutil::Timer;
+use geom::LonLat;
+use map_model::{IntersectionID, Map};
+use sim::TripMode;
+use std::collections::BTreeMap;
 use std::path::Path;
 use std::process::Command;
 
@@ -63,22 +67,6 @@ pub fn download(output: &str, url: &str) {
     }
 }
 
-// Uses osmconvert to clip the input .osm (or .pbf) against a polygon and produce some output.
-// Skips if the output exists.
-pub fn osmconvert(input: &str, clipping_polygon: String, output: String) {
-    if Path::new(&output).exists() {
-        println!("- {} already exists", output);
-        return;
-    }
-    println!("- Clipping {} to {}", input, clipping_polygon);
-
-    run(Command::new("osmconvert")
-        .arg(input)
-        .arg(format!("-B={}", clipping_polygon))
-        .arg("--complete-ways")
-        .arg(format!("-o={}", output)));
-}
-
 // Removes files. Be careful!
 pub fn rm<I: Into<String>>(path: I) {
     let path = path.into();
@@ -101,6 +89,52 @@ fn run(cmd: &mut Command) {
     }
 }
 
+// Trips that start or end off-map enter or leave through a border usable by their mode.
+pub struct Borders {
+    pub incoming: BTreeMap<TripMode, Vec<(IntersectionID, LonLat)>>,
+    pub outgoing: BTreeMap<TripMode, Vec<(IntersectionID, LonLat)>>,
+}
+
+impl Borders {
+    pub fn new(map: &Map) -> Borders {
+        let bounds = map.get_gps_bounds();
+        let mut incoming = BTreeMap::new();
+        let mut outgoing = BTreeMap::new();
+        for mode in TripMode::all() {
+            let constraints = mode.to_constraints();
+            // TODO Figure out why some polygon centers are broken
+            incoming.insert(
+                mode,
+                map.all_incoming_borders()
+                    .into_iter()
+                    .filter(|i| !i.get_outgoing_lanes(map, constraints).is_empty())
+                    .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
+                    .collect(),
+            );
+            outgoing.insert(
+                mode,
+                map.all_outgoing_borders()
+                    .into_iter()
+                    .filter(|i| !i.get_incoming_lanes(map, constraints).is_empty())
+                    .filter_map(|i| i.polygon.center().to_gps(bounds).map(|pt| (i.id, pt)))
+                    .collect(),
+            );
+        }
+        Borders { incoming, outgoing }
+    }
+}
+
+// By straight-line distance
+pub fn nearest_border(
+    borders: &Vec<(IntersectionID, LonLat)>,
+    pt: LonLat,
+) -> Option<IntersectionID> {
+    borders
+        .iter()
+        .min_by_key(|(_, border)| border.fast_dist(pt))
+        .map(|(i, _)| *i)
+}
+
 // Converts a RawMap to a Map.
 pub fn raw_to_map(name: &str, build_ch: bool, timer: &mut Timer) -> map_model::Map {
     timer.start(format!("Raw->Map for {}", name));
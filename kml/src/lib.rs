@@ -1,7 +1,8 @@
+mod parse;
+
+pub use crate::parse::{parse_placemarks, Geometry, KmlError, Placemark};
 use abstutil::{prettyprint_usize, FileWithProgress, Timer};
 use geom::{GPSBounds, LonLat};
-use quick_xml::events::Event;
-use quick_xml::Reader;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -16,80 +17,46 @@ pub struct ExtraShape {
     pub attributes: BTreeMap<String, String>,
 }
 
+/// Loads a KML file, producing one shape per simple geometry. Anything in a MultiGeometry is
+/// flattened, and only the outer boundary of polygons is kept; use `parse_placemarks` to get the
+/// full structure. Shapes with any point outside of `gps_bounds` are skipped.
 pub fn load(
     path: &str,
     gps_bounds: &GPSBounds,
     timer: &mut Timer,
-) -> Result<ExtraShapes, std::io::Error> {
+) -> Result<ExtraShapes, KmlError> {
     println!("Opening {}", path);
     let (f, done) = FileWithProgress::new(path)?;
     // TODO FileWithProgress should implement BufRead, so we don't have to double wrap like this
-    let mut reader = Reader::from_reader(std::io::BufReader::new(f));
-    reader.trim_text(true);
-
-    let mut buf = Vec::new();
+    let placemarks = parse_placemarks(std::io::BufReader::new(f))?;
+    done(timer);
 
-    // TODO uncomfortably stateful
     let mut shapes = Vec::new();
-    let mut scanned_schema = false;
-    let mut attributes: BTreeMap<String, String> = BTreeMap::new();
-    let mut attrib_key: Option<String> = None;
-
     let mut skipped_count = 0;
-
-    loop {
-        match reader.read_event(&mut buf) {
-            Ok(Event::Start(e)) => {
-                let name = e.unescape_and_decode(&reader).unwrap();
-                if name == "Placemark" {
-                    scanned_schema = true;
-                } else if name.starts_with("SimpleData name=\"") {
-                    attrib_key = Some(name["SimpleData name=\"".len()..name.len() - 1].to_string());
-                } else if name == "coordinates" {
-                    attrib_key = Some(name);
-                } else {
-                    attrib_key = None;
-                }
+    for placemark in placemarks {
+        let geometry = match placemark.geometry {
+            Some(g) => g,
+            None => {
+                skipped_count += 1;
+                continue;
             }
-            Ok(Event::Text(e)) => {
-                if scanned_schema {
-                    if let Some(ref key) = attrib_key {
-                        let text = e.unescape_and_decode(&reader).unwrap();
-                        if key == "coordinates" {
-                            let mut ok = true;
-                            let mut pts: Vec<LonLat> = Vec::new();
-                            for pair in text.split(' ') {
-                                if let Some(pt) = parse_pt(pair, gps_bounds) {
-                                    pts.push(pt);
-                                } else {
-                                    ok = false;
-                                    break;
-                                }
-                            }
-                            if ok {
-                                shapes.push(ExtraShape {
-                                    points: pts,
-                                    attributes: attributes.clone(),
-                                });
-                            } else {
-                                skipped_count += 1;
-                            }
-                            attributes.clear();
-                        } else {
-                            attributes.insert(key.to_string(), text);
-                        }
-                    }
-                }
+        };
+        for leaf in geometry.leaves() {
+            let points = match leaf {
+                Geometry::Point(pt) => vec![*pt],
+                Geometry::LineString(pts) => pts.clone(),
+                Geometry::Polygon { outer, .. } => outer.clone(),
+                Geometry::MultiGeometry(_) => unreachable!(),
+            };
+            if points.is_empty() || points.iter().any(|pt| !gps_bounds.contains(*pt)) {
+                skipped_count += 1;
+                continue;
             }
-            Ok(Event::Eof) => break,
-            Err(e) => panic!(
-                "XML error at position {}: {:?}",
-                reader.buffer_position(),
-                e
-            ),
-            _ => (),
+            shapes.push(ExtraShape {
+                points,
+                attributes: placemark.attributes.clone(),
+            });
         }
-        buf.clear();
     }
 
     println!(
@@ -98,23 +65,6 @@ pub fn load(
         path,
         prettyprint_usize(skipped_count)
     );
-    done(timer);
 
     Ok(ExtraShapes { shapes })
 }
-
-fn parse_pt(input: &str, gps_bounds: &GPSBounds) -> Option<LonLat> {
-    let coords: Vec<&str> = input.split(',').collect();
-    if coords.len() != 2 {
-        return None;
-    }
-    let pt = match (coords[0].parse::<f64>(), coords[1].parse::<f64>()) {
-        (Ok(lon), Ok(lat)) => Some(LonLat::new(lon, lat)),
-        _ => None,
-    }?;
-    if gps_bounds.contains(pt) {
-        Some(pt)
-    } else {
-        None
-    }
-}
@@ -0,0 +1,470 @@
+use geom::LonLat;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io::BufRead;
+
+/// One `<Placemark>` from a KML file.
+#[derive(Debug, PartialEq)]
+pub struct Placemark {
+    /// From `<SimpleData name="key">value</SimpleData>` and
+    /// `<Data name="key"><value>value</value></Data>`
+    pub attributes: BTreeMap<String, String>,
+    /// A placemark may have no geometry at all.
+    pub geometry: Option<Geometry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Geometry {
+    Point(LonLat),
+    /// Both `<LineString>` and a standalone `<LinearRing>`
+    LineString(Vec<LonLat>),
+    Polygon {
+        outer: Vec<LonLat>,
+        holes: Vec<Vec<LonLat>>,
+    },
+    MultiGeometry(Vec<Geometry>),
+}
+
+impl Geometry {
+    /// Flattens any nesting of MultiGeometry into the simple geometries.
+    pub fn leaves(&self) -> Vec<&Geometry> {
+        match self {
+            Geometry::MultiGeometry(list) => list.iter().flat_map(|g| g.leaves()).collect(),
+            _ => vec![self],
+        }
+    }
+
+    /// Every point in the geometry, including holes and nested geometries.
+    pub fn points(&self) -> Vec<LonLat> {
+        match self {
+            Geometry::Point(pt) => vec![*pt],
+            Geometry::LineString(pts) => pts.clone(),
+            Geometry::Polygon { outer, holes } => {
+                let mut pts = outer.clone();
+                for h in holes {
+                    pts.extend(h.clone());
+                }
+                pts
+            }
+            Geometry::MultiGeometry(list) => list.iter().flat_map(|g| g.points()).collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum KmlError {
+    Io(std::io::Error),
+    /// The XML itself is malformed. Position is the byte offset in the input.
+    Xml {
+        position: usize,
+        msg: String,
+    },
+    /// A `<coordinates>` tuple couldn't be parsed.
+    BadCoordinate {
+        position: usize,
+        text: String,
+    },
+    /// Well-formed XML, but the KML structure doesn't make sense.
+    BadStructure {
+        position: usize,
+        msg: String,
+    },
+}
+
+impl fmt::Display for KmlError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            KmlError::Io(e) => write!(f, "I/O error: {}", e),
+            KmlError::Xml { position, msg } => {
+                write!(f, "XML error at position {}: {}", position, msg)
+            }
+            KmlError::BadCoordinate { position, text } => {
+                write!(f, "bad coordinate {:?} at position {}", text, position)
+            }
+            KmlError::BadStructure { position, msg } => {
+                write!(f, "bad KML at position {}: {}", position, msg)
+            }
+        }
+    }
+}
+
+impl std::error::Error for KmlError {}
+
+impl From<std::io::Error> for KmlError {
+    fn from(e: std::io::Error) -> KmlError {
+        KmlError::Io(e)
+    }
+}
+
+// A geometry element that's been opened, but not closed yet.
+enum Partial {
+    Point(Option<LonLat>),
+    LineString(Vec<LonLat>),
+    LinearRing(Vec<LonLat>),
+    Polygon {
+        outer: Option<Vec<LonLat>>,
+        holes: Vec<Vec<LonLat>>,
+    },
+    MultiGeometry(Vec<Geometry>),
+}
+
+#[derive(Default)]
+struct PartialPlacemark {
+    attributes: BTreeMap<String, String>,
+    geometry: Option<Geometry>,
+}
+
+// Everything we need to remember while walking through the XML events
+#[derive(Default)]
+struct State {
+    // The names of all open elements
+    path: Vec<String>,
+    placemark: Option<PartialPlacemark>,
+    geometry_stack: Vec<Partial>,
+    // The key of the <SimpleData> or <Data> we're inside
+    data_key: Option<String>,
+    results: Vec<Placemark>,
+}
+
+/// Parses every `<Placemark>` from some KML input. Coordinates may be `lon,lat` or
+/// `lon,lat,alt`; altitude is ignored.
+pub fn parse_placemarks<B: BufRead>(input: B) -> Result<Vec<Placemark>, KmlError> {
+    let mut reader = Reader::from_reader(input);
+    reader.trim_text(true);
+    let mut buf = Vec::new();
+    let mut state = State::default();
+
+    loop {
+        let position = reader.buffer_position();
+        let event = reader
+            .read_event(&mut buf)
+            .map_err(|e| xml_error(position, e))?;
+        match event {
+            Event::Start(ref e) => {
+                state.start_element(e, &reader, position)?;
+            }
+            Event::Empty(ref e) => {
+                // Treat <foo/> as an immediately closed element
+                state.start_element(e, &reader, position)?;
+                state.end_element(position)?;
+            }
+            Event::End(_) => {
+                state.end_element(position)?;
+            }
+            Event::Text(ref e) | Event::CData(ref e) => {
+                let text = e
+                    .unescape_and_decode(&reader)
+                    .map_err(|err| xml_error(position, err))?;
+                state.handle_text(text, position)?;
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    if !state.path.is_empty() {
+        return Err(KmlError::Xml {
+            position: reader.buffer_position(),
+            msg: format!(
+                "unclosed elements at end of input: {}",
+                state.path.join(" > ")
+            ),
+        });
+    }
+    Ok(state.results)
+}
+
+fn xml_error(position: usize, err: quick_xml::Error) -> KmlError {
+    KmlError::Xml {
+        position,
+        msg: err.to_string(),
+    }
+}
+
+fn bad_structure<S: Into<String>>(position: usize, msg: S) -> KmlError {
+    KmlError::BadStructure {
+        position,
+        msg: msg.into(),
+    }
+}
+
+impl State {
+    fn start_element<B: BufRead>(
+        &mut self,
+        e: &BytesStart,
+        reader: &Reader<B>,
+        position: usize,
+    ) -> Result<(), KmlError> {
+        // Ignore any namespace prefix, like kml:Placemark
+        let mut name = String::from_utf8_lossy(e.name()).to_string();
+        if let Some(idx) = name.rfind(':') {
+            name = name[idx + 1..].to_string();
+        }
+
+        match name.as_str() {
+            "Placemark" => {
+                if self.placemark.is_some() {
+                    return Err(bad_structure(position, "nested Placemark"));
+                }
+                self.placemark = Some(PartialPlacemark::default());
+            }
+            "SimpleData" | "Data" => {
+                self.data_key = None;
+                for attr in e.attributes() {
+                    let attr = attr.map_err(|err| xml_error(position, err))?;
+                    if attr.key == b"name" {
+                        self.data_key = Some(
+                            attr.unescape_and_decode_value(reader)
+                                .map_err(|err| xml_error(position, err))?,
+                        );
+                    }
+                }
+            }
+            "Point" => self.geometry_stack.push(Partial::Point(None)),
+            "LineString" => self.geometry_stack.push(Partial::LineString(Vec::new())),
+            "LinearRing" => self.geometry_stack.push(Partial::LinearRing(Vec::new())),
+            "Polygon" => self.geometry_stack.push(Partial::Polygon {
+                outer: None,
+                holes: Vec::new(),
+            }),
+            "MultiGeometry" => self.geometry_stack.push(Partial::MultiGeometry(Vec::new())),
+            _ => {}
+        }
+        self.path.push(name);
+        Ok(())
+    }
+
+    fn end_element(&mut self, position: usize) -> Result<(), KmlError> {
+        let name = self.path.pop().ok_or_else(|| KmlError::Xml {
+            position,
+            msg: "closing tag without an opening tag".to_string(),
+        })?;
+        match name.as_str() {
+            "Placemark" => {
+                if let Some(p) = self.placemark.take() {
+                    self.results.push(Placemark {
+                        attributes: p.attributes,
+                        geometry: p.geometry,
+                    });
+                }
+            }
+            "SimpleData" | "Data" => {
+                self.data_key = None;
+            }
+            "Point" | "LineString" | "LinearRing" | "Polygon" | "MultiGeometry" => {
+                let partial = self.geometry_stack.pop().unwrap();
+                self.finish_geometry(partial, position)?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn finish_geometry(&mut self, partial: Partial, position: usize) -> Result<(), KmlError> {
+        let geom = match partial {
+            Partial::Point(pt) => Geometry::Point(
+                pt.ok_or_else(|| bad_structure(position, "Point without coordinates"))?,
+            ),
+            Partial::LineString(pts) => Geometry::LineString(pts),
+            Partial::LinearRing(pts) => {
+                // A ring inside a polygon is a boundary, not a standalone geometry
+                if let Some(Partial::Polygon { outer, holes }) = self.geometry_stack.last_mut() {
+                    match self.path.last().map(|s| s.as_str()) {
+                        Some("outerBoundaryIs") => {
+                            if outer.is_some() {
+                                return Err(bad_structure(
+                                    position,
+                                    "Polygon with multiple outerBoundaryIs",
+                                ));
+                            }
+                            *outer = Some(pts);
+                        }
+                        Some("innerBoundaryIs") => {
+                            holes.push(pts);
+                        }
+                        _ => {
+                            return Err(bad_structure(
+                                position,
+                                "LinearRing in a Polygon, but not in a boundary",
+                            ));
+                        }
+                    }
+                    return Ok(());
+                }
+                Geometry::LineString(pts)
+            }
+            Partial::Polygon { outer, holes } => Geometry::Polygon {
+                outer: outer
+                    .ok_or_else(|| bad_structure(position, "Polygon without outerBoundaryIs"))?,
+                holes,
+            },
+            Partial::MultiGeometry(list) => Geometry::MultiGeometry(list),
+        };
+
+        match self.geometry_stack.last_mut() {
+            Some(Partial::MultiGeometry(list)) => {
+                list.push(geom);
+            }
+            Some(_) => {
+                return Err(bad_structure(
+                    position,
+                    "geometry nested inside a non-MultiGeometry",
+                ));
+            }
+            None => {
+                // Geometry outside of a placemark is ignored
+                if let Some(p) = self.placemark.as_mut() {
+                    if p.geometry.is_some() {
+                        return Err(bad_structure(
+                            position,
+                            "Placemark with multiple geometries; use MultiGeometry",
+                        ));
+                    }
+                    p.geometry = Some(geom);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_text(&mut self, text: String, position: usize) -> Result<(), KmlError> {
+        let current = match self.path.last() {
+            Some(x) => x.as_str(),
+            None => {
+                return Ok(());
+            }
+        };
+
+        if current == "coordinates" {
+            let pts = parse_coordinates(&text, position)?;
+            match self.geometry_stack.last_mut() {
+                Some(Partial::Point(pt)) => {
+                    if pts.len() != 1 {
+                        return Err(bad_structure(
+                            position,
+                            format!("Point has {} coordinates", pts.len()),
+                        ));
+                    }
+                    *pt = Some(pts[0]);
+                }
+                Some(Partial::LineString(list)) | Some(Partial::LinearRing(list)) => {
+                    list.extend(pts);
+                }
+                _ => {
+                    return Err(bad_structure(
+                        position,
+                        "coordinates outside of Point, LineString, or LinearRing",
+                    ));
+                }
+            }
+            return Ok(());
+        }
+
+        // Either directly in <SimpleData>, or in <Data><value>
+        if current == "SimpleData" || current == "value" {
+            if let (Some(key), Some(p)) = (self.data_key.as_ref(), self.placemark.as_mut()) {
+                p.attributes.insert(key.to_string(), text);
+            }
+        }
+        Ok(())
+    }
+}
+
+fn parse_coordinates(text: &str, position: usize) -> Result<Vec<LonLat>, KmlError> {
+    let mut pts = Vec::new();
+    // Tuples are separated by any whitespace, including newlines
+    for tuple in text.split_whitespace() {
+        let bad = || KmlError::BadCoordinate {
+            position,
+            text: tuple.to_string(),
+        };
+        let parts: Vec<&str> = tuple.split(',').collect();
+        if parts.len() != 2 && parts.len() != 3 {
+            return Err(bad());
+        }
+        let mut nums = Vec::new();
+        for x in parts {
+            nums.push(x.parse::<f64>().map_err(|_| bad())?);
+        }
+        pts.push(LonLat::new(nums[0], nums[1]));
+    }
+    Ok(pts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(input: &str) -> Result<Vec<Placemark>, KmlError> {
+        parse_placemarks(std::io::BufReader::new(input.as_bytes()))
+    }
+
+    #[test]
+    fn test_nested_geometry() {
+        let placemarks = parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<kml xmlns="http://www.opengis.net/kml/2.2">
+<Document>
+  <Placemark>
+    <ExtendedData><SchemaData schemaUrl="foo">
+      <SimpleData name="ID">42</SimpleData>
+    </SchemaData></ExtendedData>
+    <Point><coordinates>-122.3,47.6,0</coordinates></Point>
+  </Placemark>
+  <Placemark>
+    <ExtendedData><Data name="kind"><value>park</value></Data></ExtendedData>
+    <MultiGeometry>
+      <LineString><coordinates>-122.3,47.6 -122.4,47.7</coordinates></LineString>
+      <Polygon>
+        <outerBoundaryIs><LinearRing><coordinates>
+          0,0,5 1,0,5 1,1,5 0,0,5
+        </coordinates></LinearRing></outerBoundaryIs>
+        <innerBoundaryIs><LinearRing><coordinates>
+          0.1,0.1 0.2,0.1 0.2,0.2 0.1,0.1
+        </coordinates></LinearRing></innerBoundaryIs>
+      </Polygon>
+    </MultiGeometry>
+  </Placemark>
+</Document>
+</kml>"#,
+        )
+        .unwrap();
+
+        assert_eq!(placemarks.len(), 2);
+        assert_eq!(placemarks[0].attributes["ID"], "42");
+        assert_eq!(
+            placemarks[0].geometry,
+            Some(Geometry::Point(LonLat::new(-122.3, 47.6)))
+        );
+
+        assert_eq!(placemarks[1].attributes["kind"], "park");
+        let geom = placemarks[1].geometry.as_ref().unwrap();
+        let leaves = geom.leaves();
+        assert_eq!(leaves.len(), 2);
+        assert_eq!(leaves[0].points().len(), 2);
+        match leaves[1] {
+            Geometry::Polygon { outer, holes } => {
+                assert_eq!(outer.len(), 4);
+                assert_eq!(holes.len(), 1);
+            }
+            _ => panic!("expected a polygon, got {:?}", leaves[1]),
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        match parse(
+            "<kml><Placemark><Point><coordinates>1,2,3,4</coordinates></Point></Placemark></kml>",
+        ) {
+            Err(KmlError::BadCoordinate { text, .. }) => assert_eq!(text, "1,2,3,4"),
+            x => panic!("expected a bad coordinate, got {:?}", x),
+        }
+        match parse("<kml><Placemark></kml>") {
+            Err(KmlError::Xml { .. }) => {}
+            x => panic!("expected an XML error, got {:?}", x),
+        }
+    }
+}
@@ -1,6 +1,7 @@
 use crate::raw::{OriginalIntersection, OriginalRoad};
 use crate::{
-    ControlStopSign, ControlTrafficSignal, IntersectionID, LaneID, LaneType, Map, RoadID, TurnID,
+    ControlStopSign, ControlTrafficSignal, IntersectionID, LaneID, LaneType, Map, RoadID,
+    TransitPriority, TurnID,
 };
 use abstutil::{deserialize_btreemap, retain_btreemap, retain_btreeset, serialize_btreemap, Timer};
 use geom::Speed;
@@ -11,12 +12,17 @@ use std::collections::{BTreeMap, BTreeSet};
 pub struct MapEdits {
     pub edits_name: String,
     pub commands: Vec<EditCmd>,
+    // Commands that were undone, with the most recently undone last. Any other new command clears
+    // this.
+    pub redo_stack: Vec<EditCmd>,
+    pub checkpoints: Vec<Checkpoint>,
 
     // Derived from commands, kept up to date by update_derived
     pub original_lts: BTreeMap<LaneID, LaneType>,
     pub reversed_lanes: BTreeSet<LaneID>,
     pub original_intersections: BTreeMap<IntersectionID, EditIntersection>,
     pub changed_speed_limits: BTreeSet<RoadID>,
+    pub changed_transit_priority: BTreeSet<IntersectionID>,
 
     // Edits without these are player generated.
     pub proposal_description: Vec<String>,
@@ -24,6 +30,13 @@ pub struct MapEdits {
     pub proposal_link: Option<String>,
 }
 
+// A named snapshot of the commands, so the player can flip between alternatives.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    pub name: String,
+    pub commands: Vec<EditCmd>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum EditIntersection {
     StopSign(ControlStopSign),
@@ -53,6 +66,11 @@ pub enum EditCmd {
         new: EditIntersection,
         old: EditIntersection,
     },
+    ChangeTransitPriority {
+        i: IntersectionID,
+        new: TransitPriority,
+        old: TransitPriority,
+    },
 }
 
 impl EditCmd {
@@ -66,6 +84,7 @@ impl EditCmd {
                 EditIntersection::TrafficSignal(_) => format!("traffic signal #{}", i.0),
                 EditIntersection::Closed => format!("close {}", i),
             },
+            EditCmd::ChangeTransitPriority { i, .. } => format!("transit priority #{}", i.0),
         }
     }
 }
@@ -85,11 +104,14 @@ impl MapEdits {
             proposal_description: Vec::new(),
             proposal_link: None,
             commands: Vec::new(),
+            redo_stack: Vec::new(),
+            checkpoints: Vec::new(),
 
             original_lts: BTreeMap::new(),
             reversed_lanes: BTreeSet::new(),
             original_intersections: BTreeMap::new(),
             changed_speed_limits: BTreeSet::new(),
+            changed_transit_priority: BTreeSet::new(),
         }
     }
 
@@ -113,11 +135,73 @@ impl MapEdits {
         );
     }
 
+    // These just change the list of commands; the caller still has to apply the edits.
+    pub fn undo(&mut self) -> Option<&EditCmd> {
+        let cmd = self.commands.pop()?;
+        self.redo_stack.push(cmd);
+        self.redo_stack.last()
+    }
+    pub fn redo(&mut self) -> Option<&EditCmd> {
+        let cmd = self.redo_stack.pop()?;
+        self.commands.push(cmd);
+        self.commands.last()
+    }
+
+    // Replaces any checkpoint with the same name
+    pub fn save_checkpoint(&mut self, name: String) {
+        self.checkpoints.retain(|cp| cp.name != name);
+        self.checkpoints.push(Checkpoint {
+            name,
+            commands: self.commands.clone(),
+        });
+    }
+
+    // Undoes and redoes to reach the checkpoint, so going back to a checkpoint from earlier in the
+    // current history can be redone. Commands that aren't in the history start a new branch,
+    // clearing the redo stack.
+    pub fn jump_to_checkpoint(&mut self, name: &str) -> Result<(), String> {
+        let target = self
+            .checkpoints
+            .iter()
+            .find(|cp| cp.name == name)
+            .ok_or(format!("No checkpoint named {}", name))?
+            .commands
+            .clone();
+        while !target.starts_with(&self.commands) {
+            self.undo();
+        }
+        while self.commands.len() < target.len()
+            && self.redo_stack.last() == Some(&target[self.commands.len()])
+        {
+            self.redo();
+        }
+        if self.commands.len() < target.len() {
+            self.redo_stack.clear();
+            self.commands
+                .extend(target[self.commands.len()..].iter().cloned());
+        }
+        Ok(())
+    }
+
+    // Undoing and redoing only move commands between the two stacks. Any other change starts a new
+    // branch of history, so the old redo stack doesn't apply anymore.
+    pub(crate) fn clear_stale_redo(&mut self, old: &MapEdits) {
+        if !self
+            .commands
+            .iter()
+            .chain(self.redo_stack.iter().rev())
+            .eq(old.commands.iter().chain(old.redo_stack.iter().rev()))
+        {
+            self.redo_stack.clear();
+        }
+    }
+
     pub(crate) fn update_derived(&mut self, map: &Map) {
         let mut orig_lts = BTreeMap::new();
         let mut reversed_lanes = BTreeSet::new();
         let mut orig_intersections: BTreeMap<IntersectionID, EditIntersection> = BTreeMap::new();
         let mut changed_speed_limits = BTreeSet::new();
+        let mut changed_transit_priority = BTreeSet::new();
 
         for cmd in &self.commands {
             match cmd {
@@ -141,6 +225,9 @@ impl MapEdits {
                         orig_intersections.insert(*i, old.clone());
                     }
                 }
+                EditCmd::ChangeTransitPriority { i, .. } => {
+                    changed_transit_priority.insert(*i);
+                }
             }
         }
 
@@ -151,11 +238,15 @@ impl MapEdits {
         retain_btreeset(&mut changed_speed_limits, |r| {
             map.get_r(*r).speed_limit != map.get_r(*r).speed_limit_from_osm()
         });
+        retain_btreeset(&mut changed_transit_priority, |i| {
+            map.get_i(*i).transit_priority != TransitPriority::none()
+        });
 
         self.original_lts = orig_lts;
         self.reversed_lanes = reversed_lanes;
         self.original_intersections = orig_intersections;
         self.changed_speed_limits = changed_speed_limits;
+        self.changed_transit_priority = changed_transit_priority;
     }
 
     // Assumes update_derived has been called.
@@ -187,6 +278,13 @@ impl MapEdits {
                 old: map.get_r(*r).speed_limit_from_osm(),
             });
         }
+        for i in &self.changed_transit_priority {
+            self.commands.push(EditCmd::ChangeTransitPriority {
+                i: *i,
+                new: map.get_i(*i).transit_priority,
+                old: TransitPriority::none(),
+            });
+        }
     }
 }
 
@@ -215,6 +313,9 @@ pub struct PermanentMapEdits {
     pub map_name: String,
     pub edits_name: String,
     commands: Vec<PermanentEditCmd>,
+    // The redo stack isn't saved; commands are compressed before saving anyway.
+    #[serde(default)]
+    checkpoints: Vec<PermanentCheckpoint>,
 
     // Edits without these are player generated.
     pub proposal_description: Vec<String>,
@@ -222,6 +323,12 @@ pub struct PermanentMapEdits {
     pub proposal_link: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+struct PermanentCheckpoint {
+    name: String,
+    commands: Vec<PermanentEditCmd>,
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 enum PermanentEditIntersection {
     StopSign {
@@ -267,6 +374,11 @@ enum PermanentEditCmd {
         new: PermanentEditIntersection,
         old: PermanentEditIntersection,
     },
+    ChangeTransitPriority {
+        i: OriginalIntersection,
+        new: TransitPriority,
+        old: TransitPriority,
+    },
 }
 
 impl PermanentMapEdits {
@@ -279,32 +391,18 @@ impl PermanentMapEdits {
             commands: edits
                 .commands
                 .iter()
-                .map(|cmd| match cmd {
-                    EditCmd::ChangeLaneType { id, lt, orig_lt } => {
-                        PermanentEditCmd::ChangeLaneType {
-                            id: OriginalLane::to_permanent(*id, map),
-                            lt: *lt,
-                            orig_lt: *orig_lt,
-                        }
-                    }
-                    EditCmd::ReverseLane { l, dst_i } => PermanentEditCmd::ReverseLane {
-                        l: OriginalLane::to_permanent(*l, map),
-                        dst_i: map.get_i(*dst_i).orig_id,
-                    },
-                    EditCmd::ChangeSpeedLimit { id, new, old } => {
-                        PermanentEditCmd::ChangeSpeedLimit {
-                            id: map.get_r(*id).orig_id,
-                            new: *new,
-                            old: *old,
-                        }
-                    }
-                    EditCmd::ChangeIntersection { i, new, old } => {
-                        PermanentEditCmd::ChangeIntersection {
-                            i: map.get_i(*i).orig_id,
-                            new: new.to_permanent(map),
-                            old: old.to_permanent(map),
-                        }
-                    }
+                .map(|cmd| cmd.to_permanent(map))
+                .collect(),
+            checkpoints: edits
+                .checkpoints
+                .iter()
+                .map(|cp| PermanentCheckpoint {
+                    name: cp.name.clone(),
+                    commands: cp
+                        .commands
+                        .iter()
+                        .map(|cmd| cmd.to_permanent(map))
+                        .collect(),
                 })
                 .collect(),
         }
@@ -318,45 +416,37 @@ impl PermanentMapEdits {
             commands: perma
                 .commands
                 .into_iter()
-                .map(|cmd| match cmd {
-                    PermanentEditCmd::ChangeLaneType { id, lt, orig_lt } => {
-                        let l = id.clone().from_permanent(map)?;
-                        if map.get_l(l).lane_type != orig_lt {
-                            return Err(format!("basemap lanetype of {:?} has changed", id));
+                .map(|cmd| cmd.from_permanent(map))
+                .collect::<Result<Vec<EditCmd>, String>>()?,
+            redo_stack: Vec::new(),
+            // A broken checkpoint shouldn't stop the rest of the edits from loading
+            checkpoints: perma
+                .checkpoints
+                .into_iter()
+                .filter_map(|cp| {
+                    match cp
+                        .commands
+                        .into_iter()
+                        .map(|cmd| cmd.from_permanent(map))
+                        .collect::<Result<Vec<EditCmd>, String>>()
+                    {
+                        Ok(commands) => Some(Checkpoint {
+                            name: cp.name,
+                            commands,
+                        }),
+                        Err(err) => {
+                            println!("Dropping checkpoint {}: {}", cp.name, err);
+                            None
                         }
-                        Ok(EditCmd::ChangeLaneType { id: l, lt, orig_lt })
-                    }
-                    PermanentEditCmd::ReverseLane { l, dst_i } => {
-                        let l = l.from_permanent(map)?;
-                        let dst_i = map.find_i_by_osm_id(dst_i.osm_node_id)?;
-                        Ok(EditCmd::ReverseLane { l, dst_i })
-                    }
-                    PermanentEditCmd::ChangeSpeedLimit { id, new, old } => {
-                        let id = map.find_r_by_osm_id(
-                            id.osm_way_id,
-                            (id.i1.osm_node_id, id.i2.osm_node_id),
-                        )?;
-                        Ok(EditCmd::ChangeSpeedLimit { id, new, old })
-                    }
-                    PermanentEditCmd::ChangeIntersection { i, new, old } => {
-                        let id = map.find_i_by_osm_id(i.osm_node_id)?;
-                        Ok(EditCmd::ChangeIntersection {
-                            i: id,
-                            new: new
-                                .from_permanent(id, map)
-                                .ok_or(format!("new ChangeIntersection of {} invalid", i))?,
-                            old: old
-                                .from_permanent(id, map)
-                                .ok_or(format!("old ChangeIntersection of {} invalid", i))?,
-                        })
                     }
                 })
-                .collect::<Result<Vec<EditCmd>, String>>()?,
+                .collect(),
 
             original_lts: BTreeMap::new(),
             reversed_lanes: BTreeSet::new(),
             original_intersections: BTreeMap::new(),
             changed_speed_limits: BTreeSet::new(),
+            changed_transit_priority: BTreeSet::new(),
         };
         edits.update_derived(map);
         Ok(edits)
@@ -381,6 +471,79 @@ impl EditIntersection {
     }
 }
 
+impl EditCmd {
+    fn to_permanent(&self, map: &Map) -> PermanentEditCmd {
+        match self {
+            EditCmd::ChangeLaneType { id, lt, orig_lt } => PermanentEditCmd::ChangeLaneType {
+                id: OriginalLane::to_permanent(*id, map),
+                lt: *lt,
+                orig_lt: *orig_lt,
+            },
+            EditCmd::ReverseLane { l, dst_i } => PermanentEditCmd::ReverseLane {
+                l: OriginalLane::to_permanent(*l, map),
+                dst_i: map.get_i(*dst_i).orig_id,
+            },
+            EditCmd::ChangeSpeedLimit { id, new, old } => PermanentEditCmd::ChangeSpeedLimit {
+                id: map.get_r(*id).orig_id,
+                new: *new,
+                old: *old,
+            },
+            EditCmd::ChangeIntersection { i, new, old } => PermanentEditCmd::ChangeIntersection {
+                i: map.get_i(*i).orig_id,
+                new: new.to_permanent(map),
+                old: old.to_permanent(map),
+            },
+            EditCmd::ChangeTransitPriority { i, new, old } => {
+                PermanentEditCmd::ChangeTransitPriority {
+                    i: map.get_i(*i).orig_id,
+                    new: *new,
+                    old: *old,
+                }
+            }
+        }
+    }
+}
+
+impl PermanentEditCmd {
+    fn from_permanent(self, map: &Map) -> Result<EditCmd, String> {
+        match self {
+            PermanentEditCmd::ChangeLaneType { id, lt, orig_lt } => {
+                let l = id.clone().from_permanent(map)?;
+                if map.get_l(l).lane_type != orig_lt {
+                    return Err(format!("basemap lanetype of {:?} has changed", id));
+                }
+                Ok(EditCmd::ChangeLaneType { id: l, lt, orig_lt })
+            }
+            PermanentEditCmd::ReverseLane { l, dst_i } => {
+                let l = l.from_permanent(map)?;
+                let dst_i = map.find_i_by_osm_id(dst_i.osm_node_id)?;
+                Ok(EditCmd::ReverseLane { l, dst_i })
+            }
+            PermanentEditCmd::ChangeSpeedLimit { id, new, old } => {
+                let id =
+                    map.find_r_by_osm_id(id.osm_way_id, (id.i1.osm_node_id, id.i2.osm_node_id))?;
+                Ok(EditCmd::ChangeSpeedLimit { id, new, old })
+            }
+            PermanentEditCmd::ChangeIntersection { i, new, old } => {
+                let id = map.find_i_by_osm_id(i.osm_node_id)?;
+                Ok(EditCmd::ChangeIntersection {
+                    i: id,
+                    new: new
+                        .from_permanent(id, map)
+                        .ok_or(format!("new ChangeIntersection of {} invalid", i))?,
+                    old: old
+                        .from_permanent(id, map)
+                        .ok_or(format!("old ChangeIntersection of {} invalid", i))?,
+                })
+            }
+            PermanentEditCmd::ChangeTransitPriority { i, new, old } => {
+                let i = map.find_i_by_osm_id(i.osm_node_id)?;
+                Ok(EditCmd::ChangeTransitPriority { i, new, old })
+            }
+        }
+    }
+}
+
 impl PermanentEditIntersection {
     fn from_permanent(self, i: IntersectionID, map: &Map) -> Option<EditIntersection> {
         match self {
@@ -442,3 +605,146 @@ impl OriginalLane {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cmd(id: usize) -> EditCmd {
+        EditCmd::ChangeSpeedLimit {
+            id: RoadID(id),
+            new: Speed::miles_per_hour(20.0),
+            old: Speed::miles_per_hour(25.0),
+        }
+    }
+
+    fn edits(cmds: Vec<usize>) -> MapEdits {
+        let mut edits = MapEdits::new();
+        edits.commands = cmds.into_iter().map(cmd).collect();
+        edits
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut e = edits(vec![1, 2]);
+        assert_eq!(e.undo(), Some(&cmd(2)));
+        assert_eq!(e.commands, vec![cmd(1)]);
+        assert_eq!(e.redo_stack, vec![cmd(2)]);
+        assert_eq!(e.undo(), Some(&cmd(1)));
+        assert_eq!(e.undo(), None);
+        assert_eq!(e.redo_stack, vec![cmd(2), cmd(1)]);
+
+        assert_eq!(e.redo(), Some(&cmd(1)));
+        assert_eq!(e.redo(), Some(&cmd(2)));
+        assert_eq!(e.redo(), None);
+        assert_eq!(e.commands, vec![cmd(1), cmd(2)]);
+        assert!(e.redo_stack.is_empty());
+    }
+
+    #[test]
+    fn new_command_clears_redo() {
+        let mut old = edits(vec![1, 2]);
+        old.undo();
+
+        // Redoing keeps the rest of the redo stack
+        let mut redone = old.clone();
+        redone.redo();
+        redone.clear_stale_redo(&old);
+        assert_eq!(redone.commands, vec![cmd(1), cmd(2)]);
+
+        // Anything else starts a new branch
+        let mut new = old.clone();
+        new.commands.push(cmd(3));
+        new.clear_stale_redo(&old);
+        assert_eq!(new.commands, vec![cmd(1), cmd(3)]);
+        assert!(new.redo_stack.is_empty());
+
+        // Undoing isn't a new command
+        let mut undone = old.clone();
+        undone.undo();
+        undone.clear_stale_redo(&old);
+        assert_eq!(undone.redo_stack, vec![cmd(2), cmd(1)]);
+
+        // Replacing the last command is a new branch, even though the length is the same
+        let mut replaced = old.clone();
+        replaced.commands.pop();
+        replaced.commands.push(cmd(3));
+        replaced.clear_stale_redo(&old);
+        assert!(replaced.redo_stack.is_empty());
+
+        // Adding and then removing a command leaves history alone
+        let mut reverted = old.clone();
+        reverted.commands.push(cmd(3));
+        reverted.commands.pop();
+        reverted.clear_stale_redo(&old);
+        assert_eq!(reverted.redo_stack, vec![cmd(2)]);
+    }
+
+    #[test]
+    fn checkpoints() {
+        let mut e = edits(vec![1]);
+        e.save_checkpoint("first".to_string());
+        e.commands.push(cmd(2));
+        e.save_checkpoint("second".to_string());
+        // Saving under an existing name replaces it
+        e.commands.push(cmd(3));
+        e.save_checkpoint("second".to_string());
+        assert_eq!(e.checkpoints.len(), 2);
+        assert_eq!(e.checkpoints[1].commands, vec![cmd(1), cmd(2), cmd(3)]);
+
+        // Going back to an earlier checkpoint can be redone
+        e.jump_to_checkpoint("first").unwrap();
+        assert_eq!(e.commands, vec![cmd(1)]);
+        assert_eq!(e.redo_stack, vec![cmd(3), cmd(2)]);
+        e.jump_to_checkpoint("second").unwrap();
+        assert_eq!(e.commands, vec![cmd(1), cmd(2), cmd(3)]);
+        assert!(e.redo_stack.is_empty());
+
+        // A checkpoint on another branch replaces the redo stack
+        e.jump_to_checkpoint("first").unwrap();
+        e.checkpoints.push(Checkpoint {
+            name: "other".to_string(),
+            commands: vec![cmd(1), cmd(4)],
+        });
+        e.jump_to_checkpoint("other").unwrap();
+        assert_eq!(e.commands, vec![cmd(1), cmd(4)]);
+        assert!(e.redo_stack.is_empty());
+
+        assert!(e.jump_to_checkpoint("missing").is_err());
+        assert_eq!(e.commands, vec![cmd(1), cmd(4)]);
+    }
+
+    #[test]
+    fn broken_checkpoint() {
+        let map = Map::blank();
+        let perma = PermanentMapEdits {
+            map_name: map.get_name().clone(),
+            edits_name: "test".to_string(),
+            commands: Vec::new(),
+            checkpoints: vec![
+                PermanentCheckpoint {
+                    name: "empty".to_string(),
+                    commands: Vec::new(),
+                },
+                // This road isn't in the map
+                PermanentCheckpoint {
+                    name: "broken".to_string(),
+                    commands: vec![PermanentEditCmd::ChangeSpeedLimit {
+                        id: OriginalRoad {
+                            osm_way_id: 1,
+                            i1: OriginalIntersection { osm_node_id: 2 },
+                            i2: OriginalIntersection { osm_node_id: 3 },
+                        },
+                        new: Speed::miles_per_hour(20.0),
+                        old: Speed::miles_per_hour(25.0),
+                    }],
+                },
+            ],
+            proposal_description: Vec::new(),
+            proposal_link: None,
+        };
+        let edits = PermanentMapEdits::from_permanent(perma, &map).unwrap();
+        assert_eq!(edits.checkpoints.len(), 1);
+        assert_eq!(edits.checkpoints[0].name, "empty");
+    }
+}
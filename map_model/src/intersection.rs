@@ -23,6 +23,25 @@ pub enum IntersectionType {
     Construction,
 }
 
+// Only meaningful for traffic signals.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+pub struct TransitPriority {
+    // Extend the current phase or end it early for buses approaching the intersection
+    pub signal_priority: bool,
+    // At the start of each phase, buses in bus-only lanes get a head start on general traffic, so
+    // they can jump ahead of the queue next to them.
+    pub queue_jump: bool,
+}
+
+impl TransitPriority {
+    pub fn none() -> TransitPriority {
+        TransitPriority {
+            signal_priority: false,
+            queue_jump: false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Intersection {
     pub id: IntersectionID,
@@ -33,6 +52,7 @@ pub struct Intersection {
 
     pub intersection_type: IntersectionType,
     pub orig_id: OriginalIntersection,
+    pub transit_priority: TransitPriority,
 
     // Note that a lane may belong to both incoming_lanes and outgoing_lanes.
     // TODO narrow down when and why. is it just sidewalks in weird cases?
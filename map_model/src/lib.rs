@@ -23,9 +23,9 @@ pub use crate::building::{Building, BuildingID, FrontPath, OffstreetParking};
 pub use crate::bus_stop::{BusRoute, BusRouteID, BusStop, BusStopID};
 pub use crate::city::City;
 pub use crate::edits::{
-    EditCmd, EditEffects, EditIntersection, MapEdits, OriginalLane, PermanentMapEdits,
+    Checkpoint, EditCmd, EditEffects, EditIntersection, MapEdits, OriginalLane, PermanentMapEdits,
 };
-pub use crate::intersection::{Intersection, IntersectionID, IntersectionType};
+pub use crate::intersection::{Intersection, IntersectionID, IntersectionType, TransitPriority};
 pub use crate::lane::{Lane, LaneID, LaneType, PARKING_LOT_SPOT_LENGTH, PARKING_SPOT_LENGTH};
 pub use crate::make::initial::lane_specs::RoadSpec;
 pub use crate::map::Map;
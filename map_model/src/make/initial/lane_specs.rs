@@ -1,13 +1,24 @@
-use crate::{osm, LaneType};
+use crate::{osm, LaneType, TurnType};
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::{fmt, iter};
 
 // (original direction, reversed direction)
 pub fn get_lane_types(osm_tags: &BTreeMap<String, String>) -> (Vec<LaneType>, Vec<LaneType>) {
+    let (fwd, back, _) = get_lane_types_and_problems(osm_tags);
+    (fwd, back)
+}
+
+// Like get_lane_types, but also describes tags that couldn't be interpreted, so they can be
+// reported when importing a whole map.
+pub fn get_lane_types_and_problems(
+    osm_tags: &BTreeMap<String, String>,
+) -> (Vec<LaneType>, Vec<LaneType>, Vec<String>) {
+    let mut problems = Vec::new();
+
     if let Some(s) = osm_tags.get(osm::SYNTHETIC_LANES) {
         if let Some(spec) = RoadSpec::parse(s.to_string()) {
-            return (spec.fwd, spec.back);
+            return (spec.fwd, spec.back, problems);
         } else {
             panic!("Bad {} RoadSpec: {}", osm::SYNTHETIC_LANES, s);
         }
@@ -15,26 +26,27 @@ pub fn get_lane_types(osm_tags: &BTreeMap<String, String>) -> (Vec<LaneType>, Ve
 
     // Easy special cases first.
     if osm_tags.get("junction") == Some(&"roundabout".to_string()) {
-        return (vec![LaneType::Driving, LaneType::Sidewalk], Vec::new());
+        return (
+            vec![LaneType::Driving, LaneType::Sidewalk],
+            Vec::new(),
+            problems,
+        );
     }
     if osm_tags.get(osm::HIGHWAY) == Some(&"footway".to_string()) {
-        return (vec![LaneType::Sidewalk], Vec::new());
+        return (vec![LaneType::Sidewalk], Vec::new(), problems);
     }
 
     // TODO Reversible roads should be handled differently?
     let oneway = osm_tags.get("oneway") == Some(&"yes".to_string())
         || osm_tags.get("oneway") == Some(&"reversible".to_string());
 
-    // How many driving lanes in each direction?
-    let num_driving_fwd = if let Some(n) = osm_tags
-        .get("lanes:forward")
-        .and_then(|num| num.parse::<usize>().ok())
-    {
+    // How many driving lanes in each direction? If the lane counts are missing, turn:lanes often
+    // implies them.
+    let turn_lanes_fwd = get_turn_lanes(osm_tags, true, &mut problems);
+    let turn_lanes_back = get_turn_lanes(osm_tags, false, &mut problems);
+    let num_driving_fwd = if let Some(n) = parse_count(osm_tags, "lanes:forward", &mut problems) {
         n
-    } else if let Some(n) = osm_tags
-        .get("lanes")
-        .and_then(|num| num.parse::<usize>().ok())
-    {
+    } else if let Some(n) = parse_count(osm_tags, "lanes", &mut problems) {
         if oneway {
             n
         } else if n % 2 == 0 {
@@ -43,19 +55,15 @@ pub fn get_lane_types(osm_tags: &BTreeMap<String, String>) -> (Vec<LaneType>, Ve
             // TODO Really, this is ambiguous, but...
             (n / 2).max(1)
         }
+    } else if let Some(ref lanes) = turn_lanes_fwd {
+        lanes.len()
     } else {
         // TODO Grrr.
         1
     };
-    let num_driving_back = if let Some(n) = osm_tags
-        .get("lanes:backward")
-        .and_then(|num| num.parse::<usize>().ok())
-    {
+    let num_driving_back = if let Some(n) = parse_count(osm_tags, "lanes:backward", &mut problems) {
         n
-    } else if let Some(n) = osm_tags
-        .get("lanes")
-        .and_then(|num| num.parse::<usize>().ok())
-    {
+    } else if let Some(n) = parse_count(osm_tags, "lanes", &mut problems) {
         if oneway {
             0
         } else if n % 2 == 0 {
@@ -64,6 +72,8 @@ pub fn get_lane_types(osm_tags: &BTreeMap<String, String>) -> (Vec<LaneType>, Ve
             // TODO Really, this is ambiguous, but...
             (n / 2).max(1)
         }
+    } else if let Some(ref lanes) = turn_lanes_back {
+        lanes.len()
     } else {
         // TODO Grrr.
         if oneway {
@@ -72,6 +82,24 @@ pub fn get_lane_types(osm_tags: &BTreeMap<String, String>) -> (Vec<LaneType>, Ve
             1
         }
     };
+    if let Some(ref lanes) = turn_lanes_fwd {
+        if lanes.len() != num_driving_fwd {
+            problems.push(format!(
+                "turn:lanes for {} lanes, but {} forward lanes",
+                lanes.len(),
+                num_driving_fwd
+            ));
+        }
+    }
+    if let Some(ref lanes) = turn_lanes_back {
+        if lanes.len() != num_driving_back {
+            problems.push(format!(
+                "turn:lanes:backward for {} lanes, but {} backward lanes",
+                lanes.len(),
+                num_driving_back
+            ));
+        }
+    }
 
     // Sup West Seattle
     let driving_lane = if osm_tags.get("access") == Some(&"no".to_string())
@@ -94,32 +122,38 @@ pub fn get_lane_types(osm_tags: &BTreeMap<String, String>) -> (Vec<LaneType>, Ve
     }
 
     if driving_lane == LaneType::Construction {
-        return (fwd_side, back_side);
+        return (fwd_side, back_side, problems);
     }
 
-    // TODO Handle bus lanes properly.
-    let has_bus_lane = osm_tags.contains_key("bus:lanes");
-    if has_bus_lane {
-        fwd_side.pop();
-        fwd_side.push(LaneType::Bus);
-        if !back_side.is_empty() {
-            back_side.pop();
-            back_side.push(LaneType::Bus);
+    // Bus lanes are included in the lane counts, so they replace the outermost driving lanes.
+    let (bus_fwd, bus_back) = if driving_lane == LaneType::Driving {
+        count_bus_lanes(osm_tags, oneway, &mut problems)
+    } else {
+        (0, 0)
+    };
+    for (side, num_bus) in vec![(&mut fwd_side, bus_fwd), (&mut back_side, bus_back)] {
+        let mut replaced = 0;
+        for lt in side.iter_mut().rev() {
+            if replaced == num_bus {
+                break;
+            }
+            if *lt == LaneType::Driving {
+                *lt = LaneType::Bus;
+                replaced += 1;
+            }
+        }
+        // A contraflow bus lane on a one-way street isn't counted in lanes
+        for _ in replaced..num_bus {
+            side.push(LaneType::Bus);
         }
     }
 
-    if osm_tags.get("cycleway") == Some(&"lane".to_string()) {
+    let (bike_fwd, bike_back) = get_bike_lanes(osm_tags, !back_side.is_empty(), &mut problems);
+    if bike_fwd {
         fwd_side.push(LaneType::Biking);
-        if !back_side.is_empty() {
-            back_side.push(LaneType::Biking);
-        }
-    } else {
-        if osm_tags.get("cycleway:right") == Some(&"lane".to_string()) {
-            fwd_side.push(LaneType::Biking);
-        }
-        if osm_tags.get("cycleway:left") == Some(&"lane".to_string()) {
-            back_side.push(LaneType::Biking);
-        }
+    }
+    if bike_back {
+        back_side.push(LaneType::Biking);
     }
 
     if driving_lane == LaneType::Driving {
@@ -152,7 +186,200 @@ pub fn get_lane_types(osm_tags: &BTreeMap<String, String>) -> (Vec<LaneType>, Ve
         back_side.push(LaneType::Sidewalk);
     }
 
-    (fwd_side, back_side)
+    (fwd_side, back_side, problems)
+}
+
+fn parse_count(
+    osm_tags: &BTreeMap<String, String>,
+    key: &str,
+    problems: &mut Vec<String>,
+) -> Option<usize> {
+    let value = osm_tags.get(key)?;
+    match value.parse::<usize>() {
+        Ok(n) => Some(n),
+        Err(_) => {
+            problems.push(format!("{}={}", key, value));
+            None
+        }
+    }
+}
+
+// (forwards, backwards). Includes lanes for buses and other public service vehicles.
+fn count_bus_lanes(
+    osm_tags: &BTreeMap<String, String>,
+    oneway: bool,
+    problems: &mut Vec<String>,
+) -> (usize, usize) {
+    let mut fwd = 0;
+    let mut back = 0;
+
+    // lanes:bus and lanes:psv, possibly split by direction
+    for mode in vec!["bus", "psv"] {
+        let dir_fwd = parse_count(osm_tags, &format!("lanes:{}:forward", mode), problems);
+        let dir_back = parse_count(osm_tags, &format!("lanes:{}:backward", mode), problems);
+        if dir_fwd.is_some() || dir_back.is_some() {
+            fwd += dir_fwd.unwrap_or(0);
+            back += dir_back.unwrap_or(0);
+        } else if let Some(n) = parse_count(osm_tags, &format!("lanes:{}", mode), problems) {
+            if oneway {
+                fwd += n;
+            } else {
+                if n % 2 == 1 {
+                    problems.push(format!(
+                        "lanes:{}={} on a two-way road, without a direction",
+                        mode, n
+                    ));
+                }
+                fwd += (n + 1) / 2;
+                back += n / 2;
+            }
+        }
+    }
+
+    // busway=lane and friends
+    let is_lane = |key: &str| osm_tags.get(key) == Some(&"lane".to_string());
+    if let Some(value) = osm_tags.get("busway") {
+        match value.as_str() {
+            "lane" => {
+                fwd += 1;
+                if !oneway {
+                    back += 1;
+                }
+            }
+            "opposite_lane" => {
+                back += 1;
+            }
+            "no" | "none" => {}
+            _ => {
+                problems.push(format!("busway={}", value));
+            }
+        }
+    }
+    if is_lane("busway:right") {
+        fwd += 1;
+    }
+    if is_lane("busway:left") {
+        back += 1;
+    }
+    if is_lane("busway:both") {
+        fwd += 1;
+        back += 1;
+    }
+
+    // bus:lanes lists an access value per lane, like "|designated"
+    if fwd == 0 && back == 0 {
+        for (key, is_fwd) in vec![
+            ("bus:lanes:forward", true),
+            ("bus:lanes:backward", false),
+            ("bus:lanes", true),
+        ] {
+            if let Some(value) = osm_tags.get(key) {
+                let n = value.split('|').filter(|x| *x == "designated").count();
+                if is_fwd {
+                    fwd = fwd.max(n);
+                } else {
+                    back = back.max(n);
+                }
+                // Mirror the forwards direction, when it's not specified
+                if key == "bus:lanes" && !oneway && !osm_tags.contains_key("bus:lanes:backward") {
+                    back = back.max(n.min(1));
+                }
+            }
+        }
+    }
+
+    (fwd, back)
+}
+
+// (forwards, backwards)
+fn get_bike_lanes(
+    osm_tags: &BTreeMap<String, String>,
+    has_back_lanes: bool,
+    problems: &mut Vec<String>,
+) -> (bool, bool) {
+    let mut fwd = false;
+    let mut back = false;
+    for (key, applies_fwd, applies_back) in vec![
+        ("cycleway", true, has_back_lanes),
+        ("cycleway:both", true, true),
+        ("cycleway:right", true, false),
+        ("cycleway:left", false, true),
+    ] {
+        if let Some(value) = osm_tags.get(key) {
+            match value.as_str() {
+                "lane" | "track" => {
+                    fwd |= applies_fwd;
+                    back |= applies_back;
+                }
+                // Contraflow, usually on a one-way street
+                "opposite_lane" | "opposite_track" => {
+                    back = true;
+                }
+                // Cyclists share space with something else
+                "opposite" | "shared_lane" | "share_busway" | "shoulder" | "separate" | "no"
+                | "none" => {}
+                _ => {
+                    problems.push(format!("{}={}", key, value));
+                }
+            }
+        }
+    }
+    (fwd, back)
+}
+
+// Interprets turn:lanes. Per lane, from left to right in the direction of travel, returns the
+// allowed movements. None for a lane means anything is allowed. None overall means the direction
+// doesn't have turn:lanes tagged.
+pub fn get_turn_lanes(
+    osm_tags: &BTreeMap<String, String>,
+    forwards: bool,
+    problems: &mut Vec<String>,
+) -> Option<Vec<Option<BTreeSet<TurnType>>>> {
+    let value = if forwards {
+        osm_tags.get("turn:lanes:forward").or_else(|| {
+            if osm_tags.get("oneway") == Some(&"yes".to_string()) {
+                osm_tags.get("turn:lanes")
+            } else {
+                None
+            }
+        })
+    } else {
+        osm_tags.get("turn:lanes:backward")
+    }?;
+
+    let mut result = Vec::new();
+    for lane in value.split('|') {
+        let mut allowed = BTreeSet::new();
+        let mut anything = false;
+        for movement in lane.split(';') {
+            match movement {
+                "" | "none" => {
+                    anything = true;
+                }
+                "left" | "slight_left" | "sharp_left" => {
+                    allowed.insert(TurnType::Left);
+                }
+                "right" | "slight_right" | "sharp_right" => {
+                    allowed.insert(TurnType::Right);
+                }
+                "through" | "merge_to_left" | "merge_to_right" => {
+                    allowed.insert(TurnType::Straight);
+                }
+                // U-turns aren't modeled
+                "reverse" => {}
+                _ => {
+                    problems.push(format!("turn:lanes with {}", movement));
+                    anything = true;
+                }
+            }
+        }
+        if anything || allowed.is_empty() {
+            result.push(None);
+        } else {
+            result.push(Some(allowed));
+        }
+    }
+    Some(result)
 }
 
 // This is a convenient way for map_editor to plumb instructions here.
@@ -225,3 +452,82 @@ impl RoadSpec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags(kv: Vec<&str>) -> BTreeMap<String, String> {
+        let mut tags = BTreeMap::new();
+        for pair in kv {
+            let parts: Vec<&str> = pair.split('=').collect();
+            tags.insert(parts[0].to_string(), parts[1].to_string());
+        }
+        tags
+    }
+
+    fn spec(osm_tags: BTreeMap<String, String>) -> (String, Vec<String>) {
+        let (fwd, back, problems) = get_lane_types_and_problems(&osm_tags);
+        (RoadSpec { fwd, back }.to_string(), problems)
+    }
+
+    #[test]
+    fn test_lane_specs() {
+        // The number of lanes comes from turn:lanes
+        assert_eq!(
+            spec(tags(vec![
+                "oneway=yes",
+                "turn:lanes=left|through|through;right"
+            ])),
+            ("ddd/".to_string(), Vec::new())
+        );
+        // Bus lanes are part of the lane count
+        assert_eq!(
+            spec(tags(vec!["lanes=4", "lanes:bus=2", "sidewalk=both"])),
+            ("dus/dus".to_string(), Vec::new())
+        );
+        assert_eq!(
+            spec(tags(vec!["oneway=yes", "lanes=2", "busway=opposite_lane"])),
+            ("dd/u".to_string(), Vec::new())
+        );
+        // Contraflow cycling
+        assert_eq!(
+            spec(tags(vec![
+                "oneway=yes",
+                "cycleway=opposite_lane",
+                "cycleway:right=track"
+            ])),
+            ("db/b".to_string(), Vec::new())
+        );
+        assert_eq!(
+            spec(tags(vec!["cycleway=lanee"])),
+            ("d/d".to_string(), vec!["cycleway=lanee".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_turn_lanes() {
+        let mut problems = Vec::new();
+        let lanes = get_turn_lanes(
+            &tags(vec!["turn:lanes:forward=left||through;slight_right"]),
+            true,
+            &mut problems,
+        )
+        .unwrap();
+        assert_eq!(lanes.len(), 3);
+        assert_eq!(lanes[0], Some(vec![TurnType::Left].into_iter().collect()));
+        assert_eq!(lanes[1], None);
+        assert_eq!(
+            lanes[2],
+            Some(
+                vec![TurnType::Straight, TurnType::Right]
+                    .into_iter()
+                    .collect()
+            )
+        );
+        assert!(problems.is_empty());
+        assert!(
+            get_turn_lanes(&tags(vec!["turn:lanes:forward=left"]), false, &mut problems).is_none()
+        );
+    }
+}
@@ -4,7 +4,7 @@ pub mod lane_specs;
 pub use self::geometry::intersection_polygon;
 use crate::raw::{OriginalIntersection, OriginalRoad, RawMap, RawRoad};
 use crate::{IntersectionType, LaneType, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS};
-use abstutil::Timer;
+use abstutil::{Counter, Timer};
 use geom::{Bounds, Distance, PolyLine, Pt2D};
 use std::collections::{BTreeMap, BTreeSet};
 
@@ -89,6 +89,7 @@ impl InitialMap {
             );
         }
 
+        let mut lane_problems: Counter<String> = Counter::new();
         for (id, r) in &raw.roads {
             if id.i1 == id.i2 {
                 timer.warn(format!("Skipping loop {}", id));
@@ -97,8 +98,20 @@ impl InitialMap {
             m.intersections.get_mut(&id.i1).unwrap().roads.insert(*id);
             m.intersections.get_mut(&id.i2).unwrap().roads.insert(*id);
 
+            for problem in lane_specs::get_lane_types_and_problems(&r.osm_tags).2 {
+                lane_problems.inc(problem);
+            }
             m.roads.insert(*id, Road::new(*id, r));
         }
+        if lane_problems.sum() > 0 {
+            timer.note(format!(
+                "{} lane tags couldn't be interpreted:",
+                lane_problems.sum()
+            ));
+            for (problem, count) in lane_problems.consume() {
+                timer.note(format!("- {} ({} roads)", problem, count));
+            }
+        }
 
         timer.start_iter("find each intersection polygon", m.intersections.len());
         for i in m.intersections.values_mut() {
@@ -1,3 +1,4 @@
+use crate::make::initial::lane_specs::get_turn_lanes;
 use crate::raw::{DrivingSide, RestrictionType};
 use crate::{
     Intersection, IntersectionID, Lane, LaneID, LaneType, Road, RoadID, Turn, TurnID, TurnType,
@@ -19,7 +20,7 @@ pub fn make_all_turns(
     assert!(!i.is_border());
 
     let mut raw_turns: Vec<Turn> = Vec::new();
-    raw_turns.extend(make_vehicle_turns(i, roads, lanes, timer));
+    raw_turns.extend(make_vehicle_turns(driving_side, i, roads, lanes, timer));
     raw_turns.extend(make_walking_turns(driving_side, i, roads, lanes, timer));
     let unique_turns = ensure_unique(raw_turns);
 
@@ -101,6 +102,7 @@ fn ensure_unique(turns: Vec<Turn>) -> Vec<Turn> {
 }
 
 fn make_vehicle_turns(
+    driving_side: DrivingSide,
     i: &Intersection,
     all_roads: &Vec<Road>,
     lanes: &Vec<Lane>,
@@ -258,7 +260,75 @@ fn make_vehicle_turns(
         }
     }
 
-    result.into_iter().filter_map(|x| x).collect()
+    let turns = result.into_iter().filter_map(|x| x).collect();
+    apply_turn_lanes(driving_side, i, &sorted_roads, turns, timer)
+}
+
+// If incoming roads have turn:lanes tagged, only allow the indicated movements from each lane.
+// Dedicated left- or right-only lanes are the main thing this captures.
+fn apply_turn_lanes(
+    driving_side: DrivingSide,
+    i: &Intersection,
+    roads: &Vec<&Road>,
+    mut turns: Vec<Turn>,
+    timer: &mut Timer,
+) -> Vec<Turn> {
+    for r in roads {
+        let forwards = r.dst_i == i.id;
+        let spec = match get_turn_lanes(&r.osm_tags, forwards, &mut Vec::new()) {
+            Some(spec) => spec,
+            None => {
+                continue;
+            }
+        };
+        // turn:lanes covers lanes for motor vehicles, listed left to right.
+        let mut incoming: Vec<LaneID> = r
+            .incoming_lanes(i.id)
+            .iter()
+            .filter(|(_, lt)| *lt == LaneType::Driving || *lt == LaneType::Bus)
+            .map(|(l, _)| *l)
+            .collect();
+        // Lanes are ordered from the center of the road out.
+        if driving_side == DrivingSide::Left {
+            incoming.reverse();
+        }
+        if incoming.len() != spec.len() {
+            timer.warn(format!(
+                "{} has turn:lanes for {} lanes, but {} incoming lanes at {}",
+                r.id,
+                spec.len(),
+                incoming.len(),
+                i.id
+            ));
+            continue;
+        }
+
+        for (l, allowed) in incoming.into_iter().zip(spec.into_iter()) {
+            let allowed = match allowed {
+                Some(x) => x,
+                None => {
+                    continue;
+                }
+            };
+            let is_allowed = |t: &Turn| {
+                t.id.src != l
+                    || allowed.contains(&match t.turn_type {
+                        TurnType::LaneChangeLeft | TurnType::LaneChangeRight => TurnType::Straight,
+                        tt => tt,
+                    })
+            };
+            // Don't orphan the lane if the tagging disagrees with the geometry.
+            if turns.iter().any(|t| t.id.src == l && is_allowed(t)) {
+                turns.retain(is_allowed);
+            } else {
+                timer.warn(format!(
+                    "turn:lanes on {} would leave {} without any turns at {}",
+                    r.id, l, i.id
+                ));
+            }
+        }
+    }
+    turns
 }
 
 fn make_vehicle_turns_for_dead_end(
@@ -4,8 +4,8 @@ use crate::{
     connectivity, make, osm, Area, AreaID, Building, BuildingID, BusRoute, BusRouteID, BusStop,
     BusStopID, ControlStopSign, ControlTrafficSignal, EditCmd, EditEffects, EditIntersection,
     Intersection, IntersectionID, IntersectionType, Lane, LaneID, LaneType, MapEdits, ParkingLot,
-    ParkingLotID, Path, PathConstraints, PathRequest, Position, Road, RoadID, Turn, TurnGroupID,
-    TurnID, TurnType, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
+    ParkingLotID, Path, PathConstraints, PathRequest, Position, Road, RoadID, TransitPriority,
+    Turn, TurnGroupID, TurnID, TurnType, NORMAL_LANE_THICKNESS, SIDEWALK_THICKNESS,
 };
 use abstutil::{deserialize_btreemap, serialize_btreemap, Error, Timer, Warn};
 use geom::{Angle, Bounds, Distance, GPSBounds, Line, PolyLine, Polygon, Pt2D, Speed};
@@ -673,7 +673,7 @@ impl Map {
         &self,
         start: Position,
         end: Position,
-    ) -> Option<(BusStopID, BusStopID, BusRouteID)> {
+    ) -> Option<Vec<(BusStopID, BusStopID, BusRouteID)>> {
         self.pathfinder
             .as_ref()
             .unwrap()
@@ -815,6 +815,8 @@ impl Map {
         // Simplest strategy: Remove common prefix.
         let mut effects = EditEffects::new();
 
+        new_edits.clear_stale_redo(&self.edits);
+
         // First undo all existing edits.
         let mut undo = std::mem::replace(&mut self.edits.commands, Vec::new());
         undo.reverse();
@@ -944,6 +946,7 @@ fn make_half_map(
             // Might change later
             intersection_type: i.intersection_type,
             orig_id: i.id,
+            transit_priority: TransitPriority::none(),
             incoming_lanes: Vec::new(),
             outgoing_lanes: Vec::new(),
             roads: i.roads.iter().map(|id| road_id_mapping[id]).collect(),
@@ -1276,6 +1279,15 @@ impl EditCmd {
                 }
                 true
             }
+            EditCmd::ChangeTransitPriority { i, new, .. } => {
+                if map.intersections[i.0].transit_priority != *new {
+                    map.intersections[i.0].transit_priority = *new;
+                    effects.changed_intersections.insert(*i);
+                    true
+                } else {
+                    false
+                }
+            }
         }
     }
 
@@ -1320,7 +1332,37 @@ impl EditCmd {
                 new: old.clone(),
             }
             .apply(effects, map, timer),
+            EditCmd::ChangeTransitPriority { i, old, .. } => {
+                if map.intersections[i.0].transit_priority != *old {
+                    map.intersections[i.0].transit_priority = *old;
+                    effects.changed_intersections.insert(*i);
+                    true
+                } else {
+                    false
+                }
+            }
+        }
+    }
+
+    // The roads and intersections that apply or undo would touch, without actually changing the
+    // map. Turns aren't calculated.
+    pub fn preview_effects(&self, map: &Map) -> EditEffects {
+        let mut effects = EditEffects::new();
+        match self {
+            EditCmd::ChangeLaneType { id, .. } | EditCmd::ReverseLane { l: id, .. } => {
+                let lane = map.get_l(*id);
+                effects.changed_roads.insert(lane.parent);
+                effects.changed_intersections.insert(lane.src_i);
+                effects.changed_intersections.insert(lane.dst_i);
+            }
+            EditCmd::ChangeSpeedLimit { id, .. } => {
+                effects.changed_roads.insert(*id);
+            }
+            EditCmd::ChangeIntersection { i, .. } | EditCmd::ChangeTransitPriority { i, .. } => {
+                effects.changed_intersections.insert(*i);
+            }
         }
+        effects
     }
 }
 
@@ -525,7 +525,7 @@ impl Pathfinder {
         map: &Map,
         start: Position,
         end: Position,
-    ) -> Option<(BusStopID, BusStopID, BusRouteID)> {
+    ) -> Option<Vec<(BusStopID, BusStopID, BusRouteID)>> {
         self.walking_with_transit_graph
             .as_ref()
             .unwrap()
@@ -4,7 +4,7 @@ use crate::{
     BusRouteID, BusStopID, LaneID, Map, Path, PathConstraints, PathRequest, PathStep, Position,
 };
 use fast_paths::{deserialize_32, serialize_32, FastGraph, InputGraph, PathCalculator};
-use geom::{Distance, Speed};
+use geom::{Distance, Duration, Speed};
 use serde::{Deserialize, Serialize};
 use std::cell::RefCell;
 use thread_local::ThreadLocal;
@@ -25,9 +25,16 @@ pub struct SidewalkPathfinder {
 enum Node {
     // false is src_i, true is dst_i
     SidewalkEndpoint(LaneID, bool),
-    RideBus(BusStopID),
+    // Standing at the stop, not on any bus yet
+    WaitForBus(BusStopID),
+    // On a bus of this route, currently at this stop
+    RideBus(BusRouteID, BusStopID),
 }
 
+// Boarding any bus costs this much on top of the expected wait, so that a path with more
+// transfers has to save real time to be preferred.
+const BOARDING_PENALTY: Duration = Duration::const_seconds(60.0);
+
 impl SidewalkPathfinder {
     pub fn new(map: &Map, use_transit: bool, bus_graph: &VehiclePathfinder) -> SidewalkPathfinder {
         let mut nodes = NodeMap::new();
@@ -39,9 +46,14 @@ impl SidewalkPathfinder {
             }
         }
         if use_transit {
-            // Add a node for each bus stop.
+            // Add a node for each bus stop, and one for each route serving it.
             for stop in map.all_bus_stops().values() {
-                nodes.get_or_insert(Node::RideBus(stop.id));
+                nodes.get_or_insert(Node::WaitForBus(stop.id));
+            }
+            for route in map.get_all_bus_routes() {
+                for stop in &route.stops {
+                    nodes.get_or_insert(Node::RideBus(route.id, *stop));
+                }
             }
         }
 
@@ -105,11 +117,11 @@ impl SidewalkPathfinder {
         for pair in path.windows(2) {
             let (l1, l1_endpt) = match pair[0] {
                 Node::SidewalkEndpoint(l, endpt) => (l, endpt),
-                Node::RideBus(_) => unreachable!(),
+                Node::WaitForBus(_) | Node::RideBus(_, _) => unreachable!(),
             };
             let l2 = match pair[1] {
                 Node::SidewalkEndpoint(l, _) => l,
-                Node::RideBus(_) => unreachable!(),
+                Node::WaitForBus(_) | Node::RideBus(_, _) => unreachable!(),
             };
 
             if l1 == l2 {
@@ -154,40 +166,40 @@ impl SidewalkPathfinder {
         Some(Path::new(map, steps, req.end.dist_along()))
     }
 
-    // Attempt the pathfinding and see if we should ride a bus.
+    // Attempt the pathfinding and see if we should ride a bus. If so, returns (board at, alight
+    // at, route) for each bus ridden, in order. Transfers may involve walking between stops.
     pub fn should_use_transit(
         &self,
         map: &Map,
         start: Position,
         end: Position,
-    ) -> Option<(BusStopID, BusStopID, BusRouteID)> {
+    ) -> Option<Vec<(BusStopID, BusStopID, BusRouteID)>> {
         let raw_path = fast_paths::calc_path(
             &self.graph,
             self.nodes.get(closest_node(start, map)),
             self.nodes.get(closest_node(end, map)),
         )?;
 
-        let mut nodes = self.nodes.translate(&raw_path);
-        let mut first_stop = None;
-        for n in &nodes {
-            if let Node::RideBus(stop) = n {
-                first_stop = Some(*stop);
-                break;
-            }
-        }
-        let first_stop = first_stop?;
-        let possible_routes = map.get_routes_serving_stop(first_stop);
-
-        nodes.reverse();
-        for n in nodes {
-            if let Node::RideBus(stop2) = n {
-                if let Some(route) = possible_routes.iter().find(|r| r.stops.contains(&stop2)) {
-                    assert_ne!(first_stop, stop2);
-                    return Some((first_stop, stop2, route.id));
+        let mut rides = Vec::new();
+        let mut boarded_at = None;
+        for pair in self.nodes.translate(&raw_path).windows(2) {
+            match (pair[0], pair[1]) {
+                (Node::WaitForBus(stop1), Node::RideBus(_, _)) => {
+                    boarded_at = Some(stop1);
+                }
+                (Node::RideBus(route, stop2), Node::WaitForBus(_)) => {
+                    let stop1 = boarded_at.take().unwrap();
+                    assert_ne!(stop1, stop2);
+                    rides.push((stop1, stop2, route));
                 }
+                _ => {}
             }
         }
-        unreachable!()
+        if rides.is_empty() {
+            None
+        } else {
+            Some(rides)
+        }
     }
 }
 
@@ -225,7 +237,7 @@ fn make_input_graph(
     if use_transit {
         // Connect bus stops with both sidewalk endpoints, using the appropriate distance.
         for stop in map.all_bus_stops().values() {
-            let ride_bus = nodes.get(Node::RideBus(stop.id));
+            let wait = nodes.get(Node::WaitForBus(stop.id));
             let lane = map.get_l(stop.sidewalk_pos.lane());
             for endpt in &[true, false] {
                 let cost = if *endpt {
@@ -237,14 +249,15 @@ fn make_input_graph(
                 // might try to pass through it uselessly.
                 let penalty = 100;
                 let sidewalk = nodes.get(Node::SidewalkEndpoint(lane.id, *endpt));
-                input_graph.add_edge(sidewalk, ride_bus, cost + penalty);
-                input_graph.add_edge(ride_bus, sidewalk, cost + penalty);
+                input_graph.add_edge(sidewalk, wait, cost + penalty);
+                input_graph.add_edge(wait, sidewalk, cost + penalty);
             }
         }
 
-        // Connect each adjacent stop along a route, with the cost based on how long it'll take a
-        // bus to drive between the stops. Optimistically assume no waiting time at a stop.
         for route in map.get_all_bus_routes() {
+            // Connect each adjacent stop along a route, with the cost based on how long it'll
+            // take a bus to drive between the stops.
+            let mut cycle_time = 0;
             for (stop1, stop2) in
                 route
                     .stops
@@ -264,10 +277,11 @@ fn make_input_graph(
                     map,
                 ) {
                     input_graph.add_edge(
-                        nodes.get(Node::RideBus(*stop1)),
-                        nodes.get(Node::RideBus(*stop2)),
+                        nodes.get(Node::RideBus(route.id, *stop1)),
+                        nodes.get(Node::RideBus(route.id, *stop2)),
                         driving_cost,
                     );
+                    cycle_time += driving_cost;
                 } else {
                     panic!(
                         "No bus route from {} to {} now! Prevent this edit",
@@ -276,6 +290,17 @@ fn make_input_graph(
                     );
                 }
             }
+
+            // The sim runs one bus per route, so the headway is however long that bus takes to
+            // loop around. On average, somebody arriving at a stop waits half of that.
+            let expected_wait = cycle_time / 2 + BOARDING_PENALTY.inner_seconds() as usize;
+            for stop in &route.stops {
+                let wait = nodes.get(Node::WaitForBus(*stop));
+                let ride = nodes.get(Node::RideBus(route.id, *stop));
+                input_graph.add_edge(wait, ride, expected_wait);
+                // Round up! 0 cost edges are ignored
+                input_graph.add_edge(ride, wait, 1);
+            }
         }
     }
     input_graph.freeze();
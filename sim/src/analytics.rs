@@ -1,4 +1,7 @@
-use crate::{AlertLocation, CarID, Event, ParkingSpot, TripID, TripMode, TripPhaseType};
+use crate::{
+    AlertLocation, CarID, Event, InfectionSpace, ParkingSpot, PassengerCapacity, PersonID, TripID,
+    TripMode, TripPhaseType,
+};
 use abstutil::Counter;
 use geom::{Distance, Duration, Histogram, Time};
 use map_model::{
@@ -17,10 +20,16 @@ pub struct Analytics {
     pub demand: BTreeMap<TurnGroupID, usize>,
     pub bus_arrivals: Vec<(Time, CarID, BusRouteID, BusStopID)>,
     pub bus_passengers_waiting: Vec<(Time, BusStopID, BusRouteID)>,
+    // Recorded when a bus leaves a stop: (time, route, departing stop, passengers, capacity)
+    pub bus_loads: Vec<(Time, BusRouteID, BusStopID, usize, PassengerCapacity)>,
+    // (time, stop, route, when they started waiting)
+    pub bus_denied_boardings: Vec<(Time, BusStopID, BusRouteID, Time)>,
     pub started_trips: BTreeMap<TripID, Time>,
     // TODO Hack: No TripMode means aborted
     // Finish time, ID, mode (or None as aborted), trip duration
     pub finished_trips: Vec<(Time, TripID, Option<TripMode>, Duration)>,
+    // Trips that never started, because of some policy
+    pub cancelled_trips: Vec<(Time, TripID)>,
     // TODO This subsumes finished_trips
     pub trip_log: Vec<(Time, TripID, Option<PathRequest>, TripPhaseType)>,
     pub intersection_delays: BTreeMap<IntersectionID, Vec<(Time, Duration, TripMode)>>,
@@ -28,6 +37,10 @@ pub struct Analytics {
     pub parking_lane_changes: BTreeMap<LaneID, Vec<(Time, bool)>>,
     pub parking_lot_changes: BTreeMap<ParkingLotID, Vec<(Time, bool)>>,
     pub(crate) alerts: Vec<(Time, AlertLocation, String)>,
+    // Only recorded when the pandemic model is enabled. Once a day: (time, sane, exposed,
+    // infectious, recovered, dead)
+    pub pandemic_counts: Vec<(Time, usize, usize, usize, usize, usize)>,
+    pub pandemic_exposures: Vec<(Time, PersonID, InfectionSpace)>,
 
     // After we restore from a savestate, don't record anything. This is only going to make sense
     // if savestates are only used for quickly previewing against prebaked results, where we have
@@ -43,13 +56,18 @@ impl Analytics {
             demand: BTreeMap::new(),
             bus_arrivals: Vec::new(),
             bus_passengers_waiting: Vec::new(),
+            bus_loads: Vec::new(),
+            bus_denied_boardings: Vec::new(),
             started_trips: BTreeMap::new(),
             finished_trips: Vec::new(),
+            cancelled_trips: Vec::new(),
             trip_log: Vec::new(),
             intersection_delays: BTreeMap::new(),
             parking_lane_changes: BTreeMap::new(),
             parking_lot_changes: BTreeMap::new(),
             alerts: Vec::new(),
+            pandemic_counts: Vec::new(),
+            pandemic_exposures: Vec::new(),
             record_anything: true,
         }
     }
@@ -93,6 +111,14 @@ impl Analytics {
                 self.bus_passengers_waiting.push((time, *stop, *route));
             }
         }
+        if let Event::BusDepartedFromStop(_, route, stop, passengers, capacity) = ev {
+            self.bus_loads
+                .push((time, route, stop, passengers, capacity));
+        }
+        if let Event::PassengerDeniedBoarding(_, _, route, stop, started_waiting) = ev {
+            self.bus_denied_boardings
+                .push((time, stop, route, started_waiting));
+        }
 
         // Started trips
         if let Event::TripPhaseStarting(id, _, _, _) = ev {
@@ -112,6 +138,8 @@ impl Analytics {
         } else if let Event::TripAborted(id) = ev {
             self.started_trips.entry(id).or_insert(time);
             self.finished_trips.push((time, id, None, Duration::ZERO));
+        } else if let Event::TripCancelled(id) = ev {
+            self.cancelled_trips.push((time, id));
         }
 
         // Intersection delays
@@ -168,6 +196,19 @@ impl Analytics {
             Event::Alert(loc, msg) => {
                 self.alerts.push((time, loc, msg));
             }
+            Event::PandemicExposure(person, space) => {
+                self.pandemic_exposures.push((time, person, space));
+            }
+            Event::PandemicCounts {
+                sane,
+                exposed,
+                infectious,
+                recovered,
+                dead,
+            } => {
+                self.pandemic_counts
+                    .push((time, sane, exposed, infectious, recovered, dead));
+            }
             _ => {}
         }
     }
@@ -325,6 +366,7 @@ impl Analytics {
         now: Time,
         r: BusRouteID,
     ) -> BTreeMap<BusStopID, Histogram<Duration>> {
+        // (when this was recorded, when they started waiting, left behind by a full bus)
         let mut waiting_per_stop = BTreeMap::new();
         for (t, stop, route) in &self.bus_passengers_waiting {
             if *t > now {
@@ -334,7 +376,20 @@ impl Analytics {
                 waiting_per_stop
                     .entry(*stop)
                     .or_insert_with(Vec::new)
-                    .push(*t);
+                    .push((*t, *t, false));
+            }
+        }
+        // Somebody left behind when a bus arrives is still waiting, from their original start.
+        // If they were denied the moment they started waiting, the entry above covers them.
+        for (t, stop, route, started) in &self.bus_denied_boardings {
+            if *t > now {
+                break;
+            }
+            if *route == r && started < t {
+                waiting_per_stop
+                    .entry(*stop)
+                    .or_insert_with(Vec::new)
+                    .push((*t, *started, true));
             }
         }
 
@@ -344,7 +399,10 @@ impl Analytics {
             }
             if *route == r {
                 if let Some(ref mut times) = waiting_per_stop.get_mut(stop) {
-                    times.retain(|time| *time > *t);
+                    // Being left behind happens at the same time the bus arrives.
+                    times.retain(|(time, _, left_behind)| {
+                        *time > *t || (*left_behind && *time == *t)
+                    });
                 }
             }
         }
@@ -353,7 +411,7 @@ impl Analytics {
             .into_iter()
             .filter_map(|(k, v)| {
                 let mut delays = Histogram::new();
-                for t in v {
+                for (_, t, _) in v {
                     delays.add(now - t);
                 }
                 if delays.count() == 0 {
@@ -365,6 +423,58 @@ impl Analytics {
             .collect()
     }
 
+    // For each stop, how full are buses leaving it for the next stop? Expressed as a percent of
+    // the total capacity.
+    pub fn bus_load_factors(
+        &self,
+        now: Time,
+        r: BusRouteID,
+    ) -> BTreeMap<BusStopID, Histogram<usize>> {
+        let mut per_stop: BTreeMap<BusStopID, Histogram<usize>> = BTreeMap::new();
+        for (t, route, stop, passengers, capacity) in &self.bus_loads {
+            if *t > now {
+                break;
+            }
+            if *route == r {
+                per_stop
+                    .entry(*stop)
+                    .or_insert_with(Histogram::new)
+                    .add(100 * passengers / capacity.total());
+            }
+        }
+        per_stop
+    }
+
+    // How many times has somebody been unable to board a full bus at each stop?
+    pub fn bus_denied_boardings(&self, now: Time, r: BusRouteID) -> Counter<BusStopID> {
+        let mut cnt = Counter::new();
+        for (t, stop, route, _) in &self.bus_denied_boardings {
+            if *t > now {
+                break;
+            }
+            if *route == r {
+                cnt.inc(*stop);
+            }
+        }
+        cnt
+    }
+
+    // For each day so far, how many people were exposed in each kind of space?
+    pub fn pandemic_exposures_per_day(&self, now: Time) -> Vec<Counter<InfectionSpace>> {
+        let mut per_day = Vec::new();
+        for (t, _, space) in &self.pandemic_exposures {
+            if *t > now {
+                break;
+            }
+            let day = ((*t - Time::START_OF_DAY) / Duration::hours(24)).floor() as usize;
+            while per_day.len() <= day {
+                per_day.push(Counter::new());
+            }
+            per_day[day].inc(*space);
+        }
+        per_day
+    }
+
     pub fn get_trip_phases(&self, trip: TripID, map: &Map) -> Vec<TripPhase> {
         let mut phases: Vec<TripPhase> = Vec::new();
         for (t, id, maybe_req, phase_type) in &self.trip_log {
@@ -679,3 +789,35 @@ impl Window {
         self.times.len()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exposures_per_day() {
+        let day = |d: usize, h: usize| Time::START_OF_DAY + Duration::hours(24 * d + h);
+        let mut analytics = Analytics::new();
+        analytics.pandemic_exposures = vec![
+            (day(0, 1), PersonID(0), InfectionSpace::Building),
+            (day(0, 23), PersonID(1), InfectionSpace::Bus),
+            (day(0, 23), PersonID(2), InfectionSpace::Bus),
+            // Midnight starts the next day
+            (day(2, 0), PersonID(3), InfectionSpace::BusStop),
+            (day(3, 1), PersonID(4), InfectionSpace::Building),
+        ];
+
+        let per_day = analytics.pandemic_exposures_per_day(day(2, 12));
+        // Nothing happened on the second day, and the fourth hasn't happened yet
+        assert_eq!(per_day.len(), 3);
+        assert_eq!(per_day[0].get(InfectionSpace::Building), 1);
+        assert_eq!(per_day[0].get(InfectionSpace::Bus), 2);
+        assert_eq!(per_day[1].sum(), 0);
+        assert_eq!(per_day[2].get(InfectionSpace::BusStop), 1);
+        assert_eq!(per_day[2].sum(), 1);
+
+        assert!(analytics
+            .pandemic_exposures_per_day(Time::START_OF_DAY)
+            .is_empty());
+    }
+}
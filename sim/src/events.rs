@@ -1,7 +1,9 @@
 use crate::{
-    AgentID, CarID, OffMapLocation, ParkingSpot, PedestrianID, PersonID, TripID, TripMode,
+    AgentID, CarID, InfectionSpace, OffMapLocation, ParkingSpot, PassengerCapacity, PedestrianID,
+    PersonID, TripID, TripMode,
 };
-use geom::Duration;
+use abstutil::Catalog;
+use geom::{Duration, Time};
 use map_model::{
     BuildingID, BusRouteID, BusStopID, IntersectionID, LaneID, Map, Path, PathRequest, Traversable,
 };
@@ -16,7 +18,10 @@ pub enum Event {
     CarLeftParkingSpot(CarID, ParkingSpot),
 
     BusArrivedAtStop(CarID, BusRouteID, BusStopID),
-    BusDepartedFromStop(CarID, BusRouteID, BusStopID),
+    // Also how many passengers are aboard for the trip to the next stop
+    BusDepartedFromStop(CarID, BusRouteID, BusStopID, usize, PassengerCapacity),
+    // The bus was full. The time is when the person started waiting.
+    PassengerDeniedBoarding(PersonID, CarID, BusRouteID, BusStopID, Time),
 
     PersonEntersBuilding(PersonID, BuildingID),
     PersonLeavesBuilding(PersonID, BuildingID),
@@ -39,6 +44,8 @@ pub enum Event {
         blocked_time: Duration,
     },
     TripAborted(TripID),
+    // The trip never started, because of some policy
+    TripCancelled(TripID),
     TripPhaseStarting(TripID, PersonID, Option<PathRequest>, TripPhaseType),
 
     // Just use for parking replanning. Not happy about copying the full path in here, but the way
@@ -46,6 +53,15 @@ pub enum Event {
     PathAmended(Path),
 
     Alert(AlertLocation, String),
+
+    PandemicExposure(PersonID, InfectionSpace),
+    PandemicCounts {
+        sane: usize,
+        exposed: usize,
+        infectious: usize,
+        recovered: usize,
+        dead: usize,
+    },
 }
 
 #[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
@@ -72,18 +88,24 @@ pub enum TripPhaseType {
 }
 
 impl TripPhaseType {
-    pub fn describe(self, map: &Map) -> String {
+    pub fn describe(self, map: &Map, catalog: &Catalog) -> String {
         match self {
-            TripPhaseType::Driving => "driving".to_string(),
-            TripPhaseType::Walking => "walking".to_string(),
-            TripPhaseType::Biking => "biking".to_string(),
-            TripPhaseType::Parking => "parking".to_string(),
-            TripPhaseType::WaitingForBus(r, _) => format!("waiting for bus {}", map.get_br(r).name),
-            TripPhaseType::RidingBus(r, _, _) => format!("riding bus {}", map.get_br(r).name),
-            TripPhaseType::Aborted => "trip aborted due to some bug".to_string(),
-            TripPhaseType::Finished => "trip finished".to_string(),
-            TripPhaseType::DelayedStart => "delayed by previous trip taking too long".to_string(),
-            TripPhaseType::Remote => "remote trip outside the map boundaries".to_string(),
+            TripPhaseType::Driving => catalog.tr("driving"),
+            TripPhaseType::Walking => catalog.tr("walking"),
+            TripPhaseType::Biking => catalog.tr("biking"),
+            TripPhaseType::Parking => catalog.tr("parking"),
+            TripPhaseType::WaitingForBus(r, _) => catalog.tr_args(
+                "waiting for bus {route}",
+                vec![("route", map.get_br(r).name.clone())],
+            ),
+            TripPhaseType::RidingBus(r, _, _) => catalog.tr_args(
+                "riding bus {route}",
+                vec![("route", map.get_br(r).name.clone())],
+            ),
+            TripPhaseType::Aborted => catalog.tr("trip aborted due to some bug"),
+            TripPhaseType::Finished => catalog.tr("trip finished"),
+            TripPhaseType::DelayedStart => catalog.tr("delayed by previous trip taking too long"),
+            TripPhaseType::Remote => catalog.tr("remote trip outside the map boundaries"),
         }
     }
 }
@@ -14,13 +14,15 @@ pub use self::analytics::{Analytics, TripPhase};
 pub(crate) use self::events::Event;
 pub use self::events::{AlertLocation, TripPhaseType};
 pub use self::make::{
-    BorderSpawnOverTime, IndividTrip, OffMapLocation, OriginDestination, PersonSpec, Scenario,
-    ScenarioGenerator, SimFlags, SpawnOverTime, SpawnTrip, TripSpawner, TripSpec,
+    ActivityModel, BorderSpawnOverTime, HouseholdSpec, IndividTrip, OffMapLocation,
+    OriginDestination, PersonSpec, Scenario, ScenarioGenerator, ScenarioModifier, SimFlags,
+    SpawnOverTime, SpawnTrip, TripSpawner, TripSpec,
 };
 pub(crate) use self::mechanics::{
     DrivingSimState, IntersectionSimState, ParkingSimState, WalkingSimState,
 };
 pub(crate) use self::pandemic::PandemicModel;
+pub use self::pandemic::{InfectionSpace, Interventions};
 pub(crate) use self::router::{ActionAtEnd, Router};
 pub(crate) use self::scheduler::{Command, Scheduler};
 pub use self::sim::{AgentProperties, AlertHandler, Sim, SimCallback, SimOptions};
@@ -49,6 +51,11 @@ pub const MIN_CAR_LENGTH: Distance = Distance::const_meters(4.5);
 pub const MAX_CAR_LENGTH: Distance = Distance::const_meters(6.5);
 // Note this is more than MAX_CAR_LENGTH
 pub const BUS_LENGTH: Distance = Distance::const_meters(12.5);
+// A typical 40-foot bus. Override with SimOptions::bus_capacity.
+pub const BUS_CAPACITY: PassengerCapacity = PassengerCapacity {
+    seated: 38,
+    standing: 22,
+};
 
 // At all speeds (including at rest), cars must be at least this far apart, measured from front of
 // one car to the back of the other.
@@ -167,6 +174,39 @@ pub struct VehicleSpec {
     pub vehicle_type: VehicleType,
     pub length: Distance,
     pub max_speed: Option<Speed>,
+    // Only for buses
+    pub capacity: Option<PassengerCapacity>,
+}
+
+// How many people fit in a transit vehicle
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct PassengerCapacity {
+    pub seated: usize,
+    pub standing: usize,
+}
+
+impl PassengerCapacity {
+    pub fn total(self) -> usize {
+        self.seated + self.standing
+    }
+
+    // Parses "seated,standing", like "38,22"
+    pub fn parse(x: &str) -> Result<PassengerCapacity, String> {
+        let parts: Vec<&str> = x.split(',').collect();
+        if parts.len() != 2 {
+            return Err(format!("{} isn't seated,standing", x));
+        }
+        let seated = parts[0]
+            .parse::<usize>()
+            .map_err(|err| format!("bad seated count in {}: {}", x, err))?;
+        let standing = parts[1]
+            .parse::<usize>()
+            .map_err(|err| format!("bad standing count in {}: {}", x, err))?;
+        if seated + standing == 0 {
+            return Err(format!("{} leaves no room for passengers", x));
+        }
+        Ok(PassengerCapacity { seated, standing })
+    }
 }
 
 impl VehicleSpec {
@@ -0,0 +1,427 @@
+use crate::{
+    DrivingGoal, HouseholdSpec, IndividTrip, PersonID, PersonSpec, Scenario, SidewalkSpot,
+    SpawnTrip, TripEndpoint, TripMode,
+};
+use abstutil::{prettyprint_usize, Timer};
+use geom::{Distance, Duration, Time};
+use map_model::{BuildingID, IntersectionID, Map, PathConstraints};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+// Buildings don't know how many floors they have, so this is a rough guess of how much footprint
+// area each resident needs.
+const SQ_METERS_PER_RESIDENT: f64 = 50.0;
+// Anything closer than this, people will walk.
+const MAX_WALKING_DIST: Distance = Distance::const_meters(1000.0);
+// Anything farther than this, people won't bike.
+const MAX_BIKING_DIST: Distance = Distance::const_meters(5000.0);
+const SCHOOL_AMENITIES: [&str; 4] = ["school", "kindergarten", "college", "university"];
+// Where people run errands. These are also workplaces.
+const SHOP_AMENITIES: [&str; 7] = [
+    "supermarket",
+    "convenience",
+    "second_hand",
+    "clothes",
+    "furniture",
+    "shoes",
+    "department_store",
+];
+
+// Generates a Scenario from land use, for maps without travel demand data. Everybody lives
+// somewhere, spends the day at work or school, maybe runs an errand afterwards, and then returns
+// home.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ActivityModel {
+    pub scenario_name: String,
+    // If None, scale the population to the footprint area of residential buildings.
+    pub num_people: Option<usize>,
+    pub percent_students: f64,
+    // Of the people who aren't students, how many commute out of the map?
+    pub percent_work_off_map: f64,
+    // How many people stop somewhere on their way home?
+    pub percent_errands: f64,
+    // For trips too far to walk
+    pub percent_biking: f64,
+    // For trips too far to walk or bike
+    pub percent_use_transit: f64,
+}
+
+// Where can people do what? Each building is weighted by its footprint area.
+struct LandUse {
+    homes: Vec<(BuildingID, f64)>,
+    workplaces: Vec<(BuildingID, f64)>,
+    schools: Vec<(BuildingID, f64)>,
+    shops: Vec<(BuildingID, f64)>,
+    // Borders that drivers can leave and return through
+    exits: Vec<IntersectionID>,
+    entrances: Vec<IntersectionID>,
+}
+
+#[derive(Debug, PartialEq)]
+enum BldgUse {
+    Home,
+    Work,
+    School,
+    Shop,
+}
+
+enum Activity {
+    Work(BuildingID),
+    WorkOffMap(IntersectionID, IntersectionID),
+    School(BuildingID),
+}
+
+impl ActivityModel {
+    pub fn new(scenario_name: &str, num_people: Option<usize>) -> ActivityModel {
+        ActivityModel {
+            scenario_name: scenario_name.to_string(),
+            num_people,
+            percent_students: 0.2,
+            percent_work_off_map: 0.3,
+            percent_errands: 0.3,
+            percent_biking: 0.2,
+            percent_use_transit: 0.3,
+        }
+    }
+
+    pub fn generate(&self, map: &Map, rng: &mut XorShiftRng, timer: &mut Timer) -> Scenario {
+        timer.start(format!("Generating scenario {}", self.scenario_name));
+        let mut scenario = Scenario::empty(map, &self.scenario_name);
+        // People might take any bus
+        scenario.only_seed_buses = None;
+
+        let land_use = LandUse::new(map, timer);
+        let num_people = self.num_people.unwrap_or_else(|| {
+            let area: f64 = land_use.homes.iter().map(|(_, area)| *area).sum();
+            (area / SQ_METERS_PER_RESIDENT) as usize
+        });
+        timer.note(format!(
+            "{} homes, {} workplaces, {} schools, {} shops. Generating tours for {} people",
+            prettyprint_usize(land_use.homes.len()),
+            prettyprint_usize(land_use.workplaces.len()),
+            prettyprint_usize(land_use.schools.len()),
+            prettyprint_usize(land_use.shops.len()),
+            prettyprint_usize(num_people)
+        ));
+
+        let mut households: BTreeMap<BuildingID, HouseholdSpec> = BTreeMap::new();
+        let mut no_tour = 0;
+        timer.start_iter("generate tours", num_people);
+        for _ in 0..num_people {
+            timer.next();
+            let id = PersonID(scenario.people.len());
+            if let Some((home, trips, mode)) = self.make_tour(&land_use, map, rng) {
+                let household = households.entry(home).or_insert_with(|| HouseholdSpec {
+                    home,
+                    members: Vec::new(),
+                    num_cars: 0,
+                });
+                household.members.push(id);
+                if mode == TripMode::Drive {
+                    household.num_cars += 1;
+                }
+                scenario.people.push(PersonSpec {
+                    id,
+                    orig_id: None,
+                    trips,
+                });
+            } else {
+                no_tour += 1;
+            }
+        }
+        if no_tour > 0 {
+            timer.warn(format!(
+                "{} people have no home or nowhere else to go, so they're not in the scenario",
+                prettyprint_usize(no_tour)
+            ));
+        }
+        scenario.households = households.into_iter().map(|(_, h)| h).collect();
+
+        timer.stop(format!("Generating scenario {}", self.scenario_name));
+        scenario
+    }
+
+    // Returns the person's home, their trips, and the mode used for all of them.
+    fn make_tour(
+        &self,
+        land_use: &LandUse,
+        map: &Map,
+        rng: &mut XorShiftRng,
+    ) -> Option<(BuildingID, Vec<IndividTrip>, TripMode)> {
+        let home = pick(&land_use.homes, rng)?;
+        let activity = if !land_use.schools.is_empty() && rng.gen_bool(self.percent_students) {
+            Activity::School(pick(&land_use.schools, rng)?)
+        } else if !land_use.exits.is_empty()
+            && !land_use.entrances.is_empty()
+            && rng.gen_bool(self.percent_work_off_map)
+        {
+            Activity::WorkOffMap(
+                *land_use.exits.choose(rng).unwrap(),
+                *land_use.entrances.choose(rng).unwrap(),
+            )
+        } else {
+            Activity::Work(pick_except(&land_use.workplaces, home, rng)?)
+        };
+
+        let (leave_home, stay) = match activity {
+            Activity::School(_) => (
+                rand_duration(rng, Duration::minutes(7 * 60 + 30), Duration::minutes(30)),
+                rand_duration(rng, Duration::minutes(6 * 60 + 30), Duration::minutes(30)),
+            ),
+            Activity::Work(_) | Activity::WorkOffMap(_, _) => (
+                rand_duration(rng, Duration::minutes(8 * 60 + 30), Duration::hours(1)),
+                rand_duration(rng, Duration::minutes(8 * 60 + 30), Duration::hours(1)),
+            ),
+        };
+        let leave_home = Time::START_OF_DAY + leave_home.max(Duration::hours(5));
+        let leave_activity = leave_home + stay.max(Duration::hours(1));
+
+        let (go, come_back, mode) = match activity {
+            Activity::WorkOffMap(exit, entrance) => (
+                TripEndpoint::Border(exit, None),
+                TripEndpoint::Border(entrance, None),
+                TripMode::Drive,
+            ),
+            Activity::Work(b) | Activity::School(b) => {
+                let mode = self.pick_mode(home, b, map, rng);
+                (TripEndpoint::Bldg(b), TripEndpoint::Bldg(b), mode)
+            }
+        };
+
+        let mut trips = vec![IndividTrip {
+            depart: leave_home,
+            trip: SpawnTrip::new(TripEndpoint::Bldg(home), go, mode, map),
+        }];
+        // Maybe run an errand on the way home. The car or bike has to come back home, so use the
+        // same mode.
+        let errand = if rng.gen_bool(self.percent_errands) {
+            pick_except(&land_use.shops, home, rng).filter(|b| {
+                TripEndpoint::Bldg(*b) != come_back
+                    && match mode {
+                        TripMode::Walk => dist(home, *b, map) <= MAX_WALKING_DIST,
+                        TripMode::Bike => bikeable(*b, map),
+                        TripMode::Drive | TripMode::Transit => true,
+                    }
+            })
+        } else {
+            None
+        };
+        if let Some(b) = errand {
+            let stay = rand_duration(rng, Duration::minutes(45), Duration::minutes(15));
+            trips.push(IndividTrip {
+                depart: leave_activity,
+                trip: SpawnTrip::new(come_back, TripEndpoint::Bldg(b), mode, map),
+            });
+            trips.push(IndividTrip {
+                depart: leave_activity + stay.max(Duration::minutes(10)),
+                trip: SpawnTrip::new(TripEndpoint::Bldg(b), TripEndpoint::Bldg(home), mode, map),
+            });
+        } else {
+            trips.push(IndividTrip {
+                depart: leave_activity,
+                trip: SpawnTrip::new(come_back, TripEndpoint::Bldg(home), mode, map),
+            });
+        }
+        Some((home, trips, mode))
+    }
+
+    fn pick_mode(
+        &self,
+        home: BuildingID,
+        activity: BuildingID,
+        map: &Map,
+        rng: &mut XorShiftRng,
+    ) -> TripMode {
+        let d = dist(home, activity, map);
+        if d <= MAX_WALKING_DIST {
+            return TripMode::Walk;
+        }
+        if d <= MAX_BIKING_DIST
+            && rng.gen_bool(self.percent_biking)
+            && bikeable(home, map)
+            && bikeable(activity, map)
+        {
+            return TripMode::Bike;
+        }
+        if rng.gen_bool(self.percent_use_transit) {
+            TripMode::Transit
+        } else {
+            TripMode::Drive
+        }
+    }
+}
+
+impl LandUse {
+    fn new(map: &Map, timer: &mut Timer) -> LandUse {
+        let mut homes = Vec::new();
+        let mut workplaces = Vec::new();
+        let mut schools = Vec::new();
+        let mut shops = Vec::new();
+        for b in map.all_buildings() {
+            let area = b.polygon.area();
+            match bldg_use(&b.amenities) {
+                BldgUse::Home => homes.push((b.id, area)),
+                BldgUse::Work => workplaces.push((b.id, area)),
+                BldgUse::School => schools.push((b.id, area)),
+                BldgUse::Shop => {
+                    workplaces.push((b.id, area));
+                    shops.push((b.id, area));
+                }
+            }
+        }
+        // Without any amenity data, anywhere could be somebody's workplace. People never work
+        // where they live; see pick_except.
+        if workplaces.is_empty() && !homes.is_empty() {
+            timer.warn(format!(
+                "No buildings have amenities, so any of the {} homes could be a workplace, and \
+                 nobody will run errands",
+                prettyprint_usize(homes.len())
+            ));
+            workplaces = homes.clone();
+        }
+
+        let exits = map
+            .all_outgoing_borders()
+            .into_iter()
+            .filter(|i| {
+                i.some_incoming_road(map)
+                    .and_then(|dr| DrivingGoal::end_at_border(dr, PathConstraints::Car, None, map))
+                    .is_some()
+            })
+            .map(|i| i.id)
+            .collect();
+        let entrances = map
+            .all_incoming_borders()
+            .into_iter()
+            .filter(|i| {
+                i.some_outgoing_road(map)
+                    .map(|dr| !dr.lanes(PathConstraints::Car, map).is_empty())
+                    .unwrap_or(false)
+            })
+            .map(|i| i.id)
+            .collect();
+
+        LandUse {
+            homes,
+            workplaces,
+            schools,
+            shops,
+            exits,
+            entrances,
+        }
+    }
+}
+
+fn bldg_use(amenities: &BTreeSet<(String, String)>) -> BldgUse {
+    if amenities.is_empty() {
+        BldgUse::Home
+    } else if amenities
+        .iter()
+        .any(|(_, amenity)| SCHOOL_AMENITIES.contains(&amenity.as_str()))
+    {
+        BldgUse::School
+    } else if amenities
+        .iter()
+        .any(|(_, amenity)| SHOP_AMENITIES.contains(&amenity.as_str()))
+    {
+        BldgUse::Shop
+    } else {
+        BldgUse::Work
+    }
+}
+
+fn pick(choices: &Vec<(BuildingID, f64)>, rng: &mut XorShiftRng) -> Option<BuildingID> {
+    choices
+        .choose_weighted(rng, |(_, area)| *area)
+        .ok()
+        .map(|(b, _)| *b)
+}
+
+// Like pick, but never returns the excluded building. None if there's nothing else to choose.
+fn pick_except(
+    choices: &Vec<(BuildingID, f64)>,
+    exclude: BuildingID,
+    rng: &mut XorShiftRng,
+) -> Option<BuildingID> {
+    choices
+        .choose_weighted(rng, |(b, area)| if *b == exclude { 0.0 } else { *area })
+        .ok()
+        .map(|(b, _)| *b)
+}
+
+fn rand_duration(rng: &mut XorShiftRng, mean: Duration, stddev: Duration) -> Duration {
+    let normal = Normal::new(mean.inner_seconds(), stddev.inner_seconds()).unwrap();
+    Duration::seconds(normal.sample(rng).max(0.0))
+}
+
+fn dist(b1: BuildingID, b2: BuildingID, map: &Map) -> Distance {
+    map.get_b(b1)
+        .polygon
+        .center()
+        .dist_to(map.get_b(b2).polygon.center())
+}
+
+// Can somebody start and end a bike trip at this building? TripSpawner skips bike trips that
+// can't.
+fn bikeable(b: BuildingID, map: &Map) -> bool {
+    if SidewalkSpot::bike_from_bike_rack(map.get_b(b).sidewalk(), map).is_none() {
+        return false;
+    }
+    let last_lane = DrivingGoal::ParkNear(b)
+        .goal_pos(PathConstraints::Bike, map)
+        .lane();
+    map.get_parent(last_lane)
+        .bike_to_sidewalk(last_lane)
+        .is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+
+    fn amenities(list: Vec<&str>) -> BTreeSet<(String, String)> {
+        list.into_iter()
+            .map(|a| ("unnamed".to_string(), a.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn building_uses() {
+        assert_eq!(bldg_use(&amenities(Vec::new())), BldgUse::Home);
+        assert_eq!(bldg_use(&amenities(vec!["dentist"])), BldgUse::Work);
+        assert_eq!(bldg_use(&amenities(vec!["cafe", "clothes"])), BldgUse::Shop);
+        assert_eq!(
+            bldg_use(&amenities(vec!["supermarket", "school"])),
+            BldgUse::School
+        );
+    }
+
+    #[test]
+    fn never_pick_home() {
+        let mut rng = XorShiftRng::seed_from_u64(42);
+        let home = BuildingID(0);
+        let choices = vec![(home, 1000.0), (BuildingID(1), 1.0)];
+        for _ in 0..100 {
+            assert_eq!(pick_except(&choices, home, &mut rng), Some(BuildingID(1)));
+        }
+        assert_eq!(pick_except(&vec![(home, 1000.0)], home, &mut rng), None);
+        assert_eq!(pick_except(&Vec::new(), home, &mut rng), None);
+    }
+
+    #[test]
+    fn empty_map() {
+        let mut rng = XorShiftRng::seed_from_u64(42);
+        let scenario = ActivityModel::new("land use", Some(10)).generate(
+            &Map::blank(),
+            &mut rng,
+            &mut Timer::throwaway(),
+        );
+        assert!(scenario.people.is_empty());
+        assert!(scenario.households.is_empty());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::{AlertHandler, Scenario, Sim, SimOptions};
+use crate::{
+    AlertHandler, Interventions, PassengerCapacity, Scenario, Sim, SimOptions, BUS_CAPACITY,
+};
 use abstutil::CmdArgs;
 use map_model::{Map, MapEdits};
 use rand::SeedableRng;
@@ -37,6 +39,7 @@ impl SimFlags {
                 } else {
                     None
                 },
+                pandemic_interventions: Interventions::from_args(args),
                 alerts: args
                     .optional("--alerts")
                     .map(|x| match x.as_ref() {
@@ -47,6 +50,9 @@ impl SimFlags {
                     })
                     .unwrap_or(AlertHandler::Print),
                 pathfinding_upfront: args.enabled("--pathfinding_upfront"),
+                bus_capacity: args
+                    .optional_parse("--bus_capacity", PassengerCapacity::parse)
+                    .unwrap_or(BUS_CAPACITY),
             },
         }
     }
@@ -68,13 +74,18 @@ impl SimFlags {
         XorShiftRng::from_seed([self.rng_seed; 16])
     }
 
+    // Savestates resume a simulation from partway through the day. Anything else starts a new one.
+    pub fn is_savestate(&self) -> bool {
+        self.load.starts_with("../data/player/saves/")
+    }
+
     // Convenience method to setup everything.
     pub fn load(&self, timer: &mut abstutil::Timer) -> (Map, Sim, XorShiftRng) {
         let mut rng = self.make_rng();
 
         let mut opts = self.opts.clone();
 
-        if self.load.starts_with("../data/player/saves/") {
+        if self.is_savestate() {
             timer.note(format!("Resuming from {}", self.load));
 
             let mut sim: Sim = abstutil::read_binary(self.load.clone(), timer);
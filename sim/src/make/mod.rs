@@ -1,11 +1,17 @@
+mod activity_model;
 mod generator;
 mod load;
+mod modifier;
 mod scenario;
 mod spawner;
 
+pub use self::activity_model::ActivityModel;
 pub use self::generator::{
     BorderSpawnOverTime, OriginDestination, ScenarioGenerator, SpawnOverTime,
 };
 pub use self::load::SimFlags;
-pub use self::scenario::{IndividTrip, OffMapLocation, PersonSpec, Scenario, SpawnTrip};
+pub use self::modifier::ScenarioModifier;
+pub use self::scenario::{
+    HouseholdSpec, IndividTrip, OffMapLocation, PersonSpec, Scenario, SpawnTrip,
+};
 pub use self::spawner::{TripSpawner, TripSpec};
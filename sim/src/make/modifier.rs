@@ -0,0 +1,443 @@
+use crate::{
+    HouseholdSpec, OrigPersonID, PersonID, PersonSpec, Scenario, SpawnTrip, TripEndpoint, TripMode,
+};
+use abstutil::Timer;
+use geom::{Duration, LonLat, Polygon, Pt2D, Time};
+use map_model::Map;
+use rand::Rng;
+use rand_xorshift::XorShiftRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Transforms a Scenario to build what-if variations of travel demand. Applying the same list of
+// modifiers with the same RNG seed always produces the same result.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub enum ScenarioModifier {
+    RepeatDays(usize),
+    // Less than 1 samples people; more than 1 clones them.
+    ScaleDemand(f64),
+    // Can be negative
+    ShiftDepartures(Duration),
+    ChangeMode {
+        // From 0 to 100
+        pct: usize,
+        from: TripMode,
+        to: TripMode,
+    },
+    // A polygon, in GPS coordinates
+    OnlyTouching(Vec<LonLat>),
+    // The name of another scenario for the same map
+    Merge(String),
+}
+
+impl ScenarioModifier {
+    pub fn apply(
+        &self,
+        s: Scenario,
+        map: &Map,
+        rng: &mut XorShiftRng,
+        timer: &mut Timer,
+    ) -> Scenario {
+        match self {
+            ScenarioModifier::RepeatDays(n) => s.repeat_days(*n),
+            ScenarioModifier::ScaleDemand(factor) => s.scale_demand(*factor, rng),
+            ScenarioModifier::ShiftDepartures(by) => s.shift_departures(*by),
+            ScenarioModifier::ChangeMode { pct, from, to } => {
+                s.change_mode(*pct, *from, *to, map, rng)
+            }
+            ScenarioModifier::OnlyTouching(pts) => {
+                let polygon = Polygon::new(
+                    &pts.iter()
+                        .map(|pt| Pt2D::forcibly_from_gps(*pt, map.get_gps_bounds()))
+                        .collect(),
+                );
+                s.only_touching(&polygon, map)
+            }
+            ScenarioModifier::Merge(name) => {
+                let other: Scenario =
+                    abstutil::read_binary(abstutil::path_scenario(map.get_name(), name), timer);
+                s.merge(other)
+            }
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            ScenarioModifier::RepeatDays(n) => format!("repeat the entire day {} times", n),
+            ScenarioModifier::ScaleDemand(factor) => format!("scale demand by {}x", factor),
+            ScenarioModifier::ShiftDepartures(by) => format!("shift all departures by {}", by),
+            ScenarioModifier::ChangeMode { pct, from, to } => format!(
+                "{}% of people who {} {} instead",
+                pct,
+                from.verb(),
+                to.verb()
+            ),
+            ScenarioModifier::OnlyTouching(_) => "only keep people visiting an area".to_string(),
+            ScenarioModifier::Merge(name) => format!("add everybody from {}", name),
+        }
+    }
+}
+
+impl Scenario {
+    // Each household is kept or cloned randomly, so the total only matches the factor on average.
+    // All members of a household are sampled together. People outside of any household are
+    // sampled on their own.
+    pub fn scale_demand(self, factor: f64, rng: &mut XorShiftRng) -> Scenario {
+        assert!(factor >= 0.0);
+        let mut sample = || {
+            let mut n = factor.floor() as usize;
+            if rng.gen_bool(factor.fract()) {
+                n += 1;
+            }
+            n
+        };
+        let mut per_household = BTreeMap::new();
+        for h in &self.households {
+            let n = sample();
+            for p in &h.members {
+                per_household.insert(*p, n);
+            }
+        }
+        let copies = self
+            .people
+            .iter()
+            .map(|p| match per_household.get(&p.id) {
+                Some(n) => *n,
+                None => sample(),
+            })
+            .collect();
+        self.copy_people(copies)
+    }
+
+    // Shifts every trip by the same amount. Nobody can depart before midnight, so the shift is
+    // limited for people whose first trip would.
+    pub fn shift_departures(mut self, by: Duration) -> Scenario {
+        for person in &mut self.people {
+            if let Some(first) = person.trips.first() {
+                let by = by.max(Time::START_OF_DAY - first.depart);
+                for trip in &mut person.trips {
+                    trip.depart = trip.depart + by;
+                }
+            }
+        }
+        self
+    }
+
+    // Each person who takes any trips by one mode switches all of those trips to another mode with
+    // some probability. Switching the whole person keeps their tours consistent -- somebody who
+    // drives to work shouldn't walk home and leave their car behind. Trips through a border the
+    // new mode can't use are left alone.
+    pub fn change_mode(
+        mut self,
+        pct: usize,
+        from: TripMode,
+        to: TripMode,
+        map: &Map,
+        rng: &mut XorShiftRng,
+    ) -> Scenario {
+        assert!(pct <= 100, "change_mode of {}%", pct);
+        for person in &mut self.people {
+            if !person.trips.iter().any(|t| t.trip.mode() == from)
+                || !rng.gen_bool((pct as f64) / 100.0)
+            {
+                continue;
+            }
+            for trip in &mut person.trips {
+                if trip.trip.mode() != from {
+                    continue;
+                }
+                match trip.trip {
+                    SpawnTrip::Remote { ref mut mode, .. } => {
+                        *mode = to;
+                    }
+                    // Only for interactive / debug trips
+                    SpawnTrip::VehicleAppearing { .. } => {}
+                    _ => {
+                        let start = trip.trip.start(map);
+                        let end = trip.trip.end(map);
+                        if can_start(&start, to, map) && can_end(&end, to, map) {
+                            trip.trip = SpawnTrip::new(start, end, to, map);
+                        }
+                    }
+                }
+            }
+        }
+        self
+    }
+
+    // Only keeps people with at least one trip starting or ending inside the polygon. All of their
+    // trips are kept, so schedules stay intact.
+    pub fn only_touching(self, polygon: &Polygon, map: &Map) -> Scenario {
+        let inside = |endpt: TripEndpoint| {
+            polygon.contains_pt(match endpt {
+                TripEndpoint::Bldg(b) => map.get_b(b).polygon.center(),
+                TripEndpoint::Border(i, _) => map.get_i(i).polygon.center(),
+            })
+        };
+        let copies = self
+            .people
+            .iter()
+            .map(|person| {
+                let touches = person.trips.iter().any(|t| match t.trip {
+                    // Off-map the whole time
+                    SpawnTrip::Remote { .. } => false,
+                    _ => inside(t.trip.start(map)) || inside(t.trip.end(map)),
+                });
+                if touches {
+                    1
+                } else {
+                    0
+                }
+            })
+            .collect();
+        self.copy_people(copies)
+    }
+
+    // Adds everybody from another scenario for the same map. Buses seeded by either scenario are
+    // seeded. Like copy_people, the household part of the other scenario's orig_ids is shifted
+    // past every household here, so they stay unique.
+    pub fn merge(mut self, other: Scenario) -> Scenario {
+        assert_eq!(self.map_name, other.map_name);
+        let orig_households = self.num_orig_households();
+        let mut new_ids = BTreeMap::new();
+        for mut person in other.people {
+            let id = PersonID(self.people.len());
+            new_ids.insert(person.id, id);
+            person.id = id;
+            person.orig_id = person
+                .orig_id
+                .map(|id| OrigPersonID(id.0 + orig_households, id.1));
+            self.people.push(person);
+        }
+        for mut h in other.households {
+            h.members = h.members.iter().map(|p| new_ids[p]).collect();
+            self.households.push(h);
+        }
+        self.only_seed_buses = match (self.only_seed_buses, other.only_seed_buses) {
+            (Some(mut routes1), Some(routes2)) => {
+                routes1.extend(routes2);
+                Some(routes1)
+            }
+            _ => None,
+        };
+        self
+    }
+
+    // Replaces each person with some number of copies of them, possibly 0. Households are copied
+    // along with their members. The first copy keeps the original orig_id. Later copies shift the
+    // household part of it past every original household, so clones can be told apart but members
+    // of a cloned household still share one.
+    fn copy_people(mut self, copies: Vec<usize>) -> Scenario {
+        let max_copies = copies.iter().max().cloned().unwrap_or(0);
+        let orig_households = self.num_orig_households();
+        let mut new_ids = BTreeMap::new();
+        let mut people = Vec::new();
+        for (person, n) in self.people.into_iter().zip(copies) {
+            for copy in 0..n {
+                let id = PersonID(people.len());
+                new_ids.insert((person.id, copy), id);
+                people.push(PersonSpec {
+                    id,
+                    orig_id: person
+                        .orig_id
+                        .map(|id| OrigPersonID(id.0 + copy * orig_households, id.1)),
+                    trips: person.trips.clone(),
+                });
+            }
+        }
+
+        let mut households = Vec::new();
+        for h in self.households {
+            for copy in 0..max_copies {
+                let members: Vec<PersonID> = h
+                    .members
+                    .iter()
+                    .filter_map(|p| new_ids.get(&(*p, copy)).cloned())
+                    .collect();
+                if !members.is_empty() {
+                    households.push(HouseholdSpec {
+                        home: h.home,
+                        members,
+                        num_cars: h.num_cars,
+                    });
+                }
+            }
+        }
+
+        self.people = people;
+        self.households = households;
+        self
+    }
+
+    // One more than the biggest household part of any orig_id
+    fn num_orig_households(&self) -> usize {
+        self.people
+            .iter()
+            .filter_map(|p| p.orig_id)
+            .map(|id| id.0 + 1)
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+fn can_start(endpt: &TripEndpoint, mode: TripMode, map: &Map) -> bool {
+    match endpt {
+        TripEndpoint::Bldg(_) => true,
+        TripEndpoint::Border(i, _) => !map
+            .get_i(*i)
+            .get_outgoing_lanes(map, mode.to_constraints())
+            .is_empty(),
+    }
+}
+
+fn can_end(endpt: &TripEndpoint, mode: TripMode, map: &Map) -> bool {
+    match endpt {
+        TripEndpoint::Bldg(_) => true,
+        TripEndpoint::Border(i, _) => !map
+            .get_i(*i)
+            .get_incoming_lanes(map, mode.to_constraints())
+            .is_empty(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{IndividTrip, OffMapLocation};
+    use map_model::BuildingID;
+    use rand::SeedableRng;
+    use std::collections::BTreeSet;
+
+    // Everybody makes one remote trip per departure time. People 0 and 1 share a household.
+    fn scenario(name: &str, departures: Vec<Vec<Time>>) -> Scenario {
+        let loc = OffMapLocation {
+            parcel_id: 0,
+            gps: LonLat::new(0.0, 0.0),
+        };
+        let people: Vec<PersonSpec> = departures
+            .into_iter()
+            .enumerate()
+            .map(|(idx, times)| PersonSpec {
+                id: PersonID(idx),
+                orig_id: Some(OrigPersonID(if idx < 2 { 0 } else { idx }, idx)),
+                trips: times
+                    .into_iter()
+                    .map(|depart| IndividTrip {
+                        depart,
+                        trip: SpawnTrip::Remote {
+                            from: loc.clone(),
+                            to: loc.clone(),
+                            trip_time: Duration::minutes(10),
+                            mode: TripMode::Drive,
+                        },
+                    })
+                    .collect(),
+            })
+            .collect();
+        Scenario {
+            scenario_name: name.to_string(),
+            map_name: "test".to_string(),
+            households: if people.len() >= 2 {
+                vec![HouseholdSpec {
+                    home: BuildingID(0),
+                    members: vec![PersonID(0), PersonID(1)],
+                    num_cars: 1,
+                }]
+            } else {
+                Vec::new()
+            },
+            people,
+            only_seed_buses: None,
+        }
+    }
+
+    fn hours(h: usize) -> Time {
+        Time::START_OF_DAY + Duration::hours(h)
+    }
+
+    #[test]
+    fn scale_demand() {
+        let departures = vec![vec![hours(8)]; 4];
+
+        let s = scenario("test", departures.clone())
+            .scale_demand(3.0, &mut XorShiftRng::seed_from_u64(42));
+        assert_eq!(s.people.len(), 12);
+        assert_eq!(s.households.len(), 3);
+        assert_eq!(s.check_households(), Ok(()));
+        // Every clone is distinguishable
+        let orig_ids: BTreeSet<OrigPersonID> =
+            s.people.iter().map(|p| p.orig_id.unwrap()).collect();
+        assert_eq!(orig_ids.len(), 12);
+        // ... but members of a cloned household still share one
+        for h in &s.households {
+            let orig0 = s.people[h.members[0].0].orig_id.unwrap();
+            let orig1 = s.people[h.members[1].0].orig_id.unwrap();
+            assert_eq!(orig0.0, orig1.0);
+            assert_ne!(orig0.1, orig1.1);
+        }
+
+        assert!(scenario("test", departures.clone())
+            .scale_demand(0.0, &mut XorShiftRng::seed_from_u64(42))
+            .people
+            .is_empty());
+
+        // Households are never split up
+        for seed in 0..20 {
+            let s = scenario("test", departures.clone())
+                .scale_demand(0.5, &mut XorShiftRng::seed_from_u64(seed));
+            assert_eq!(s.check_households(), Ok(()));
+            for h in &s.households {
+                assert_eq!(h.members.len(), 2);
+            }
+        }
+    }
+
+    #[test]
+    fn shift_departures() {
+        let s = scenario("test", vec![vec![hours(8), hours(17)], vec![hours(1)]])
+            .shift_departures(Duration::hours(2));
+        assert_eq!(s.people[0].trips[0].depart, hours(10));
+        assert_eq!(s.people[0].trips[1].depart, hours(19));
+        assert_eq!(s.people[1].trips[0].depart, hours(3));
+
+        // Nobody leaves before midnight, and the gaps between trips are kept
+        let s = scenario("test", vec![vec![hours(8), hours(17)], vec![hours(1)]])
+            .shift_departures(Duration::ZERO - Duration::hours(2));
+        assert_eq!(s.people[0].trips[0].depart, hours(6));
+        assert_eq!(s.people[0].trips[1].depart, hours(15));
+        assert_eq!(s.people[1].trips[0].depart, Time::START_OF_DAY);
+    }
+
+    #[test]
+    fn merge() {
+        let mut s1 = scenario("s1", vec![vec![hours(8)]; 3]);
+        s1.only_seed_buses = Some(vec!["1".to_string()].into_iter().collect());
+        let mut s2 = scenario("s2", vec![vec![hours(9)]; 2]);
+        s2.only_seed_buses = Some(vec!["2".to_string()].into_iter().collect());
+
+        let merged = s1.merge(s2);
+        assert_eq!(merged.scenario_name, "s1");
+        assert_eq!(merged.people.len(), 5);
+        for (idx, p) in merged.people.iter().enumerate() {
+            assert_eq!(p.id, PersonID(idx));
+        }
+        assert_eq!(merged.people[3].trips[0].depart, hours(9));
+        // s1's orig_ids use households 0 and 2, so s2's start at 3
+        assert_eq!(merged.people[3].orig_id, Some(OrigPersonID(3, 0)));
+        assert_eq!(merged.people[4].orig_id, Some(OrigPersonID(3, 1)));
+        let orig_ids: BTreeSet<OrigPersonID> =
+            merged.people.iter().map(|p| p.orig_id.unwrap()).collect();
+        assert_eq!(orig_ids.len(), 5);
+        assert_eq!(merged.households.len(), 2);
+        assert_eq!(merged.households[1].members, vec![PersonID(3), PersonID(4)]);
+        assert_eq!(merged.check_households(), Ok(()));
+        assert_eq!(
+            merged.only_seed_buses,
+            Some(vec!["1".to_string(), "2".to_string()].into_iter().collect())
+        );
+
+        // If either scenario seeds every bus, so does the result
+        let merged = scenario("s1", Vec::new()).merge(scenario("s2", Vec::new()));
+        assert_eq!(merged.only_seed_buses, None);
+    }
+}
@@ -21,10 +21,23 @@ pub struct Scenario {
     pub map_name: String,
 
     pub people: Vec<PersonSpec>,
+    // People who don't belong to any household only use their own vehicles. Nobody can belong to
+    // more than one; see check_households.
+    #[serde(default)]
+    pub households: Vec<HouseholdSpec>,
     // None means seed all buses. Otherwise the route name must be present here.
     pub only_seed_buses: Option<BTreeSet<String>>,
 }
 
+// People living together, sharing some cars. The cars start parked at home, and only one member
+// can use each car at a time.
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct HouseholdSpec {
+    pub home: BuildingID,
+    pub members: Vec<PersonID>,
+    pub num_cars: usize,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct PersonSpec {
     pub id: PersonID,
@@ -57,7 +70,12 @@ pub enum SpawnTrip {
     UsingParkedCar(BuildingID, DrivingGoal),
     UsingBike(SidewalkSpot, DrivingGoal),
     JustWalking(SidewalkSpot, SidewalkSpot),
-    UsingTransit(SidewalkSpot, SidewalkSpot, BusRouteID, BusStopID, BusStopID),
+    // (board at, alight at, route) for each bus ridden, in order
+    UsingTransit(
+        SidewalkSpot,
+        SidewalkSpot,
+        Vec<(BusStopID, BusStopID, BusRouteID)>,
+    ),
     // Completely off-map trip. Don't really simulate much of it.
     Remote {
         from: OffMapLocation,
@@ -93,6 +111,12 @@ impl Scenario {
             }
         }
 
+        let household_per_person = match self.household_per_person() {
+            Ok(x) => x,
+            Err(err) => panic!("{}", err),
+        };
+        let mut shared_vehicles: Vec<Option<Vec<Vehicle>>> = vec![None; self.households.len()];
+
         timer.start_iter("trips for People", self.people.len());
         let mut spawner = sim.make_spawner();
         let mut parked_cars: Vec<(Vehicle, BuildingID)> = Vec::new();
@@ -103,24 +127,49 @@ impl Scenario {
                 panic!("{}", err);
             }
 
+            let household = household_per_person
+                .get(&p.id)
+                .map(|idx| (*idx, &self.households[*idx]));
+            // The first member of a household to show up creates the shared cars.
+            let shared = if let Some((idx, h)) = household {
+                if shared_vehicles[idx].is_none() {
+                    let specs = (0..h.num_cars)
+                        .map(|car| (h.car_owner(car), Scenario::rand_car(rng)))
+                        .collect();
+                    let vehicles = sim.new_shared_vehicles(specs);
+                    for v in &vehicles {
+                        parked_cars.push((v.clone(), h.home));
+                    }
+                    shared_vehicles[idx] = Some(vehicles);
+                }
+                shared_vehicles[idx].clone().unwrap()
+            } else {
+                Vec::new()
+            };
+
             let (vehicle_specs, cars_initially_parked_at, vehicle_foreach_trip) =
-                p.get_vehicles(rng);
+                p.get_vehicles(household.and_then(|(_, h)| h.default_car(p.id)), rng);
             sim.new_person(
                 p.id,
                 p.orig_id,
+                household.map(|(_, h)| h.home),
                 Scenario::rand_ped_speed(rng),
                 vehicle_specs,
+                shared.clone(),
             );
             let person = sim.get_person(p.id);
             for (idx, b) in cars_initially_parked_at {
                 parked_cars.push((person.vehicles[idx].clone(), b));
             }
-            for (t, maybe_idx) in p.trips.iter().zip(vehicle_foreach_trip) {
+            for (t, maybe_choice) in p.trips.iter().zip(vehicle_foreach_trip) {
                 // The RNG call might change over edits for picking the spawning lane from a border
                 // with multiple choices for a vehicle type.
                 let mut tmp_rng = abstutil::fork_rng(rng);
                 let spec = t.trip.clone().to_trip_spec(
-                    maybe_idx.map(|idx| person.vehicles[idx].id),
+                    maybe_choice.map(|choice| match choice {
+                        VehicleChoice::Own(idx) => person.vehicles[idx].id,
+                        VehicleChoice::Shared(idx) => shared[idx].id,
+                    }),
                     &mut tmp_rng,
                     map,
                 );
@@ -148,6 +197,7 @@ impl Scenario {
             scenario_name: name.to_string(),
             map_name: map.get_name().to_string(),
             people: Vec::new(),
+            households: Vec::new(),
             only_seed_buses: Some(BTreeSet::new()),
         }
     }
@@ -158,6 +208,7 @@ impl Scenario {
             vehicle_type: VehicleType::Car,
             length,
             max_speed: None,
+            capacity: None,
         }
     }
 
@@ -171,6 +222,7 @@ impl Scenario {
             vehicle_type: VehicleType::Bike,
             length: BIKE_LENGTH,
             max_speed,
+            capacity: None,
         }
     }
 
@@ -222,15 +274,62 @@ impl Scenario {
         let mut per_bldg = Counter::new();
         // Pass in a dummy RNG
         let mut rng = XorShiftRng::from_seed([0; 16]);
+        let household_per_person = match self.household_per_person() {
+            Ok(x) => x,
+            Err(err) => panic!("{}", err),
+        };
         for p in &self.people {
-            let (_, cars_initially_parked_at, _) = p.get_vehicles(&mut rng);
+            let shared_car = household_per_person
+                .get(&p.id)
+                .and_then(|idx| self.households[*idx].default_car(p.id));
+            let (_, cars_initially_parked_at, _) = p.get_vehicles(shared_car, &mut rng);
             for (_, b) in cars_initially_parked_at {
                 per_bldg.inc(b);
             }
         }
+        for h in &self.households {
+            for _ in 0..h.num_cars {
+                per_bldg.inc(h.home);
+            }
+        }
         per_bldg
     }
 
+    // Anything building a scenario with households should call this before saving it.
+    pub fn check_households(&self) -> Result<(), String> {
+        self.household_per_person()?;
+        let people: BTreeSet<PersonID> = self.people.iter().map(|p| p.id).collect();
+        for h in &self.households {
+            if h.members.is_empty() {
+                return Err(format!("The household at {} has nobody in it", h.home));
+            }
+            for p in &h.members {
+                if !people.contains(p) {
+                    return Err(format!(
+                        "{} belongs to the household at {}, but isn't in the scenario",
+                        p, h.home
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn household_per_person(&self) -> Result<BTreeMap<PersonID, usize>, String> {
+        let mut result = BTreeMap::new();
+        for (idx, h) in self.households.iter().enumerate() {
+            for p in &h.members {
+                if let Some(other) = result.insert(*p, idx) {
+                    return Err(format!(
+                        "{} belongs to the households at {} and {}",
+                        p, self.households[other].home, h.home
+                    ));
+                }
+            }
+        }
+        Ok(result)
+    }
+
     pub fn remove_weird_schedules(mut self, map: &Map) -> Scenario {
         let orig = self.people.len();
         self.people
@@ -247,13 +346,48 @@ impl Scenario {
             prettyprint_usize(orig)
         );
         // Fix up IDs
+        let mut new_ids = BTreeMap::new();
         for (idx, person) in self.people.iter_mut().enumerate() {
+            new_ids.insert(person.id, PersonID(idx));
             person.id = PersonID(idx);
         }
+        for h in &mut self.households {
+            h.members = h
+                .members
+                .iter()
+                .filter_map(|p| new_ids.get(p).cloned())
+                .collect();
+        }
+        self.households.retain(|h| !h.members.is_empty());
         self
     }
 }
 
+impl HouseholdSpec {
+    // Which of the household's cars does this member plan to use? If there are more members than
+    // cars, some will share the same default, and whoever gets to it first will use it.
+    fn default_car(&self, person: PersonID) -> Option<(usize, BuildingID)> {
+        if self.num_cars == 0 {
+            return None;
+        }
+        let idx = self.members.iter().position(|p| *p == person)?;
+        Some((idx % self.num_cars, self.home))
+    }
+
+    // Each car belongs to the first member who uses it by default.
+    fn car_owner(&self, car: usize) -> PersonID {
+        self.members[car % self.members.len()]
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VehicleChoice {
+    // Indexes into the vehicles the person owns
+    Own(usize),
+    // Indexes into the household's shared cars
+    Shared(usize),
+}
+
 fn seed_parked_cars(
     parked_cars: Vec<(Vehicle, BuildingID)>,
     sim: &mut Sim,
@@ -428,13 +562,9 @@ impl SpawnTrip {
                 goal,
             },
             SpawnTrip::JustWalking(start, goal) => TripSpec::JustWalking { start, goal },
-            SpawnTrip::UsingTransit(start, goal, route, stop1, stop2) => TripSpec::UsingTransit {
-                start,
-                goal,
-                route,
-                stop1,
-                stop2,
-            },
+            SpawnTrip::UsingTransit(start, goal, rides) => {
+                TripSpec::UsingTransit { start, goal, rides }
+            }
             SpawnTrip::Remote {
                 from,
                 to,
@@ -449,6 +579,23 @@ impl SpawnTrip {
         }
     }
 
+    pub fn mode(&self) -> TripMode {
+        match self {
+            SpawnTrip::VehicleAppearing { is_bike, .. } | SpawnTrip::FromBorder { is_bike, .. } => {
+                if *is_bike {
+                    TripMode::Bike
+                } else {
+                    TripMode::Drive
+                }
+            }
+            SpawnTrip::UsingParkedCar(_, _) => TripMode::Drive,
+            SpawnTrip::UsingBike(_, _) => TripMode::Bike,
+            SpawnTrip::JustWalking(_, _) => TripMode::Walk,
+            SpawnTrip::UsingTransit(_, _, _) => TripMode::Transit,
+            SpawnTrip::Remote { mode, .. } => *mode,
+        }
+    }
+
     pub fn start(&self, map: &Map) -> TripEndpoint {
         match self {
             SpawnTrip::VehicleAppearing { ref start, .. } => {
@@ -460,7 +607,7 @@ impl SpawnTrip {
             SpawnTrip::UsingParkedCar(b, _) => TripEndpoint::Bldg(*b),
             SpawnTrip::UsingBike(ref spot, _)
             | SpawnTrip::JustWalking(ref spot, _)
-            | SpawnTrip::UsingTransit(ref spot, _, _, _, _) => match spot.connection {
+            | SpawnTrip::UsingTransit(ref spot, _, _) => match spot.connection {
                 SidewalkPOI::Building(b) => TripEndpoint::Bldg(b),
                 SidewalkPOI::Border(i, ref loc) => TripEndpoint::Border(i, loc.clone()),
                 SidewalkPOI::SuddenlyAppear => {
@@ -484,7 +631,7 @@ impl SpawnTrip {
                 DrivingGoal::ParkNear(b) => TripEndpoint::Bldg(*b),
                 DrivingGoal::Border(i, _, ref loc) => TripEndpoint::Border(*i, loc.clone()),
             },
-            SpawnTrip::JustWalking(_, ref spot) | SpawnTrip::UsingTransit(_, ref spot, _, _, _) => {
+            SpawnTrip::JustWalking(_, ref spot) | SpawnTrip::UsingTransit(_, ref spot, _) => {
                 match spot.connection {
                     SidewalkPOI::Building(b) => TripEndpoint::Bldg(b),
                     SidewalkPOI::Border(i, ref loc) => TripEndpoint::Border(i, loc.clone()),
@@ -529,10 +676,8 @@ impl SpawnTrip {
             TripMode::Transit => {
                 let start = from.start_sidewalk_spot(map);
                 let goal = to.end_sidewalk_spot(map);
-                if let Some((stop1, stop2, route)) =
-                    map.should_use_transit(start.sidewalk_pos, goal.sidewalk_pos)
-                {
-                    SpawnTrip::UsingTransit(start, goal, route, stop1, stop2)
+                if let Some(rides) = map.should_use_transit(start.sidewalk_pos, goal.sidewalk_pos) {
+                    SpawnTrip::UsingTransit(start, goal, rides)
                 } else {
                     //timer.warn(format!("{:?} not actually using transit, because pathfinding
                     // didn't find any useful route", trip));
@@ -586,21 +731,27 @@ impl PersonSpec {
         Ok(())
     }
 
+    // If the person belongs to a household, shared_car is the household car they'd use by default,
+    // and the home where it starts.
     fn get_vehicles(
         &self,
+        shared_car: Option<(usize, BuildingID)>,
         rng: &mut XorShiftRng,
     ) -> (
         Vec<VehicleSpec>,
         Vec<(usize, BuildingID)>,
-        Vec<Option<usize>>,
+        Vec<Option<VehicleChoice>>,
     ) {
         let mut vehicle_specs = Vec::new();
         let mut cars_initially_parked_at = Vec::new();
         let mut vehicle_foreach_trip = Vec::new();
 
         let mut bike_idx = None;
-        // For each indexed car, is it parked somewhere, or off-map?
-        let mut car_locations: Vec<(usize, Option<BuildingID>)> = Vec::new();
+        // For each car, is it parked somewhere, or off-map?
+        let mut car_locations: Vec<(VehicleChoice, Option<BuildingID>)> = Vec::new();
+        if let Some((idx, home)) = shared_car {
+            car_locations.push((VehicleChoice::Shared(idx), Some(home)));
+        }
 
         for trip in &self.trips {
             let use_for_trip = match trip.trip {
@@ -612,7 +763,7 @@ impl PersonSpec {
                 } => {
                     if is_bike {
                         if bike_idx.is_none() {
-                            bike_idx = Some(vehicle_specs.len());
+                            bike_idx = Some(VehicleChoice::Own(vehicle_specs.len()));
                             vehicle_specs.push(Scenario::rand_bike(rng));
                         }
                         bike_idx
@@ -626,7 +777,7 @@ impl PersonSpec {
                             idx
                         } else {
                             // Need a new car, starting off-map
-                            let idx = vehicle_specs.len();
+                            let idx = VehicleChoice::Own(vehicle_specs.len());
                             vehicle_specs.push(Scenario::rand_car(rng));
                             idx
                         };
@@ -658,7 +809,7 @@ impl PersonSpec {
                         let idx = vehicle_specs.len();
                         vehicle_specs.push(Scenario::rand_car(rng));
                         cars_initially_parked_at.push((idx, b));
-                        idx
+                        VehicleChoice::Own(idx)
                     };
 
                     // Where does this car wind up?
@@ -676,12 +827,12 @@ impl PersonSpec {
                 }
                 SpawnTrip::UsingBike(_, _) => {
                     if bike_idx.is_none() {
-                        bike_idx = Some(vehicle_specs.len());
+                        bike_idx = Some(VehicleChoice::Own(vehicle_specs.len()));
                         vehicle_specs.push(Scenario::rand_bike(rng));
                     }
                     bike_idx
                 }
-                SpawnTrip::JustWalking(_, _) | SpawnTrip::UsingTransit(_, _, _, _, _) => None,
+                SpawnTrip::JustWalking(_, _) | SpawnTrip::UsingTransit(_, _, _) => None,
                 SpawnTrip::Remote { .. } => None,
             };
             vehicle_foreach_trip.push(use_for_trip);
@@ -705,3 +856,92 @@ impl PersonSpec {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scenario(num_people: usize, households: Vec<HouseholdSpec>) -> Scenario {
+        Scenario {
+            scenario_name: "test".to_string(),
+            map_name: "test".to_string(),
+            people: (0..num_people)
+                .map(|idx| PersonSpec {
+                    id: PersonID(idx),
+                    orig_id: None,
+                    trips: Vec::new(),
+                })
+                .collect(),
+            households,
+            only_seed_buses: None,
+        }
+    }
+
+    fn household(home: usize, members: Vec<usize>, num_cars: usize) -> HouseholdSpec {
+        HouseholdSpec {
+            home: BuildingID(home),
+            members: members.into_iter().map(PersonID).collect(),
+            num_cars,
+        }
+    }
+
+    #[test]
+    fn check_households() {
+        assert_eq!(
+            scenario(
+                4,
+                vec![household(1, vec![0, 1], 1), household(2, vec![2], 0)]
+            )
+            .check_households(),
+            Ok(())
+        );
+        assert!(scenario(
+            4,
+            vec![household(1, vec![0, 1], 1), household(2, vec![1], 0)]
+        )
+        .check_households()
+        .unwrap_err()
+        .contains("belongs to the households"));
+        assert!(scenario(2, vec![household(1, vec![0, 5], 1)])
+            .check_households()
+            .is_err());
+        assert!(scenario(2, vec![household(1, Vec::new(), 1)])
+            .check_households()
+            .is_err());
+    }
+
+    #[test]
+    fn shared_cars() {
+        let h = household(1, vec![3, 4, 5], 2);
+        // Members 3 and 5 both default to the first car
+        assert_eq!(h.default_car(PersonID(3)), Some((0, BuildingID(1))));
+        assert_eq!(h.default_car(PersonID(4)), Some((1, BuildingID(1))));
+        assert_eq!(h.default_car(PersonID(5)), Some((0, BuildingID(1))));
+        assert_eq!(h.default_car(PersonID(6)), None);
+        // Cars don't all belong to the first member
+        assert_eq!(h.car_owner(0), PersonID(3));
+        assert_eq!(h.car_owner(1), PersonID(4));
+
+        assert_eq!(household(1, vec![3], 0).default_car(PersonID(3)), None);
+    }
+
+    #[test]
+    fn load_without_households() {
+        let json = abstutil::to_json(&scenario(1, Vec::new()));
+        let old_format: String = json
+            .lines()
+            .filter(|line| !line.contains("\"households\""))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_ne!(json, old_format);
+
+        let path = std::env::temp_dir()
+            .join("scenario_without_households.json")
+            .display()
+            .to_string();
+        std::fs::write(&path, old_format).unwrap();
+        let loaded: Scenario = abstutil::maybe_read_json(path, &mut Timer::throwaway()).unwrap();
+        assert_eq!(loaded.people.len(), 1);
+        assert!(loaded.households.is_empty());
+    }
+}
@@ -47,9 +47,9 @@ pub enum TripSpec {
     UsingTransit {
         start: SidewalkSpot,
         goal: SidewalkSpot,
-        route: BusRouteID,
-        stop1: BusStopID,
-        stop2: BusStopID,
+        // (board at, alight at, route) for each bus ridden, in order. Transfers walk between the
+        // stops, even if that's a trivial walk at the same stop.
+        rides: Vec<(BusStopID, BusStopID, BusRouteID)>,
     },
     // Completely off-map trip. Don't really simulate much of it.
     Remote {
@@ -169,7 +169,19 @@ impl TripSpawner {
                     }
                 }
             }
-            TripSpec::UsingTransit { .. } => {}
+            TripSpec::UsingTransit { rides, .. } => {
+                if rides.is_empty() {
+                    panic!("A transit trip without riding any buses doesn't make sense");
+                }
+                for (stop1, stop2, route) in rides {
+                    if stop1 == stop2 {
+                        panic!(
+                            "Riding {} from {:?} to the same stop doesn't make sense",
+                            route, stop1
+                        );
+                    }
+                }
+            }
             TripSpec::Remote { .. } => {}
         };
 
@@ -301,27 +313,22 @@ impl TripSpawner {
                     };
                     trips.new_trip(person.id, start_time, trip_start, TripMode::Bike, legs, map)
                 }
-                TripSpec::UsingTransit {
-                    route,
-                    stop1,
-                    stop2,
-                    goal,
-                    ..
-                } => {
-                    let walk_to = SidewalkSpot::bus_stop(stop1, map);
-                    trips.new_trip(
-                        person.id,
-                        start_time,
-                        trip_start,
-                        TripMode::Transit,
-                        vec![
-                            TripLeg::Walk(walk_to.clone()),
-                            TripLeg::RideBus(route, stop2),
-                            TripLeg::Walk(goal),
-                        ],
-                        map,
-                    )
-                }
+                TripSpec::UsingTransit { rides, goal, .. } => trips.new_trip(
+                    person.id,
+                    start_time,
+                    trip_start,
+                    TripMode::Transit,
+                    transit_legs(
+                        rides
+                            .into_iter()
+                            .map(|(stop1, stop2, route)| {
+                                (SidewalkSpot::bus_stop(stop1, map), route, stop2)
+                            })
+                            .collect(),
+                        goal,
+                    ),
+                    map,
+                ),
                 TripSpec::Remote { to, mode, .. } => trips.new_trip(
                     person.id,
                     start_time,
@@ -392,12 +399,110 @@ impl TripSpec {
                     .sidewalk_pos,
                 constraints: PathConstraints::Pedestrian,
             }),
-            TripSpec::UsingTransit { start, stop1, .. } => Some(PathRequest {
+            TripSpec::UsingTransit { start, rides, .. } => Some(PathRequest {
                 start: start.sidewalk_pos,
-                end: SidewalkSpot::bus_stop(*stop1, map).sidewalk_pos,
+                end: SidewalkSpot::bus_stop(rides[0].0, map).sidewalk_pos,
                 constraints: PathConstraints::Pedestrian,
             }),
             TripSpec::Remote { .. } => None,
         }
     }
 }
+
+// Walk to the first stop, then alternate riding and walking to wherever the next bus is boarded.
+// A transfer at the same stop still gets a trivial walking leg, so TripManager doesn't need a
+// special case. Each ride is (where to board, route, where to alight).
+fn transit_legs(
+    rides: Vec<(SidewalkSpot, BusRouteID, BusStopID)>,
+    goal: SidewalkSpot,
+) -> Vec<TripLeg> {
+    let mut legs = Vec::new();
+    for (board_at, route, alight_at) in rides {
+        legs.push(TripLeg::Walk(board_at));
+        legs.push(TripLeg::RideBus(route, alight_at));
+    }
+    legs.push(TripLeg::Walk(goal));
+    legs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SidewalkPOI;
+    use geom::{Distance, Speed};
+    use map_model::LaneID;
+
+    fn stop(sidewalk: usize, idx: usize) -> BusStopID {
+        BusStopID {
+            sidewalk: LaneID(sidewalk),
+            idx,
+        }
+    }
+
+    fn spot(stop: BusStopID) -> SidewalkSpot {
+        SidewalkSpot {
+            connection: SidewalkPOI::BusStop(stop),
+            sidewalk_pos: Position::new(stop.sidewalk, Distance::meters(10.0)),
+        }
+    }
+
+    #[test]
+    fn transfer_legs() {
+        let (a, b, c, d) = (stop(1, 0), stop(2, 0), stop(3, 0), stop(4, 0));
+        let goal = SidewalkSpot {
+            connection: SidewalkPOI::SuddenlyAppear,
+            sidewalk_pos: Position::new(LaneID(5), Distance::meters(10.0)),
+        };
+        // Ride route 0 from a to b, walk to c, then ride route 1 to d
+        let legs = transit_legs(
+            vec![(spot(a), BusRouteID(0), b), (spot(c), BusRouteID(1), d)],
+            goal.clone(),
+        );
+        assert_eq!(
+            legs,
+            vec![
+                TripLeg::Walk(spot(a)),
+                TripLeg::RideBus(BusRouteID(0), b),
+                TripLeg::Walk(spot(c)),
+                TripLeg::RideBus(BusRouteID(1), d),
+                TripLeg::Walk(goal),
+            ]
+        );
+    }
+
+    fn schedule_transit(rides: Vec<(BusStopID, BusStopID, BusRouteID)>) -> TripSpawner {
+        let mut trips = TripManager::new(false);
+        let person = trips
+            .random_person(Speed::meters_per_second(1.0), Vec::new())
+            .clone();
+        let mut spawner = TripSpawner::new();
+        let start = spot(stop(9, 0));
+        spawner.schedule_trip(
+            &person,
+            Time::START_OF_DAY,
+            TripSpec::UsingTransit {
+                start: start.clone(),
+                goal: spot(stop(9, 1)),
+                rides,
+            },
+            TripEndpoint::Border(IntersectionID(0), None),
+            &Map::blank(),
+        );
+        spawner
+    }
+
+    #[test]
+    fn schedule_transfer() {
+        let spawner = schedule_transit(vec![
+            (stop(1, 0), stop(2, 0), BusRouteID(0)),
+            (stop(2, 0), stop(3, 0), BusRouteID(1)),
+        ]);
+        assert_eq!(spawner.trips.len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn schedule_transit_without_rides() {
+        schedule_transit(Vec::new());
+    }
+}
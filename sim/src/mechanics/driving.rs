@@ -3,18 +3,17 @@ use crate::mechanics::Queue;
 use crate::{
     ActionAtEnd, AgentID, AgentProperties, CarID, Command, CreateCar, DistanceInterval,
     DrawCarInput, Event, IntersectionSimState, ParkedCar, ParkingSimState, PersonID, Scheduler,
-    TimeInterval, TransitSimState, TripManager, TripPositions, UnzoomedAgent, Vehicle,
+    TimeInterval, TransitSimState, TripManager, TripPositions, UnzoomedAgent, Vehicle, VehicleType,
     WalkingSimState, FOLLOWING_DISTANCE,
 };
 use abstutil::{deserialize_btreemap, serialize_btreemap};
 use geom::{Distance, Duration, PolyLine, Time};
-use map_model::{LaneID, Map, Path, PathStep, Traversable};
+use map_model::{IntersectionID, LaneID, Map, Path, PathStep, Traversable, TurnID};
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashSet, VecDeque};
 
 const TIME_TO_UNPARK: Duration = Duration::const_seconds(10.0);
 const TIME_TO_PARK: Duration = Duration::const_seconds(15.0);
-const TIME_TO_WAIT_AT_STOP: Duration = Duration::const_seconds(10.0);
 
 // TODO Do something else.
 pub(crate) const BLIND_RETRY_TO_CREEP_FORWARDS: Duration = Duration::const_seconds(0.1);
@@ -494,7 +493,7 @@ impl DrivingSimState {
                         false
                     }
                     Some(ActionAtEnd::BusAtStop) => {
-                        transit.bus_arrived_at_stop(
+                        let dwell_time = transit.bus_arrived_at_stop(
                             now,
                             car.vehicle.id,
                             trips,
@@ -503,10 +502,8 @@ impl DrivingSimState {
                             map,
                         );
                         car.total_blocked_time += now - blocked_since;
-                        car.state = CarState::Idling(
-                            our_dist,
-                            TimeInterval::new(now, now + TIME_TO_WAIT_AT_STOP),
-                        );
+                        car.state =
+                            CarState::Idling(our_dist, TimeInterval::new(now, now + dwell_time));
                         scheduler
                             .push(car.state.get_end_time(), Command::UpdateCar(car.vehicle.id));
                         true
@@ -829,6 +826,43 @@ impl DrivingSimState {
         result
     }
 
+    // For transit signal priority: the turns that buses will make at this intersection once they
+    // reach the end of their current lane, and roughly how long until then.
+    // TODO A bus that'll first serve a stop on its current lane arrives later than this says.
+    pub fn buses_approaching(
+        &self,
+        now: Time,
+        i: IntersectionID,
+        map: &Map,
+    ) -> Vec<(TurnID, Duration)> {
+        let mut results = Vec::new();
+        for l in &map.get_i(i).incoming_lanes {
+            let queue = match self.queues.get(&Traversable::Lane(*l)) {
+                Some(q) => q,
+                None => {
+                    continue;
+                }
+            };
+            if !queue.cars.iter().any(|c| c.1 == VehicleType::Bus) {
+                continue;
+            }
+            let speed_limit = map.get_parent(*l).speed_limit;
+            for (id, dist) in queue.get_car_positions(now, &self.cars, &self.queues) {
+                if id.1 != VehicleType::Bus {
+                    continue;
+                }
+                let car = &self.cars[&id];
+                if let CarState::Idling(_, _) = car.state {
+                    continue;
+                }
+                if let Some(Traversable::Turn(t)) = car.router.maybe_next() {
+                    results.push((t, (queue.geom_len - dist) / speed_limit));
+                }
+            }
+        }
+        results
+    }
+
     pub fn does_car_exist(&self, id: CarID) -> bool {
         self.cars.contains_key(&id)
     }
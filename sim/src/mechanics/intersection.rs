@@ -1,11 +1,13 @@
 use crate::mechanics::car::Car;
 use crate::mechanics::Queue;
-use crate::{AgentID, AlertLocation, CarID, Command, Event, Scheduler, Speed, TripMode};
+use crate::{
+    AgentID, AlertLocation, CarID, Command, Event, Scheduler, Speed, TripMode, VehicleType,
+};
 use abstutil::{deserialize_btreemap, retain_btreeset, serialize_btreemap};
 use geom::{Duration, Time};
 use map_model::{
-    ControlStopSign, ControlTrafficSignal, IntersectionID, LaneID, Map, RoadID, Traversable,
-    TurnID, TurnPriority, TurnType,
+    ControlStopSign, ControlTrafficSignal, IntersectionID, LaneID, LaneType, Map, Phase, RoadID,
+    Traversable, TurnID, TurnPriority, TurnType,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{BTreeMap, BTreeSet, HashSet};
@@ -13,6 +15,13 @@ use std::collections::{BTreeMap, BTreeSet, HashSet};
 const WAIT_AT_STOP_SIGN: Duration = Duration::const_seconds(0.5);
 const WAIT_BEFORE_YIELD_AT_TRAFFIC_SIGNAL: Duration = Duration::const_seconds(0.2);
 
+// Transit signal priority can hold a phase at most this much longer for an approaching bus.
+const MAX_GREEN_EXTENSION: Duration = Duration::const_seconds(15.0);
+// After reaching the intersection, how long a bus needs to get through it
+const BUS_CLEARANCE_TIME: Duration = Duration::const_seconds(5.0);
+// With a queue jump, how long general traffic waits at the start of each phase
+const QUEUE_JUMP_HEAD_START: Duration = Duration::const_seconds(5.0);
+
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 pub struct IntersectionSimState {
     state: BTreeMap<IntersectionID, State>,
@@ -35,6 +44,57 @@ struct State {
         deserialize_with = "deserialize_btreemap"
     )]
     waiting: BTreeMap<Request, Time>,
+    // Only for traffic signals. Usually this just follows the timing plan, but transit signal
+    // priority can stretch out or cut short the current phase.
+    signal: Option<SignalState>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct SignalState {
+    current_phase: usize,
+    phase_started: Time,
+    phase_ends: Time,
+    // Transit signal priority only changes each phase once, so a stream of buses can't hold a
+    // phase forever.
+    adjusted: bool,
+}
+
+impl SignalState {
+    // Buses will make turns that're protected now but not in the next phase, arriving after these
+    // amounts of time. Hold the phase long enough for all of them to get through, except for
+    // buses far enough away to just wait for the next cycle. Returns the new end of the phase, if
+    // it changed.
+    fn extend_green(&mut self, now: Time, etas: Vec<Duration>) -> Option<Time> {
+        if self.adjusted {
+            return None;
+        }
+        let extension = etas
+            .into_iter()
+            .map(|eta| eta + BUS_CLEARANCE_TIME)
+            .filter(|needed| *needed <= MAX_GREEN_EXTENSION)
+            .max()?;
+        if extension == Duration::ZERO {
+            return None;
+        }
+        self.adjusted = true;
+        self.phase_ends = now + extension;
+        Some(self.phase_ends)
+    }
+
+    // A bus is waiting for a turn protected in the next phase. Returns the new end of the phase,
+    // if it can end any sooner.
+    fn end_early(&mut self, now: Time, planned_duration: Duration) -> Option<Time> {
+        if self.adjusted {
+            return None;
+        }
+        let earliest_end = (self.phase_started + planned_duration / 2.0).max(now);
+        if earliest_end >= self.phase_ends {
+            return None;
+        }
+        self.adjusted = true;
+        self.phase_ends = earliest_end;
+        Some(earliest_end)
+    }
 }
 
 #[derive(PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Clone, Debug)]
@@ -60,17 +120,28 @@ impl IntersectionSimState {
             events: Vec::new(),
         };
         for i in map.all_intersections() {
-            sim.state.insert(
-                i.id,
-                State {
-                    id: i.id,
-                    accepted: BTreeSet::new(),
-                    waiting: BTreeMap::new(),
-                },
-            );
+            let mut state = State {
+                id: i.id,
+                accepted: BTreeSet::new(),
+                waiting: BTreeMap::new(),
+                signal: None,
+            };
             if i.is_traffic_signal() && !use_freeform_policy_everywhere {
-                sim.update_intersection(Time::START_OF_DAY, i.id, map, scheduler);
+                let (idx, _, remaining) = map
+                    .get_traffic_signal(i.id)
+                    .current_phase_and_remaining_time(Time::START_OF_DAY);
+                state.signal = Some(SignalState {
+                    current_phase: idx,
+                    phase_started: Time::START_OF_DAY,
+                    phase_ends: Time::START_OF_DAY + remaining,
+                    adjusted: false,
+                });
+                scheduler.push(
+                    Time::START_OF_DAY + remaining,
+                    Command::UpdateIntersection(i.id),
+                );
             }
+            sim.state.insert(i.id, state);
         }
         sim
     }
@@ -152,7 +223,7 @@ impl IntersectionSimState {
                 protected.push(req);
             }
         } else if let Some(ref signal) = map.maybe_get_traffic_signal(i) {
-            let (_, phase, _) = signal.current_phase_and_remaining_time(now);
+            let (_, phase, _) = self.current_phase(now, signal);
             for (req, _) in all {
                 match phase.get_priority_of_turn(req.turn, signal) {
                     TurnPriority::Protected => {
@@ -193,19 +264,124 @@ impl IntersectionSimState {
         }
     }
 
-    // This is only triggered for traffic signals.
+    // This is only triggered for traffic signals, when the current phase is supposed to end.
+    // buses_approaching lists the turns that buses will soon make here and roughly how long until
+    // they arrive.
     pub fn update_intersection(
-        &self,
+        &mut self,
         now: Time,
         id: IntersectionID,
         map: &Map,
         scheduler: &mut Scheduler,
+        buses_approaching: Vec<(TurnID, Duration)>,
     ) {
+        let signal = map.get_traffic_signal(id);
+        let signal_priority = map.get_i(id).transit_priority.signal_priority;
+        // The signal might've been edited live, so don't trust current_phase to be in bounds.
+        let state = self
+            .state
+            .get_mut(&id)
+            .unwrap()
+            .signal
+            .get_or_insert(SignalState {
+                current_phase: 0,
+                phase_started: now,
+                phase_ends: now,
+                adjusted: false,
+            });
+        let idx = state.current_phase.min(signal.phases.len() - 1);
+
+        // Green extension: hold the current phase a bit longer if a bus is about to use it.
+        if signal_priority {
+            let current = &signal.phases[idx];
+            let next = &signal.phases[(idx + 1) % signal.phases.len()];
+            let etas = buses_approaching
+                .into_iter()
+                .filter(|(turn, _)| {
+                    current.get_priority_of_turn(*turn, signal) == TurnPriority::Protected
+                        && next.get_priority_of_turn(*turn, signal) != TurnPriority::Protected
+                })
+                .map(|(_, eta)| eta)
+                .collect();
+            if let Some(phase_ends) = state.extend_green(now, etas) {
+                scheduler.push(phase_ends, Command::UpdateIntersection(id));
+                // A bus already at the intersection might not have had enough time before.
+                self.wakeup_waiting(now, id, scheduler, map);
+                return;
+            }
+        }
+
+        state.current_phase = (idx + 1) % signal.phases.len();
+        state.phase_started = now;
+        state.phase_ends = now + signal.phases[state.current_phase].duration;
+        state.adjusted = false;
+        let phase_ends = state.phase_ends;
+
         self.wakeup_waiting(now, id, scheduler, map);
-        let (_, _, remaining) = map
-            .get_traffic_signal(id)
-            .current_phase_and_remaining_time(now);
-        scheduler.push(now + remaining, Command::UpdateIntersection(id));
+        scheduler.push(phase_ends, Command::UpdateIntersection(id));
+    }
+
+    // The current phase of a traffic signal, and how much longer it'll last.
+    pub fn current_phase_and_remaining_time(
+        &self,
+        now: Time,
+        id: IntersectionID,
+        map: &Map,
+    ) -> (usize, Duration) {
+        let (idx, _, remaining) = self.current_phase(now, map.get_traffic_signal(id));
+        (idx, remaining)
+    }
+
+    fn current_phase<'a>(
+        &self,
+        now: Time,
+        signal: &'a ControlTrafficSignal,
+    ) -> (usize, &'a Phase, Duration) {
+        match self.state[&signal.id].signal {
+            Some(ref state) => {
+                let idx = state.current_phase.min(signal.phases.len() - 1);
+                let remaining = if state.phase_ends > now {
+                    state.phase_ends - now
+                } else {
+                    Duration::ZERO
+                };
+                (idx, &signal.phases[idx], remaining)
+            }
+            // Using the freeform policy
+            None => signal.current_phase_and_remaining_time(now),
+        }
+    }
+
+    // Early green: if a bus is waiting for a turn that's protected in the next phase, end the
+    // current phase sooner. Phases still run for at least half of their planned duration, so
+    // pedestrians and cross traffic aren't starved.
+    fn maybe_end_phase_early(
+        &mut self,
+        req: &Request,
+        signal: &ControlTrafficSignal,
+        now: Time,
+        scheduler: &mut Scheduler,
+    ) {
+        match req.agent {
+            AgentID::Car(CarID(_, VehicleType::Bus)) => {}
+            _ => {
+                return;
+            }
+        }
+        let state = match self.state.get_mut(&signal.id).unwrap().signal {
+            Some(ref mut state) => state,
+            None => {
+                return;
+            }
+        };
+        let idx = state.current_phase.min(signal.phases.len() - 1);
+        let next = &signal.phases[(idx + 1) % signal.phases.len()];
+        if next.get_priority_of_turn(req.turn, signal) != TurnPriority::Protected {
+            return;
+        }
+        if let Some(phase_ends) = state.end_early(now, signal.phases[idx].duration) {
+            scheduler.update(phase_ends, Command::UpdateIntersection(signal.id));
+        }
     }
 
     // For cars: The head car calls this when they're at the end of the lane WaitingToAdvance. If
@@ -469,14 +645,35 @@ impl IntersectionSimState {
             return true;
         }
 
-        let (_, phase, remaining_phase_time) = signal.current_phase_and_remaining_time(now);
+        let (_, phase, remaining_phase_time) = self.current_phase(now, signal);
+        let transit_priority = map.get_i(signal.id).transit_priority;
 
         // Can't go at all this phase.
         let our_priority = phase.get_priority_of_turn(req.turn, signal);
         if our_priority == TurnPriority::Banned {
+            if transit_priority.signal_priority {
+                self.maybe_end_phase_early(req, signal, now, scheduler);
+            }
             return false;
         }
 
+        if transit_priority.queue_jump {
+            if let AgentID::Car(CarID(_, vt)) = req.agent {
+                let phase_started = self.state[&signal.id]
+                    .signal
+                    .as_ref()
+                    .map(|s| s.phase_started)
+                    .unwrap_or(now);
+                if let Some(retry) =
+                    queue_jump_hold(vt, has_bus_lane(req.turn.src, map), phase_started, now)
+                {
+                    // Like yielding below, we have "ownership" of scheduling for req.agent.
+                    scheduler.push(retry, Command::update_agent(req.agent));
+                    return false;
+                }
+            }
+        }
+
         // Somebody might already be doing a Yield turn that conflicts with this one.
         if !self.handle_accepted_conflicts(req, map, maybe_cars_and_queues) {
             return false;
@@ -619,6 +816,35 @@ impl IntersectionSimState {
     }
 }
 
+// Is there a bus-only lane on the same side of the road as this lane?
+fn has_bus_lane(l: LaneID, map: &Map) -> bool {
+    let r = map.get_parent(l);
+    let (fwds, _) = r.dir_and_offset(l);
+    let children = if fwds {
+        &r.children_forwards
+    } else {
+        &r.children_backwards
+    };
+    children.iter().any(|(_, lt)| *lt == LaneType::Bus)
+}
+
+// With a queue jump, cars sharing an approach with a bus-only lane wait a moment at the start of
+// the phase, so buses get through first. Bikes don't compete with buses for the same space, so
+// they go as usual. If the vehicle has to wait, returns when to try again.
+fn queue_jump_hold(
+    vt: VehicleType,
+    bus_lane_on_approach: bool,
+    phase_started: Time,
+    now: Time,
+) -> Option<Time> {
+    if vt == VehicleType::Car && bus_lane_on_approach && now < phase_started + QUEUE_JUMP_HEAD_START
+    {
+        Some(phase_started + QUEUE_JUMP_HEAD_START)
+    } else {
+        None
+    }
+}
+
 // TODO Sometimes a traffic signal is surrounded by tiny lanes with almost no capacity. Workaround
 // for now.
 fn allow_block_the_box(osm_node_id: i64) -> bool {
@@ -665,3 +891,114 @@ fn allow_conflicting_turns(osm_node_id: i64) -> bool {
     ]
     .contains(&osm_node_id)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue_jump() {
+        let started = Time::START_OF_DAY + Duration::minutes(10);
+        let early = started + Duration::seconds(1.0);
+        let late = started + QUEUE_JUMP_HEAD_START;
+
+        // Cars next to a bus lane wait for the head start
+        assert_eq!(
+            queue_jump_hold(VehicleType::Car, true, started, early),
+            Some(started + QUEUE_JUMP_HEAD_START)
+        );
+        assert_eq!(queue_jump_hold(VehicleType::Car, true, started, late), None);
+        // But not without a bus lane
+        assert_eq!(
+            queue_jump_hold(VehicleType::Car, false, started, early),
+            None
+        );
+        // Buses and bikes never wait
+        assert_eq!(
+            queue_jump_hold(VehicleType::Bus, true, started, early),
+            None
+        );
+        assert_eq!(
+            queue_jump_hold(VehicleType::Bike, true, started, early),
+            None
+        );
+    }
+
+    fn phase(started: Time, duration: Duration) -> SignalState {
+        SignalState {
+            current_phase: 0,
+            phase_started: started,
+            phase_ends: started + duration,
+            adjusted: false,
+        }
+    }
+
+    #[test]
+    fn green_extension() {
+        let started = Time::START_OF_DAY + Duration::minutes(10);
+        let ends = started + Duration::seconds(30.0);
+
+        // Hold the phase for the slowest bus that can make it
+        let mut state = phase(started, Duration::seconds(30.0));
+        assert_eq!(
+            state.extend_green(
+                ends,
+                vec![
+                    Duration::seconds(2.0),
+                    Duration::seconds(8.0),
+                    MAX_GREEN_EXTENSION
+                ]
+            ),
+            Some(ends + Duration::seconds(8.0) + BUS_CLEARANCE_TIME)
+        );
+        assert_eq!(
+            state.phase_ends,
+            ends + Duration::seconds(8.0) + BUS_CLEARANCE_TIME
+        );
+        // Only once per phase
+        assert_eq!(
+            state.extend_green(state.phase_ends, vec![Duration::seconds(1.0)]),
+            None
+        );
+
+        // Buses too far away or not coming at all don't hold the phase
+        let mut state = phase(started, Duration::seconds(30.0));
+        assert_eq!(state.extend_green(ends, vec![MAX_GREEN_EXTENSION]), None);
+        assert_eq!(state.extend_green(ends, Vec::new()), None);
+        assert_eq!(state.phase_ends, ends);
+        assert!(!state.adjusted);
+    }
+
+    #[test]
+    fn end_phase_early() {
+        let started = Time::START_OF_DAY + Duration::minutes(10);
+        let duration = Duration::seconds(30.0);
+
+        // The phase still runs for half of its planned duration
+        let mut state = phase(started, duration);
+        assert_eq!(
+            state.end_early(started + Duration::seconds(5.0), duration),
+            Some(started + Duration::seconds(15.0))
+        );
+        assert_eq!(state.phase_ends, started + Duration::seconds(15.0));
+        // Only once per phase
+        assert_eq!(
+            state.end_early(started + Duration::seconds(6.0), duration),
+            None
+        );
+
+        // Past the halfway point, end right away
+        let mut state = phase(started, duration);
+        let now = started + Duration::seconds(20.0);
+        assert_eq!(state.end_early(now, duration), Some(now));
+
+        // A phase already ending sooner isn't stretched out
+        let mut state = phase(started, duration);
+        state.phase_ends = started + Duration::seconds(10.0);
+        assert_eq!(
+            state.end_early(started + Duration::seconds(5.0), duration),
+            None
+        );
+        assert!(!state.adjusted);
+    }
+}
@@ -0,0 +1,89 @@
+use abstutil::CmdArgs;
+use geom::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+// Anybody who shares a space with somebody for at least this long is a close contact.
+pub const CLOSE_CONTACT: Duration = Duration::const_seconds(15.0 * 60.0);
+// How far back does contact tracing look?
+pub const TRACING_WINDOW: Duration = Duration::const_seconds(14.0 * 24.0 * 3600.0);
+
+// Policies that the PandemicModel enforces. These're fixed when the simulation starts, so that
+// different runs can be compared.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interventions {
+    // What fraction of people wear masks? Each person decides once, at the start.
+    pub mask_compliance: f64,
+    // How much does each mask reduce transmission between two people?
+    pub mask_efficacy: f64,
+    // Trips to buildings with any of these amenity types (like "restaurant" or "school") are
+    // cancelled.
+    pub closed_amenities: BTreeSet<String>,
+    // Trips to a building that already has this many people inside are cancelled.
+    pub max_bldg_occupancy: Option<usize>,
+    // Once somebody becomes infectious, cancel all of their remaining trips.
+    pub quarantine_symptomatic: bool,
+    // When somebody is quarantined, also quarantine all of their recent close contacts. Only
+    // matters with quarantine_symptomatic.
+    pub contact_tracing: bool,
+}
+
+impl Interventions {
+    pub fn none() -> Interventions {
+        Interventions {
+            mask_compliance: 0.0,
+            mask_efficacy: 0.5,
+            closed_amenities: BTreeSet::new(),
+            max_bldg_occupancy: None,
+            quarantine_symptomatic: false,
+            contact_tracing: false,
+        }
+    }
+
+    // --masks=0.8 --mask_efficacy=0.5 --close=restaurant,school --max_occupancy=50 --quarantine
+    // --contact_tracing
+    pub fn from_args(args: &mut CmdArgs) -> Interventions {
+        let default = Interventions::none();
+        Interventions {
+            mask_compliance: args
+                .optional_parse("--masks", parse_fraction)
+                .unwrap_or(default.mask_compliance),
+            mask_efficacy: args
+                .optional_parse("--mask_efficacy", parse_fraction)
+                .unwrap_or(default.mask_efficacy),
+            closed_amenities: args
+                .optional("--close")
+                .map(|x| x.split(',').map(|a| a.to_string()).collect())
+                .unwrap_or(default.closed_amenities),
+            max_bldg_occupancy: args.optional_parse("--max_occupancy", |s| s.parse::<usize>()),
+            quarantine_symptomatic: args.enabled("--quarantine"),
+            contact_tracing: args.enabled("--contact_tracing"),
+        }
+    }
+}
+
+// A number in [0, 1]
+fn parse_fraction(x: &str) -> Result<f64, String> {
+    let value = x.parse::<f64>().map_err(|err| err.to_string())?;
+    if (0.0..=1.0).contains(&value) {
+        Ok(value)
+    } else {
+        Err(format!("{} isn't between 0 and 1", x))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fractions() {
+        assert_eq!(parse_fraction("0"), Ok(0.0));
+        assert_eq!(parse_fraction("0.8"), Ok(0.8));
+        assert_eq!(parse_fraction("1"), Ok(1.0));
+        assert!(parse_fraction("80").is_err());
+        assert!(parse_fraction("-0.1").is_err());
+        assert!(parse_fraction("NaN").is_err());
+        assert!(parse_fraction("most").is_err());
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{OffMapLocation, PersonID, TripMode};
+use abstutil::{deserialize_btreemap, serialize_btreemap};
+use geom::{Duration, Pt2D, Speed, Time};
+use map_model::{IntersectionID, Map};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Off-map parcels (keyed by OffMapLocation::parcel_id) act like shared spaces, but we usually
+// only find out how long somebody spent at one after the fact. When somebody leaves the map, we
+// only know the parcel they're heading towards; when they return, we only know the parcel they're
+// coming from. So infer each visit once it's over, and compare it against other recent visits to
+// the same parcel.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct OffMapActivity {
+    // Who's off-map right now, at what parcel, and since when?
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    away: BTreeMap<PersonID, (usize, Time)>,
+    // Finished visits to each parcel that could still overlap somebody's future visit
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    visits: BTreeMap<usize, Vec<(PersonID, Time, Time)>>,
+}
+
+impl OffMapActivity {
+    pub fn new() -> OffMapActivity {
+        OffMapActivity {
+            away: BTreeMap::new(),
+            visits: BTreeMap::new(),
+        }
+    }
+
+    pub fn person_enters_remote_bldg(&mut self, now: Time, person: PersonID, loc: &OffMapLocation) {
+        self.away.insert(person, (loc.parcel_id, now));
+    }
+
+    // Returns all other people that the person shared the parcel with, and for how long.
+    pub fn person_leaves_remote_bldg(
+        &mut self,
+        now: Time,
+        person: PersonID,
+        loc: &OffMapLocation,
+    ) -> Vec<(PersonID, Duration)> {
+        if let Some(overlaps) = self.person_returns(now, person, loc.parcel_id, now) {
+            overlaps
+        } else {
+            panic!("{} left {:?}, but they weren't inside", person, loc);
+        }
+    }
+
+    pub fn person_leaves_map(
+        &mut self,
+        now: Time,
+        person: PersonID,
+        mode: TripMode,
+        i: IntersectionID,
+        loc: &OffMapLocation,
+        map: &Map,
+    ) {
+        let arrived = now + travel_time(mode, i, loc, map);
+        self.away.insert(person, (loc.parcel_id, arrived));
+    }
+
+    // Returns all other people that the person shared a parcel with since they left the map, and
+    // for how long.
+    pub fn person_enters_map(
+        &mut self,
+        now: Time,
+        person: PersonID,
+        mode: TripMode,
+        i: IntersectionID,
+        loc: &OffMapLocation,
+        map: &Map,
+    ) -> Vec<(PersonID, Duration)> {
+        let departed = departed_before(now, travel_time(mode, i, loc, map));
+        // This is expected for somebody whose day starts off-map, or who left the map without a
+        // known destination. Since we don't know how long they were at the parcel, skip it.
+        self.person_returns(now, person, loc.parcel_id, departed)
+            .unwrap_or_else(Vec::new)
+    }
+
+    // Returns None if the person wasn't away.
+    fn person_returns(
+        &mut self,
+        now: Time,
+        person: PersonID,
+        parcel2: usize,
+        departed: Time,
+    ) -> Option<Vec<(PersonID, Duration)>> {
+        let (parcel1, arrived) = self.away.remove(&person)?;
+        let departed = departed.max(arrived);
+
+        // If they return from a different parcel than the one they left for, assume they split
+        // the time evenly between both.
+        let stays = if parcel1 == parcel2 {
+            vec![(parcel1, arrived, departed)]
+        } else {
+            let midpoint = arrived + (departed - arrived) / 2.0;
+            vec![(parcel1, arrived, midpoint), (parcel2, midpoint, departed)]
+        };
+
+        let mut overlaps = Vec::new();
+        for (parcel, start, end) in stays {
+            let visits = self.visits.entry(parcel).or_insert_with(Vec::new);
+            for (other, other_start, other_end) in visits.iter() {
+                let overlap = end.min(*other_end) - start.max(*other_start);
+                if overlap > Duration::ZERO {
+                    overlaps.push((*other, overlap));
+                }
+            }
+            visits.push((person, start, end));
+        }
+
+        // Nobody's future visit can start before now, or before anybody currently away arrived
+        // somewhere. Forget about visits that ended before then.
+        let mut earliest = now;
+        for (_, t) in self.away.values() {
+            if *t < earliest {
+                earliest = *t;
+            }
+        }
+        for visits in self.visits.values_mut() {
+            visits.retain(|(_, _, end)| *end >= earliest);
+        }
+
+        Some(overlaps)
+    }
+}
+
+// When did somebody leave a parcel, if they reached the map at this time? Times before midnight
+// can't be represented, so anybody arriving early in the day left at midnight.
+fn departed_before(now: Time, travel_time: Duration) -> Time {
+    if now - Time::START_OF_DAY > travel_time {
+        now - travel_time
+    } else {
+        Time::START_OF_DAY
+    }
+}
+
+// Roughly how long does it take to get between the border and the parcel?
+fn travel_time(mode: TripMode, i: IntersectionID, loc: &OffMapLocation, map: &Map) -> Duration {
+    let speed = match mode {
+        TripMode::Walk => Speed::meters_per_second(1.34),
+        TripMode::Bike => Speed::miles_per_hour(10.0),
+        TripMode::Drive | TripMode::Transit => Speed::miles_per_hour(25.0),
+    };
+    let dist = Pt2D::forcibly_from_gps(loc.gps, map.get_gps_bounds())
+        .dist_to(map.get_i(i).polygon.center());
+    dist / speed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geom::LonLat;
+
+    fn time(x: usize) -> Time {
+        Time::START_OF_DAY + Duration::hours(x)
+    }
+
+    fn parcel(id: usize) -> OffMapLocation {
+        OffMapLocation {
+            parcel_id: id,
+            gps: LonLat::new(0.0, 0.0),
+        }
+    }
+
+    #[test]
+    fn remote_bldg_overlap() {
+        let mut activity = OffMapActivity::new();
+        let (p1, p2, p3) = (PersonID(1), PersonID(2), PersonID(3));
+
+        activity.person_enters_remote_bldg(time(1), p1, &parcel(1));
+        activity.person_enters_remote_bldg(time(2), p2, &parcel(1));
+        activity.person_enters_remote_bldg(time(2), p3, &parcel(2));
+        // Nobody else has finished a visit yet
+        assert_eq!(
+            activity.person_leaves_remote_bldg(time(4), p1, &parcel(1)),
+            Vec::new()
+        );
+        // p1 was there from 2 to 4. p3 was somewhere else.
+        assert_eq!(
+            activity.person_leaves_remote_bldg(time(5), p2, &parcel(1)),
+            vec![(p1, Duration::hours(2))]
+        );
+        assert_eq!(
+            activity.person_leaves_remote_bldg(time(5), p3, &parcel(2)),
+            Vec::new()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn leave_remote_bldg_without_entering() {
+        OffMapActivity::new().person_leaves_remote_bldg(time(1), PersonID(1), &parcel(1));
+    }
+
+    #[test]
+    fn return_from_different_parcel() {
+        let mut activity = OffMapActivity::new();
+        let (p1, p2, p3) = (PersonID(1), PersonID(2), PersonID(3));
+
+        activity.person_enters_remote_bldg(time(0), p2, &parcel(1));
+        activity.person_enters_remote_bldg(time(0), p3, &parcel(2));
+        // Keep their visits around until p1 returns
+        activity.away.insert(p1, (1, time(2)));
+        activity.person_leaves_remote_bldg(time(10), p2, &parcel(1));
+        activity.person_leaves_remote_bldg(time(10), p3, &parcel(2));
+
+        // p1 arrived at parcel 1 at 2 and left parcel 2 at 8, so assume 2-5 at parcel 1 and 5-8 at
+        // parcel 2.
+        assert_eq!(
+            activity.person_returns(time(9), p1, 2, time(8)),
+            Some(vec![(p2, Duration::hours(3)), (p3, Duration::hours(3))])
+        );
+        // Nobody's away anymore, so p1's visits, which ended before now, are forgotten
+        assert!(activity.visits.values().flatten().all(|(p, _, _)| *p != p1));
+        // Returning again without leaving isn't a visit
+        assert_eq!(activity.person_returns(time(9), p1, 2, time(8)), None);
+    }
+
+    #[test]
+    fn departed_before_midnight() {
+        assert_eq!(
+            departed_before(time(2), Duration::minutes(30)),
+            time(1) + Duration::minutes(30)
+        );
+        assert_eq!(
+            departed_before(time(0) + Duration::minutes(10), Duration::minutes(30)),
+            time(0)
+        );
+    }
+}
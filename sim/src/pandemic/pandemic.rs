@@ -1,28 +1,52 @@
-use crate::pandemic::{AnyTime, State};
-use crate::{CarID, Event, OffMapLocation, Person, PersonID, Scheduler, TripPhaseType};
+use crate::pandemic::interventions::{CLOSE_CONTACT, TRACING_WINDOW};
+use crate::pandemic::off_map::OffMapActivity;
+use crate::pandemic::{AnyTime, Interventions, State};
+use crate::{CarID, Command, Event, Person, PersonID, Scheduler, TripEndpoint, TripPhaseType};
+use abstutil::{deserialize_btreemap, serialize_btreemap};
 use geom::{Duration, Time};
-use map_model::{BuildingID, BusStopID};
+use map_model::{BuildingID, BusStopID, Map};
 use rand::Rng;
 use rand_xorshift::XorShiftRng;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 // TODO This does not model transmission by surfaces; only person-to-person.
 // TODO If two people are in the same shared space indefinitely and neither leaves, we don't model
 // transmission. It only occurs when people leave a space.
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PandemicModel {
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
     pop: BTreeMap<PersonID, State>,
 
     bldgs: SharedSpace<BuildingID>,
-    remote_bldgs: SharedSpace<OffMapLocation>,
+    // Shared spaces outside the map boundary, keyed by OffMapLocation::parcel_id
+    off_map: OffMapActivity,
     bus_stops: SharedSpace<BusStopID>,
     buses: SharedSpace<CarID>,
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
     person_to_bus: BTreeMap<PersonID, CarID>,
 
+    interventions: Interventions,
+    masked: BTreeSet<PersonID>,
+    quarantined: BTreeSet<PersonID>,
+    // Only recorded for contact tracing. (other person, when they parted ways)
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    contacts: BTreeMap<PersonID, Vec<(PersonID, Time)>>,
+
     rng: XorShiftRng,
     initialized: bool,
+
+    events: Vec<Event>,
 }
 
 // You can schedule callbacks in the future by doing scheduler.push(future time, one of these)
@@ -30,6 +54,17 @@ pub struct PandemicModel {
 pub enum Cmd {
     BecomeHospitalized(PersonID),
     BecomeQuarantined(PersonID),
+    // Happens once per day, to record SEIR counts
+    RecordCounts,
+}
+
+// Where was somebody exposed?
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum InfectionSpace {
+    Building,
+    RemoteBuilding,
+    BusStop,
+    Bus,
 }
 
 // TODO Pretend handle_event and handle_cmd also take in some object that lets you do things like:
@@ -41,24 +76,31 @@ pub enum Cmd {
 // from there.
 
 impl PandemicModel {
-    pub fn new(rng: XorShiftRng) -> PandemicModel {
+    pub fn new(rng: XorShiftRng, interventions: Interventions) -> PandemicModel {
         PandemicModel {
             pop: BTreeMap::new(),
 
             bldgs: SharedSpace::new(),
-            remote_bldgs: SharedSpace::new(),
+            off_map: OffMapActivity::new(),
             bus_stops: SharedSpace::new(),
             buses: SharedSpace::new(),
             person_to_bus: BTreeMap::new(),
 
+            interventions,
+            masked: BTreeSet::new(),
+            quarantined: BTreeSet::new(),
+            contacts: BTreeMap::new(),
+
             rng,
             initialized: false,
+
+            events: Vec::new(),
         }
     }
 
     // Sorry, initialization order of simulations is still a bit messy. This'll be called at
     // Time::START_OF_DAY after all of the people have been created from a Scenario.
-    pub fn initialize(&mut self, population: &Vec<Person>, _scheduler: &mut Scheduler) {
+    pub fn initialize(&mut self, population: &Vec<Person>, scheduler: &mut Scheduler) {
         assert!(!self.initialized);
         self.initialized = true;
 
@@ -87,7 +129,18 @@ impl PandemicModel {
                 state
             };
             self.pop.insert(p.id, state);
+            self.maybe_schedule_quarantine(Time::START_OF_DAY, p.id, scheduler);
+
+            // Don't touch the RNG at all without masks, so those runs match ones from before masks
+            // existed.
+            if self.interventions.mask_compliance > 0.0
+                && self.rng.gen_bool(self.interventions.mask_compliance)
+            {
+                self.masked.insert(p.id);
+            }
         }
+
+        scheduler.push(Time::START_OF_DAY, Command::Pandemic(Cmd::RecordCounts));
     }
 
     pub fn count_sane(&self) -> usize {
@@ -153,7 +206,7 @@ impl PandemicModel {
             + self.count_dead()
     }
 
-    pub fn handle_event(&mut self, now: Time, ev: &Event, scheduler: &mut Scheduler) {
+    pub fn handle_event(&mut self, now: Time, ev: &Event, map: &Map, scheduler: &mut Scheduler) {
         assert!(self.initialized);
 
         match ev {
@@ -162,24 +215,23 @@ impl PandemicModel {
             }
             Event::PersonLeavesBuilding(person, bldg) => {
                 if let Some(others) = self.bldgs.person_leaves_space(now, *person, *bldg) {
-                    self.transmission(now, *person, others, scheduler);
+                    self.transmission(now, *person, others, InfectionSpace::Building, scheduler);
                 } else {
                     panic!("{} left {}, but they weren't inside", person, bldg);
                 }
             }
             Event::PersonEntersRemoteBuilding(person, loc) => {
-                self.remote_bldgs
-                    .person_enters_space(now, *person, loc.clone());
+                self.off_map.person_enters_remote_bldg(now, *person, loc);
             }
             Event::PersonLeavesRemoteBuilding(person, loc) => {
-                if let Some(others) =
-                    self.remote_bldgs
-                        .person_leaves_space(now, *person, loc.clone())
-                {
-                    self.transmission(now, *person, others, scheduler);
-                } else {
-                    panic!("{} left {:?}, but they weren't inside", person, loc);
-                }
+                let others = self.off_map.person_leaves_remote_bldg(now, *person, loc);
+                self.transmission(
+                    now,
+                    *person,
+                    others,
+                    InfectionSpace::RemoteBuilding,
+                    scheduler,
+                );
             }
             Event::TripPhaseStarting(_, p, _, tpt) => {
                 let person = *p;
@@ -192,7 +244,7 @@ impl PandemicModel {
                             .bus_stops
                             .person_leaves_space(now, person, *stop)
                             .unwrap();
-                        self.transmission(now, person, others, scheduler);
+                        self.transmission(now, person, others, InfectionSpace::BusStop, scheduler);
 
                         self.buses.person_enters_space(now, person, *bus);
                         self.person_to_bus.insert(person, *bus);
@@ -203,7 +255,7 @@ impl PandemicModel {
                         // of a bus ride.
                         if let Some(car) = self.person_to_bus.remove(&person) {
                             let others = self.buses.person_leaves_space(now, person, car).unwrap();
-                            self.transmission(now, person, others, scheduler);
+                            self.transmission(now, person, others, InfectionSpace::Bus, scheduler);
                         }
                     }
                     _ => {
@@ -211,39 +263,120 @@ impl PandemicModel {
                     }
                 }
             }
-            Event::PersonLeavesMap(_person, _, _, loc) => {
-                if let Some(_loc) = loc {
-                    // TODO Could make a SharedSpace for loc.parcel_id, representing buildings
-                    // off-map.
-                }
+            Event::PersonLeavesMap(person, mode, i, Some(loc)) => {
+                self.off_map
+                    .person_leaves_map(now, *person, *mode, *i, loc, map);
             }
-            Event::PersonEntersMap(_person, _, _, loc) => {
-                if let Some(_loc) = loc {
-                    // TODO But we don't know how long the person spent at these parcels. They
-                    // could've taken tons of trips to other off-map parcels in between
-                    // PersonLeavesMap and PersonEntersMap.
-                }
+            Event::PersonEntersMap(person, mode, i, Some(loc)) => {
+                // Only now do we know how long the person spent at off-map parcels.
+                let others = self
+                    .off_map
+                    .person_enters_map(now, *person, *mode, *i, loc, map);
+                self.transmission(
+                    now,
+                    *person,
+                    others,
+                    InfectionSpace::RemoteBuilding,
+                    scheduler,
+                );
             }
             _ => {}
         }
     }
 
-    pub fn handle_cmd(&mut self, _now: Time, cmd: Cmd, _scheduler: &mut Scheduler) {
+    // Returns everybody who just went into quarantine. The caller should cancel their remaining
+    // trips.
+    pub fn handle_cmd(&mut self, now: Time, cmd: Cmd, scheduler: &mut Scheduler) -> Vec<PersonID> {
         assert!(self.initialized);
 
-        // TODO Here we might enforce policies. Like severe -> become hospitalized
-        // Symptomatic -> stay quaratined, and/or track contacts to quarantine them too (or test
-        // them)
         match cmd {
             Cmd::BecomeHospitalized(_person) => {
                 // self.hospitalized.insert(person);
+                Vec::new()
+            }
+            Cmd::BecomeQuarantined(person) => {
+                if !self.quarantined.insert(person) {
+                    return Vec::new();
+                }
+                let mut newly_quarantined = vec![person];
+                if self.interventions.contact_tracing {
+                    for (other, t) in self.contacts.remove(&person).unwrap_or_else(Vec::new) {
+                        if now - t <= TRACING_WINDOW && self.quarantined.insert(other) {
+                            newly_quarantined.push(other);
+                        }
+                    }
+                }
+                newly_quarantined
             }
-            Cmd::BecomeQuarantined(_person) => {
-                // self.quarantined.insert(person);
+            Cmd::RecordCounts => {
+                self.forget_old_contacts(now);
+                self.events.push(Event::PandemicCounts {
+                    sane: self.count_sane(),
+                    exposed: self.count_exposed(),
+                    infectious: self.count_infected(),
+                    recovered: self.count_recovered(),
+                    dead: self.count_dead(),
+                });
+                scheduler.push(
+                    now + Duration::hours(24),
+                    Command::Pandemic(Cmd::RecordCounts),
+                );
+                Vec::new()
             }
         }
     }
 
+    pub fn collect_events(&mut self) -> Vec<Event> {
+        std::mem::replace(&mut self.events, Vec::new())
+    }
+
+    // Should a trip heading somewhere be cancelled, due to closures or capacity limits? home is
+    // where the person making the trip lives, if known.
+    pub fn should_cancel_trip(
+        &self,
+        end: &TripEndpoint,
+        home: Option<BuildingID>,
+        map: &Map,
+    ) -> bool {
+        match end {
+            TripEndpoint::Bldg(b) => self.turned_away(*b, home, &map.get_b(*b).amenities),
+            _ => false,
+        }
+    }
+
+    // Closures and capacity limits only apply to visitors. People can always go home, and
+    // buildings without amenities are assumed to be residential.
+    fn turned_away(
+        &self,
+        b: BuildingID,
+        home: Option<BuildingID>,
+        amenities: &BTreeSet<(String, String)>,
+    ) -> bool {
+        if home == Some(b) || amenities.is_empty() {
+            return false;
+        }
+        if amenities
+            .iter()
+            .any(|(_, amenity)| self.interventions.closed_amenities.contains(amenity))
+        {
+            return true;
+        }
+        if let Some(max) = self.interventions.max_bldg_occupancy {
+            if self.bldgs.num_occupants(&b) >= max {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn is_quarantined(&self, person: PersonID) -> bool {
+        self.quarantined.contains(&person)
+    }
+
+    pub fn is_masked(&self, person: PersonID) -> bool {
+        self.masked.contains(&person)
+    }
+
     pub fn get_time(&self, person: PersonID) -> Option<Time> {
         match self.pop.get(&person) {
             Some(state) => state.get_time(),
@@ -300,15 +433,66 @@ impl PandemicModel {
         now: Time,
         person: PersonID,
         other_occupants: Vec<(PersonID, Duration)>,
+        space: InfectionSpace,
         scheduler: &mut Scheduler,
     ) {
         // person has spent some duration in the same space as other people. Does transmission
         // occur?
         for (other, overlap) in other_occupants {
+            if self.interventions.contact_tracing && overlap >= CLOSE_CONTACT {
+                self.contacts
+                    .entry(person)
+                    .or_insert_with(Vec::new)
+                    .push((other, now));
+                self.contacts
+                    .entry(other)
+                    .or_insert_with(Vec::new)
+                    .push((person, now));
+            }
+
             if let Some(pid) = self.infectious_contact(person, other) {
-                self.become_exposed(now, overlap, pid, scheduler);
+                let exposure = self.effective_exposure(person, other, overlap);
+                self.become_exposed(now, exposure, pid, space, scheduler);
+            }
+        }
+    }
+
+    // Contacts from before the tracing window will never be traced, so stop remembering them.
+    fn forget_old_contacts(&mut self, now: Time) {
+        self.contacts.retain(|_, list| {
+            list.retain(|(_, t)| now - *t <= TRACING_WINDOW);
+            !list.is_empty()
+        });
+    }
+
+    // Each mask worn cuts down the effective exposure.
+    fn effective_exposure(&self, person: PersonID, other: PersonID, overlap: Duration) -> Duration {
+        let mut exposure = overlap;
+        for p in &[person, other] {
+            if self.masked.contains(p) {
+                exposure = exposure * (1.0 - self.interventions.mask_efficacy);
             }
         }
+        exposure
+    }
+
+    // If this person is exposed or infectious and quarantine is on, schedule them to quarantine
+    // once they're symptomatic.
+    fn maybe_schedule_quarantine(&self, now: Time, person: PersonID, scheduler: &mut Scheduler) {
+        if !self.interventions.quarantine_symptomatic {
+            return;
+        }
+        let state = &self.pop[&person];
+        let when = if state.is_infectious() {
+            now
+        } else if state.is_exposed() {
+            // The sampled incubation time could be negative
+            let t: Time = state.get_event_time().unwrap().into();
+            t.max(now)
+        } else {
+            return;
+        };
+        scheduler.push(when, Command::Pandemic(Cmd::BecomeQuarantined(person)));
     }
 
     // transition from a state to another without interaction with others
@@ -330,7 +514,8 @@ impl PandemicModel {
         now: Time,
         overlap: Duration,
         person: PersonID,
-        _scheduler: &mut Scheduler,
+        space: InfectionSpace,
+        scheduler: &mut Scheduler,
     ) {
         // When poeple become expose
         let state = self.pop.remove(&person).unwrap();
@@ -341,7 +526,12 @@ impl PandemicModel {
         let state = state
             .start(AnyTime::from(now), overlap, &mut self.rng)
             .unwrap();
+        let exposed = state.is_exposed();
         self.pop.insert(person, state);
+        if exposed {
+            self.events.push(Event::PandemicExposure(person, space));
+        }
+        self.maybe_schedule_quarantine(now, person, scheduler);
 
         // if self.rng.gen_bool(0.1) {
         //     scheduler.push(
@@ -352,12 +542,17 @@ impl PandemicModel {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(bound(serialize = "T: Serialize", deserialize = "T: Deserialize<'de> + Ord"))]
 struct SharedSpace<T: Ord> {
     // Since when has a person been in some shared space?
     // TODO This is an awkward data structure; abstutil::MultiMap is also bad, because key removal
     // would require knowing the time. Want something closer to
     // https://guava.dev/releases/19.0/api/docs/com/google/common/collect/Table.html.
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
     occupants: BTreeMap<T, Vec<(PersonID, Time)>>,
 }
 
@@ -368,6 +563,10 @@ impl<T: Ord> SharedSpace<T> {
         }
     }
 
+    fn num_occupants(&self, space: &T) -> usize {
+        self.occupants.get(space).map(|v| v.len()).unwrap_or(0)
+    }
+
     fn person_enters_space(&mut self, now: Time, person: PersonID, space: T) {
         self.occupants
             .entry(space)
@@ -410,6 +609,9 @@ impl<T: Ord> SharedSpace<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::TripManager;
+    use geom::Speed;
+    use rand::SeedableRng;
 
     fn time(x: usize) -> Time {
         Time::START_OF_DAY + Duration::hours(x)
@@ -469,4 +671,164 @@ mod tests {
             Some(vec![(person3, Duration::hours(5))])
         );
     }
+
+    fn model(interventions: Interventions, num_people: usize) -> PandemicModel {
+        let mut trips = TripManager::new(false);
+        for _ in 0..num_people {
+            trips.random_person(Speed::meters_per_second(1.0), Vec::new());
+        }
+        let mut model = PandemicModel::new(XorShiftRng::from_seed([42; 16]), interventions);
+        model.initialize(trips.get_all_people(), &mut Scheduler::new());
+        model
+    }
+
+    #[test]
+    fn masks_dont_change_baseline() {
+        let mut no_masks = model(Interventions::none(), 100);
+        assert!(no_masks.masked.is_empty());
+
+        // gen_bool(1.0) doesn't consume anything from the RNG, so if the no-mask run also skips
+        // it, both runs are identical apart from who's masked.
+        let mut everyone = Interventions::none();
+        everyone.mask_compliance = 1.0;
+        let mut all_masks = model(everyone, 100);
+        assert_eq!(all_masks.masked.len(), 100);
+        assert_eq!(
+            format!("{:?}", no_masks.pop),
+            format!("{:?}", all_masks.pop)
+        );
+        assert_eq!(no_masks.rng.gen::<u64>(), all_masks.rng.gen::<u64>());
+    }
+
+    #[test]
+    fn mask_efficacy() {
+        let mut interventions = Interventions::none();
+        interventions.mask_efficacy = 0.5;
+        let mut model = model(interventions, 3);
+        model.masked.insert(PersonID(0));
+        model.masked.insert(PersonID(1));
+
+        let overlap = Duration::hours(1);
+        assert_eq!(
+            model.effective_exposure(PersonID(2), PersonID(2), overlap),
+            overlap
+        );
+        assert_eq!(
+            model.effective_exposure(PersonID(0), PersonID(2), overlap),
+            Duration::minutes(30)
+        );
+        assert_eq!(
+            model.effective_exposure(PersonID(0), PersonID(1), overlap),
+            Duration::minutes(15)
+        );
+    }
+
+    #[test]
+    fn contact_tracing() {
+        let mut interventions = Interventions::none();
+        interventions.quarantine_symptomatic = true;
+        interventions.contact_tracing = true;
+        let mut model = model(interventions, 4);
+        // Make sure nobody can infect anybody, so only contact tracing is involved
+        for state in model.pop.values_mut() {
+            *state = State::new(0.5, 0.5);
+        }
+        let mut scheduler = Scheduler::new();
+        let (p0, p1, p2, p3) = (PersonID(0), PersonID(1), PersonID(2), PersonID(3));
+
+        // p1 is a close contact of p0; p2 wasn't around for long enough. p3 was a close contact,
+        // but long ago.
+        model.transmission(
+            time(1),
+            p0,
+            vec![(p3, CLOSE_CONTACT)],
+            InfectionSpace::Building,
+            &mut scheduler,
+        );
+        let later = time(1) + TRACING_WINDOW + Duration::hours(2);
+        model.transmission(
+            later,
+            p0,
+            vec![
+                (p1, CLOSE_CONTACT),
+                (p2, CLOSE_CONTACT - Duration::minutes(1)),
+            ],
+            InfectionSpace::Bus,
+            &mut scheduler,
+        );
+
+        assert_eq!(
+            model.handle_cmd(later, Cmd::BecomeQuarantined(p0), &mut scheduler),
+            vec![p0, p1]
+        );
+        assert!(model.is_quarantined(p1));
+        assert!(!model.is_quarantined(p2));
+        assert!(!model.is_quarantined(p3));
+        // Quarantining again does nothing
+        assert!(model
+            .handle_cmd(later, Cmd::BecomeQuarantined(p0), &mut scheduler)
+            .is_empty());
+    }
+
+    #[test]
+    fn old_contacts_forgotten() {
+        let mut interventions = Interventions::none();
+        interventions.contact_tracing = true;
+        let mut model = model(interventions, 3);
+        for state in model.pop.values_mut() {
+            *state = State::new(0.5, 0.5);
+        }
+        let mut scheduler = Scheduler::new();
+        let (p0, p1, p2) = (PersonID(0), PersonID(1), PersonID(2));
+
+        model.transmission(
+            time(1),
+            p0,
+            vec![(p1, CLOSE_CONTACT)],
+            InfectionSpace::Building,
+            &mut scheduler,
+        );
+        let later = time(1) + TRACING_WINDOW + Duration::hours(1);
+        model.transmission(
+            later,
+            p0,
+            vec![(p2, CLOSE_CONTACT)],
+            InfectionSpace::Building,
+            &mut scheduler,
+        );
+        assert_eq!(model.contacts[&p0].len(), 2);
+
+        model.handle_cmd(later, Cmd::RecordCounts, &mut scheduler);
+        assert_eq!(model.contacts[&p0], vec![(p2, later)]);
+        assert!(!model.contacts.contains_key(&p1));
+        assert_eq!(model.contacts[&p2], vec![(p0, later)]);
+    }
+
+    #[test]
+    fn always_allowed_home() {
+        let mut interventions = Interventions::none();
+        interventions
+            .closed_amenities
+            .insert("restaurant".to_string());
+        interventions.max_bldg_occupancy = Some(1);
+        let mut model = model(interventions, 2);
+        let restaurant = vec![("Cafe".to_string(), "restaurant".to_string())]
+            .into_iter()
+            .collect();
+        let office = vec![("Startup".to_string(), "office".to_string())]
+            .into_iter()
+            .collect();
+        let (b1, b2) = (BuildingID(1), BuildingID(2));
+
+        assert!(model.turned_away(b1, None, &restaurant));
+        // Somebody living above the restaurant can still go home
+        assert!(!model.turned_away(b1, Some(b1), &restaurant));
+        assert!(model.turned_away(b1, Some(b2), &restaurant));
+
+        model.bldgs.person_enters_space(time(1), PersonID(0), b2);
+        assert!(model.turned_away(b2, None, &office));
+        assert!(!model.turned_away(b2, Some(b2), &office));
+        // Residential buildings aren't limited
+        assert!(!model.turned_away(b2, None, &BTreeSet::new()));
+    }
 }
@@ -1,10 +1,11 @@
 use crate::{
     AgentID, AlertLocation, Analytics, CarID, Command, CreateCar, DrawCarInput, DrawPedCrowdInput,
-    DrawPedestrianInput, DrivingSimState, Event, GetDrawAgents, IntersectionSimState, OrigPersonID,
-    PandemicModel, ParkedCar, ParkingSimState, ParkingSpot, PedestrianID, Person, PersonID,
-    PersonState, Router, Scheduler, SidewalkPOI, SidewalkSpot, TransitSimState, TripEndpoint,
-    TripID, TripManager, TripMode, TripPhaseType, TripPositions, TripResult, TripSpawner,
-    UnzoomedAgent, Vehicle, VehicleSpec, VehicleType, WalkingSimState, BUS_LENGTH, MIN_CAR_LENGTH,
+    DrawPedestrianInput, DrivingSimState, Event, GetDrawAgents, IntersectionSimState,
+    Interventions, OrigPersonID, PandemicModel, ParkedCar, ParkingSimState, ParkingSpot,
+    PassengerCapacity, PedestrianID, Person, PersonID, PersonState, Router, Scheduler, SidewalkPOI,
+    SidewalkSpot, TransitSimState, TripEndpoint, TripID, TripManager, TripMode, TripPhaseType,
+    TripPositions, TripResult, TripSpawner, UnzoomedAgent, Vehicle, VehicleSpec, VehicleType,
+    WalkingSimState, BUS_CAPACITY, BUS_LENGTH, MIN_CAR_LENGTH,
 };
 use abstutil::Timer;
 use derivative::Derivative;
@@ -32,7 +33,6 @@ pub struct Sim {
     transit: TransitSimState,
     trips: TripManager,
     #[derivative(PartialEq = "ignore")]
-    #[serde(skip_serializing, skip_deserializing)]
     pandemic: Option<PandemicModel>,
     scheduler: Scheduler,
     time: Time,
@@ -70,8 +70,11 @@ pub struct SimOptions {
     pub recalc_lanechanging: bool,
     pub break_turn_conflict_cycles: bool,
     pub enable_pandemic_model: Option<XorShiftRng>,
+    pub pandemic_interventions: Interventions,
     pub alerts: AlertHandler,
     pub pathfinding_upfront: bool,
+    // How many people fit on every bus
+    pub bus_capacity: PassengerCapacity,
 }
 
 #[derive(Clone)]
@@ -99,8 +102,10 @@ impl SimOptions {
             recalc_lanechanging: true,
             break_turn_conflict_cycles: true,
             enable_pandemic_model: None,
+            pandemic_interventions: Interventions::none(),
             alerts: AlertHandler::Print,
             pathfinding_upfront: false,
+            bus_capacity: BUS_CAPACITY,
         }
     }
 }
@@ -120,10 +125,10 @@ impl Sim {
                 opts.dont_block_the_box,
                 opts.break_turn_conflict_cycles,
             ),
-            transit: TransitSimState::new(),
+            transit: TransitSimState::new(opts.bus_capacity),
             trips: TripManager::new(opts.pathfinding_upfront),
             pandemic: if let Some(rng) = opts.enable_pandemic_model {
-                Some(PandemicModel::new(rng))
+                Some(PandemicModel::new(rng, opts.pandemic_interventions))
             } else {
                 None
             },
@@ -223,14 +228,23 @@ impl Sim {
         &mut self,
         p: PersonID,
         orig_id: Option<OrigPersonID>,
+        home: Option<BuildingID>,
         ped_speed: Speed,
         vehicle_specs: Vec<VehicleSpec>,
+        shared_vehicles: Vec<Vehicle>,
     ) {
-        self.trips.new_person(p, orig_id, ped_speed, vehicle_specs);
+        self.trips
+            .new_person(p, orig_id, home, ped_speed, vehicle_specs, shared_vehicles);
     }
     pub fn random_person(&mut self, ped_speed: Speed, vehicle_specs: Vec<VehicleSpec>) -> &Person {
         self.trips.random_person(ped_speed, vehicle_specs)
     }
+    pub(crate) fn new_shared_vehicles(
+        &mut self,
+        vehicles: Vec<(PersonID, VehicleSpec)>,
+    ) -> Vec<Vehicle> {
+        self.trips.new_shared_vehicles(vehicles)
+    }
     pub(crate) fn seed_parked_car(&mut self, vehicle: Vehicle, spot: ParkingSpot) {
         self.parking.reserve_spot(spot);
         self.parking.add_parked_car(ParkedCar { vehicle, spot });
@@ -246,12 +260,15 @@ impl Sim {
         {
             // For now, no desire for randomness. Caller can pass in list of specs if that ever
             // changes.
-            let vehicle = VehicleSpec {
+            let spec = VehicleSpec {
                 vehicle_type: VehicleType::Bus,
                 length: BUS_LENGTH,
                 max_speed: None,
-            }
-            .make(CarID(self.trips.new_car_id(), VehicleType::Bus), None);
+                capacity: Some(self.transit.bus_capacity()),
+            };
+            // The capacity only matters to TransitSimState, so Vehicle doesn't keep it.
+            let capacity = spec.capacity.unwrap();
+            let vehicle = spec.make(CarID(self.trips.new_car_id(), VehicleType::Bus), None);
             let id = vehicle.id;
 
             loop {
@@ -291,7 +308,8 @@ impl Sim {
                     &self.parking,
                     &mut self.scheduler,
                 ) {
-                    self.transit.bus_created(id, route.id, next_stop_idx);
+                    self.transit
+                        .bus_created(id, route.id, next_stop_idx, capacity);
                     self.analytics.record_demand(&path, map);
                     results.push(id);
                     return results;
@@ -430,16 +448,31 @@ impl Sim {
         let mut halt = false;
         match cmd {
             Command::StartTrip(id, trip_spec, maybe_req, maybe_path) => {
-                self.trips.start_trip(
-                    self.time,
-                    id,
-                    trip_spec,
-                    maybe_req,
-                    maybe_path,
-                    &mut self.parking,
-                    &mut self.scheduler,
-                    map,
-                );
+                if self
+                    .pandemic
+                    .as_ref()
+                    .map(|m| {
+                        let home = self
+                            .trips
+                            .get_person(self.trips.trip_to_person(id))
+                            .and_then(|p| p.home);
+                        m.should_cancel_trip(&self.trips.trip_info(id).2, home, map)
+                    })
+                    .unwrap_or(false)
+                {
+                    self.trips.cancel_trip(id);
+                } else {
+                    self.trips.start_trip(
+                        self.time,
+                        id,
+                        trip_spec,
+                        maybe_req,
+                        maybe_path,
+                        &mut self.parking,
+                        &mut self.scheduler,
+                        map,
+                    );
+                }
             }
             Command::SpawnCar(create_car, retry_if_no_room) => {
                 if self.driving.start_car_on_lane(
@@ -575,8 +608,18 @@ impl Sim {
                 );
             }
             Command::UpdateIntersection(i) => {
-                self.intersections
-                    .update_intersection(self.time, i, map, &mut self.scheduler);
+                let buses_approaching = if map.get_i(i).transit_priority.signal_priority {
+                    self.driving.buses_approaching(self.time, i, map)
+                } else {
+                    Vec::new()
+                };
+                self.intersections.update_intersection(
+                    self.time,
+                    i,
+                    map,
+                    &mut self.scheduler,
+                    buses_approaching,
+                );
             }
             Command::Callback(frequency) => {
                 self.scheduler
@@ -586,10 +629,14 @@ impl Sim {
                 }
             }
             Command::Pandemic(cmd) => {
-                self.pandemic
-                    .as_mut()
-                    .unwrap()
-                    .handle_cmd(self.time, cmd, &mut self.scheduler);
+                let quarantined =
+                    self.pandemic
+                        .as_mut()
+                        .unwrap()
+                        .handle_cmd(self.time, cmd, &mut self.scheduler);
+                for person in quarantined {
+                    self.trips.cancel_remaining_trips(person);
+                }
             }
             Command::FinishRemoteTrip(trip) => {
                 self.trips.remote_trip_finished(
@@ -617,11 +664,16 @@ impl Sim {
         events.extend(self.parking.collect_events());
         for ev in events {
             if let Some(ref mut m) = self.pandemic {
-                m.handle_event(self.time, &ev, &mut self.scheduler);
+                m.handle_event(self.time, &ev, map, &mut self.scheduler);
             }
 
             self.analytics.event(ev, self.time, map);
         }
+        if let Some(ref mut m) = self.pandemic {
+            for ev in m.collect_events() {
+                self.analytics.event(ev, self.time, map);
+            }
+        }
     }
 
     pub fn timed_step(
@@ -939,12 +991,16 @@ impl Sim {
     // TODO Temporary until we figure out all the info to expose
     pub fn bus_properties(&self, car: CarID, map: &Map) -> Vec<(String, String)> {
         let passengers = self.transit.get_passengers(car);
+        let capacity = self.transit.get_capacity(car);
         vec![
             (
                 "Route".to_string(),
                 map.get_br(self.transit.bus_route(car)).name.clone(),
             ),
-            ("Passengers".to_string(), passengers.len().to_string()),
+            (
+                "Passengers".to_string(),
+                format!("{} / {}", passengers.len(), capacity.total()),
+            ),
         ]
     }
 
@@ -1099,6 +1155,17 @@ impl Sim {
         }
     }
 
+    // The current phase of a traffic signal and how much longer it'll last. Transit signal
+    // priority means this doesn't always match the signal's timing plan.
+    pub fn current_phase_and_remaining_time(
+        &self,
+        id: IntersectionID,
+        map: &Map,
+    ) -> (usize, Duration) {
+        self.intersections
+            .current_phase_and_remaining_time(self.time, id, map)
+    }
+
     pub fn get_accepted_agents(&self, id: IntersectionID) -> HashSet<AgentID> {
         self.intersections.get_accepted_agents(id)
     }
@@ -1233,3 +1300,31 @@ pub struct AgentProperties {
     pub lanes_crossed: usize,
     pub total_lanes: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bus_capacity_option() {
+        assert_eq!(
+            PassengerCapacity::parse("20,5"),
+            Ok(PassengerCapacity {
+                seated: 20,
+                standing: 5
+            })
+        );
+        assert!(PassengerCapacity::parse("20").is_err());
+        assert!(PassengerCapacity::parse("20,-5").is_err());
+        assert!(PassengerCapacity::parse("0,0").is_err());
+
+        let mut opts = SimOptions::new("test");
+        assert_eq!(opts.bus_capacity, BUS_CAPACITY);
+        opts.bus_capacity = PassengerCapacity {
+            seated: 10,
+            standing: 0,
+        };
+        let sim = Sim::new(&Map::blank(), opts, &mut Timer::throwaway());
+        assert_eq!(sim.transit.bus_capacity().total(), 10);
+    }
+}
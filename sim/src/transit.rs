@@ -1,9 +1,9 @@
 use crate::{
-    CarID, Event, PedestrianID, PersonID, Router, Scheduler, TripID, TripManager, TripPhaseType,
-    WalkingSimState,
+    CarID, Event, PassengerCapacity, PedestrianID, PersonID, Router, Scheduler, TripID,
+    TripManager, TripPhaseType, WalkingSimState,
 };
 use abstutil::{deserialize_btreemap, serialize_btreemap};
-use geom::{Distance, Time};
+use geom::{Distance, Duration, Time};
 use map_model::{
     BusRoute, BusRouteID, BusStopID, Map, Path, PathConstraints, PathRequest, Position,
 };
@@ -13,6 +13,12 @@ use std::collections::BTreeMap;
 // These index stops along a route, not stops along a single sidewalk.
 type StopIdx = usize;
 
+// How long a bus waits at a stop is the overhead plus however long the slower of boarding and
+// deboarding takes. People get on and off through different doors at the same time.
+const DWELL_OVERHEAD: Duration = Duration::const_seconds(5.0);
+const TIME_TO_BOARD: Duration = Duration::const_seconds(3.0);
+const TIME_TO_DEBOARD: Duration = Duration::const_seconds(2.0);
+
 #[derive(Serialize, Deserialize, PartialEq, Clone)]
 struct StopForRoute {
     id: BusStopID,
@@ -34,6 +40,7 @@ struct Bus {
     route: BusRouteID,
     // Where does each passenger want to deboard?
     passengers: Vec<(PersonID, BusStopID)>,
+    capacity: PassengerCapacity,
     state: BusState,
 }
 
@@ -56,26 +63,33 @@ pub struct TransitSimState {
         deserialize_with = "deserialize_btreemap"
     )]
     routes: BTreeMap<BusRouteID, Route>,
-    // waiting at => (ped, route, bound for, started waiting)
+    // waiting at => (ped, person, route, bound for, started waiting)
     #[serde(
         serialize_with = "serialize_btreemap",
         deserialize_with = "deserialize_btreemap"
     )]
-    peds_waiting: BTreeMap<BusStopID, Vec<(PedestrianID, BusRouteID, BusStopID, Time)>>,
+    peds_waiting: BTreeMap<BusStopID, Vec<(PedestrianID, PersonID, BusRouteID, BusStopID, Time)>>,
+    // For new buses
+    bus_capacity: PassengerCapacity,
 
     events: Vec<Event>,
 }
 
 impl TransitSimState {
-    pub fn new() -> TransitSimState {
+    pub fn new(bus_capacity: PassengerCapacity) -> TransitSimState {
         TransitSimState {
             buses: BTreeMap::new(),
             routes: BTreeMap::new(),
             peds_waiting: BTreeMap::new(),
+            bus_capacity,
             events: Vec::new(),
         }
     }
 
+    pub fn bus_capacity(&self) -> PassengerCapacity {
+        self.bus_capacity
+    }
+
     // Returns (next stop, first path, end distance for next stop) for all of the stops in the
     // route.
     pub fn create_empty_route(
@@ -134,7 +148,13 @@ impl TransitSimState {
         stops
     }
 
-    pub fn bus_created(&mut self, bus: CarID, route: BusRouteID, next_stop_idx: StopIdx) {
+    pub fn bus_created(
+        &mut self,
+        bus: CarID,
+        route: BusRouteID,
+        next_stop_idx: StopIdx,
+        capacity: PassengerCapacity,
+    ) {
         self.routes.get_mut(&route).unwrap().buses.push(bus);
         self.buses.insert(
             bus,
@@ -142,11 +162,13 @@ impl TransitSimState {
                 car: bus,
                 route,
                 passengers: Vec::new(),
+                capacity,
                 state: BusState::DrivingToStop(next_stop_idx),
             },
         );
     }
 
+    // Returns how long the bus should wait at the stop.
     pub fn bus_arrived_at_stop(
         &mut self,
         now: Time,
@@ -155,7 +177,7 @@ impl TransitSimState {
         walking: &mut WalkingSimState,
         scheduler: &mut Scheduler,
         map: &Map,
-    ) {
+    ) -> Duration {
         let mut bus = self.buses.get_mut(&id).unwrap();
         match bus.state {
             BusState::DrivingToStop(stop_idx) => {
@@ -166,21 +188,34 @@ impl TransitSimState {
 
                 // Deboard existing passengers.
                 let mut still_riding = Vec::new();
+                let mut deboarded = 0;
                 for (person, stop2) in bus.passengers.drain(..) {
                     if stop1 == stop2 {
                         trips.person_left_bus(now, person, bus.car, map, scheduler);
+                        deboarded += 1;
                     } else {
                         still_riding.push((person, stop2));
                     }
                 }
                 bus.passengers = still_riding;
 
-                // Board new passengers.
+                // Board new passengers, in the order they started waiting. Anybody who doesn't
+                // fit keeps waiting for the next bus.
                 let mut still_waiting = Vec::new();
-                for (ped, route, stop2, started_waiting) in
+                let mut boarded = 0;
+                for (ped, person, route, stop2, started_waiting) in
                     self.peds_waiting.remove(&stop1).unwrap_or_else(Vec::new)
                 {
-                    if bus.route == route {
+                    if bus.route == route && bus.passengers.len() == bus.capacity.total() {
+                        self.events.push(Event::PassengerDeniedBoarding(
+                            person,
+                            bus.car,
+                            route,
+                            stop1,
+                            started_waiting,
+                        ));
+                        still_waiting.push((ped, person, route, stop2, started_waiting));
+                    } else if bus.route == route {
                         let (trip, person) = trips.ped_boarded_bus(
                             now,
                             ped,
@@ -199,14 +234,21 @@ impl TransitSimState {
                             TripPhaseType::RidingBus(route, stop1, bus.car),
                         ));
                         bus.passengers.push((person, stop2));
+                        boarded += 1;
                     } else {
-                        still_waiting.push((ped, route, stop2, started_waiting));
+                        still_waiting.push((ped, person, route, stop2, started_waiting));
                     }
                 }
                 self.peds_waiting.insert(stop1, still_waiting);
+
+                DWELL_OVERHEAD
+                    + std::cmp::max(
+                        TIME_TO_BOARD * (boarded as f64),
+                        TIME_TO_DEBOARD * (deboarded as f64),
+                    )
             }
             BusState::AtStop(_) => unreachable!(),
-        };
+        }
     }
 
     pub fn bus_departed_from_stop(&mut self, id: CarID) -> Router {
@@ -218,8 +260,13 @@ impl TransitSimState {
                 let stop = &route.stops[stop_idx];
 
                 bus.state = BusState::DrivingToStop(stop.next_stop_idx);
-                self.events
-                    .push(Event::BusDepartedFromStop(id, bus.route, stop.id));
+                self.events.push(Event::BusDepartedFromStop(
+                    id,
+                    bus.route,
+                    stop.id,
+                    bus.passengers.len(),
+                    bus.capacity,
+                ));
                 Router::follow_bus_route(
                     stop.path_to_next_stop.clone(),
                     route.stops[stop.next_stop_idx].driving_pos.dist_along(),
@@ -245,11 +292,14 @@ impl TransitSimState {
             for bus in &route.buses {
                 if let BusState::AtStop(idx) = self.buses[bus].state {
                     if route.stops[idx].id == stop1 {
-                        self.buses
-                            .get_mut(bus)
-                            .unwrap()
-                            .passengers
-                            .push((person, stop2));
+                        let this_bus = self.buses.get_mut(bus).unwrap();
+                        if this_bus.passengers.len() == this_bus.capacity.total() {
+                            self.events.push(Event::PassengerDeniedBoarding(
+                                person, *bus, route_id, stop1, now,
+                            ));
+                            continue;
+                        }
+                        this_bus.passengers.push((person, stop2));
                         self.events.push(Event::TripPhaseStarting(
                             trip,
                             person,
@@ -274,7 +324,7 @@ impl TransitSimState {
         self.peds_waiting
             .entry(stop1)
             .or_insert_with(Vec::new)
-            .push((ped, route_id, stop2, now));
+            .push((ped, person, route_id, stop2, now));
         None
     }
 
@@ -286,6 +336,10 @@ impl TransitSimState {
         &self.buses[&bus].passengers
     }
 
+    pub fn get_capacity(&self, bus: CarID) -> PassengerCapacity {
+        self.buses[&bus].capacity
+    }
+
     pub fn bus_route(&self, bus: CarID) -> BusRouteID {
         self.buses[&bus].route
     }
@@ -27,6 +27,14 @@ pub struct TripManager {
     pub pathfinding_upfront: bool,
 
     car_id_counter: usize,
+    // Who's using each car? A car is taken from the moment somebody starts walking to it until
+    // it's parked again, and it stays with a driver who leaves the map. Members of a household
+    // share cars, so this keeps two people from using the same one.
+    #[serde(
+        serialize_with = "serialize_btreemap",
+        deserialize_with = "deserialize_btreemap"
+    )]
+    car_users: BTreeMap<CarID, PersonID>,
 
     events: Vec<Event>,
 }
@@ -39,6 +47,7 @@ impl TripManager {
             active_trip_mode: BTreeMap::new(),
             unfinished_trips: 0,
             car_id_counter: 0,
+            car_users: BTreeMap::new(),
             events: Vec::new(),
             pathfinding_upfront,
         }
@@ -49,21 +58,25 @@ impl TripManager {
         &mut self,
         id: PersonID,
         orig_id: Option<OrigPersonID>,
+        home: Option<BuildingID>,
         ped_speed: Speed,
         vehicle_specs: Vec<VehicleSpec>,
+        shared_vehicles: Vec<Vehicle>,
     ) {
         assert_eq!(id.0, self.people.len());
-        let vehicles = vehicle_specs
+        let mut vehicles: Vec<Vehicle> = vehicle_specs
             .into_iter()
             .map(|v| {
                 let c = CarID(self.new_car_id(), v.vehicle_type);
                 v.make(c, Some(id))
             })
             .collect();
+        vehicles.extend(shared_vehicles);
         self.people.push(Person {
             id,
             orig_id,
             trips: Vec::new(),
+            home,
             // The first new_trip will set this properly.
             state: PersonState::OffMap,
             ped: PedestrianID(id.0),
@@ -75,10 +88,22 @@ impl TripManager {
     }
     pub fn random_person(&mut self, ped_speed: Speed, vehicle_specs: Vec<VehicleSpec>) -> &Person {
         let id = PersonID(self.people.len());
-        self.new_person(id, None, ped_speed, vehicle_specs);
+        self.new_person(id, None, None, ped_speed, vehicle_specs, Vec::new());
         self.get_person(id).unwrap()
     }
 
+    // Vehicles shared by a household, each with the member who owns it. Pass them to new_person
+    // for every member.
+    pub fn new_shared_vehicles(&mut self, vehicles: Vec<(PersonID, VehicleSpec)>) -> Vec<Vehicle> {
+        vehicles
+            .into_iter()
+            .map(|(owner, v)| {
+                let c = CarID(self.new_car_id(), v.vehicle_type);
+                v.make(c, Some(owner))
+            })
+            .collect()
+    }
+
     pub fn new_car_id(&mut self) -> usize {
         let id = self.car_id_counter;
         self.car_id_counter += 1;
@@ -121,6 +146,7 @@ impl TripManager {
             finished_at: None,
             total_blocked_time: Duration::ZERO,
             aborted: false,
+            cancelled: false,
             mode,
             legs: VecDeque::from(legs),
             start,
@@ -184,6 +210,7 @@ impl TripManager {
             }
             _ => unreachable!(),
         };
+        self.car_users.remove(&car);
 
         match &trip.legs[0] {
             TripLeg::Walk(to) => match (spot, &to.connection) {
@@ -704,6 +731,7 @@ impl TripManager {
         };
         // Don't forget the car!
         if let Some(vehicle) = abandoned_vehicle {
+            self.car_users.remove(&vehicle.id);
             if vehicle.vehicle_type == VehicleType::Car {
                 if let TripEndpoint::Bldg(b) = trip.end {
                     let driving_lane = map.find_driving_lane_near_building(b);
@@ -751,11 +779,42 @@ impl TripManager {
                     assert_eq!(t, trip.id);
                 }
             }
+            // If they were walking to a car, somebody else can use it now.
+            for leg in &trip.legs {
+                if let TripLeg::Drive(c, _) = leg {
+                    if parking.lookup_parked_car(*c).is_some() {
+                        self.car_users.remove(c);
+                    }
+                }
+            }
         }
 
         self.person_finished_trip(now, person, parking, scheduler, map);
     }
 
+    // Cancel a trip before it starts. The person stays wherever they are.
+    pub fn cancel_trip(&mut self, id: TripID) {
+        let trip = &mut self.trips[id.0];
+        assert!(!trip.started);
+        if trip.cancelled {
+            return;
+        }
+        trip.cancelled = true;
+        self.unfinished_trips -= 1;
+        self.events.push(Event::TripCancelled(id));
+    }
+
+    // Cancel every trip the person hasn't started yet. A trip in progress still finishes.
+    pub fn cancel_remaining_trips(&mut self, person: PersonID) {
+        self.people[person.0].delayed_trips.clear();
+        for id in self.people[person.0].trips.clone() {
+            let trip = &self.trips[id.0];
+            if !trip.started && !trip.aborted {
+                self.cancel_trip(id);
+            }
+        }
+    }
+
     pub fn active_agents(&self) -> Vec<AgentID> {
         self.active_trip_mode.keys().cloned().collect()
     }
@@ -776,6 +835,9 @@ impl TripManager {
         if trip.aborted {
             return TripResult::TripAborted;
         }
+        if trip.cancelled {
+            return TripResult::TripCancelled;
+        }
         if !trip.started {
             return TripResult::TripNotStarted;
         }
@@ -918,6 +980,27 @@ impl TripManager {
         scheduler: &mut Scheduler,
         map: &Map,
     ) {
+        if self.trips[trip.0].cancelled {
+            return;
+        }
+        // If an earlier trip was cancelled, the person might not be where this trip starts. Then
+        // they can't take this trip either.
+        {
+            let person = &self.people[self.trips[trip.0].person.0];
+            let at_start = match (&person.state, &self.trips[trip.0].start) {
+                (PersonState::Inside(b1), TripEndpoint::Bldg(b2)) => b1 == b2,
+                (PersonState::OffMap, TripEndpoint::Border(_, _)) => true,
+                (PersonState::Trip(_), _) => true,
+                _ => false,
+            };
+            if !at_start && person.trips.iter().any(|t| self.trips[t.0].cancelled) {
+                let person = person.id;
+                self.cancel_trip(trip);
+                self.person_finished_trip(now, person, parking, scheduler, map);
+                return;
+            }
+        }
+
         if !self.pathfinding_upfront && maybe_path.is_none() && maybe_req.is_some() {
             maybe_path = map.pathfind(maybe_req.clone().unwrap());
         }
@@ -962,6 +1045,31 @@ impl TripManager {
                 ));
                 person.state = PersonState::Trip(trip);
 
+                let use_vehicle = if use_vehicle.1 == VehicleType::Car {
+                    if let Some(car) =
+                        pick_appearing_car(person, use_vehicle, &self.car_users, parking)
+                    {
+                        self.car_users.insert(car, person.id);
+                        if let TripLeg::Drive(ref mut c, _) = self.trips[trip.0].legs[0] {
+                            *c = car;
+                        }
+                        car
+                    } else {
+                        self.events.push(Event::Alert(
+                            AlertLocation::Person(person.id),
+                            format!(
+                                "{} was going to bring {} onto the map, but somebody else is \
+                                 using it, so aborting {}",
+                                person.id, use_vehicle, trip
+                            ),
+                        ));
+                        self.abort_trip(now, trip, None, parking, scheduler, map);
+                        return;
+                    }
+                } else {
+                    use_vehicle
+                };
+
                 let vehicle = person.get_vehicle(use_vehicle);
                 assert!(parking.lookup_parked_car(vehicle.id).is_none());
                 let req = maybe_req.unwrap();
@@ -1006,10 +1114,15 @@ impl TripManager {
                 assert_eq!(person.state, PersonState::Inside(start_bldg));
                 person.state = PersonState::Trip(trip);
 
-                // TODO For now, use the car we decided to statically. That makes sense in most
-                // cases.
-
-                if let Some(parked_car) = parking.lookup_parked_car(car).cloned() {
+                // The car we decided to use statically might be in use by another member of the
+                // household, so fall back to any other car nearby.
+                if let Some(parked_car) =
+                    pick_parked_car(person, car, start_bldg, &self.car_users, parking, map)
+                {
+                    self.car_users.insert(parked_car.vehicle.id, person.id);
+                    if let TripLeg::Drive(ref mut c, _) = self.trips[trip.0].legs[1] {
+                        *c = parked_car.vehicle.id;
+                    }
                     let start = SidewalkSpot::building(start_bldg, map);
                     let walking_goal = SidewalkSpot::parking_spot(parked_car.spot, map, parking);
                     let req = PathRequest {
@@ -1048,13 +1161,14 @@ impl TripManager {
                         );
                     }
                 } else {
-                    // This should only happen when a driving trip has been aborted and there was
-                    // absolutely no room to warp the car.
+                    // This happens when a driving trip has been aborted and there was absolutely no
+                    // room to warp the car, or when other members of the household took every
+                    // car nearby.
                     self.events.push(Event::Alert(
                         AlertLocation::Person(person.id),
                         format!(
-                            "{} should have {} parked somewhere, but it's unavailable, so \
-                             aborting {}",
+                            "{} should have {} parked somewhere, but it's unavailable and no \
+                             other car is nearby, so aborting {}",
                             person.id, car, trip
                         ),
                     ));
@@ -1169,7 +1283,7 @@ impl TripManager {
                     self.abort_trip(now, trip, None, parking, scheduler, map);
                 }
             }
-            TripSpec::UsingTransit { start, stop1, .. } => {
+            TripSpec::UsingTransit { start, rides, .. } => {
                 assert_eq!(
                     person.state,
                     match start.connection {
@@ -1199,7 +1313,7 @@ impl TripManager {
                 );
                 person.state = PersonState::Trip(trip);
 
-                let walk_to = SidewalkSpot::bus_stop(stop1, map);
+                let walk_to = SidewalkSpot::bus_stop(rides[0].0, map);
                 let req = maybe_req.unwrap();
                 if let Some(path) = maybe_path {
                     scheduler.push(
@@ -1250,6 +1364,8 @@ struct Trip {
     finished_at: Option<Time>,
     total_blocked_time: Duration,
     aborted: bool,
+    // Cancelled before it started, by some policy
+    cancelled: bool,
     legs: VecDeque<TripLeg>,
     mode: TripMode,
     start: TripEndpoint,
@@ -1387,6 +1503,15 @@ impl TripMode {
             TripMode::Drive => "Car",
         }
     }
+
+    // Transit riders walk to and from their stops.
+    pub fn to_constraints(self) -> PathConstraints {
+        match self {
+            TripMode::Walk | TripMode::Transit => PathConstraints::Pedestrian,
+            TripMode::Bike => PathConstraints::Bike,
+            TripMode::Drive => PathConstraints::Car,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -1402,6 +1527,7 @@ pub enum TripResult<T> {
     TripDoesntExist,
     TripNotStarted,
     TripAborted,
+    TripCancelled,
     RemoteTrip,
 }
 
@@ -1421,6 +1547,7 @@ impl<T> TripResult<T> {
             TripResult::TripDoesntExist => TripResult::TripDoesntExist,
             TripResult::TripNotStarted => TripResult::TripNotStarted,
             TripResult::TripAborted => TripResult::TripAborted,
+            TripResult::TripCancelled => TripResult::TripCancelled,
             TripResult::RemoteTrip => TripResult::RemoteTrip,
         }
     }
@@ -1431,7 +1558,8 @@ pub struct Person {
     pub id: PersonID,
     pub orig_id: Option<OrigPersonID>,
     pub trips: Vec<TripID>,
-    // TODO home
+    // Where the person's household lives, if we know
+    pub home: Option<BuildingID>,
     pub state: PersonState,
 
     pub ped: PedestrianID,
@@ -1443,12 +1571,78 @@ pub struct Person {
     on_bus: Option<CarID>,
 }
 
+// How far will somebody walk to a car, when the one they planned to use is taken?
+const MAX_WALK_TO_CAR: Distance = Distance::const_meters(500.0);
+
 impl Person {
     pub(crate) fn get_vehicle(&self, id: CarID) -> Vehicle {
         self.vehicles.iter().find(|v| v.id == id).unwrap().clone()
     }
 }
 
+// Use the planned car if nobody else took it. Otherwise, use the closest free car parked near the
+// building.
+fn pick_parked_car(
+    person: &Person,
+    planned: CarID,
+    start_bldg: BuildingID,
+    car_users: &BTreeMap<CarID, PersonID>,
+    parking: &ParkingSimState,
+    map: &Map,
+) -> Option<ParkedCar> {
+    let available = move |id: CarID| {
+        if car_users.contains_key(&id) {
+            None
+        } else {
+            parking.lookup_parked_car(id)
+        }
+    };
+    if let Some(p) = available(planned) {
+        return Some(p.clone());
+    }
+
+    let pt = map.get_b(start_bldg).front_path.sidewalk.pt(map);
+    person
+        .vehicles
+        .iter()
+        .filter(|v| v.vehicle_type == VehicleType::Car)
+        .filter_map(|v| available(v.id))
+        .map(|p| {
+            let dist = parking
+                .spot_to_sidewalk_pos(p.spot, map)
+                .pt(map)
+                .dist_to(pt);
+            (dist, p)
+        })
+        .filter(|(dist, _)| *dist <= MAX_WALK_TO_CAR)
+        .min_by_key(|(dist, _)| *dist)
+        .map(|(_, p)| p.clone())
+}
+
+// Somebody entering the map drives whatever car they left in. If they didn't leave in a car, use
+// the planned car, as long as it's not on the map or with somebody else.
+fn pick_appearing_car(
+    person: &Person,
+    planned: CarID,
+    car_users: &BTreeMap<CarID, PersonID>,
+    parking: &ParkingSimState,
+) -> Option<CarID> {
+    if car_users.get(&planned) == Some(&person.id) {
+        return Some(planned);
+    }
+    if let Some(v) = person
+        .vehicles
+        .iter()
+        .find(|v| car_users.get(&v.id) == Some(&person.id))
+    {
+        return Some(v.id);
+    }
+    if !car_users.contains_key(&planned) && parking.lookup_parked_car(planned).is_none() {
+        return Some(planned);
+    }
+    None
+}
+
 #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub enum PersonState {
     Trip(TripID),